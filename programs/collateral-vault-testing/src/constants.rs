@@ -6,11 +6,145 @@ pub const VAULT_SEED: &[u8] = b"vault";
 /// Seed for authority PDA derivation
 pub const AUTHORITY_SEED: &[u8] = b"authority";
 
+/// Seed for protocol state PDA derivation
+pub const PROTOCOL_STATE_SEED: &[u8] = b"protocol_state";
+
+/// Seed for per-mint withdrawal cooldown PDA derivation
+pub const COOLDOWN_SEED: &[u8] = b"cooldown";
+
+/// Seed for per-program outflow rate limit PDA derivation
+pub const RATE_LIMIT_SEED: &[u8] = b"rate_limit";
+
+/// Seed for owner spending policy PDA derivation
+pub const SPENDING_POLICY_SEED: &[u8] = b"spending_policy";
+
+/// Seed for vault operator PDA derivation
+pub const OPERATOR_SEED: &[u8] = b"operator";
+
+/// Seed for per-owner subaccount index PDA derivation
+pub const OWNER_INDEX_SEED: &[u8] = b"owner_index";
+
+/// Seed for guardian recovery config PDA derivation
+pub const RECOVERY_SEED: &[u8] = b"recovery";
+
+/// Seed for multisig owner PDA derivation
+pub const MULTISIG_SEED: &[u8] = b"multisig";
+
+/// Seed for multisig proposal PDA derivation
+pub const PROPOSAL_SEED: &[u8] = b"proposal";
+
+/// Seed for session key PDA derivation
+pub const SESSION_SEED: &[u8] = b"session";
+
+/// Seed for per-mint share pool PDA derivation
+pub const SHARE_POOL_SEED: &[u8] = b"share_pool";
+
+/// Seed for per-mint yield strategy PDA derivation
+pub const STRATEGY_SEED: &[u8] = b"strategy";
+
+/// Seed for per-program broken report PDA derivation
+pub const BROKEN_PROGRAM_SEED: &[u8] = b"broken_program";
+
+/// Seed for per-mint insurance fund PDA derivation
+pub const INSURANCE_SEED: &[u8] = b"insurance";
+
+/// Seed for recorded bad-debt PDA derivation
+pub const BAD_DEBT_SEED: &[u8] = b"bad_debt";
+
+/// Seed for per-mint TVL and outflow window PDA derivation
+pub const MINT_STATE_SEED: &[u8] = b"mint_state";
+
+/// Seed for reward pool PDA derivation
+pub const REWARD_POOL_SEED: &[u8] = b"reward_pool";
+
+/// Seed for per-vault reward position PDA derivation
+pub const REWARD_POSITION_SEED: &[u8] = b"reward_position";
+
+/// Fixed-point scale of a reward pool's emission rate and reward per token
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000;
+
+/// Fixed-point scale of a mint's socialized loss per token
+pub const LOSS_PRECISION: u128 = 1_000_000_000_000;
+
 /// Maximum number of authorized programs
 pub const MAX_AUTHORIZED_PROGRAMS: usize = 20;
 
+/// Maximum number of allowlisted withdrawal destinations per vault
+pub const MAX_WITHDRAWAL_DESTINATIONS: usize = 10;
+
+/// Maximum number of open subaccount vaults per owner
+pub const MAX_SUBACCOUNTS: usize = 16;
+
+/// Maximum number of recovery guardians per vault
+pub const MAX_GUARDIANS: usize = 10;
+
+/// Maximum number of reward positions open per vault
+pub const MAX_REWARD_POSITIONS: u8 = 4;
+
+/// Maximum number of members in a multisig
+pub const MAX_MULTISIG_MEMBERS: usize = 10;
+
+/// Maximum number of accounts a multisig proposal passes to a vault instruction
+pub const MAX_PROPOSAL_ACCOUNTS: usize = 16;
+
+/// Maximum instruction data length of a multisig proposal
+pub const MAX_PROPOSAL_DATA_LEN: usize = 256;
+
+/// Number of balance checkpoints kept per vault and for each mint TVL
+pub const MAX_BALANCE_CHECKPOINTS: usize = 16;
+
 /// Minimum deposit amount (1 token with 6 decimals)
 pub const MIN_DEPOSIT_AMOUNT: u64 = 1_000_000;
 
 /// Token decimals
-pub const TOKEN_DECIMALS: u8 = 6;
\ No newline at end of file
+pub const TOKEN_DECIMALS: u8 = 6;
+
+/// Basis points denominator
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Default circuit breaker threshold: net outflow of 20% of TVL per window
+pub const DEFAULT_MAX_OUTFLOW_BPS: u16 = 2_000;
+
+/// Default circuit breaker accounting window (1 day)
+pub const DEFAULT_OUTFLOW_WINDOW_SECONDS: i64 = 86_400;
+
+/// Default pause duration after which emergency withdrawals open (7 days)
+pub const DEFAULT_EMERGENCY_PAUSE_SECONDS: i64 = 7 * 86_400;
+
+/// Maximum protocol fee on any single action (10%)
+pub const MAX_FEE_BPS: u16 = 1_000;
+
+/// Anchor discriminator of a strategy program's `deposit(amount)`
+pub const STRATEGY_DEPOSIT_DISCRIMINATOR: [u8; 8] = [242, 35, 198, 137, 82, 225, 242, 182];
+
+/// Anchor discriminator of a strategy program's `withdraw(amount)`
+pub const STRATEGY_WITHDRAW_DISCRIMINATOR: [u8; 8] = [183, 18, 70, 156, 148, 109, 161, 34];
+
+/// Length of the spending policy daily limit window
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Operator may deposit into the vault from its own token account
+pub const OPERATOR_SCOPE_DEPOSIT: u8 = 1 << 0;
+
+/// Operator may withdraw to token accounts owned by the vault owner
+pub const OPERATOR_SCOPE_WITHDRAW_TO_OWNER: u8 = 1 << 1;
+
+/// Operator may withdraw to the owner's allowlisted destinations
+pub const OPERATOR_SCOPE_WITHDRAW_TO_ALLOWLIST: u8 = 1 << 2;
+
+/// All operator scopes
+pub const OPERATOR_SCOPE_ALL: u8 =
+    OPERATOR_SCOPE_DEPOSIT | OPERATOR_SCOPE_WITHDRAW_TO_OWNER | OPERATOR_SCOPE_WITHDRAW_TO_ALLOWLIST;
+
+/// Session key may deposit into the vault
+pub const SESSION_SCOPE_DEPOSIT: u8 = 1 << 0;
+
+/// Session key may withdraw to token accounts owned by the vault owner
+pub const SESSION_SCOPE_WITHDRAW: u8 = 1 << 1;
+
+/// Session key may move collateral between the owner's subaccounts
+pub const SESSION_SCOPE_TRANSFER: u8 = 1 << 2;
+
+/// All session key scopes
+pub const SESSION_SCOPE_ALL: u8 =
+    SESSION_SCOPE_DEPOSIT | SESSION_SCOPE_WITHDRAW | SESSION_SCOPE_TRANSFER;
\ No newline at end of file
@@ -40,4 +40,307 @@ pub enum ErrorCode {
 
     #[msg("Cannot transfer to same vault")]
     SameVaultTransfer,
+
+    #[msg("Outflow rate limit exceeded for calling program")]
+    RateLimitExceeded,
+
+    #[msg("Invalid rate limit: capacity and window must be greater than 0")]
+    InvalidRateLimitConfig,
+
+    #[msg("Rate limit account does not match calling program")]
+    InvalidRateLimitAccount,
+
+    #[msg("Protocol is paused")]
+    ProtocolPaused,
+
+    #[msg("Protocol is in withdraw-only mode")]
+    ProtocolWithdrawOnly,
+
+    #[msg("Invalid circuit breaker configuration")]
+    InvalidCircuitBreakerConfig,
+
+    #[msg("Vault still has locked collateral")]
+    VaultHasLockedBalance,
+
+    #[msg("Withdrawals are subject to a cooldown: use request_withdrawal")]
+    WithdrawalCooldownRequired,
+
+    #[msg("Withdrawal cooldown has not elapsed")]
+    WithdrawalNotReady,
+
+    #[msg("No pending withdrawal")]
+    NoPendingWithdrawal,
+
+    #[msg("Vault has a pending withdrawal")]
+    PendingWithdrawalExists,
+
+    #[msg("Invalid withdrawal cooldown")]
+    InvalidCooldownConfig,
+
+    #[msg("Cooldown account does not match vault mint")]
+    InvalidCooldownAccount,
+
+    #[msg("Invalid spending policy")]
+    InvalidSpendingPolicy,
+
+    #[msg("Spending policy account does not match vault")]
+    InvalidSpendingPolicyAccount,
+
+    #[msg("Destination is not an active allowlisted withdrawal destination")]
+    DestinationNotAllowed,
+
+    #[msg("Destination already allowlisted")]
+    DestinationAlreadyAllowed,
+
+    #[msg("Maximum withdrawal destinations reached")]
+    MaxWithdrawalDestinationsReached,
+
+    #[msg("Withdrawal exceeds per-transaction limit")]
+    WithdrawalExceedsTransactionLimit,
+
+    #[msg("Withdrawal exceeds daily limit")]
+    WithdrawalExceedsDailyLimit,
+
+    #[msg("No pending spending policy change")]
+    NoPendingPolicyChange,
+
+    #[msg("Unauthorized: operator lacks the required scope")]
+    UnauthorizedOperator,
+
+    #[msg("Operator allowance exceeded")]
+    OperatorAllowanceExceeded,
+
+    #[msg("Invalid operator scopes")]
+    InvalidOperatorScopes,
+
+    #[msg("Permit has expired")]
+    PermitExpired,
+
+    #[msg("Permit nonce does not match vault")]
+    InvalidPermitNonce,
+
+    #[msg("Permit is not backed by a matching Ed25519 signature instruction")]
+    InvalidPermitSignature,
+
+    #[msg("Relayer token account required to pay relayer fee")]
+    MissingRelayerTokenAccount,
+
+    #[msg("Rent payer does not match vault record")]
+    InvalidRentPayer,
+
+    #[msg("Maximum number of subaccounts reached")]
+    MaxSubaccountsReached,
+
+    #[msg("Subaccount is not listed in the owner index")]
+    SubaccountNotFound,
+
+    #[msg("Invalid new owner")]
+    InvalidNewOwner,
+
+    #[msg("No ownership transfer is pending")]
+    NoPendingOwnerTransfer,
+
+    #[msg("Signer is not the pending owner")]
+    UnauthorizedPendingOwner,
+
+    #[msg("Invalid recovery guardian configuration")]
+    InvalidRecoveryConfig,
+
+    #[msg("Signer is not a recovery guardian for this vault")]
+    UnauthorizedGuardian,
+
+    #[msg("A recovery is already pending")]
+    RecoveryAlreadyPending,
+
+    #[msg("No recovery is pending")]
+    NoPendingRecovery,
+
+    #[msg("Guardian has already approved this recovery")]
+    GuardianAlreadyApproved,
+
+    #[msg("Recovery has not reached its threshold or delay")]
+    RecoveryNotReady,
+
+    #[msg("Vault is locked down by its owner")]
+    VaultLockedDown,
+
+    #[msg("Vault is not locked down")]
+    VaultNotLockedDown,
+
+    #[msg("Lockdown recovery key is not configured")]
+    LockdownNotConfigured,
+
+    #[msg("Invalid lockdown configuration")]
+    InvalidLockdownConfig,
+
+    #[msg("Signer is not the lockdown recovery key")]
+    UnauthorizedRecoveryKey,
+
+    #[msg("Lockdown release has not been requested or is not ready")]
+    LockdownReleaseNotReady,
+
+    #[msg("Withdrawal above the co-signer threshold requires the co-signer")]
+    CosignerRequired,
+
+    #[msg("Invalid co-signer")]
+    InvalidCosigner,
+
+    #[msg("Invalid multisig members or threshold")]
+    InvalidMultisigConfig,
+
+    #[msg("Signer is not a multisig member")]
+    NotMultisigMember,
+
+    #[msg("Member has already approved this proposal")]
+    MemberAlreadyApproved,
+
+    #[msg("Proposal has already been executed")]
+    ProposalAlreadyExecuted,
+
+    #[msg("Proposal has not reached the approval threshold")]
+    ProposalThresholdNotMet,
+
+    #[msg("Accounts do not match the proposal")]
+    InvalidProposalAccounts,
+
+    #[msg("Invalid session key scopes, limit or expiry")]
+    InvalidSessionKey,
+
+    #[msg("Session key has expired")]
+    SessionExpired,
+
+    #[msg("Session key is not allowed this action")]
+    SessionScopeNotAllowed,
+
+    #[msg("Session key amount limit exceeded")]
+    SessionLimitExceeded,
+
+    #[msg("Invalid fee configuration")]
+    InvalidFeeConfig,
+
+    #[msg("Treasury token account required to collect the protocol fee")]
+    MissingTreasuryAccount,
+
+    #[msg("Treasury token account is not held by the protocol")]
+    InvalidTreasuryAccount,
+
+    #[msg("Unauthorized: Only the admin or fee manager can withdraw fees")]
+    UnauthorizedFeeManager,
+
+    #[msg("Vault collateral is held in the share pool")]
+    VaultPooled,
+
+    #[msg("Vault does not use share accounting")]
+    VaultNotPooled,
+
+    #[msg("Vault must be empty to change its accounting mode")]
+    VaultNotEmpty,
+
+    #[msg("Share pool does not match vault mint")]
+    InvalidSharePool,
+
+    #[msg("Amount too small to mint or burn a share")]
+    ZeroShares,
+
+    #[msg("Unauthorized: only the pool keeper or admin can move strategy funds")]
+    UnauthorizedKeeper,
+
+    #[msg("Invalid strategy configuration")]
+    InvalidStrategyConfig,
+
+    #[msg("Strategy is not active")]
+    StrategyInactive,
+
+    #[msg("Strategy allocation limit exceeded")]
+    StrategyAllocationExceeded,
+
+    #[msg("Share pool lacks the idle liquidity for this operation")]
+    InsufficientPoolLiquidity,
+
+    #[msg("Strategy did not move the expected amount")]
+    StrategyBalanceMismatch,
+
+    #[msg("Insurance fund does not match vault mint")]
+    InvalidInsuranceFund,
+
+    #[msg("Bad debt is already fully resolved")]
+    BadDebtResolved,
+
+    #[msg("Insurance fund must be exhausted before socializing bad debt")]
+    InsuranceFundNotExhausted,
+
+    #[msg("Reward pool does not match vault mint")]
+    InvalidRewardPool,
+
+    #[msg("Reward amount too small for the emission period")]
+    InvalidRewardRate,
+
+    #[msg("No rewards available to claim")]
+    NoRewardsToClaim,
+
+    #[msg("No retained balance checkpoint covers the requested slot")]
+    CheckpointUnavailable,
+
+    #[msg("Commitment must end in the future and cannot be shortened")]
+    InvalidCommitment,
+
+    #[msg("Committed collateral is still locked")]
+    CommitmentActive,
+
+    #[msg("Settlement leg is empty, self-referencing or out of range")]
+    InvalidSettlementLeg,
+
+    #[msg("Settlement vault accounts are missing, duplicated or mismatched")]
+    InvalidSettlementAccounts,
+
+    #[msg("Emergency mode is not active")]
+    EmergencyModeInactive,
+
+    #[msg("Invalid emergency pause duration")]
+    InvalidEmergencyConfig,
+
+    #[msg("Mint state account does not match vault mint")]
+    InvalidMintState,
+
+    #[msg("Program has no withdrawal cooldown")]
+    ProgramCooldownNotSet,
+
+    #[msg("Strategy loss exceeds its allocation")]
+    StrategyLossExceedsAllocation,
+
+    #[msg("Debtor vault must be emptied before recording bad debt")]
+    DebtorVaultNotEmpty,
+
+    #[msg("Bad debt exceeds what the debtor vault ever held")]
+    BadDebtExceedsObligation,
+
+    #[msg("Only the program that recorded the bad debt can resolve it")]
+    BadDebtCallerMismatch,
+
+    #[msg("Reward emission period must be greater than zero")]
+    InvalidRewardDuration,
+
+    #[msg("Delegation was granted by a previous vault owner")]
+    DelegationRevoked,
+
+    #[msg("Spending policy already exists")]
+    SpendingPolicyAlreadyExists,
+
+    #[msg("Socialized loss must be collected into the insurance fund first")]
+    SocializedLossUncollected,
+
+    #[msg("Vault has no socialized loss to collect")]
+    NoSocializedLoss,
+
+    #[msg("Reward pool and position accounts are missing, duplicated or mismatched")]
+    InvalidRewardAccounts,
+
+    #[msg("Vault has reached the maximum number of reward positions")]
+    MaxRewardPositionsReached,
+
+    #[msg("Reward positions must be closed before the vault")]
+    RewardPositionsOpen,
+
+    #[msg("Claim earned rewards before closing the position")]
+    UnclaimedRewards,
 }
\ No newline at end of file
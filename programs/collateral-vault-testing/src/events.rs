@@ -1,11 +1,15 @@
 use anchor_lang::prelude::*;
+use crate::state::{MultisigAction, PermitAction, ProtocolStatus};
 
 #[event]
 pub struct VaultInitializedEvent {
     pub vault: Pubkey,
     pub owner: Pubkey,
+    pub subaccount: u16,
     pub token_account: Pubkey,
+    pub rent_payer: Pubkey,
     pub initial_deposit: u64,
+    pub fee: u64,
     pub timestamp: i64,
 }
 
@@ -14,6 +18,20 @@ pub struct DepositEvent {
     pub vault: Pubkey,
     pub user: Pubkey,
     pub amount: u64,
+    pub fee: u64,
+    pub new_total_balance: u64,
+    pub new_available_balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DepositForEvent {
+    pub vault: Pubkey,
+    pub payer: Pubkey,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub vault_created: bool,
     pub new_total_balance: u64,
     pub new_available_balance: u64,
     pub timestamp: i64,
@@ -24,11 +42,548 @@ pub struct WithdrawEvent {
     pub vault: Pubkey,
     pub user: Pubkey,
     pub amount: u64,
+    pub fee: u64,
+    pub new_total_balance: u64,
+    pub new_available_balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PermitExecutedEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub relayer: Pubkey,
+    pub action: PermitAction,
+    pub amount: u64,
+    pub fee: u64,
+    pub relayer_fee: u64,
+    pub nonce: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalRequestedEvent {
+    pub vault: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub pending_withdrawal: u64,
+    pub ready_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalExecutedEvent {
+    pub vault: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
     pub new_total_balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalCancelledEvent {
+    pub vault: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
     pub new_available_balance: u64,
     pub timestamp: i64,
 }
 
+#[event]
+pub struct WithdrawalCooldownUpdatedEvent {
+    pub mint: Option<Pubkey>,
+    pub program_id: Option<Pubkey>,
+    pub cooldown_seconds: i64,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SpendingPolicyUpdatedEvent {
+    pub vault: Pubkey,
+    pub max_per_transaction: u64,
+    pub max_per_day: u64,
+    pub policy_delay: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SpendingPolicyChangeQueuedEvent {
+    pub vault: Pubkey,
+    pub max_per_transaction: u64,
+    pub max_per_day: u64,
+    pub policy_delay: i64,
+    pub effective_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SpendingPolicyChangeCancelledEvent {
+    pub vault: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalDestinationAddedEvent {
+    pub vault: Pubkey,
+    pub destination: Pubkey,
+    pub active_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalDestinationRemovedEvent {
+    pub vault: Pubkey,
+    pub destination: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OperatorGrantedEvent {
+    pub vault: Pubkey,
+    pub operator: Pubkey,
+    pub scopes: u8,
+    pub allowance: Option<u64>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OperatorRevokedEvent {
+    pub vault: Pubkey,
+    pub operator: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OwnerTransferProposedEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub pending_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OwnerTransferCancelledEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub pending_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OwnerTransferredEvent {
+    pub vault: Pubkey,
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RecoveryConfiguredEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub guardians: Vec<Pubkey>,
+    pub threshold: u8,
+    pub recovery_delay: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RecoveryInitiatedEvent {
+    pub vault: Pubkey,
+    pub guardian: Pubkey,
+    pub proposed_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RecoveryApprovedEvent {
+    pub vault: Pubkey,
+    pub guardian: Pubkey,
+    pub proposed_owner: Pubkey,
+    pub approvals: u8,
+    pub threshold: u8,
+    pub recovery_ready_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RecoveryVetoedEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub proposed_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RecoveryExecutedEvent {
+    pub vault: Pubkey,
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LockdownConfiguredEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub recovery_key: Pubkey,
+    pub lockdown_delay: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultLockedDownEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LockdownReleaseRequestedEvent {
+    pub vault: Pubkey,
+    pub recovery_key: Pubkey,
+    pub release_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LockdownLiftedEvent {
+    pub vault: Pubkey,
+    pub recovery_key: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CosignerUpdatedEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub cosigner: Option<Pubkey>,
+    pub cosigner_threshold: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MultisigCreatedEvent {
+    pub multisig: Pubkey,
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MultisigProposalCreatedEvent {
+    pub multisig: Pubkey,
+    pub proposal: Pubkey,
+    pub vault: Pubkey,
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub action: MultisigAction,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MultisigProposalApprovedEvent {
+    pub multisig: Pubkey,
+    pub proposal: Pubkey,
+    pub member: Pubkey,
+    pub approvals: u8,
+    pub threshold: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MultisigProposalExecutedEvent {
+    pub multisig: Pubkey,
+    pub proposal: Pubkey,
+    pub vault: Pubkey,
+    pub index: u64,
+    pub executor: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MultisigMembersChangedEvent {
+    pub multisig: Pubkey,
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SessionKeyRegisteredEvent {
+    pub vault: Pubkey,
+    pub session_key: Pubkey,
+    pub scopes: u8,
+    pub max_amount: u64,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SessionKeyRevokedEvent {
+    pub vault: Pubkey,
+    pub session_key: Pubkey,
+    pub used_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SharePoolInitializedEvent {
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PooledAccountingUpdatedEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub pooled: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PooledDepositEvent {
+    pub vault: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub shares: u64,
+    pub total_shares: u64,
+    pub total_assets: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PooledWithdrawEvent {
+    pub vault: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub shares: u64,
+    pub total_shares: u64,
+    pub total_assets: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolYieldAddedEvent {
+    pub pool: Pubkey,
+    pub contributor: Pubkey,
+    pub amount: u64,
+    pub total_shares: u64,
+    pub total_assets: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolStrategyConfigUpdatedEvent {
+    pub pool: Pubkey,
+    pub keeper: Pubkey,
+    pub reserve_bps: u16,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StrategyUpdatedEvent {
+    pub strategy: Pubkey,
+    pub mint: Pubkey,
+    pub program_id: Pubkey,
+    pub max_allocation_bps: u16,
+    pub active: bool,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StrategyDeployedEvent {
+    pub strategy: Pubkey,
+    pub program_id: Pubkey,
+    pub amount: u64,
+    pub allocated: u64,
+    pub deployed_assets: u64,
+    pub keeper: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StrategyRecalledEvent {
+    pub strategy: Pubkey,
+    pub program_id: Pubkey,
+    pub principal: u64,
+    pub yield_amount: u64,
+    pub allocated: u64,
+    pub deployed_assets: u64,
+    pub keeper: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StrategyWrittenDownEvent {
+    pub strategy: Pubkey,
+    pub program_id: Pubkey,
+    pub loss: u64,
+    pub allocated: u64,
+    pub deployed_assets: u64,
+    pub total_assets: u64,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InsuranceFundInitializedEvent {
+    pub insurance_fund: Pubkey,
+    pub mint: Pubkey,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InsuranceContributedEvent {
+    pub insurance_fund: Pubkey,
+    pub contributor: Pubkey,
+    pub amount: u64,
+    pub from_fees: bool,
+    pub new_balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BadDebtRecordedEvent {
+    pub bad_debt: Pubkey,
+    pub index: u64,
+    pub debtor_vault: Pubkey,
+    pub creditor_vault: Pubkey,
+    pub shortfall: u64,
+    pub caller_program: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BadDebtCoveredEvent {
+    pub bad_debt: Pubkey,
+    pub creditor_vault: Pubkey,
+    pub amount: u64,
+    pub remaining: u64,
+    pub fund_balance: u64,
+    pub caller_program: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BadDebtSocializedEvent {
+    pub bad_debt: Pubkey,
+    pub pool: Option<Pubkey>,
+    pub creditor_vault: Pubkey,
+    pub amount: u64,
+    pub assessed: u64,
+    pub loss_per_token: u128,
+    pub remaining: u64,
+    pub total_shares: u64,
+    pub total_assets: u64,
+    pub caller_program: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SocializedLossCollectedEvent {
+    pub insurance_fund: Pubkey,
+    pub vault: Pubkey,
+    pub amount: u64,
+    pub fund_balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardPoolCreatedEvent {
+    pub reward_pool: Pubkey,
+    pub collateral_mint: Pubkey,
+    pub reward_mint: Pubkey,
+    pub reward_duration: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardPoolFundedEvent {
+    pub reward_pool: Pubkey,
+    pub funder: Pubkey,
+    pub amount: u64,
+    pub reward_rate: u128,
+    pub period_finish: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardPositionOpenedEvent {
+    pub reward_pool: Pubkey,
+    pub vault: Pubkey,
+    pub staked: u64,
+    pub total_staked: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardPositionClosedEvent {
+    pub reward_pool: Pubkey,
+    pub vault: Pubkey,
+    pub total_staked: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardPositionSyncedEvent {
+    pub reward_pool: Pubkey,
+    pub vault: Pubkey,
+    pub staked: u64,
+    pub total_staked: u64,
+    pub earned: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardsClaimedEvent {
+    pub reward_pool: Pubkey,
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub staked: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultClosedEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub subaccount: u16,
+    pub token_account: Pubkey,
+    pub rent_payer: Pubkey,
+    pub withdrawn: u64,
+    pub fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CommitmentLockedEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub committed_balance: u64,
+    pub committed_until: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CommitmentReleasedEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct LockEvent {
     pub vault: Pubkey,
@@ -54,10 +609,181 @@ pub struct TransferEvent {
     pub from_vault: Pubkey,
     pub to_vault: Pubkey,
     pub amount: u64,
+    pub fee: u64,
+    pub caller_program: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultSettledEvent {
+    pub vault: Pubkey,
+    pub debited: u64,
+    pub credited: u64,
+    pub fee: u64,
     pub caller_program: Pubkey,
     pub timestamp: i64,
 }
 
+#[event]
+pub struct BatchSettledEvent {
+    pub caller_program: Pubkey,
+    pub legs: u16,
+    pub vaults: u16,
+    pub transfers: u16,
+    pub gross_amount: u64,
+    pub net_amount: u64,
+    pub fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SubaccountTransferEvent {
+    pub owner: Pubkey,
+    pub from_vault: Pubkey,
+    pub to_vault: Pubkey,
+    pub from_subaccount: u16,
+    pub to_subaccount: u16,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RateLimitUpdatedEvent {
+    pub program_id: Pubkey,
+    pub capacity: u64,
+    pub window_slots: u64,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RateLimitRemovedEvent {
+    pub program_id: Pubkey,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RateLimitExceededEvent {
+    pub program_id: Pubkey,
+    pub vault: Pubkey,
+    pub amount: u64,
+    pub remaining: u64,
+    pub capacity: u64,
+    pub window_slots: u64,
+    pub slot: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CircuitBreakerTrippedEvent {
+    pub mint: Pubkey,
+    pub status: ProtocolStatus,
+    pub net_outflow: u64,
+    pub threshold: u64,
+    pub total_value_locked: u64,
+    pub window_start: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CircuitBreakerConfiguredEvent {
+    pub max_outflow_bps: u16,
+    pub window_seconds: i64,
+    pub trip_status: ProtocolStatus,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CircuitBreakerResetEvent {
+    pub previous_status: ProtocolStatus,
+    pub tripped_mint: Pubkey,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProtocolStatusChangedEvent {
+    pub previous_status: ProtocolStatus,
+    pub new_status: ProtocolStatus,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeesConfiguredEvent {
+    pub deposit_fee_bps: u16,
+    pub withdraw_fee_bps: u16,
+    pub transfer_fee_bps: u16,
+    pub min_fee: u64,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeeManagerUpdatedEvent {
+    pub previous_fee_manager: Pubkey,
+    pub new_fee_manager: Pubkey,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeesWithdrawnEvent {
+    pub treasury: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub withdrawn_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EmergencyDeclaredEvent {
+    pub previous_status: ProtocolStatus,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EmergencyConfiguredEvent {
+    pub emergency_pause_seconds: i64,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProgramMarkedBrokenEvent {
+    pub program_id: Pubkey,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProgramBrokenClearedEvent {
+    pub program_id: Pubkey,
+    pub broken_since: i64,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EmergencyWithdrawEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub released_locked: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EmergencyUnlockNoticeEvent {
+    pub program_id: Pubkey,
+    pub vault: Pubkey,
+    pub released_locked: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct ProgramAuthorizedEvent {
     pub program_id: Pubkey,
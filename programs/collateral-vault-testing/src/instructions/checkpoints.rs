@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+use crate::state::{CollateralVault, MintState};
+use crate::constants::{MINT_STATE_SEED, VAULT_SEED};
+use crate::errors::ErrorCode;
+
+/// A pooled vault's balance follows the pool's share price, which moves on
+/// strategy yield and write-downs without touching the vault. Its
+/// checkpoints hold the value as of the last instruction that synced it.
+#[derive(Accounts)]
+pub struct BalanceAt<'info> {
+    #[account(
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, CollateralVault>,
+}
+
+#[derive(Accounts)]
+pub struct TotalValueLockedAt<'info> {
+    #[account(
+        seeds = [MINT_STATE_SEED, mint_state.mint.as_ref()],
+        bump = mint_state.bump
+    )]
+    pub mint_state: Account<'info, MintState>,
+}
+
+/// Balances for slots that have not finished yet could still change.
+fn require_past_slot(slot: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    require!(slot < clock.slot, ErrorCode::CheckpointUnavailable);
+    Ok(())
+}
+
+pub fn balance_at(ctx: Context<BalanceAt>, slot: u64) -> Result<u64> {
+    require_past_slot(slot)?;
+
+    let balance = ctx.accounts.vault.balance_checkpoints.balance_at(slot)?;
+
+    msg!("Vault {} balance at slot {}: {}", ctx.accounts.vault.key(), slot, balance);
+
+    Ok(balance)
+}
+
+pub fn total_value_locked_at(ctx: Context<TotalValueLockedAt>, slot: u64) -> Result<u64> {
+    require_past_slot(slot)?;
+
+    let mint_state = &ctx.accounts.mint_state;
+    let total_value_locked = mint_state.tvl_checkpoints.balance_at(slot)?;
+
+    msg!("Total value locked in {} at slot {}: {}", mint_state.mint, slot, total_value_locked);
+
+    Ok(total_value_locked)
+}
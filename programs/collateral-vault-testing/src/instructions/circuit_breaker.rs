@@ -0,0 +1,152 @@
+use anchor_lang::prelude::*;
+use crate::state::{MintState, ProtocolState, ProtocolStatus, VaultAuthority};
+use crate::constants::{AUTHORITY_SEED, MINT_STATE_SEED, PROTOCOL_STATE_SEED, BPS_DENOMINATOR};
+use crate::errors::ErrorCode;
+use crate::events::{
+    CircuitBreakerConfiguredEvent, CircuitBreakerResetEvent, ProtocolStatusChangedEvent,
+};
+
+#[derive(Accounts)]
+pub struct ConfigureCircuitBreaker<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+}
+
+#[derive(Accounts)]
+pub struct ResetCircuitBreaker<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    /// Accounting of the mint that tripped the breaker; its window restarts
+    /// so the outflow that tripped it does not immediately trip it again.
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, mint_state.mint.as_ref()],
+        bump = mint_state.bump
+    )]
+    pub mint_state: Option<Account<'info, MintState>>,
+}
+
+#[derive(Accounts)]
+pub struct SetProtocolStatus<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+}
+
+pub fn configure_circuit_breaker(
+    ctx: Context<ConfigureCircuitBreaker>,
+    max_outflow_bps: u16,
+    window_seconds: i64,
+    trip_status: ProtocolStatus,
+) -> Result<()> {
+    require!(
+        max_outflow_bps > 0 && max_outflow_bps as u64 <= BPS_DENOMINATOR,
+        ErrorCode::InvalidCircuitBreakerConfig
+    );
+    require!(window_seconds > 0, ErrorCode::InvalidCircuitBreakerConfig);
+    require!(
+        trip_status != ProtocolStatus::Active,
+        ErrorCode::InvalidCircuitBreakerConfig
+    );
+
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    protocol_state.configure(max_outflow_bps, window_seconds, trip_status);
+
+    let clock = Clock::get()?;
+    emit!(CircuitBreakerConfiguredEvent {
+        max_outflow_bps,
+        window_seconds,
+        trip_status,
+        admin: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Circuit breaker configured: {} bps per {}s", max_outflow_bps, window_seconds);
+
+    Ok(())
+}
+
+pub fn reset_circuit_breaker(ctx: Context<ResetCircuitBreaker>) -> Result<()> {
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    let previous_status = protocol_state.status;
+    let tripped_mint = protocol_state.tripped_mint;
+
+    let clock = Clock::get()?;
+    if tripped_mint != Pubkey::default() {
+        let mint_state = ctx.accounts.mint_state
+            .as_mut()
+            .ok_or(ErrorCode::InvalidMintState)?;
+        require_keys_eq!(mint_state.mint, tripped_mint, ErrorCode::InvalidMintState);
+        mint_state.reset_window(clock.unix_timestamp);
+    }
+    protocol_state.set_status(ProtocolStatus::Active, clock.unix_timestamp);
+
+    emit!(CircuitBreakerResetEvent {
+        previous_status,
+        tripped_mint,
+        admin: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Circuit breaker reset, protocol active");
+
+    Ok(())
+}
+
+pub fn set_protocol_status(ctx: Context<SetProtocolStatus>, status: ProtocolStatus) -> Result<()> {
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    let previous_status = protocol_state.status;
+
+    let clock = Clock::get()?;
+    protocol_state.set_status(status, clock.unix_timestamp);
+
+    emit!(ProtocolStatusChangedEvent {
+        previous_status,
+        new_status: status,
+        admin: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Protocol status: {:?} -> {:?}", previous_status, status);
+
+    Ok(())
+}
@@ -0,0 +1,201 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, Token, TokenAccount, Transfer};
+use crate::state::{CollateralVault, MintState, OwnerIndex, ProtocolState};
+use crate::constants::{VAULT_SEED, OWNER_INDEX_SEED, PROTOCOL_STATE_SEED, MINT_STATE_SEED};
+use crate::errors::ErrorCode;
+use crate::events::VaultClosedEvent;
+use crate::instructions::shared::{
+    enforce_spending_policy, fee_treasury, record_outflow, withdrawal_cooldown,
+};
+
+#[derive(Accounts)]
+pub struct CloseVault<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        close = rent_payer,
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    pub cosigner: Option<Signer<'info>>,
+
+    /// CHECK: Receives the vault and token account rent; must match the vault record
+    #[account(
+        mut,
+        address = vault.rent_payer @ ErrorCode::InvalidRentPayer
+    )]
+    pub rent_payer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [OWNER_INDEX_SEED, user.key().as_ref()],
+        bump = owner_index.bump
+    )]
+    pub owner_index: Account<'info, OwnerIndex>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, vault_token_account.mint.as_ref()],
+        bump = mint_state.bump
+    )]
+    pub mint_state: Account<'info, MintState>,
+
+    #[account(mut)]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Withdrawal cooldown PDA for the vault mint, validated in handler
+    pub cooldown_config: UncheckedAccount<'info>,
+
+    /// CHECK: Owner spending policy PDA for the vault, validated in handler
+    #[account(mut)]
+    pub spending_policy: UncheckedAccount<'info>,
+
+    /// Protocol treasury for the vault mint; required while a withdraw fee is set.
+    #[account(mut)]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<CloseVault>) -> Result<()> {
+    let clock = Clock::get()?;
+    let vault = &mut ctx.accounts.vault;
+
+    vault.require_not_locked_down()?;
+    vault.require_unpooled()?;
+    // The sweep below would otherwise hand written-off tokens to the owner.
+    vault.absorb_socialized_loss(&ctx.accounts.mint_state, &clock)?;
+    require!(vault.loss_uncollected == 0, ErrorCode::SocializedLossUncollected);
+    require!(
+        vault.locked_balance == 0 && vault.committed_balance == 0,
+        ErrorCode::VaultHasLockedBalance
+    );
+    require!(vault.pending_withdrawal == 0, ErrorCode::PendingWithdrawalExists);
+    require!(vault.reward_positions == 0, ErrorCode::RewardPositionsOpen);
+
+    let user_key = ctx.accounts.user.key();
+    let seed_owner = vault.seed_owner;
+    let subaccount = vault.subaccount.to_le_bytes();
+    let seeds = &[
+        VAULT_SEED,
+        seed_owner.as_ref(),
+        subaccount.as_ref(),
+        &[vault.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    let withdrawn = vault.total_balance;
+    let mut fee = 0;
+    if withdrawn > 0 {
+        ctx.accounts.protocol_state.require_withdrawals_enabled()?;
+
+        // Closing must not become a way around the withdrawal cooldown.
+        let cooldown = withdrawal_cooldown(
+            &ctx.accounts.protocol_state,
+            &ctx.accounts.cooldown_config,
+            &ctx.accounts.vault_token_account.mint,
+        )?;
+        require!(cooldown == 0, ErrorCode::WithdrawalCooldownRequired);
+        vault.require_cosigner(withdrawn, ctx.accounts.cosigner.as_ref().map(|c| c.key()))?;
+
+        enforce_spending_policy(
+            &ctx.accounts.spending_policy,
+            vault.key(),
+            vault.owner,
+            vault.delegation_epoch,
+            Some(&ctx.accounts.user_token_account),
+            withdrawn,
+            false,
+        )?;
+
+        let protocol_state = &ctx.accounts.protocol_state;
+        fee = protocol_state.fee_for(protocol_state.withdraw_fee_bps, withdrawn)?;
+        if fee > 0 {
+            let treasury = fee_treasury(
+                ctx.accounts.treasury_token_account.as_ref(),
+                &protocol_state.key(),
+                &ctx.accounts.vault_token_account.mint,
+            )?;
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.vault_token_account.to_account_info(),
+                        to: treasury.to_account_info(),
+                        authority: vault.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                fee,
+            )?;
+        }
+
+        vault.withdraw(withdrawn, &clock)?;
+        record_outflow(&mut ctx.accounts.protocol_state, &mut ctx.accounts.mint_state, withdrawn, true)?;
+        ctx.accounts.protocol_state.record_fee(fee)?;
+    }
+
+    // Sweep the full token balance, including anything sent to the vault
+    // outside of `deposit`, so the token account can be closed.
+    let swept = ctx.accounts.vault_token_account.amount - fee;
+    if swept > 0 {
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault_token_account.to_account_info(),
+                    to: ctx.accounts.user_token_account.to_account_info(),
+                    authority: vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            swept,
+        )?;
+    }
+
+    token::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.vault_token_account.to_account_info(),
+            destination: ctx.accounts.rent_payer.to_account_info(),
+            authority: vault.to_account_info(),
+        },
+        signer_seeds,
+    ))?;
+
+    ctx.accounts.owner_index.remove_subaccount(vault.key())?;
+
+    emit!(VaultClosedEvent {
+        vault: vault.key(),
+        owner: user_key,
+        subaccount: vault.subaccount,
+        token_account: ctx.accounts.vault_token_account.key(),
+        rent_payer: ctx.accounts.rent_payer.key(),
+        withdrawn: swept,
+        fee,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Vault closed for user: {}", user_key);
+    msg!("Withdrew {} tokens (fee: {})", swept, fee);
+
+    Ok(())
+}
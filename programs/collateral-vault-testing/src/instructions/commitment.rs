@@ -0,0 +1,132 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+use crate::state::{CollateralVault, SharePool};
+use crate::constants::{SHARE_POOL_SEED, VAULT_SEED};
+use crate::errors::ErrorCode;
+use crate::instructions::shared::{pooled_share_pool, sync_reward_positions};
+use crate::events::{CommitmentLockedEvent, CommitmentReleasedEvent};
+
+/// Reward positions of the vault follow in `remaining_accounts`, as for
+/// `LockCollateral`.
+#[derive(Accounts)]
+pub struct CommitLock<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    /// Share pool behind the vault; required when the vault uses share accounting.
+    #[account(
+        mut,
+        seeds = [SHARE_POOL_SEED, share_pool.mint.as_ref()],
+        bump = share_pool.bump
+    )]
+    pub share_pool: Option<Account<'info, SharePool>>,
+
+    /// Vault token account, matched against the share pool mint.
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+}
+
+/// Reward positions of the vault follow in `remaining_accounts`, as for
+/// `LockCollateral`.
+#[derive(Accounts)]
+pub struct ReleaseCommitment<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    /// Share pool behind the vault; required when the vault uses share accounting.
+    #[account(
+        mut,
+        seeds = [SHARE_POOL_SEED, share_pool.mint.as_ref()],
+        bump = share_pool.bump
+    )]
+    pub share_pool: Option<Account<'info, SharePool>>,
+
+    /// Vault token account, matched against the share pool mint.
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+}
+
+pub fn commit_lock<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CommitLock<'info>>,
+    amount: u64,
+    until: i64,
+) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+
+    let vault = &mut ctx.accounts.vault;
+    let share_pool = pooled_share_pool(
+        vault,
+        ctx.accounts.share_pool.as_mut(),
+        ctx.accounts.vault_token_account.as_ref(),
+    )?;
+
+    require!(
+        vault.available_balance >= amount,
+        ErrorCode::InsufficientAvailableBalance
+    );
+
+    let clock = Clock::get()?;
+    vault.commit_lock(amount, until, &clock)?;
+    if let Some(share_pool) = share_pool {
+        share_pool.commit_assets(amount)?;
+    }
+    sync_reward_positions(vault, ctx.remaining_accounts, &clock)?;
+
+    emit!(CommitmentLockedEvent {
+        vault: vault.key(),
+        owner: ctx.accounts.user.key(),
+        amount,
+        committed_balance: vault.committed_balance,
+        committed_until: vault.committed_until,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Committed {} tokens until {}", amount, vault.committed_until);
+    msg!("Committed balance: {}", vault.committed_balance);
+
+    Ok(())
+}
+
+pub fn release_commitment<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ReleaseCommitment<'info>>,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(vault.committed_balance > 0, ErrorCode::InvalidAmount);
+
+    let share_pool = pooled_share_pool(
+        vault,
+        ctx.accounts.share_pool.as_mut(),
+        ctx.accounts.vault_token_account.as_ref(),
+    )?;
+
+    let clock = Clock::get()?;
+    let amount = vault.release_commitment(&clock)?;
+    if let Some(share_pool) = share_pool {
+        share_pool.release_committed_assets(amount)?;
+    }
+    sync_reward_positions(vault, ctx.remaining_accounts, &clock)?;
+
+    emit!(CommitmentReleasedEvent {
+        vault: vault.key(),
+        owner: ctx.accounts.user.key(),
+        amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Released {} committed tokens", amount);
+    msg!("Available balance: {}", vault.available_balance);
+
+    Ok(())
+}
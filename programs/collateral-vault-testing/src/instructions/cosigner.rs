@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use crate::state::CollateralVault;
+use crate::constants::VAULT_SEED;
+use crate::errors::ErrorCode;
+use crate::events::CosignerUpdatedEvent;
+
+#[derive(Accounts)]
+pub struct SetCosigner<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    /// The co-signer currently on the vault; required to change or remove it.
+    pub current_cosigner: Option<Signer<'info>>,
+}
+
+pub fn set_cosigner(
+    ctx: Context<SetCosigner>,
+    cosigner: Option<Pubkey>,
+    cosigner_threshold: u64,
+) -> Result<()> {
+    if let Some(new_cosigner) = cosigner {
+        require!(
+            new_cosigner != Pubkey::default() && new_cosigner != ctx.accounts.user.key(),
+            ErrorCode::InvalidCosigner
+        );
+    }
+
+    let vault = &mut ctx.accounts.vault;
+
+    // Otherwise a stolen owner key could simply drop the second factor.
+    if let Some(current) = vault.cosigner {
+        require!(
+            ctx.accounts.current_cosigner.as_ref().map(|c| c.key()) == Some(current),
+            ErrorCode::CosignerRequired
+        );
+    }
+
+    vault.cosigner = cosigner;
+    vault.cosigner_threshold = cosigner_threshold;
+
+    let clock = Clock::get()?;
+    emit!(CosignerUpdatedEvent {
+        vault: vault.key(),
+        owner: ctx.accounts.user.key(),
+        cosigner,
+        cosigner_threshold,
+        timestamp: clock.unix_timestamp,
+    });
+
+    match cosigner {
+        Some(key) => msg!("✅ Co-signer set: {} above {} tokens", key, cosigner_threshold),
+        None => msg!("✅ Co-signer removed"),
+    }
+
+    Ok(())
+}
@@ -1,8 +1,12 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
-use crate::state::CollateralVault;
-use crate::constants::VAULT_SEED;
+use crate::state::{CollateralVault, MintState, Multisig, ProtocolState, SessionKey, VaultOperator};
+use crate::constants::{
+    VAULT_SEED, OPERATOR_SEED, PROTOCOL_STATE_SEED, MINT_STATE_SEED, SESSION_SEED,
+    OPERATOR_SCOPE_DEPOSIT, SESSION_SCOPE_DEPOSIT,
+};
 use crate::errors::ErrorCode;
+use crate::instructions::shared::fee_treasury;
 use crate::events::DepositEvent;
 
 #[derive(Accounts)]
@@ -12,12 +16,45 @@ pub struct Deposit<'info> {
 
     #[account(
         mut,
-        seeds = [VAULT_SEED, user.key().as_ref()],
-        bump = vault.bump,
-        constraint= vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump
     )]
     pub vault: Account<'info, CollateralVault>,
 
+    #[account(
+        seeds = [OPERATOR_SEED, vault.key().as_ref(), user.key().as_ref()],
+        bump = vault_operator.bump,
+        constraint = vault_operator.delegation_epoch == vault.delegation_epoch @ ErrorCode::DelegationRevoked
+    )]
+    pub vault_operator: Option<Account<'info, VaultOperator>>,
+
+    #[account(
+        constraint = multisig.key() == vault.owner @ ErrorCode::UnauthorizedOwner
+    )]
+    pub multisig: Option<Account<'info, Multisig>>,
+
+    #[account(
+        mut,
+        seeds = [SESSION_SEED, vault.key().as_ref(), user.key().as_ref()],
+        bump = session.bump,
+        constraint = session.delegation_epoch == vault.delegation_epoch @ ErrorCode::DelegationRevoked
+    )]
+    pub session: Option<Account<'info, SessionKey>>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, vault_token_account.mint.as_ref()],
+        bump = mint_state.bump
+    )]
+    pub mint_state: Account<'info, MintState>,
+
     #[account(mut)]
     pub user_token_account: Account<'info, TokenAccount>,
 
@@ -27,11 +64,60 @@ pub struct Deposit<'info> {
     )]
     pub vault_token_account: Account<'info, TokenAccount>,
 
+    /// Protocol treasury for the vault mint; required while a deposit fee is set.
+    #[account(mut)]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+
     pub token_program: Program<'info, Token>,
 }
 
 pub fn handler(ctx: Context<Deposit>, amount: u64) -> Result<()> {
     require!(amount > 0, ErrorCode::InvalidAmount);
+    ctx.accounts.protocol_state.require_active()?;
+    ctx.accounts.vault.require_unpooled()?;
+
+    let user_key = ctx.accounts.user.key();
+    let is_multisig_member = ctx.accounts.multisig
+        .as_ref()
+        .is_some_and(|m| m.is_member(&user_key));
+    if user_key != ctx.accounts.vault.owner && !is_multisig_member {
+        if let Some(session) = ctx.accounts.session.as_mut() {
+            let clock = Clock::get()?;
+            session.authorize(SESSION_SCOPE_DEPOSIT, amount, clock.unix_timestamp)?;
+        } else {
+            let operator = ctx.accounts.vault_operator
+                .as_ref()
+                .ok_or(ErrorCode::UnauthorizedOwner)?;
+            require!(
+                operator.has_scope(OPERATOR_SCOPE_DEPOSIT),
+                ErrorCode::UnauthorizedOperator
+            );
+        }
+    }
+
+    let protocol_state = &ctx.accounts.protocol_state;
+    let fee = protocol_state.fee_for(protocol_state.deposit_fee_bps, amount)?;
+    let credited = amount - fee;
+    require!(credited > 0, ErrorCode::InvalidAmount);
+
+    if fee > 0 {
+        let treasury = fee_treasury(
+            ctx.accounts.treasury_token_account.as_ref(),
+            &protocol_state.key(),
+            &ctx.accounts.vault_token_account.mint,
+        )?;
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.user_token_account.to_account_info(),
+                    to: treasury.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            fee,
+        )?;
+    }
 
     token::transfer(
         CpiContext::new(
@@ -42,23 +128,27 @@ pub fn handler(ctx: Context<Deposit>, amount: u64) -> Result<()> {
                 authority: ctx.accounts.user.to_account_info(),
             },
         ),
-        amount,
+        credited,
     )?;
 
+    let clock = Clock::get()?;
     let vault = &mut ctx.accounts.vault;
-    vault.deposit(amount)?;
+    vault.absorb_socialized_loss(&ctx.accounts.mint_state, &clock)?;
+    vault.deposit(credited, &clock)?;
 
-    let clock = Clock::get()?;
+    ctx.accounts.mint_state.record_inflow(&ctx.accounts.protocol_state, credited, &clock)?;
+    ctx.accounts.protocol_state.record_fee(fee)?;
     emit!(DepositEvent {
         vault: vault.key(),
         user: ctx.accounts.user.key(),
         amount,
+        fee,
         new_total_balance: vault.total_balance,
         new_available_balance: vault.available_balance,
         timestamp: clock.unix_timestamp,
     });
 
-    msg!("✅ Deposited {} tokens", amount);
+    msg!("✅ Deposited {} tokens (fee: {})", credited, fee);
     msg!("New total balance: {}", vault.total_balance);
 
     Ok(())
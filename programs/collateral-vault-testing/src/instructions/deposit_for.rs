@@ -0,0 +1,191 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Mint, Token, TokenAccount, Transfer},
+};
+use crate::state::{CollateralVault, MintState, OwnerIndex, ProtocolState};
+use crate::constants::{
+    VAULT_SEED, OWNER_INDEX_SEED, PROTOCOL_STATE_SEED, MINT_STATE_SEED, MIN_DEPOSIT_AMOUNT,
+};
+use crate::errors::ErrorCode;
+use crate::events::{DepositForEvent, VaultInitializedEvent};
+use crate::instructions::shared::fee_treasury;
+
+#[derive(Accounts)]
+#[instruction(beneficiary: Pubkey, subaccount: u16)]
+pub struct DepositFor<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = CollateralVault::LEN,
+        seeds = [VAULT_SEED, beneficiary.as_ref(), subaccount.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = OwnerIndex::LEN,
+        seeds = [OWNER_INDEX_SEED, beneficiary.as_ref()],
+        bump
+    )]
+    pub owner_index: Account<'info, OwnerIndex>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = MintState::LEN,
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub mint_state: Account<'info, MintState>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = vault
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub payer_token_account: Account<'info, TokenAccount>,
+
+    pub mint: Account<'info, Mint>,
+
+    /// Protocol treasury for the vault mint; required while a deposit fee is set.
+    #[account(mut)]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<DepositFor>,
+    beneficiary: Pubkey,
+    subaccount: u16,
+    amount: u64,
+) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    ctx.accounts.protocol_state.require_active()?;
+    ctx.accounts.vault.require_unpooled()?;
+
+    // A vault that has never been initialized still has a zeroed owner.
+    let vault_created = ctx.accounts.vault.owner == Pubkey::default();
+    if vault_created {
+        require!(amount >= MIN_DEPOSIT_AMOUNT, ErrorCode::DepositBelowMinimum);
+    } else {
+        require!(
+            ctx.accounts.vault_token_account.key() == ctx.accounts.vault.token_account,
+            ErrorCode::UnauthorizedOwner
+        );
+    }
+
+    let protocol_state = &ctx.accounts.protocol_state;
+    let fee = protocol_state.fee_for(protocol_state.deposit_fee_bps, amount)?;
+    let credited = amount - fee;
+    require!(credited > 0, ErrorCode::InvalidAmount);
+
+    if fee > 0 {
+        let treasury = fee_treasury(
+            ctx.accounts.treasury_token_account.as_ref(),
+            &protocol_state.key(),
+            &ctx.accounts.mint.key(),
+        )?;
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.payer_token_account.to_account_info(),
+                    to: treasury.to_account_info(),
+                    authority: ctx.accounts.payer.to_account_info(),
+                },
+            ),
+            fee,
+        )?;
+    }
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.payer_token_account.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.payer.to_account_info(),
+            },
+        ),
+        credited,
+    )?;
+
+    let vault = &mut ctx.accounts.vault;
+    let clock = Clock::get()?;
+
+    if vault_created {
+        vault.initialize(
+            beneficiary,
+            subaccount,
+            ctx.accounts.vault_token_account.key(),
+            ctx.accounts.payer.key(),
+            credited,
+            ctx.accounts.mint_state.loss_per_token,
+            &clock,
+            ctx.bumps.vault,
+        );
+
+        ctx.accounts.owner_index.add_subaccount(
+            beneficiary,
+            subaccount,
+            vault.key(),
+            ctx.bumps.owner_index,
+        )?;
+
+        emit!(VaultInitializedEvent {
+            vault: vault.key(),
+            owner: beneficiary,
+            subaccount,
+            token_account: ctx.accounts.vault_token_account.key(),
+            rent_payer: ctx.accounts.payer.key(),
+            initial_deposit: credited,
+            fee,
+            timestamp: clock.unix_timestamp,
+        });
+    } else {
+        vault.absorb_socialized_loss(&ctx.accounts.mint_state, &clock)?;
+        vault.deposit(credited, &clock)?;
+    }
+
+    let mint_state = &mut ctx.accounts.mint_state;
+    mint_state.initialize(ctx.accounts.mint.key(), clock.unix_timestamp, ctx.bumps.mint_state);
+    mint_state.record_inflow(&ctx.accounts.protocol_state, credited, &clock)?;
+    ctx.accounts.protocol_state.record_fee(fee)?;
+
+    emit!(DepositForEvent {
+        vault: vault.key(),
+        payer: ctx.accounts.payer.key(),
+        beneficiary: vault.owner,
+        amount,
+        fee,
+        vault_created,
+        new_total_balance: vault.total_balance,
+        new_available_balance: vault.available_balance,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Deposited {} tokens for: {} (fee: {})", credited, vault.owner, fee);
+    msg!("New total balance: {}", vault.total_balance);
+
+    Ok(())
+}
@@ -0,0 +1,436 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::state::{
+    BrokenProgram, CollateralVault, MintState, ProgramLock, ProtocolState, SharePool, Strategy,
+    VaultAuthority,
+};
+use crate::constants::{
+    VAULT_SEED, AUTHORITY_SEED, PROTOCOL_STATE_SEED, MINT_STATE_SEED, BROKEN_PROGRAM_SEED,
+    SHARE_POOL_SEED, STRATEGY_SEED,
+};
+use crate::errors::ErrorCode;
+use crate::events::{
+    EmergencyConfiguredEvent, EmergencyDeclaredEvent, EmergencyUnlockNoticeEvent,
+    EmergencyWithdrawEvent, ProgramBrokenClearedEvent, ProgramMarkedBrokenEvent,
+};
+use crate::instructions::shared::{pooled_share_pool, record_outflow, sync_reward_positions};
+use crate::instructions::strategy::recall_strategy_funds;
+
+#[derive(Accounts)]
+pub struct DeclareEmergency<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureEmergency<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+}
+
+#[derive(Accounts)]
+#[instruction(program_id: Pubkey)]
+pub struct MarkProgramBroken<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = BrokenProgram::LEN,
+        seeds = [BROKEN_PROGRAM_SEED, program_id.as_ref()],
+        bump
+    )]
+    pub broken_program: Account<'info, BrokenProgram>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClearProgramBroken<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [BROKEN_PROGRAM_SEED, broken_program.program_id.as_ref()],
+        bump = broken_program.bump
+    )]
+    pub broken_program: Account<'info, BrokenProgram>,
+}
+
+/// Reward positions of the vault follow in `remaining_accounts`, as for
+/// `LockCollateral`.
+#[derive(Accounts)]
+pub struct EmergencyWithdraw<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    /// Broken report for an authorized program; once it is older than the
+    /// emergency pause period the owner may take out the collateral that
+    /// program holds locked, whatever the protocol status.
+    #[account(
+        seeds = [BROKEN_PROGRAM_SEED, broken_program.program_id.as_ref()],
+        bump = broken_program.bump
+    )]
+    pub broken_program: Option<Account<'info, BrokenProgram>>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, vault_token_account.mint.as_ref()],
+        bump = mint_state.bump
+    )]
+    pub mint_state: Account<'info, MintState>,
+
+    #[account(mut)]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// Share pool behind the vault; required when the vault uses share accounting.
+    #[account(
+        mut,
+        seeds = [SHARE_POOL_SEED, share_pool.mint.as_ref()],
+        bump = share_pool.bump
+    )]
+    pub share_pool: Option<Account<'info, SharePool>>,
+
+    /// Pool token account a pooled vault is paid from.
+    #[account(mut)]
+    pub pool_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Strategy to recall from when the pool's idle tokens fall short of
+    /// the pooled vault's balance.
+    #[account(
+        mut,
+        seeds = [STRATEGY_SEED, strategy.mint.as_ref(), strategy.program_id.as_ref()],
+        bump = strategy.bump
+    )]
+    pub strategy: Option<Account<'info, Strategy>>,
+
+    /// CHECK: Strategy program, matched against the strategy account in handler
+    pub strategy_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Strategy-owned state, validated by the strategy program
+    #[account(mut)]
+    pub strategy_state: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Strategy-owned token account, validated by the strategy program
+    #[account(mut)]
+    pub strategy_token_account: Option<UncheckedAccount<'info>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn declare_emergency(ctx: Context<DeclareEmergency>) -> Result<()> {
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    let previous_status = protocol_state.status;
+
+    let clock = Clock::get()?;
+    protocol_state.declare_emergency(clock.unix_timestamp);
+
+    emit!(EmergencyDeclaredEvent {
+        previous_status,
+        admin: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("🚨 Emergency mode declared, protocol paused and locks no longer bind withdrawals");
+
+    Ok(())
+}
+
+pub fn configure_emergency(
+    ctx: Context<ConfigureEmergency>,
+    emergency_pause_seconds: i64,
+) -> Result<()> {
+    require!(emergency_pause_seconds > 0, ErrorCode::InvalidEmergencyConfig);
+
+    ctx.accounts.protocol_state.emergency_pause_seconds = emergency_pause_seconds;
+
+    let clock = Clock::get()?;
+    emit!(EmergencyConfiguredEvent {
+        emergency_pause_seconds,
+        admin: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Emergency mode opens after {}s of pause", emergency_pause_seconds);
+
+    Ok(())
+}
+
+pub fn mark_program_broken(ctx: Context<MarkProgramBroken>, program_id: Pubkey) -> Result<()> {
+    require!(
+        ctx.accounts.authority.is_authorized(&program_id),
+        ErrorCode::ProgramNotAuthorized
+    );
+
+    let clock = Clock::get()?;
+    let broken_program = &mut ctx.accounts.broken_program;
+    broken_program.program_id = program_id;
+    broken_program.broken_since = clock.unix_timestamp;
+    broken_program.bump = ctx.bumps.broken_program;
+
+    emit!(ProgramMarkedBrokenEvent {
+        program_id,
+        admin: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("🚨 Program marked broken: {}", program_id);
+
+    Ok(())
+}
+
+pub fn clear_program_broken(ctx: Context<ClearProgramBroken>) -> Result<()> {
+    let broken_program = &ctx.accounts.broken_program;
+
+    let clock = Clock::get()?;
+    emit!(ProgramBrokenClearedEvent {
+        program_id: broken_program.program_id,
+        broken_since: broken_program.broken_since,
+        admin: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Program no longer marked broken: {}", broken_program.program_id);
+
+    Ok(())
+}
+
+pub fn emergency_withdraw<'info>(
+    ctx: Context<'_, '_, 'info, 'info, EmergencyWithdraw<'info>>,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let protocol_state = &ctx.accounts.protocol_state;
+    // A declared emergency empties the vault; a broken program only frees
+    // the collateral it holds locked.
+    let full_exit = protocol_state.is_emergency(clock.unix_timestamp);
+    let broken_program_id = ctx.accounts.broken_program
+        .as_ref()
+        .filter(|broken_program| {
+            broken_program.opens_emergency(protocol_state.emergency_pause_seconds, clock.unix_timestamp)
+        })
+        .map(|broken_program| broken_program.program_id);
+    require!(
+        full_exit || broken_program_id.is_some(),
+        ErrorCode::EmergencyModeInactive
+    );
+
+    let vault = &mut ctx.accounts.vault;
+    vault.require_not_locked_down()?;
+    vault.absorb_socialized_loss(&ctx.accounts.mint_state, &clock)?;
+    let share_pool = pooled_share_pool(
+        vault,
+        ctx.accounts.share_pool.as_mut(),
+        Some(&ctx.accounts.vault_token_account),
+    )?;
+    let amount = match broken_program_id {
+        Some(program_id) if !full_exit => vault.program_locked_balance(&program_id),
+        _ => vault.total_balance,
+    };
+    require!(amount > 0, ErrorCode::InvalidAmount);
+
+    let user_key = ctx.accounts.user.key();
+    if let Some(share_pool) = share_pool {
+        let pool_token_account = ctx.accounts.pool_token_account
+            .as_mut()
+            .filter(|account| account.key() == share_pool.token_account)
+            .ok_or(ErrorCode::InvalidSharePool)?;
+
+        // Whatever the pool cannot pay from idle tokens comes back from the
+        // strategy the owner points at.
+        let shortfall = amount.saturating_sub(share_pool.idle_assets());
+        if shortfall > 0 {
+            let (
+                Some(strategy),
+                Some(strategy_program),
+                Some(strategy_state),
+                Some(strategy_token_account),
+            ) = (
+                ctx.accounts.strategy.as_mut(),
+                ctx.accounts.strategy_program.as_ref(),
+                ctx.accounts.strategy_state.as_ref(),
+                ctx.accounts.strategy_token_account.as_ref(),
+            ) else {
+                return err!(ErrorCode::InsufficientPoolLiquidity);
+            };
+            require!(
+                strategy.mint == share_pool.mint && strategy_program.key() == strategy.program_id,
+                ErrorCode::InvalidStrategyConfig
+            );
+            let (_, yield_amount) = recall_strategy_funds(
+                shortfall,
+                share_pool,
+                pool_token_account,
+                strategy,
+                strategy_program,
+                strategy_state,
+                strategy_token_account,
+                &ctx.accounts.token_program,
+            )?;
+            if yield_amount > 0 {
+                ctx.accounts.mint_state.record_inflow(&ctx.accounts.protocol_state, yield_amount, &clock)?;
+            }
+        }
+
+        if full_exit {
+            share_pool.release_reserves(
+                vault.locked_balance,
+                vault.committed_balance,
+                vault.pending_withdrawal,
+            );
+            share_pool.burn_shares(vault.shares, amount)?;
+        } else {
+            share_pool.unlock_assets(amount)?;
+        }
+
+        let mint = share_pool.mint;
+        let seeds = &[SHARE_POOL_SEED, mint.as_ref(), &[share_pool.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: pool_token_account.to_account_info(),
+                    to: ctx.accounts.user_token_account.to_account_info(),
+                    authority: share_pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+    } else {
+        let seed_owner = vault.seed_owner;
+        let subaccount = vault.subaccount.to_le_bytes();
+        let seeds = &[
+            VAULT_SEED,
+            seed_owner.as_ref(),
+            subaccount.as_ref(),
+            &[vault.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault_token_account.to_account_info(),
+                    to: ctx.accounts.user_token_account.to_account_info(),
+                    authority: vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+    }
+
+    let released_locks = match broken_program_id {
+        Some(program_id) if !full_exit => {
+            vault.release_program_lock(&program_id, &clock)?;
+            match ctx.accounts.share_pool.as_mut().filter(|_| vault.pooled) {
+                Some(share_pool) => {
+                    vault.redeem_pooled(share_pool, amount, &clock)?;
+                }
+                None => vault.withdraw(amount, &clock)?,
+            }
+            vec![ProgramLock { program_id, amount }]
+        }
+        _ => vault.emergency_withdraw(&clock)?,
+    };
+    let released_locked: u64 = released_locks.iter().map(|lock| lock.amount).sum();
+    sync_reward_positions(vault, ctx.remaining_accounts, &clock)?;
+    record_outflow(&mut ctx.accounts.protocol_state, &mut ctx.accounts.mint_state, amount, true)?;
+
+    emit!(EmergencyWithdrawEvent {
+        vault: vault.key(),
+        owner: user_key,
+        amount,
+        released_locked,
+        timestamp: clock.unix_timestamp,
+    });
+
+    // Programs holding locks against this vault must learn that the
+    // collateral backing their positions is gone.
+    for lock in released_locks.iter() {
+        emit!(EmergencyUnlockNoticeEvent {
+            program_id: lock.program_id,
+            vault: vault.key(),
+            released_locked: lock.amount,
+            timestamp: clock.unix_timestamp,
+        });
+    }
+
+    msg!("🚨 Emergency withdrew {} tokens", amount);
+    msg!("Released locked balance: {}", released_locked);
+
+    Ok(())
+}
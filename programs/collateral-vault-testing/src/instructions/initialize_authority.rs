@@ -1,6 +1,9 @@
 use anchor_lang::prelude::*;
-use crate::state::VaultAuthority;
-use crate::constants::AUTHORITY_SEED;
+use crate::state::{ProtocolState, ProtocolStatus, VaultAuthority};
+use crate::constants::{
+    AUTHORITY_SEED, PROTOCOL_STATE_SEED, DEFAULT_MAX_OUTFLOW_BPS, DEFAULT_OUTFLOW_WINDOW_SECONDS,
+    DEFAULT_EMERGENCY_PAUSE_SECONDS,
+};
 
 #[derive(Accounts)]
 pub struct InitializeAuthority<'info> {
@@ -16,6 +19,15 @@ pub struct InitializeAuthority<'info> {
     )]
     pub authority: Account<'info, VaultAuthority>,
 
+    #[account(
+        init,
+        payer = admin,
+        space = ProtocolState::LEN,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -25,8 +37,19 @@ pub fn handler(ctx: Context<InitializeAuthority>) -> Result<()> {
 
     authority.initialize(ctx.accounts.admin.key(), bump);
 
+    let clock = Clock::get()?;
+    ctx.accounts.protocol_state.initialize(
+        DEFAULT_MAX_OUTFLOW_BPS,
+        DEFAULT_OUTFLOW_WINDOW_SECONDS,
+        ProtocolStatus::WithdrawOnly,
+        DEFAULT_EMERGENCY_PAUSE_SECONDS,
+        ctx.accounts.admin.key(),
+        clock.unix_timestamp,
+        ctx.bumps.protocol_state,
+    );
+
     msg!("✅ Vault Authority initialized");
     msg!("Admin: {}", ctx.accounts.admin.key());
 
     Ok(())
-}
\ No newline at end of file
+}
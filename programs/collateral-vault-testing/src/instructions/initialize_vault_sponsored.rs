@@ -0,0 +1,170 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Mint, Token, TokenAccount, Transfer},
+};
+use crate::state::{CollateralVault, MintState, OwnerIndex, ProtocolState};
+use crate::constants::{
+    VAULT_SEED, OWNER_INDEX_SEED, PROTOCOL_STATE_SEED, MINT_STATE_SEED, MIN_DEPOSIT_AMOUNT,
+};
+use crate::errors::ErrorCode;
+use crate::events::VaultInitializedEvent;
+use crate::instructions::shared::fee_treasury;
+
+#[derive(Accounts)]
+#[instruction(subaccount: u16)]
+pub struct InitializeVaultSponsored<'info> {
+    /// Pays the vault and token account rent; refunded when the vault closes.
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+
+    pub user: Signer<'info>,
+
+    #[account(
+        init,
+        payer = sponsor,
+        space = CollateralVault::LEN,
+        seeds = [VAULT_SEED, user.key().as_ref(), subaccount.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        init_if_needed,
+        payer = sponsor,
+        space = OwnerIndex::LEN,
+        seeds = [OWNER_INDEX_SEED, user.key().as_ref()],
+        bump
+    )]
+    pub owner_index: Account<'info, OwnerIndex>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        init_if_needed,
+        payer = sponsor,
+        space = MintState::LEN,
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub mint_state: Account<'info, MintState>,
+
+    #[account(
+        init_if_needed,
+        payer = sponsor,
+        associated_token::mint = mint,
+        associated_token::authority = vault
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    pub mint: Account<'info, Mint>,
+
+    /// Protocol treasury for the vault mint; required while a deposit fee is set.
+    #[account(mut)]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<InitializeVaultSponsored>,
+    subaccount: u16,
+    initial_deposit: u64,
+) -> Result<()> {
+    require!(
+        initial_deposit >= MIN_DEPOSIT_AMOUNT,
+        ErrorCode::DepositBelowMinimum
+    );
+    ctx.accounts.protocol_state.require_active()?;
+
+    let protocol_state = &ctx.accounts.protocol_state;
+    let fee = protocol_state.fee_for(protocol_state.deposit_fee_bps, initial_deposit)?;
+    let credited = initial_deposit - fee;
+    require!(credited > 0, ErrorCode::InvalidAmount);
+
+    if fee > 0 {
+        let treasury = fee_treasury(
+            ctx.accounts.treasury_token_account.as_ref(),
+            &protocol_state.key(),
+            &ctx.accounts.mint.key(),
+        )?;
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.user_token_account.to_account_info(),
+                    to: treasury.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            fee,
+        )?;
+    }
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        credited,
+    )?;
+
+    let vault = &mut ctx.accounts.vault;
+    let clock = Clock::get()?;
+    let bump = ctx.bumps.vault;
+
+    let mint_state = &mut ctx.accounts.mint_state;
+    mint_state.initialize(ctx.accounts.mint.key(), clock.unix_timestamp, ctx.bumps.mint_state);
+    mint_state.record_inflow(&ctx.accounts.protocol_state, credited, &clock)?;
+    ctx.accounts.protocol_state.record_fee(fee)?;
+
+    vault.initialize(
+        ctx.accounts.user.key(),
+        subaccount,
+        ctx.accounts.vault_token_account.key(),
+        ctx.accounts.sponsor.key(),
+        credited,
+        mint_state.loss_per_token,
+        &clock,
+        bump,
+    );
+
+    ctx.accounts.owner_index.add_subaccount(
+        ctx.accounts.user.key(),
+        subaccount,
+        vault.key(),
+        ctx.bumps.owner_index,
+    )?;
+
+    emit!(VaultInitializedEvent {
+        vault: vault.key(),
+        owner: ctx.accounts.user.key(),
+        subaccount,
+        token_account: ctx.accounts.vault_token_account.key(),
+        rent_payer: ctx.accounts.sponsor.key(),
+        initial_deposit: credited,
+        fee,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Vault initialized for user: {}", ctx.accounts.user.key());
+    msg!("Subaccount: {}", subaccount);
+    msg!("Rent sponsored by: {}", ctx.accounts.sponsor.key());
+    msg!("Initial deposit: {} tokens (fee: {})", credited, fee);
+
+    Ok(())
+}
\ No newline at end of file
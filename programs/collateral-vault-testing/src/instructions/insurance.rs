@@ -0,0 +1,756 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Mint, Token, TokenAccount, Transfer},
+};
+use crate::state::{
+    BadDebt, CollateralVault, InsuranceFund, MintState, ProtocolState, SharePool, VaultAuthority,
+};
+use crate::constants::{
+    AUTHORITY_SEED, BAD_DEBT_SEED, INSURANCE_SEED, PROTOCOL_STATE_SEED, MINT_STATE_SEED,
+    SHARE_POOL_SEED, VAULT_SEED,
+};
+use crate::errors::ErrorCode;
+use crate::instructions::shared::{
+    authorized_caller, enforce_rate_limit, load_pda_if_initialized, record_outflow, store_pda,
+};
+use crate::events::{
+    BadDebtCoveredEvent, BadDebtRecordedEvent, BadDebtSocializedEvent,
+    InsuranceContributedEvent, InsuranceFundInitializedEvent, SocializedLossCollectedEvent,
+};
+
+#[derive(Accounts)]
+pub struct InitializeInsuranceFund<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = InsuranceFund::LEN,
+        seeds = [INSURANCE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        associated_token::mint = mint,
+        associated_token::authority = insurance_fund
+    )]
+    pub fund_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ContributeInsurance<'info> {
+    pub contributor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [INSURANCE_SEED, insurance_fund.mint.as_ref()],
+        bump = insurance_fund.bump
+    )]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+
+    #[account(
+        mut,
+        constraint = fund_token_account.key() == insurance_fund.token_account @ ErrorCode::InvalidInsuranceFund
+    )]
+    pub fund_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub contributor_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct FundInsuranceFromFees<'info> {
+    /// The admin or the fee manager.
+    pub signer: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == protocol_state.key() @ ErrorCode::InvalidTreasuryAccount,
+        constraint = treasury_token_account.mint == insurance_fund.mint @ ErrorCode::InvalidTreasuryAccount
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [INSURANCE_SEED, insurance_fund.mint.as_ref()],
+        bump = insurance_fund.bump
+    )]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+
+    #[account(
+        mut,
+        constraint = fund_token_account.key() == insurance_fund.token_account @ ErrorCode::InvalidInsuranceFund
+    )]
+    pub fund_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RecordBadDebt<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        mut,
+        seeds = [INSURANCE_SEED, insurance_fund.mint.as_ref()],
+        bump = insurance_fund.bump
+    )]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, debtor_vault.seed_owner.as_ref(), debtor_vault.subaccount.to_le_bytes().as_ref()],
+        bump = debtor_vault.bump
+    )]
+    pub debtor_vault: Account<'info, CollateralVault>,
+
+    #[account(
+        constraint = debtor_vault_token_account.key() == debtor_vault.token_account @ ErrorCode::UnauthorizedOwner,
+        constraint = debtor_vault_token_account.mint == insurance_fund.mint @ ErrorCode::InvalidInsuranceFund
+    )]
+    pub debtor_vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds = [VAULT_SEED, creditor_vault.seed_owner.as_ref(), creditor_vault.subaccount.to_le_bytes().as_ref()],
+        bump = creditor_vault.bump
+    )]
+    pub creditor_vault: Account<'info, CollateralVault>,
+
+    #[account(
+        constraint = creditor_vault_token_account.key() == creditor_vault.token_account @ ErrorCode::UnauthorizedOwner,
+        constraint = creditor_vault_token_account.mint == insurance_fund.mint @ ErrorCode::InvalidInsuranceFund
+    )]
+    pub creditor_vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = BadDebt::LEN,
+        seeds = [
+            BAD_DEBT_SEED,
+            insurance_fund.key().as_ref(),
+            insurance_fund.bad_debt_count.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub bad_debt: Account<'info, BadDebt>,
+
+    /// CHECK: Instructions sysvar used to identify the calling program
+    #[account(address = solana_instructions_sysvar::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CoverBadDebt<'info> {
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, insurance_fund.mint.as_ref()],
+        bump = mint_state.bump
+    )]
+    pub mint_state: Account<'info, MintState>,
+
+    #[account(
+        mut,
+        seeds = [INSURANCE_SEED, insurance_fund.mint.as_ref()],
+        bump = insurance_fund.bump
+    )]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+
+    #[account(
+        mut,
+        constraint = fund_token_account.key() == insurance_fund.token_account @ ErrorCode::InvalidInsuranceFund
+    )]
+    pub fund_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [BAD_DEBT_SEED, insurance_fund.key().as_ref(), bad_debt.index.to_le_bytes().as_ref()],
+        bump = bad_debt.bump,
+        has_one = insurance_fund,
+        has_one = creditor_vault
+    )]
+    pub bad_debt: Account<'info, BadDebt>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, creditor_vault.seed_owner.as_ref(), creditor_vault.subaccount.to_le_bytes().as_ref()],
+        bump = creditor_vault.bump
+    )]
+    pub creditor_vault: Account<'info, CollateralVault>,
+
+    #[account(
+        mut,
+        constraint = creditor_vault_token_account.key() == creditor_vault.token_account @ ErrorCode::UnauthorizedOwner
+    )]
+    pub creditor_vault_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Outflow rate limit PDA for the calling program, validated in handler
+    #[account(mut)]
+    pub rate_limit: UncheckedAccount<'info>,
+
+    /// CHECK: Instructions sysvar used to identify the calling program
+    #[account(address = solana_instructions_sysvar::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Splits what the insurance fund could not cover across every depositor of
+/// the mint, pro rata to its share of TVL. The share pool pays its part to
+/// the creditor at once and writes it off its shares. The unpooled part is
+/// assessed through the mint's loss index: each vault writes off its share
+/// the next time it is touched, and the collected tokens reach the creditor
+/// through the fund.
+#[derive(Accounts)]
+pub struct SocializeBadDebt<'info> {
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, insurance_fund.mint.as_ref()],
+        bump = mint_state.bump
+    )]
+    pub mint_state: Account<'info, MintState>,
+
+    #[account(
+        seeds = [INSURANCE_SEED, insurance_fund.mint.as_ref()],
+        bump = insurance_fund.bump
+    )]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+
+    /// CHECK: Share pool PDA for the mint, which may not exist; validated in handler
+    #[account(mut)]
+    pub share_pool: UncheckedAccount<'info>,
+
+    /// Required while the share pool pays its part.
+    #[account(mut)]
+    pub pool_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [BAD_DEBT_SEED, insurance_fund.key().as_ref(), bad_debt.index.to_le_bytes().as_ref()],
+        bump = bad_debt.bump,
+        has_one = insurance_fund,
+        has_one = creditor_vault
+    )]
+    pub bad_debt: Account<'info, BadDebt>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, creditor_vault.seed_owner.as_ref(), creditor_vault.subaccount.to_le_bytes().as_ref()],
+        bump = creditor_vault.bump
+    )]
+    pub creditor_vault: Account<'info, CollateralVault>,
+
+    #[account(
+        mut,
+        constraint = creditor_vault_token_account.key() == creditor_vault.token_account @ ErrorCode::UnauthorizedOwner
+    )]
+    pub creditor_vault_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Outflow rate limit PDA for the calling program, validated in handler
+    #[account(mut)]
+    pub rate_limit: UncheckedAccount<'info>,
+
+    /// CHECK: Instructions sysvar used to identify the calling program
+    #[account(address = solana_instructions_sysvar::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Moves the loss a vault has written off out of its token account into
+/// the insurance fund. Anyone may crank it.
+#[derive(Accounts)]
+pub struct CollectSocializedLoss<'info> {
+    #[account(
+        seeds = [MINT_STATE_SEED, insurance_fund.mint.as_ref()],
+        bump = mint_state.bump
+    )]
+    pub mint_state: Account<'info, MintState>,
+
+    #[account(
+        mut,
+        seeds = [INSURANCE_SEED, insurance_fund.mint.as_ref()],
+        bump = insurance_fund.bump
+    )]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+
+    #[account(
+        mut,
+        constraint = fund_token_account.key() == insurance_fund.token_account @ ErrorCode::InvalidInsuranceFund
+    )]
+    pub fund_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account @ ErrorCode::UnauthorizedOwner,
+        constraint = vault_token_account.mint == insurance_fund.mint @ ErrorCode::InvalidInsuranceFund
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn initialize_insurance_fund(ctx: Context<InitializeInsuranceFund>) -> Result<()> {
+    let insurance_fund = &mut ctx.accounts.insurance_fund;
+    insurance_fund.mint = ctx.accounts.mint.key();
+    insurance_fund.token_account = ctx.accounts.fund_token_account.key();
+    insurance_fund.balance = 0;
+    insurance_fund.total_contributed = 0;
+    insurance_fund.total_covered = 0;
+    insurance_fund.total_loss_collected = 0;
+    insurance_fund.bad_debt_count = 0;
+    insurance_fund.bump = ctx.bumps.insurance_fund;
+
+    let clock = Clock::get()?;
+    emit!(InsuranceFundInitializedEvent {
+        insurance_fund: insurance_fund.key(),
+        mint: insurance_fund.mint,
+        admin: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Insurance fund initialized for mint: {}", insurance_fund.mint);
+
+    Ok(())
+}
+
+pub fn contribute_insurance(ctx: Context<ContributeInsurance>, amount: u64) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.contributor_token_account.to_account_info(),
+                to: ctx.accounts.fund_token_account.to_account_info(),
+                authority: ctx.accounts.contributor.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let insurance_fund = &mut ctx.accounts.insurance_fund;
+    insurance_fund.contribute(amount)?;
+
+    let clock = Clock::get()?;
+    emit!(InsuranceContributedEvent {
+        insurance_fund: insurance_fund.key(),
+        contributor: ctx.accounts.contributor.key(),
+        amount,
+        from_fees: false,
+        new_balance: insurance_fund.balance,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Contributed {} tokens to the insurance fund", amount);
+    msg!("Fund balance: {}", insurance_fund.balance);
+
+    Ok(())
+}
+
+pub fn fund_insurance_from_fees(ctx: Context<FundInsuranceFromFees>, amount: u64) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+
+    let signer = ctx.accounts.signer.key();
+    let protocol_state = &ctx.accounts.protocol_state;
+    require!(
+        signer == ctx.accounts.authority.admin || signer == protocol_state.fee_manager,
+        ErrorCode::UnauthorizedFeeManager
+    );
+
+    let seeds = &[PROTOCOL_STATE_SEED, &[protocol_state.bump]];
+    let signer_seeds = &[&seeds[..]];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.treasury_token_account.to_account_info(),
+                to: ctx.accounts.fund_token_account.to_account_info(),
+                authority: protocol_state.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )?;
+
+    let insurance_fund = &mut ctx.accounts.insurance_fund;
+    insurance_fund.contribute(amount)?;
+
+    let clock = Clock::get()?;
+    emit!(InsuranceContributedEvent {
+        insurance_fund: insurance_fund.key(),
+        contributor: signer,
+        amount,
+        from_fees: true,
+        new_balance: insurance_fund.balance,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Moved {} tokens of fees into the insurance fund", amount);
+    msg!("Fund balance: {}", insurance_fund.balance);
+
+    Ok(())
+}
+
+pub fn record_bad_debt(ctx: Context<RecordBadDebt>, shortfall: u64) -> Result<()> {
+    require!(shortfall > 0, ErrorCode::InvalidAmount);
+    require!(
+        ctx.accounts.debtor_vault.key() != ctx.accounts.creditor_vault.key(),
+        ErrorCode::SameVaultTransfer
+    );
+
+    let caller_program = authorized_caller(
+        &ctx.accounts.authority,
+        &ctx.accounts.instructions_sysvar,
+    )?;
+
+    ctx.accounts.debtor_vault.record_bad_debt(shortfall)?;
+
+    let insurance_fund = &mut ctx.accounts.insurance_fund;
+    let index = insurance_fund.next_bad_debt_index()?;
+
+    let clock = Clock::get()?;
+    let bad_debt = &mut ctx.accounts.bad_debt;
+    bad_debt.insurance_fund = insurance_fund.key();
+    bad_debt.index = index;
+    bad_debt.debtor_vault = ctx.accounts.debtor_vault.key();
+    bad_debt.creditor_vault = ctx.accounts.creditor_vault.key();
+    bad_debt.caller_program = caller_program;
+    bad_debt.shortfall = shortfall;
+    bad_debt.covered = 0;
+    bad_debt.socialized = 0;
+    bad_debt.assessed = 0;
+    bad_debt.recorded_at = clock.unix_timestamp;
+    bad_debt.bump = ctx.bumps.bad_debt;
+
+    emit!(BadDebtRecordedEvent {
+        bad_debt: bad_debt.key(),
+        index,
+        debtor_vault: bad_debt.debtor_vault,
+        creditor_vault: bad_debt.creditor_vault,
+        shortfall,
+        caller_program,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("⚠️ Bad debt #{} recorded: {} tokens", index, shortfall);
+    msg!("Debtor vault: {}", bad_debt.debtor_vault);
+
+    Ok(())
+}
+
+pub fn cover_bad_debt(ctx: Context<CoverBadDebt>) -> Result<()> {
+    ctx.accounts.protocol_state.require_active()?;
+    ctx.accounts.creditor_vault.require_unpooled()?;
+
+    let caller_program = authorized_caller(
+        &ctx.accounts.authority,
+        &ctx.accounts.instructions_sysvar,
+    )?;
+
+    require!(
+        caller_program == ctx.accounts.bad_debt.caller_program,
+        ErrorCode::BadDebtCallerMismatch
+    );
+
+    let outstanding = ctx.accounts.bad_debt.outstanding();
+    require!(outstanding > 0, ErrorCode::BadDebtResolved);
+
+    let insurance_fund = &mut ctx.accounts.insurance_fund;
+    let covered = insurance_fund.cover(outstanding)?;
+    require!(covered > 0, ErrorCode::InsufficientAvailableBalance);
+
+    enforce_rate_limit(
+        &ctx.accounts.rate_limit,
+        &caller_program,
+        ctx.accounts.creditor_vault.key(),
+        covered,
+    )?;
+
+    let mint = insurance_fund.mint;
+    let seeds = &[INSURANCE_SEED, mint.as_ref(), &[insurance_fund.bump]];
+    let signer_seeds = &[&seeds[..]];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.fund_token_account.to_account_info(),
+                to: ctx.accounts.creditor_vault_token_account.to_account_info(),
+                authority: insurance_fund.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        covered,
+    )?;
+
+    let bad_debt = &mut ctx.accounts.bad_debt;
+    bad_debt.record_covered(covered)?;
+
+    let clock = Clock::get()?;
+    let creditor_vault = &mut ctx.accounts.creditor_vault;
+    creditor_vault.absorb_socialized_loss(&ctx.accounts.mint_state, &clock)?;
+    creditor_vault.deposit(covered, &clock)?;
+
+    // The fund sits outside TVL, so its payout is new collateral.
+    ctx.accounts.mint_state.record_inflow(&ctx.accounts.protocol_state, covered, &clock)?;
+    emit!(BadDebtCoveredEvent {
+        bad_debt: bad_debt.key(),
+        creditor_vault: creditor_vault.key(),
+        amount: covered,
+        remaining: bad_debt.outstanding(),
+        fund_balance: insurance_fund.balance,
+        caller_program,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Insurance covered {} tokens of bad debt #{}", covered, bad_debt.index);
+    msg!("Remaining shortfall: {}", bad_debt.outstanding());
+
+    Ok(())
+}
+
+pub fn socialize_bad_debt(ctx: Context<SocializeBadDebt>) -> Result<()> {
+    ctx.accounts.protocol_state.require_active()?;
+    ctx.accounts.creditor_vault.require_unpooled()?;
+    require!(
+        ctx.accounts.insurance_fund.balance == 0,
+        ErrorCode::InsuranceFundNotExhausted
+    );
+
+    let caller_program = authorized_caller(
+        &ctx.accounts.authority,
+        &ctx.accounts.instructions_sysvar,
+    )?;
+
+    require!(
+        caller_program == ctx.accounts.bad_debt.caller_program,
+        ErrorCode::BadDebtCallerMismatch
+    );
+
+    let remaining = ctx.accounts.bad_debt.remaining();
+    require!(remaining > 0, ErrorCode::BadDebtResolved);
+
+    let mint = ctx.accounts.insurance_fund.mint;
+    let mut share_pool = load_pda_if_initialized::<SharePool>(
+        &ctx.accounts.share_pool,
+        &[SHARE_POOL_SEED, mint.as_ref()],
+        ErrorCode::InvalidSharePool,
+    )?;
+
+    // Depositors cannot be charged more than they hold between them.
+    let mint_state = &mut ctx.accounts.mint_state;
+    let total_value_locked = mint_state.total_value_locked;
+    let pool_assets = share_pool
+        .as_ref()
+        .map_or(0, |share_pool| share_pool.total_assets)
+        .min(total_value_locked);
+    let unpooled_balance = total_value_locked - pool_assets;
+    let chargeable = remaining.min(total_value_locked);
+    let pool_part = if total_value_locked > 0 {
+        (chargeable as u128 * pool_assets as u128 / total_value_locked as u128) as u64
+    } else {
+        0
+    };
+    let assessed = chargeable - pool_part;
+
+    // Whatever the pool cannot pay out right now stays recorded for a
+    // later call once strategies have been recalled. Reserved tokens of
+    // pooled vaults are never paid out.
+    let paid = share_pool.as_ref().map_or(0, |share_pool| {
+        pool_part.min(share_pool.idle_assets().saturating_sub(share_pool.reserved_assets()))
+    });
+    require!(paid > 0 || assessed > 0, ErrorCode::InsufficientPoolLiquidity);
+
+    let clock = Clock::get()?;
+    if assessed > 0 {
+        mint_state.socialize_loss(assessed, unpooled_balance, &clock)?;
+        ctx.accounts.bad_debt.record_assessed(assessed)?;
+    }
+
+    // The creditor bears its own share on what it held before the payout.
+    let creditor_vault = &mut ctx.accounts.creditor_vault;
+    creditor_vault.absorb_socialized_loss(mint_state, &clock)?;
+
+    if let (Some(share_pool), true) = (share_pool.as_mut(), paid > 0) {
+        enforce_rate_limit(
+            &ctx.accounts.rate_limit,
+            &caller_program,
+            ctx.accounts.share_pool.key(),
+            paid,
+        )?;
+
+        let pool_token_account = ctx.accounts.pool_token_account
+            .as_ref()
+            .filter(|account| account.key() == share_pool.token_account)
+            .ok_or(ErrorCode::InvalidSharePool)?;
+        let seeds = &[SHARE_POOL_SEED, mint.as_ref(), &[share_pool.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: pool_token_account.to_account_info(),
+                    to: ctx.accounts.creditor_vault_token_account.to_account_info(),
+                    authority: ctx.accounts.share_pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            paid,
+        )?;
+
+        share_pool.socialize_loss(paid)?;
+        store_pda(&ctx.accounts.share_pool, share_pool)?;
+
+        ctx.accounts.bad_debt.record_socialized(paid)?;
+        creditor_vault.deposit(paid, &clock)?;
+        record_outflow(&mut ctx.accounts.protocol_state, mint_state, paid, false)?;
+    }
+
+    let bad_debt = &ctx.accounts.bad_debt;
+    emit!(BadDebtSocializedEvent {
+        bad_debt: bad_debt.key(),
+        pool: share_pool.as_ref().map(|_| ctx.accounts.share_pool.key()),
+        creditor_vault: creditor_vault.key(),
+        amount: paid,
+        assessed,
+        loss_per_token: mint_state.loss_per_token,
+        remaining: bad_debt.remaining(),
+        total_shares: share_pool.as_ref().map_or(0, |share_pool| share_pool.total_shares),
+        total_assets: share_pool.as_ref().map_or(0, |share_pool| share_pool.total_assets),
+        caller_program,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("⚠️ Socialized bad debt #{} across depositors", bad_debt.index);
+    msg!("Paid by the share pool: {}", paid);
+    msg!("Assessed on unpooled vaults: {}", assessed);
+    msg!("Remaining shortfall: {}", bad_debt.remaining());
+
+    Ok(())
+}
+
+pub fn collect_socialized_loss(ctx: Context<CollectSocializedLoss>) -> Result<()> {
+    let clock = Clock::get()?;
+    let vault = &mut ctx.accounts.vault;
+    vault.absorb_socialized_loss(&ctx.accounts.mint_state, &clock)?;
+
+    let amount = vault.loss_uncollected;
+    require!(amount > 0, ErrorCode::NoSocializedLoss);
+
+    let seed_owner = vault.seed_owner;
+    let subaccount = vault.subaccount.to_le_bytes();
+    let seeds = &[
+        VAULT_SEED,
+        seed_owner.as_ref(),
+        subaccount.as_ref(),
+        &[vault.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                to: ctx.accounts.fund_token_account.to_account_info(),
+                authority: vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )?;
+
+    vault.loss_uncollected = 0;
+    let insurance_fund = &mut ctx.accounts.insurance_fund;
+    insurance_fund.collect_loss(amount)?;
+
+    emit!(SocializedLossCollectedEvent {
+        insurance_fund: insurance_fund.key(),
+        vault: vault.key(),
+        amount,
+        fund_balance: insurance_fund.balance,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Collected {} tokens of socialized loss", amount);
+    msg!("Fund balance: {}", insurance_fund.balance);
+
+    Ok(())
+}
@@ -1,9 +1,13 @@
 use anchor_lang::prelude::*;
-use crate::state::{CollateralVault, VaultAuthority};
-use crate::constants::{VAULT_SEED, AUTHORITY_SEED};
+use anchor_spl::token::TokenAccount;
+use crate::state::{CollateralVault, SharePool, VaultAuthority};
+use crate::constants::{VAULT_SEED, AUTHORITY_SEED, SHARE_POOL_SEED};
 use crate::errors::ErrorCode;
+use crate::instructions::shared::{authorized_caller, pooled_share_pool, sync_reward_positions};
 use crate::events::LockEvent;
 
+/// The vault's open reward positions follow in `remaining_accounts` as
+/// writable (reward pool, reward position) pairs and are restaked.
 #[derive(Accounts)]
 pub struct LockCollateral<'info> {
     #[account(
@@ -14,18 +18,41 @@ pub struct LockCollateral<'info> {
 
     #[account(
         mut,
-        seeds = [VAULT_SEED, vault.owner.as_ref()],
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
         bump = vault.bump
     )]
     pub vault: Account<'info, CollateralVault>,
 
     /// CHECK: Vault owner for validation
     pub vault_owner: UncheckedAccount<'info>,
+
+    /// Share pool behind the vault; required when the vault uses share accounting.
+    #[account(
+        mut,
+        seeds = [SHARE_POOL_SEED, share_pool.mint.as_ref()],
+        bump = share_pool.bump
+    )]
+    pub share_pool: Option<Account<'info, SharePool>>,
+
+    /// Vault token account, matched against the share pool mint.
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: Instructions sysvar used to identify the calling program
+    #[account(address = solana_instructions_sysvar::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
 }
 
-pub fn handler(ctx: Context<LockCollateral>, amount: u64) -> Result<()> {
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, LockCollateral<'info>>,
+    amount: u64,
+) -> Result<()> {
     require!(amount > 0, ErrorCode::InvalidAmount);
 
+    let caller_program = authorized_caller(
+        &ctx.accounts.authority,
+        &ctx.accounts.instructions_sysvar,
+    )?;
+
     let vault = &mut ctx.accounts.vault;
 
     require!(
@@ -33,20 +60,30 @@ pub fn handler(ctx: Context<LockCollateral>, amount: u64) -> Result<()> {
         ErrorCode::UnauthorizedOwner
     );
 
+    let share_pool = pooled_share_pool(
+        vault,
+        ctx.accounts.share_pool.as_mut(),
+        ctx.accounts.vault_token_account.as_ref(),
+    )?;
+
     require!(
         vault.available_balance >= amount,
         ErrorCode::InsufficientAvailableBalance
     );
 
-    vault.lock(amount)?;
-
     let clock = Clock::get()?;
+    vault.lock(caller_program, amount, &clock)?;
+    if let Some(share_pool) = share_pool {
+        share_pool.lock_assets(amount)?;
+    }
+    sync_reward_positions(vault, ctx.remaining_accounts, &clock)?;
+
     emit!(LockEvent {
         vault: vault.key(),
         amount,
         new_locked_balance: vault.locked_balance,
         new_available_balance: vault.available_balance,
-        caller_program: *ctx.program_id,
+        caller_program,
         timestamp: clock.unix_timestamp,
     });
 
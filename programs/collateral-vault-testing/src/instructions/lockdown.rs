@@ -0,0 +1,165 @@
+use anchor_lang::prelude::*;
+use crate::state::CollateralVault;
+use crate::constants::VAULT_SEED;
+use crate::errors::ErrorCode;
+use crate::events::{
+    LockdownConfiguredEvent, LockdownLiftedEvent, LockdownReleaseRequestedEvent,
+    VaultLockedDownEvent,
+};
+
+#[derive(Accounts)]
+pub struct ConfigureLockdown<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+}
+
+#[derive(Accounts)]
+pub struct OwnerLockdown<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+}
+
+#[derive(Accounts)]
+pub struct RequestLockdownRelease<'info> {
+    pub recovery_key: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.lockdown_recovery_key == Some(recovery_key.key()) @ ErrorCode::UnauthorizedRecoveryKey
+    )]
+    pub vault: Account<'info, CollateralVault>,
+}
+
+#[derive(Accounts)]
+pub struct LiftLockdown<'info> {
+    pub recovery_key: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.lockdown_recovery_key == Some(recovery_key.key()) @ ErrorCode::UnauthorizedRecoveryKey
+    )]
+    pub vault: Account<'info, CollateralVault>,
+}
+
+pub fn configure_lockdown(
+    ctx: Context<ConfigureLockdown>,
+    recovery_key: Pubkey,
+    lockdown_delay: i64,
+) -> Result<()> {
+    require!(
+        recovery_key != Pubkey::default()
+            && recovery_key != ctx.accounts.user.key()
+            && lockdown_delay > 0,
+        ErrorCode::InvalidLockdownConfig
+    );
+
+    let vault = &mut ctx.accounts.vault;
+    // Swapping the recovery key mid-lockdown would let a stolen owner key
+    // lift the freeze it was meant to stop.
+    vault.require_not_locked_down()?;
+
+    vault.lockdown_recovery_key = Some(recovery_key);
+    vault.lockdown_delay = lockdown_delay;
+
+    let clock = Clock::get()?;
+    emit!(LockdownConfiguredEvent {
+        vault: vault.key(),
+        owner: ctx.accounts.user.key(),
+        recovery_key,
+        lockdown_delay,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Lockdown recovery key set: {}", recovery_key);
+    msg!("Lockdown release delay: {}s", lockdown_delay);
+
+    Ok(())
+}
+
+pub fn owner_lockdown(ctx: Context<OwnerLockdown>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(
+        vault.lockdown_recovery_key.is_some(),
+        ErrorCode::LockdownNotConfigured
+    );
+
+    // Re-triggering restarts the freeze and drops any release in progress.
+    vault.locked_down = true;
+    vault.lockdown_release_at = 0;
+    vault.pending_owner = None;
+
+    let clock = Clock::get()?;
+    emit!(VaultLockedDownEvent {
+        vault: vault.key(),
+        owner: ctx.accounts.user.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("🚨 Vault locked down by owner: {}", ctx.accounts.user.key());
+
+    Ok(())
+}
+
+pub fn request_lockdown_release(ctx: Context<RequestLockdownRelease>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(vault.locked_down, ErrorCode::VaultNotLockedDown);
+
+    let clock = Clock::get()?;
+    let release_at = clock.unix_timestamp
+        .checked_add(vault.lockdown_delay)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    vault.lockdown_release_at = release_at;
+
+    emit!(LockdownReleaseRequestedEvent {
+        vault: vault.key(),
+        recovery_key: ctx.accounts.recovery_key.key(),
+        release_at,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Lockdown release requested, ready at {}", release_at);
+
+    Ok(())
+}
+
+pub fn lift_lockdown(ctx: Context<LiftLockdown>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    require!(vault.locked_down, ErrorCode::VaultNotLockedDown);
+
+    let clock = Clock::get()?;
+    require!(
+        vault.lockdown_release_at != 0 && clock.unix_timestamp >= vault.lockdown_release_at,
+        ErrorCode::LockdownReleaseNotReady
+    );
+
+    vault.locked_down = false;
+    vault.lockdown_release_at = 0;
+
+    emit!(LockdownLiftedEvent {
+        vault: vault.key(),
+        recovery_key: ctx.accounts.recovery_key.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Vault lockdown lifted");
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
-use crate::state::VaultAuthority;
-use crate::constants::AUTHORITY_SEED;
+use crate::state::{ProtocolState, VaultAuthority};
+use crate::constants::{AUTHORITY_SEED, PROTOCOL_STATE_SEED};
 use crate::errors::ErrorCode;
 use crate::events::{ProgramAuthorizedEvent, ProgramDeauthorizedEvent};
 
@@ -30,6 +30,14 @@ pub struct RemoveAuthorizedProgram<'info> {
         has_one = admin @ ErrorCode::UnauthorizedAdmin
     )]
     pub authority: Account<'info, VaultAuthority>,
+
+    /// Holds the program's withdrawal cooldown, dropped along with it.
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
 }
 
 pub fn add_authorized_program(
@@ -57,6 +65,7 @@ pub fn remove_authorized_program(
 ) -> Result<()> {
     let authority = &mut ctx.accounts.authority;
     authority.remove_program(program_id)?;
+    ctx.accounts.protocol_state.remove_program_cooldown(&program_id);
 
     let clock = Clock::get()?;
     emit!(ProgramDeauthorizedEvent {
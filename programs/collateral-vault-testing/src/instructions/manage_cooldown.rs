@@ -0,0 +1,228 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+use crate::state::{CooldownConfig, ProtocolState, VaultAuthority};
+use crate::constants::{AUTHORITY_SEED, COOLDOWN_SEED, PROTOCOL_STATE_SEED};
+use crate::errors::ErrorCode;
+use crate::events::WithdrawalCooldownUpdatedEvent;
+
+#[derive(Accounts)]
+pub struct SetWithdrawalCooldown<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+}
+
+#[derive(Accounts)]
+pub struct SetMintCooldown<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = CooldownConfig::LEN,
+        seeds = [COOLDOWN_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub cooldown_config: Account<'info, CooldownConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveMintCooldown<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [COOLDOWN_SEED, cooldown_config.mint.as_ref()],
+        bump = cooldown_config.bump
+    )]
+    pub cooldown_config: Account<'info, CooldownConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetProgramCooldown<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveProgramCooldown<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+}
+
+pub fn set_withdrawal_cooldown(
+    ctx: Context<SetWithdrawalCooldown>,
+    cooldown_seconds: i64,
+) -> Result<()> {
+    require!(cooldown_seconds >= 0, ErrorCode::InvalidCooldownConfig);
+
+    ctx.accounts.protocol_state.withdrawal_cooldown_seconds = cooldown_seconds;
+
+    let clock = Clock::get()?;
+    emit!(WithdrawalCooldownUpdatedEvent {
+        mint: None,
+        program_id: None,
+        cooldown_seconds,
+        admin: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Default withdrawal cooldown: {}s", cooldown_seconds);
+
+    Ok(())
+}
+
+pub fn set_mint_cooldown(ctx: Context<SetMintCooldown>, cooldown_seconds: i64) -> Result<()> {
+    require!(cooldown_seconds >= 0, ErrorCode::InvalidCooldownConfig);
+
+    let mint = ctx.accounts.mint.key();
+    let cooldown_config = &mut ctx.accounts.cooldown_config;
+    cooldown_config.mint = mint;
+    cooldown_config.cooldown_seconds = cooldown_seconds;
+    cooldown_config.bump = ctx.bumps.cooldown_config;
+
+    let clock = Clock::get()?;
+    emit!(WithdrawalCooldownUpdatedEvent {
+        mint: Some(mint),
+        program_id: None,
+        cooldown_seconds,
+        admin: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Withdrawal cooldown for mint {}: {}s", mint, cooldown_seconds);
+
+    Ok(())
+}
+
+pub fn remove_mint_cooldown(ctx: Context<RemoveMintCooldown>) -> Result<()> {
+    let mint = ctx.accounts.cooldown_config.mint;
+    // Vaults of this mint fall back to the protocol-wide default.
+    let cooldown_seconds = ctx.accounts.protocol_state.withdrawal_cooldown_seconds;
+
+    let clock = Clock::get()?;
+    emit!(WithdrawalCooldownUpdatedEvent {
+        mint: Some(mint),
+        program_id: None,
+        cooldown_seconds,
+        admin: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Withdrawal cooldown override removed for mint: {}", mint);
+
+    Ok(())
+}
+
+pub fn set_program_cooldown(
+    ctx: Context<SetProgramCooldown>,
+    program_id: Pubkey,
+    cooldown_seconds: i64,
+) -> Result<()> {
+    require!(cooldown_seconds >= 0, ErrorCode::InvalidCooldownConfig);
+    require!(
+        ctx.accounts.authority.is_authorized(&program_id),
+        ErrorCode::ProgramNotAuthorized
+    );
+
+    ctx.accounts.protocol_state.set_program_cooldown(program_id, cooldown_seconds)?;
+
+    let clock = Clock::get()?;
+    emit!(WithdrawalCooldownUpdatedEvent {
+        mint: None,
+        program_id: Some(program_id),
+        cooldown_seconds,
+        admin: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Withdrawal cooldown for program {}: {}s", program_id, cooldown_seconds);
+
+    Ok(())
+}
+
+pub fn remove_program_cooldown(ctx: Context<RemoveProgramCooldown>, program_id: Pubkey) -> Result<()> {
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    require!(
+        protocol_state.remove_program_cooldown(&program_id),
+        ErrorCode::ProgramCooldownNotSet
+    );
+
+    let clock = Clock::get()?;
+    emit!(WithdrawalCooldownUpdatedEvent {
+        mint: None,
+        program_id: Some(program_id),
+        cooldown_seconds: 0,
+        admin: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Withdrawal cooldown removed for program: {}", program_id);
+
+    Ok(())
+}
@@ -0,0 +1,180 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::state::{ProtocolState, VaultAuthority};
+use crate::constants::{AUTHORITY_SEED, PROTOCOL_STATE_SEED, MAX_FEE_BPS};
+use crate::errors::ErrorCode;
+use crate::events::{FeeManagerUpdatedEvent, FeesConfiguredEvent, FeesWithdrawnEvent};
+
+#[derive(Accounts)]
+pub struct ConfigureFees<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+}
+
+#[derive(Accounts)]
+pub struct SetFeeManager<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFees<'info> {
+    /// The admin or the fee manager.
+    pub signer: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == protocol_state.key() @ ErrorCode::InvalidTreasuryAccount
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = destination.mint == treasury_token_account.mint @ ErrorCode::InvalidTreasuryAccount
+    )]
+    pub destination: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn configure_fees(
+    ctx: Context<ConfigureFees>,
+    deposit_fee_bps: u16,
+    withdraw_fee_bps: u16,
+    transfer_fee_bps: u16,
+    min_fee: u64,
+) -> Result<()> {
+    require!(
+        deposit_fee_bps <= MAX_FEE_BPS
+            && withdraw_fee_bps <= MAX_FEE_BPS
+            && transfer_fee_bps <= MAX_FEE_BPS,
+        ErrorCode::InvalidFeeConfig
+    );
+
+    ctx.accounts.protocol_state.configure_fees(
+        deposit_fee_bps,
+        withdraw_fee_bps,
+        transfer_fee_bps,
+        min_fee,
+    );
+
+    let clock = Clock::get()?;
+    emit!(FeesConfiguredEvent {
+        deposit_fee_bps,
+        withdraw_fee_bps,
+        transfer_fee_bps,
+        min_fee,
+        admin: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Fees configured");
+    msg!(
+        "Deposit: {} bps, withdraw: {} bps, transfer: {} bps, minimum: {}",
+        deposit_fee_bps,
+        withdraw_fee_bps,
+        transfer_fee_bps,
+        min_fee
+    );
+
+    Ok(())
+}
+
+pub fn set_fee_manager(ctx: Context<SetFeeManager>, fee_manager: Pubkey) -> Result<()> {
+    require!(fee_manager != Pubkey::default(), ErrorCode::InvalidFeeConfig);
+
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    let previous_fee_manager = protocol_state.fee_manager;
+    protocol_state.fee_manager = fee_manager;
+
+    let clock = Clock::get()?;
+    emit!(FeeManagerUpdatedEvent {
+        previous_fee_manager,
+        new_fee_manager: fee_manager,
+        admin: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Fee manager set: {}", fee_manager);
+
+    Ok(())
+}
+
+pub fn withdraw_fees(ctx: Context<WithdrawFees>, amount: u64) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+
+    let signer = ctx.accounts.signer.key();
+    let protocol_state = &ctx.accounts.protocol_state;
+    require!(
+        signer == ctx.accounts.authority.admin || signer == protocol_state.fee_manager,
+        ErrorCode::UnauthorizedFeeManager
+    );
+
+    let seeds = &[PROTOCOL_STATE_SEED, &[protocol_state.bump]];
+    let signer_seeds = &[&seeds[..]];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.treasury_token_account.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+                authority: protocol_state.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )?;
+
+    let clock = Clock::get()?;
+    emit!(FeesWithdrawnEvent {
+        treasury: ctx.accounts.treasury_token_account.key(),
+        destination: ctx.accounts.destination.key(),
+        amount,
+        withdrawn_by: signer,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Withdrew {} tokens in fees", amount);
+    msg!("Destination: {}", ctx.accounts.destination.key());
+
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+use crate::state::{CollateralVault, VaultOperator};
+use crate::constants::{VAULT_SEED, OPERATOR_SEED};
+use crate::errors::ErrorCode;
+use crate::events::{OperatorGrantedEvent, OperatorRevokedEvent};
+
+#[derive(Accounts)]
+#[instruction(operator: Pubkey)]
+pub struct GrantOperator<'info> {
+    pub user: Signer<'info>,
+
+    /// Funds the operator account; a multisig owner cannot pay rent itself.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = VaultOperator::LEN,
+        seeds = [OPERATOR_SEED, vault.key().as_ref(), operator.as_ref()],
+        bump
+    )]
+    pub vault_operator: Account<'info, VaultOperator>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeOperator<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        mut,
+        close = user,
+        seeds = [OPERATOR_SEED, vault.key().as_ref(), vault_operator.operator.as_ref()],
+        bump = vault_operator.bump
+    )]
+    pub vault_operator: Account<'info, VaultOperator>,
+}
+
+pub fn grant_operator(
+    ctx: Context<GrantOperator>,
+    operator: Pubkey,
+    scopes: u8,
+    allowance: Option<u64>,
+) -> Result<()> {
+    require!(operator != ctx.accounts.user.key(), ErrorCode::InvalidOperatorScopes);
+
+    let clock = Clock::get()?;
+    let vault_key = ctx.accounts.vault.key();
+    let delegation_epoch = ctx.accounts.vault.delegation_epoch;
+    ctx.accounts.vault_operator.grant(
+        vault_key,
+        operator,
+        scopes,
+        allowance,
+        clock.unix_timestamp,
+        delegation_epoch,
+        ctx.bumps.vault_operator,
+    )?;
+
+    emit!(OperatorGrantedEvent {
+        vault: vault_key,
+        operator,
+        scopes,
+        allowance,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Operator granted: {}", operator);
+    msg!("Scopes: {:#05b}", scopes);
+
+    Ok(())
+}
+
+pub fn revoke_operator(ctx: Context<RevokeOperator>) -> Result<()> {
+    let operator = ctx.accounts.vault_operator.operator;
+
+    let clock = Clock::get()?;
+    emit!(OperatorRevokedEvent {
+        vault: ctx.accounts.vault.key(),
+        operator,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Operator revoked: {}", operator);
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+use crate::state::{ProgramRateLimit, VaultAuthority};
+use crate::constants::{AUTHORITY_SEED, RATE_LIMIT_SEED};
+use crate::errors::ErrorCode;
+use crate::events::{RateLimitRemovedEvent, RateLimitUpdatedEvent};
+
+#[derive(Accounts)]
+#[instruction(program_id: Pubkey)]
+pub struct SetRateLimit<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = ProgramRateLimit::LEN,
+        seeds = [RATE_LIMIT_SEED, program_id.as_ref()],
+        bump
+    )]
+    pub rate_limit: Account<'info, ProgramRateLimit>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(program_id: Pubkey)]
+pub struct RemoveRateLimit<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [RATE_LIMIT_SEED, program_id.as_ref()],
+        bump = rate_limit.bump
+    )]
+    pub rate_limit: Account<'info, ProgramRateLimit>,
+}
+
+pub fn set_rate_limit(
+    ctx: Context<SetRateLimit>,
+    program_id: Pubkey,
+    capacity: u64,
+    window_slots: u64,
+) -> Result<()> {
+    require!(
+        capacity > 0 && window_slots > 0,
+        ErrorCode::InvalidRateLimitConfig
+    );
+
+    let clock = Clock::get()?;
+    let rate_limit = &mut ctx.accounts.rate_limit;
+    rate_limit.configure(
+        program_id,
+        capacity,
+        window_slots,
+        clock.slot,
+        ctx.bumps.rate_limit,
+    );
+
+    emit!(RateLimitUpdatedEvent {
+        program_id,
+        capacity,
+        window_slots,
+        admin: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Rate limit set for program: {}", program_id);
+    msg!("Capacity: {} tokens per {} slots", capacity, window_slots);
+
+    Ok(())
+}
+
+pub fn remove_rate_limit(ctx: Context<RemoveRateLimit>, program_id: Pubkey) -> Result<()> {
+    let clock = Clock::get()?;
+    emit!(RateLimitRemovedEvent {
+        program_id,
+        admin: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Rate limit removed for program: {}", program_id);
+
+    Ok(())
+}
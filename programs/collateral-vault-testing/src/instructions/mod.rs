@@ -1,17 +1,74 @@
+// Every instruction module names its entry point `handler`; lib.rs calls
+// them through the module path, so the clashing glob re-exports are unused.
+#![allow(ambiguous_glob_reexports)]
+
 pub mod initialize_authority;
 pub mod initialize_vault;
+pub mod initialize_vault_sponsored;
 pub mod deposit;
+pub mod deposit_for;
+pub mod permit;
 pub mod withdraw;
 pub mod lock_collateral;
 pub mod unlock_collateral;
+pub mod commitment;
 pub mod transfer_collateral;
+pub mod settle_batch;
+pub mod transfer_between_subaccounts;
+pub mod close_vault;
+pub mod owner_transfer;
+pub mod recovery;
+pub mod lockdown;
+pub mod cosigner;
+pub mod multisig;
+pub mod session;
+pub mod share_pool;
+pub mod strategy;
+pub mod insurance;
+pub mod rewards;
+pub mod checkpoints;
+pub mod withdrawal_cooldown;
+pub mod spending_policy;
 pub mod manage_authority;
+pub mod manage_rate_limit;
+pub mod manage_cooldown;
+pub mod manage_operator;
+pub mod manage_fees;
+pub mod circuit_breaker;
+pub mod emergency;
+pub mod shared;
 
 pub use initialize_authority::*;
 pub use initialize_vault::*;
+pub use initialize_vault_sponsored::*;
 pub use deposit::*;
+pub use deposit_for::*;
+pub use permit::*;
 pub use withdraw::*;
 pub use lock_collateral::*;
 pub use unlock_collateral::*;
+pub use commitment::*;
 pub use transfer_collateral::*;
-pub use manage_authority::*;
\ No newline at end of file
+pub use settle_batch::*;
+pub use transfer_between_subaccounts::*;
+pub use close_vault::*;
+pub use owner_transfer::*;
+pub use recovery::*;
+pub use lockdown::*;
+pub use cosigner::*;
+pub use multisig::*;
+pub use session::*;
+pub use share_pool::*;
+pub use strategy::*;
+pub use insurance::*;
+pub use rewards::*;
+pub use checkpoints::*;
+pub use withdrawal_cooldown::*;
+pub use spending_policy::*;
+pub use manage_authority::*;
+pub use manage_rate_limit::*;
+pub use manage_cooldown::*;
+pub use manage_operator::*;
+pub use manage_fees::*;
+pub use circuit_breaker::*;
+pub use emergency::*;
\ No newline at end of file
@@ -0,0 +1,717 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+};
+use anchor_lang::Discriminator;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Mint, Token, TokenAccount, Transfer},
+};
+use crate::state::{
+    CollateralVault, MintState, Multisig, MultisigAction, MultisigProposal, OwnerIndex,
+    ProposalAccount, ProtocolState,
+};
+use crate::constants::{
+    VAULT_SEED, MULTISIG_SEED, OWNER_INDEX_SEED, PROPOSAL_SEED, PROTOCOL_STATE_SEED,
+    MINT_STATE_SEED, MIN_DEPOSIT_AMOUNT, MAX_PROPOSAL_ACCOUNTS, MAX_PROPOSAL_DATA_LEN,
+};
+use crate::errors::ErrorCode;
+use crate::events::{
+    MultisigCreatedEvent, MultisigMembersChangedEvent, MultisigProposalApprovedEvent,
+    MultisigProposalCreatedEvent, MultisigProposalExecutedEvent, VaultInitializedEvent,
+    WithdrawEvent, WithdrawalExecutedEvent, WithdrawalRequestedEvent,
+};
+use crate::instructions::shared::{
+    enforce_spending_policy, fee_treasury, record_outflow, withdrawal_cooldown,
+};
+
+#[derive(Accounts)]
+#[instruction(create_key: Pubkey)]
+pub struct CreateMultisig<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = Multisig::LEN,
+        seeds = [MULTISIG_SEED, create_key.as_ref()],
+        bump
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(subaccount: u16)]
+pub struct InitializeMultisigVault<'info> {
+    #[account(mut)]
+    pub member: Signer<'info>,
+
+    #[account(
+        seeds = [MULTISIG_SEED, multisig.create_key.as_ref()],
+        bump = multisig.bump,
+        constraint = multisig.is_member(&member.key()) @ ErrorCode::NotMultisigMember
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        init,
+        payer = member,
+        space = CollateralVault::LEN,
+        seeds = [VAULT_SEED, multisig.key().as_ref(), subaccount.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        init_if_needed,
+        payer = member,
+        space = OwnerIndex::LEN,
+        seeds = [OWNER_INDEX_SEED, multisig.key().as_ref()],
+        bump
+    )]
+    pub owner_index: Account<'info, OwnerIndex>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        init_if_needed,
+        payer = member,
+        space = MintState::LEN,
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub mint_state: Account<'info, MintState>,
+
+    #[account(
+        init_if_needed,
+        payer = member,
+        associated_token::mint = mint,
+        associated_token::authority = vault
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub member_token_account: Account<'info, TokenAccount>,
+
+    pub mint: Account<'info, Mint>,
+
+    /// Protocol treasury for the vault mint; required while a deposit fee is set.
+    #[account(mut)]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateMultisigProposal<'info> {
+    #[account(mut)]
+    pub member: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [MULTISIG_SEED, multisig.create_key.as_ref()],
+        bump = multisig.bump,
+        constraint = multisig.is_member(&member.key()) @ ErrorCode::NotMultisigMember
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == multisig.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        init,
+        payer = member,
+        space = MultisigProposal::LEN,
+        seeds = [PROPOSAL_SEED, multisig.key().as_ref(), multisig.proposal_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, MultisigProposal>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveMultisigProposal<'info> {
+    pub member: Signer<'info>,
+
+    #[account(
+        seeds = [MULTISIG_SEED, multisig.create_key.as_ref()],
+        bump = multisig.bump,
+        constraint = multisig.is_member(&member.key()) @ ErrorCode::NotMultisigMember
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, multisig.key().as_ref(), proposal.index.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+        has_one = multisig
+    )]
+    pub proposal: Account<'info, MultisigProposal>,
+}
+
+/// A `VaultInstruction` proposal takes the accounts of its instruction,
+/// and the vault program itself, in `remaining_accounts`.
+#[derive(Accounts)]
+pub struct ExecuteMultisigProposal<'info> {
+    pub member: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [MULTISIG_SEED, multisig.create_key.as_ref()],
+        bump = multisig.bump,
+        constraint = multisig.is_member(&member.key()) @ ErrorCode::NotMultisigMember
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, multisig.key().as_ref(), proposal.index.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+        has_one = multisig,
+        has_one = vault @ ErrorCode::InvalidProposalAccounts
+    )]
+    pub proposal: Account<'info, MultisigProposal>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == multisig.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, vault_token_account.mint.as_ref()],
+        bump = mint_state.bump
+    )]
+    pub mint_state: Account<'info, MintState>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// Receives the funds of a `Withdraw` proposal.
+    #[account(mut)]
+    pub destination: Option<Account<'info, TokenAccount>>,
+
+    pub cosigner: Option<Signer<'info>>,
+
+    /// CHECK: Withdrawal cooldown PDA for the vault mint, validated in handler
+    pub cooldown_config: UncheckedAccount<'info>,
+
+    /// CHECK: Owner spending policy PDA for the vault, validated in handler
+    #[account(mut)]
+    pub spending_policy: UncheckedAccount<'info>,
+
+    /// Protocol treasury for the vault mint; required while a withdraw fee is set.
+    #[account(mut)]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn create_multisig(
+    ctx: Context<CreateMultisig>,
+    create_key: Pubkey,
+    members: Vec<Pubkey>,
+    threshold: u8,
+) -> Result<()> {
+    let multisig = &mut ctx.accounts.multisig;
+    multisig.set_members(members.clone(), threshold)?;
+    multisig.create_key = create_key;
+    multisig.proposal_count = 0;
+    multisig.bump = ctx.bumps.multisig;
+
+    let clock = Clock::get()?;
+    emit!(MultisigCreatedEvent {
+        multisig: multisig.key(),
+        members,
+        threshold,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Multisig created: {} of {}", threshold, multisig.members.len());
+
+    Ok(())
+}
+
+pub fn initialize_multisig_vault(
+    ctx: Context<InitializeMultisigVault>,
+    subaccount: u16,
+    initial_deposit: u64,
+) -> Result<()> {
+    require!(
+        initial_deposit >= MIN_DEPOSIT_AMOUNT,
+        ErrorCode::DepositBelowMinimum
+    );
+    ctx.accounts.protocol_state.require_active()?;
+
+    let protocol_state = &ctx.accounts.protocol_state;
+    let fee = protocol_state.fee_for(protocol_state.deposit_fee_bps, initial_deposit)?;
+    let credited = initial_deposit - fee;
+    require!(credited > 0, ErrorCode::InvalidAmount);
+
+    if fee > 0 {
+        let treasury = fee_treasury(
+            ctx.accounts.treasury_token_account.as_ref(),
+            &protocol_state.key(),
+            &ctx.accounts.mint.key(),
+        )?;
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.member_token_account.to_account_info(),
+                    to: treasury.to_account_info(),
+                    authority: ctx.accounts.member.to_account_info(),
+                },
+            ),
+            fee,
+        )?;
+    }
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.member_token_account.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.member.to_account_info(),
+            },
+        ),
+        credited,
+    )?;
+
+    let vault = &mut ctx.accounts.vault;
+    let multisig_key = ctx.accounts.multisig.key();
+    let clock = Clock::get()?;
+
+    let mint_state = &mut ctx.accounts.mint_state;
+    mint_state.initialize(ctx.accounts.mint.key(), clock.unix_timestamp, ctx.bumps.mint_state);
+    mint_state.record_inflow(&ctx.accounts.protocol_state, credited, &clock)?;
+    ctx.accounts.protocol_state.record_fee(fee)?;
+
+    vault.initialize(
+        multisig_key,
+        subaccount,
+        ctx.accounts.vault_token_account.key(),
+        ctx.accounts.member.key(),
+        credited,
+        mint_state.loss_per_token,
+        &clock,
+        ctx.bumps.vault,
+    );
+
+    ctx.accounts.owner_index.add_subaccount(
+        multisig_key,
+        subaccount,
+        vault.key(),
+        ctx.bumps.owner_index,
+    )?;
+
+    emit!(VaultInitializedEvent {
+        vault: vault.key(),
+        owner: multisig_key,
+        subaccount,
+        token_account: ctx.accounts.vault_token_account.key(),
+        rent_payer: ctx.accounts.member.key(),
+        initial_deposit: credited,
+        fee,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Vault initialized for multisig: {}", multisig_key);
+    msg!("Initial deposit: {} tokens (fee: {})", credited, fee);
+
+    Ok(())
+}
+
+pub fn create_multisig_proposal(
+    ctx: Context<CreateMultisigProposal>,
+    action: MultisigAction,
+) -> Result<()> {
+    match &action {
+        MultisigAction::Withdraw { amount, .. } => {
+            require!(*amount > 0, ErrorCode::InvalidAmount);
+        }
+        MultisigAction::ChangeMembers { members, threshold } => {
+            // Validate up front so members do not approve a change that
+            // can never execute.
+            let mut preview = (*ctx.accounts.multisig).clone();
+            preview.set_members(members.clone(), *threshold)?;
+        }
+        MultisigAction::VaultInstruction { accounts, data } => {
+            // A proposal may not execute another proposal on its way in.
+            require!(
+                accounts.len() <= MAX_PROPOSAL_ACCOUNTS
+                    && data.len() <= MAX_PROPOSAL_DATA_LEN
+                    && !data.starts_with(crate::instruction::ExecuteMultisigProposal::DISCRIMINATOR)
+                    && accounts.iter().any(|a| a.pubkey == ctx.accounts.vault.key()),
+                ErrorCode::InvalidProposalAccounts
+            );
+        }
+    }
+
+    let multisig = &mut ctx.accounts.multisig;
+    let proposal = &mut ctx.accounts.proposal;
+    let member = ctx.accounts.member.key();
+    let clock = Clock::get()?;
+
+    proposal.multisig = multisig.key();
+    proposal.vault = ctx.accounts.vault.key();
+    proposal.index = multisig.proposal_count;
+    proposal.proposer = member;
+    proposal.action = action.clone();
+    proposal.approvals = vec![member];
+    proposal.executed = false;
+    proposal.created_at = clock.unix_timestamp;
+    proposal.withdrawal_ready_at = 0;
+    proposal.bump = ctx.bumps.proposal;
+
+    multisig.proposal_count = multisig.proposal_count
+        .checked_add(1)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+    emit!(MultisigProposalCreatedEvent {
+        multisig: multisig.key(),
+        proposal: proposal.key(),
+        vault: proposal.vault,
+        index: proposal.index,
+        proposer: member,
+        action,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Multisig proposal {} created", proposal.index);
+
+    Ok(())
+}
+
+pub fn approve_multisig_proposal(ctx: Context<ApproveMultisigProposal>) -> Result<()> {
+    let multisig = &ctx.accounts.multisig;
+    let proposal = &mut ctx.accounts.proposal;
+    let member = ctx.accounts.member.key();
+
+    proposal.approve(member, &multisig.members)?;
+    let approvals = multisig.count_approvals(&proposal.approvals);
+
+    let clock = Clock::get()?;
+    emit!(MultisigProposalApprovedEvent {
+        multisig: multisig.key(),
+        proposal: proposal.key(),
+        member,
+        approvals: approvals as u8,
+        threshold: multisig.threshold,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Multisig proposal {} approved: {} of {}", proposal.index, approvals, multisig.threshold);
+
+    Ok(())
+}
+
+pub fn execute_multisig_proposal<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ExecuteMultisigProposal<'info>>,
+) -> Result<()> {
+    let proposal = &ctx.accounts.proposal;
+    require!(!proposal.executed, ErrorCode::ProposalAlreadyExecuted);
+    require!(
+        ctx.accounts.multisig.count_approvals(&proposal.approvals)
+            >= ctx.accounts.multisig.threshold as usize,
+        ErrorCode::ProposalThresholdNotMet
+    );
+
+    match proposal.action.clone() {
+        MultisigAction::Withdraw { destination, amount } => {
+            // Under a cooldown the first execution only requests the
+            // withdrawal; the proposal stays open until it is paid out.
+            if !execute_withdraw(ctx.accounts, destination, amount)? {
+                return Ok(());
+            }
+        }
+        MultisigAction::ChangeMembers { members, threshold } => {
+            let multisig = &mut ctx.accounts.multisig;
+            multisig.set_members(members.clone(), threshold)?;
+
+            let clock = Clock::get()?;
+            emit!(MultisigMembersChangedEvent {
+                multisig: multisig.key(),
+                members,
+                threshold,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+        MultisigAction::VaultInstruction { accounts, data } => {
+            execute_vault_instruction(&ctx, accounts, data)?;
+
+            // The instruction may have changed accounts this one writes back
+            // on exit, or closed the vault.
+            ctx.accounts.multisig.reload()?;
+            ctx.accounts.protocol_state.reload()?;
+            ctx.accounts.mint_state.reload()?;
+            if !ctx.accounts.vault.to_account_info().data_is_empty() {
+                ctx.accounts.vault.reload()?;
+            }
+        }
+    }
+
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.executed = true;
+
+    let clock = Clock::get()?;
+    emit!(MultisigProposalExecutedEvent {
+        multisig: ctx.accounts.multisig.key(),
+        proposal: proposal.key(),
+        vault: proposal.vault,
+        index: proposal.index,
+        executor: ctx.accounts.member.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Multisig proposal {} executed", proposal.index);
+
+    Ok(())
+}
+
+/// Calls a vault program instruction with the multisig PDA as a signer.
+/// Any other signer it names has to sign the proposal's execution.
+fn execute_vault_instruction<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, ExecuteMultisigProposal<'info>>,
+    accounts: Vec<ProposalAccount>,
+    data: Vec<u8>,
+) -> Result<()> {
+    let multisig = &ctx.accounts.multisig;
+    let multisig_info = multisig.to_account_info();
+    let program_info = ctx.remaining_accounts
+        .iter()
+        .find(|info| info.key() == crate::ID)
+        .ok_or(ErrorCode::InvalidProposalAccounts)?;
+
+    let mut account_infos = Vec::with_capacity(accounts.len() + 1);
+    for account in &accounts {
+        let info = if account.pubkey == multisig.key() {
+            &multisig_info
+        } else {
+            ctx.remaining_accounts
+                .iter()
+                .find(|info| info.key() == account.pubkey)
+                .ok_or(ErrorCode::InvalidProposalAccounts)?
+        };
+        account_infos.push(info.clone());
+    }
+    account_infos.push(program_info.clone());
+
+    let ix = Instruction {
+        program_id: crate::ID,
+        accounts: accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.pubkey,
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data,
+    };
+
+    let create_key = multisig.create_key;
+    let seeds = &[MULTISIG_SEED, create_key.as_ref(), &[multisig.bump]];
+    invoke_signed(&ix, &account_infos, &[&seeds[..]])?;
+    Ok(())
+}
+
+/// Returns whether the withdrawal was paid out. While the mint has a
+/// cooldown, the first call requests it and a later one pays it out once
+/// the request is ready.
+fn execute_withdraw(
+    accounts: &mut ExecuteMultisigProposal,
+    destination: Pubkey,
+    amount: u64,
+) -> Result<bool> {
+    accounts.protocol_state.require_withdrawals_enabled()?;
+    accounts.vault.require_not_locked_down()?;
+    accounts.vault.require_unpooled()?;
+
+    let destination_account = accounts.destination
+        .as_ref()
+        .ok_or(ErrorCode::InvalidProposalAccounts)?;
+    require_keys_eq!(
+        destination_account.key(),
+        destination,
+        ErrorCode::InvalidProposalAccounts
+    );
+
+    let cooldown = withdrawal_cooldown(
+        &accounts.protocol_state,
+        &accounts.cooldown_config,
+        &accounts.vault_token_account.mint,
+    )?;
+
+    let clock = Clock::get()?;
+    let vault = &mut accounts.vault;
+    vault.absorb_socialized_loss(&accounts.mint_state, &clock)?;
+    let cosigner = accounts.cosigner.as_ref().map(|c| c.key());
+    let requested = accounts.proposal.withdrawal_ready_at > 0;
+
+    if cooldown > 0 && !requested {
+        // One request at a time, so the payout is this proposal's alone.
+        require!(vault.pending_withdrawal == 0, ErrorCode::PendingWithdrawalExists);
+        require!(
+            vault.available_balance >= amount,
+            ErrorCode::InsufficientAvailableBalance
+        );
+        vault.require_cosigner(amount, cosigner)?;
+
+        let ready_at = clock.unix_timestamp
+            .checked_add(cooldown)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        vault.request_withdrawal(amount, ready_at, &clock)?;
+        accounts.proposal.withdrawal_ready_at = ready_at;
+
+        emit!(WithdrawalRequestedEvent {
+            vault: vault.key(),
+            user: accounts.member.key(),
+            amount,
+            pending_withdrawal: vault.pending_withdrawal,
+            ready_at,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("✅ Multisig requested withdrawal of {} tokens", amount);
+        msg!("Ready at: {}", ready_at);
+
+        return Ok(false);
+    }
+
+    if requested {
+        // Cancelled or replaced requests leave nothing for this proposal.
+        require!(vault.pending_withdrawal == amount, ErrorCode::NoPendingWithdrawal);
+        require!(
+            clock.unix_timestamp >= vault.withdrawal_ready_at,
+            ErrorCode::WithdrawalNotReady
+        );
+    } else {
+        require!(
+            vault.available_balance >= amount,
+            ErrorCode::InsufficientAvailableBalance
+        );
+        vault.require_cosigner(amount, cosigner)?;
+    }
+
+    enforce_spending_policy(
+        &accounts.spending_policy,
+        vault.key(),
+        vault.owner,
+        vault.delegation_epoch,
+        Some(destination_account),
+        amount,
+        false,
+    )?;
+
+    let protocol_state = &accounts.protocol_state;
+    let fee = protocol_state.fee_for(protocol_state.withdraw_fee_bps, amount)?;
+    let received = amount - fee;
+    require!(received > 0, ErrorCode::InvalidAmount);
+
+    let seed_owner = vault.seed_owner;
+    let subaccount = vault.subaccount.to_le_bytes();
+    let seeds = &[
+        VAULT_SEED,
+        seed_owner.as_ref(),
+        subaccount.as_ref(),
+        &[vault.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            accounts.token_program.to_account_info(),
+            Transfer {
+                from: accounts.vault_token_account.to_account_info(),
+                to: destination_account.to_account_info(),
+                authority: vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        received,
+    )?;
+
+    if fee > 0 {
+        let treasury = fee_treasury(
+            accounts.treasury_token_account.as_ref(),
+            &protocol_state.key(),
+            &accounts.vault_token_account.mint,
+        )?;
+        token::transfer(
+            CpiContext::new_with_signer(
+                accounts.token_program.to_account_info(),
+                Transfer {
+                    from: accounts.vault_token_account.to_account_info(),
+                    to: treasury.to_account_info(),
+                    authority: vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            fee,
+        )?;
+    }
+
+    if requested {
+        vault.execute_withdrawal(&clock)?;
+    } else {
+        vault.withdraw(amount, &clock)?;
+    }
+    record_outflow(&mut accounts.protocol_state, &mut accounts.mint_state, amount, true)?;
+    accounts.protocol_state.record_fee(fee)?;
+
+    if requested {
+        emit!(WithdrawalExecutedEvent {
+            vault: vault.key(),
+            user: accounts.member.key(),
+            amount,
+            fee,
+            new_total_balance: vault.total_balance,
+            timestamp: clock.unix_timestamp,
+        });
+    } else {
+        emit!(WithdrawEvent {
+            vault: vault.key(),
+            user: accounts.member.key(),
+            amount,
+            fee,
+            new_total_balance: vault.total_balance,
+            new_available_balance: vault.available_balance,
+            timestamp: clock.unix_timestamp,
+        });
+    }
+
+    msg!("✅ Multisig withdrew {} tokens to {} (fee: {})", received, destination, fee);
+
+    Ok(true)
+}
@@ -0,0 +1,137 @@
+use anchor_lang::prelude::*;
+use crate::state::{CollateralVault, OwnerIndex};
+use crate::constants::{VAULT_SEED, OWNER_INDEX_SEED};
+use crate::errors::ErrorCode;
+use crate::events::{OwnerTransferCancelledEvent, OwnerTransferProposedEvent, OwnerTransferredEvent};
+
+#[derive(Accounts)]
+pub struct ProposeOwnerTransfer<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+}
+
+#[derive(Accounts)]
+pub struct CancelOwnerTransfer<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptOwnerTransfer<'info> {
+    #[account(mut)]
+    pub new_owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        mut,
+        seeds = [OWNER_INDEX_SEED, vault.owner.as_ref()],
+        bump = previous_owner_index.bump
+    )]
+    pub previous_owner_index: Account<'info, OwnerIndex>,
+
+    #[account(
+        init_if_needed,
+        payer = new_owner,
+        space = OwnerIndex::LEN,
+        seeds = [OWNER_INDEX_SEED, new_owner.key().as_ref()],
+        bump
+    )]
+    pub new_owner_index: Account<'info, OwnerIndex>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn propose_owner_transfer(ctx: Context<ProposeOwnerTransfer>, new_owner: Pubkey) -> Result<()> {
+    require!(
+        new_owner != Pubkey::default() && new_owner != ctx.accounts.user.key(),
+        ErrorCode::InvalidNewOwner
+    );
+
+    let vault = &mut ctx.accounts.vault;
+    vault.require_not_locked_down()?;
+    vault.pending_owner = Some(new_owner);
+
+    let clock = Clock::get()?;
+    emit!(OwnerTransferProposedEvent {
+        vault: vault.key(),
+        owner: vault.owner,
+        pending_owner: new_owner,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Ownership transfer proposed to: {}", new_owner);
+
+    Ok(())
+}
+
+pub fn cancel_owner_transfer(ctx: Context<CancelOwnerTransfer>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let pending_owner = vault.pending_owner
+        .take()
+        .ok_or(ErrorCode::NoPendingOwnerTransfer)?;
+
+    let clock = Clock::get()?;
+    emit!(OwnerTransferCancelledEvent {
+        vault: vault.key(),
+        owner: vault.owner,
+        pending_owner,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Ownership transfer to {} cancelled", pending_owner);
+
+    Ok(())
+}
+
+pub fn accept_owner_transfer(ctx: Context<AcceptOwnerTransfer>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let new_owner = ctx.accounts.new_owner.key();
+    vault.require_not_locked_down()?;
+
+    let pending_owner = vault.pending_owner.ok_or(ErrorCode::NoPendingOwnerTransfer)?;
+    require!(pending_owner == new_owner, ErrorCode::UnauthorizedPendingOwner);
+
+    let previous_owner = vault.owner;
+    vault.transfer_ownership(new_owner)?;
+
+    ctx.accounts.previous_owner_index.remove_subaccount(vault.key())?;
+    ctx.accounts.new_owner_index.add_subaccount(
+        new_owner,
+        vault.subaccount,
+        vault.key(),
+        ctx.bumps.new_owner_index,
+    )?;
+
+    let clock = Clock::get()?;
+    emit!(OwnerTransferredEvent {
+        vault: vault.key(),
+        previous_owner,
+        new_owner,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Vault ownership transferred to: {}", new_owner);
+    msg!("Previous owner: {}", previous_owner);
+
+    Ok(())
+}
@@ -0,0 +1,487 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use solana_instructions_sysvar::{load_current_index_checked, load_instruction_at_checked};
+use crate::state::{CollateralVault, MintState, PermitAction, PermitMessage, ProtocolState};
+use crate::constants::{VAULT_SEED, PROTOCOL_STATE_SEED, MINT_STATE_SEED};
+use crate::errors::ErrorCode;
+use crate::events::PermitExecutedEvent;
+use crate::instructions::shared::{
+    enforce_spending_policy, fee_treasury, record_outflow, withdrawal_cooldown,
+};
+
+/// Size of the Ed25519 instruction header (signature count + padding).
+const ED25519_HEADER_LEN: usize = 2;
+/// Size of one Ed25519 signature offsets record.
+const ED25519_OFFSETS_LEN: usize = 14;
+const ED25519_PUBKEY_LEN: usize = 32;
+
+#[derive(Accounts)]
+pub struct PermitDeposit<'info> {
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, vault_token_account.mint.as_ref()],
+        bump = mint_state.bump
+    )]
+    pub mint_state: Account<'info, MintState>,
+
+    /// Owner's token account; the vault PDA must be an approved delegate.
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == vault.owner @ ErrorCode::UnauthorizedOwner
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub relayer_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Protocol treasury for the vault mint; required while a deposit fee is set.
+    #[account(mut)]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: Instructions sysvar used to find the Ed25519 signature check
+    #[account(address = solana_instructions_sysvar::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct PermitWithdraw<'info> {
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    pub cosigner: Option<Signer<'info>>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, vault_token_account.mint.as_ref()],
+        bump = mint_state.bump
+    )]
+    pub mint_state: Account<'info, MintState>,
+
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == vault.owner @ ErrorCode::UnauthorizedOwner
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub relayer_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Protocol treasury for the vault mint; required while a withdraw fee is set.
+    #[account(mut)]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: Withdrawal cooldown PDA for the vault mint, validated in handler
+    pub cooldown_config: UncheckedAccount<'info>,
+
+    /// CHECK: Owner spending policy PDA for the vault, validated in handler
+    #[account(mut)]
+    pub spending_policy: UncheckedAccount<'info>,
+
+    /// CHECK: Instructions sysvar used to find the Ed25519 signature check
+    #[account(address = solana_instructions_sysvar::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Requires the instruction immediately before this one to be an Ed25519
+/// program check of `signer`'s signature over exactly `message`.
+fn verify_permit_signature(
+    instructions_sysvar: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let current_index = load_current_index_checked(instructions_sysvar)? as usize;
+    require!(current_index > 0, ErrorCode::InvalidPermitSignature);
+
+    let ed25519_ix = load_instruction_at_checked(current_index - 1, instructions_sysvar)?;
+    require_keys_eq!(
+        ed25519_ix.program_id,
+        solana_sdk_ids::ed25519_program::ID,
+        ErrorCode::InvalidPermitSignature
+    );
+
+    let data = &ed25519_ix.data;
+    require!(
+        data.len() >= ED25519_HEADER_LEN + ED25519_OFFSETS_LEN && data[0] == 1,
+        ErrorCode::InvalidPermitSignature
+    );
+
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let offsets = ED25519_HEADER_LEN;
+    let signature_ix_index = read_u16(offsets + 2);
+    let pubkey_offset = read_u16(offsets + 4) as usize;
+    let pubkey_ix_index = read_u16(offsets + 6);
+    let message_offset = read_u16(offsets + 8) as usize;
+    let message_size = read_u16(offsets + 10) as usize;
+    let message_ix_index = read_u16(offsets + 12);
+
+    // All signature data must live inside the Ed25519 instruction itself.
+    require!(
+        signature_ix_index == u16::MAX
+            && pubkey_ix_index == u16::MAX
+            && message_ix_index == u16::MAX,
+        ErrorCode::InvalidPermitSignature
+    );
+
+    let signed_pubkey = data
+        .get(pubkey_offset..pubkey_offset + ED25519_PUBKEY_LEN)
+        .ok_or(ErrorCode::InvalidPermitSignature)?;
+    let signed_message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(ErrorCode::InvalidPermitSignature)?;
+
+    require!(
+        signed_pubkey == signer.as_ref() && signed_message == message,
+        ErrorCode::InvalidPermitSignature
+    );
+
+    Ok(())
+}
+
+/// Checks the permit and burns its nonce.
+fn consume_permit(
+    vault: &mut Account<CollateralVault>,
+    instructions_sysvar: &AccountInfo,
+    message: PermitMessage,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    require!(clock.unix_timestamp <= message.expiry, ErrorCode::PermitExpired);
+
+    let message_bytes = message.try_to_vec()?;
+    verify_permit_signature(instructions_sysvar, &vault.owner, &message_bytes)?;
+    vault.use_permit_nonce(message.nonce)
+}
+
+/// Pays the signed relayer fee out of the vault's available balance.
+fn pay_relayer_fee<'info>(
+    vault: &mut Account<'info, CollateralVault>,
+    vault_token_account: &Account<'info, TokenAccount>,
+    relayer_token_account: Option<&Account<'info, TokenAccount>>,
+    protocol_state: &mut ProtocolState,
+    mint_state: &mut MintState,
+    token_program: &Program<'info, Token>,
+    relayer_fee: u64,
+) -> Result<()> {
+    if relayer_fee == 0 {
+        return Ok(());
+    }
+    let relayer_token_account = relayer_token_account
+        .ok_or(ErrorCode::MissingRelayerTokenAccount)?;
+
+    require!(
+        vault.available_balance >= relayer_fee,
+        ErrorCode::InsufficientAvailableBalance
+    );
+
+    let seed_owner = vault.seed_owner;
+    let subaccount = vault.subaccount.to_le_bytes();
+    let seeds = &[
+        VAULT_SEED,
+        seed_owner.as_ref(),
+        subaccount.as_ref(),
+        &[vault.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: vault_token_account.to_account_info(),
+                to: relayer_token_account.to_account_info(),
+                authority: vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        relayer_fee,
+    )?;
+
+    let clock = Clock::get()?;
+    vault.withdraw(relayer_fee, &clock)?;
+    record_outflow(protocol_state, mint_state, relayer_fee, true)
+}
+
+pub fn permit_deposit(
+    ctx: Context<PermitDeposit>,
+    amount: u64,
+    relayer_fee: u64,
+    nonce: u64,
+    expiry: i64,
+) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    ctx.accounts.protocol_state.require_active()?;
+    ctx.accounts.vault.require_unpooled()?;
+
+    let vault = &mut ctx.accounts.vault;
+    let message = PermitMessage {
+        vault: vault.key(),
+        action: PermitAction::Deposit,
+        amount,
+        relayer_fee,
+        nonce,
+        expiry,
+    };
+    consume_permit(vault, &ctx.accounts.instructions_sysvar, message)?;
+
+    let vault_owner = vault.owner;
+    let seed_owner = vault.seed_owner;
+    let subaccount = vault.subaccount.to_le_bytes();
+    let seeds = &[
+        VAULT_SEED,
+        seed_owner.as_ref(),
+        subaccount.as_ref(),
+        &[vault.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    let protocol_state = &ctx.accounts.protocol_state;
+    let fee = protocol_state.fee_for(protocol_state.deposit_fee_bps, amount)?;
+    let credited = amount - fee;
+    require!(credited > 0, ErrorCode::InvalidAmount);
+
+    // The vault PDA moves the owner's tokens as their approved delegate.
+    if fee > 0 {
+        let treasury = fee_treasury(
+            ctx.accounts.treasury_token_account.as_ref(),
+            &protocol_state.key(),
+            &ctx.accounts.vault_token_account.mint,
+        )?;
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.owner_token_account.to_account_info(),
+                    to: treasury.to_account_info(),
+                    authority: vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            fee,
+        )?;
+    }
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.owner_token_account.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        credited,
+    )?;
+
+    let clock = Clock::get()?;
+    vault.absorb_socialized_loss(&ctx.accounts.mint_state, &clock)?;
+    vault.deposit(credited, &clock)?;
+
+    ctx.accounts.mint_state.record_inflow(&ctx.accounts.protocol_state, credited, &clock)?;
+    ctx.accounts.protocol_state.record_fee(fee)?;
+
+    pay_relayer_fee(
+        vault,
+        &ctx.accounts.vault_token_account,
+        ctx.accounts.relayer_token_account.as_ref(),
+        &mut ctx.accounts.protocol_state,
+        &mut ctx.accounts.mint_state,
+        &ctx.accounts.token_program,
+        relayer_fee,
+    )?;
+
+    emit!(PermitExecutedEvent {
+        vault: vault.key(),
+        owner: vault_owner,
+        relayer: ctx.accounts.relayer.key(),
+        action: PermitAction::Deposit,
+        amount,
+        fee,
+        relayer_fee,
+        nonce,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Permit deposit of {} tokens (fee: {})", credited, fee);
+    msg!("Relayer fee: {}", relayer_fee);
+
+    Ok(())
+}
+
+pub fn permit_withdraw(
+    ctx: Context<PermitWithdraw>,
+    amount: u64,
+    relayer_fee: u64,
+    nonce: u64,
+    expiry: i64,
+) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    ctx.accounts.protocol_state.require_withdrawals_enabled()?;
+    ctx.accounts.vault.require_not_locked_down()?;
+    ctx.accounts.vault.require_unpooled()?;
+
+    let cooldown = withdrawal_cooldown(
+        &ctx.accounts.protocol_state,
+        &ctx.accounts.cooldown_config,
+        &ctx.accounts.vault_token_account.mint,
+    )?;
+    require!(cooldown == 0, ErrorCode::WithdrawalCooldownRequired);
+
+    let vault = &mut ctx.accounts.vault;
+    let message = PermitMessage {
+        vault: vault.key(),
+        action: PermitAction::Withdraw,
+        amount,
+        relayer_fee,
+        nonce,
+        expiry,
+    };
+    consume_permit(vault, &ctx.accounts.instructions_sysvar, message)?;
+    let clock = Clock::get()?;
+    vault.absorb_socialized_loss(&ctx.accounts.mint_state, &clock)?;
+
+    let total_out = amount
+        .checked_add(relayer_fee)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    require!(
+        vault.available_balance >= total_out,
+        ErrorCode::InsufficientAvailableBalance
+    );
+    vault.require_cosigner(total_out, ctx.accounts.cosigner.as_ref().map(|c| c.key()))?;
+
+    enforce_spending_policy(
+        &ctx.accounts.spending_policy,
+        vault.key(),
+        vault.owner,
+        vault.delegation_epoch,
+        Some(&ctx.accounts.owner_token_account),
+        total_out,
+        false,
+    )?;
+
+    let vault_owner = vault.owner;
+    let seed_owner = vault.seed_owner;
+    let subaccount = vault.subaccount.to_le_bytes();
+    let seeds = &[
+        VAULT_SEED,
+        seed_owner.as_ref(),
+        subaccount.as_ref(),
+        &[vault.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    let protocol_state = &ctx.accounts.protocol_state;
+    let fee = protocol_state.fee_for(protocol_state.withdraw_fee_bps, amount)?;
+    let received = amount - fee;
+    require!(received > 0, ErrorCode::InvalidAmount);
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                to: ctx.accounts.owner_token_account.to_account_info(),
+                authority: vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        received,
+    )?;
+
+    if fee > 0 {
+        let treasury = fee_treasury(
+            ctx.accounts.treasury_token_account.as_ref(),
+            &protocol_state.key(),
+            &ctx.accounts.vault_token_account.mint,
+        )?;
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault_token_account.to_account_info(),
+                    to: treasury.to_account_info(),
+                    authority: vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            fee,
+        )?;
+    }
+
+    vault.withdraw(amount, &clock)?;
+    record_outflow(&mut ctx.accounts.protocol_state, &mut ctx.accounts.mint_state, amount, true)?;
+    ctx.accounts.protocol_state.record_fee(fee)?;
+
+    pay_relayer_fee(
+        vault,
+        &ctx.accounts.vault_token_account,
+        ctx.accounts.relayer_token_account.as_ref(),
+        &mut ctx.accounts.protocol_state,
+        &mut ctx.accounts.mint_state,
+        &ctx.accounts.token_program,
+        relayer_fee,
+    )?;
+
+    emit!(PermitExecutedEvent {
+        vault: vault.key(),
+        owner: vault_owner,
+        relayer: ctx.accounts.relayer.key(),
+        action: PermitAction::Withdraw,
+        amount,
+        fee,
+        relayer_fee,
+        nonce,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Permit withdrawal of {} tokens (fee: {})", received, fee);
+    msg!("Relayer fee: {}", relayer_fee);
+
+    Ok(())
+}
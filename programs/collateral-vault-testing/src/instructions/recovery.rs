@@ -0,0 +1,293 @@
+use anchor_lang::prelude::*;
+use crate::state::{CollateralVault, OwnerIndex, RecoveryConfig};
+use crate::constants::{VAULT_SEED, OWNER_INDEX_SEED, RECOVERY_SEED};
+use crate::errors::ErrorCode;
+use crate::events::{
+    RecoveryApprovedEvent, RecoveryConfiguredEvent, RecoveryExecutedEvent,
+    RecoveryInitiatedEvent, RecoveryVetoedEvent,
+};
+
+#[derive(Accounts)]
+pub struct ConfigureRecovery<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = RecoveryConfig::LEN,
+        seeds = [RECOVERY_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub recovery_config: Account<'info, RecoveryConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitiateRecovery<'info> {
+    pub guardian: Signer<'info>,
+
+    #[account(
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        mut,
+        seeds = [RECOVERY_SEED, vault.key().as_ref()],
+        bump = recovery_config.bump,
+        constraint = recovery_config.is_guardian(&guardian.key()) @ ErrorCode::UnauthorizedGuardian,
+        constraint = recovery_config.delegation_epoch == vault.delegation_epoch @ ErrorCode::DelegationRevoked
+    )]
+    pub recovery_config: Account<'info, RecoveryConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveRecovery<'info> {
+    pub guardian: Signer<'info>,
+
+    #[account(
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        mut,
+        seeds = [RECOVERY_SEED, vault.key().as_ref()],
+        bump = recovery_config.bump,
+        constraint = recovery_config.is_guardian(&guardian.key()) @ ErrorCode::UnauthorizedGuardian,
+        constraint = recovery_config.delegation_epoch == vault.delegation_epoch @ ErrorCode::DelegationRevoked
+    )]
+    pub recovery_config: Account<'info, RecoveryConfig>,
+}
+
+#[derive(Accounts)]
+pub struct VetoRecovery<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        mut,
+        seeds = [RECOVERY_SEED, vault.key().as_ref()],
+        bump = recovery_config.bump
+    )]
+    pub recovery_config: Account<'info, RecoveryConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteRecovery<'info> {
+    #[account(mut)]
+    pub new_owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        mut,
+        seeds = [RECOVERY_SEED, vault.key().as_ref()],
+        bump = recovery_config.bump,
+        constraint = recovery_config.delegation_epoch == vault.delegation_epoch @ ErrorCode::DelegationRevoked
+    )]
+    pub recovery_config: Account<'info, RecoveryConfig>,
+
+    #[account(
+        mut,
+        seeds = [OWNER_INDEX_SEED, vault.owner.as_ref()],
+        bump = previous_owner_index.bump
+    )]
+    pub previous_owner_index: Account<'info, OwnerIndex>,
+
+    #[account(
+        init_if_needed,
+        payer = new_owner,
+        space = OwnerIndex::LEN,
+        seeds = [OWNER_INDEX_SEED, new_owner.key().as_ref()],
+        bump
+    )]
+    pub new_owner_index: Account<'info, OwnerIndex>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn configure_recovery(
+    ctx: Context<ConfigureRecovery>,
+    guardians: Vec<Pubkey>,
+    threshold: u8,
+    recovery_delay: i64,
+) -> Result<()> {
+    let recovery_config = &mut ctx.accounts.recovery_config;
+    let delegation_epoch = ctx.accounts.vault.delegation_epoch;
+
+    // A pending recovery must be vetoed explicitly so guardians see why it
+    // stopped. One started by a previous owner's guardians is simply dropped.
+    require!(
+        recovery_config.delegation_epoch != delegation_epoch
+            || !recovery_config.has_pending_recovery(),
+        ErrorCode::RecoveryAlreadyPending
+    );
+
+    recovery_config.configure(
+        ctx.accounts.vault.key(),
+        guardians.clone(),
+        threshold,
+        recovery_delay,
+        delegation_epoch,
+        ctx.bumps.recovery_config,
+    )?;
+
+    let clock = Clock::get()?;
+    emit!(RecoveryConfiguredEvent {
+        vault: ctx.accounts.vault.key(),
+        owner: ctx.accounts.user.key(),
+        guardians,
+        threshold,
+        recovery_delay,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Recovery configured: {} of {} guardians", threshold, recovery_config.guardians.len());
+    msg!("Recovery delay: {}s", recovery_delay);
+
+    Ok(())
+}
+
+pub fn initiate_recovery(ctx: Context<InitiateRecovery>, new_owner: Pubkey) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    require!(
+        new_owner != Pubkey::default() && new_owner != vault.owner,
+        ErrorCode::InvalidNewOwner
+    );
+
+    let recovery_config = &mut ctx.accounts.recovery_config;
+    let guardian = ctx.accounts.guardian.key();
+    let clock = Clock::get()?;
+
+    recovery_config.initiate(guardian, new_owner, clock.unix_timestamp)?;
+
+    emit!(RecoveryInitiatedEvent {
+        vault: vault.key(),
+        guardian,
+        proposed_owner: new_owner,
+        timestamp: clock.unix_timestamp,
+    });
+    emit!(RecoveryApprovedEvent {
+        vault: vault.key(),
+        guardian,
+        proposed_owner: new_owner,
+        approvals: recovery_config.approvals.len() as u8,
+        threshold: recovery_config.threshold,
+        recovery_ready_at: recovery_config.recovery_ready_at,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Recovery initiated to new owner: {}", new_owner);
+
+    Ok(())
+}
+
+pub fn approve_recovery(ctx: Context<ApproveRecovery>) -> Result<()> {
+    let recovery_config = &mut ctx.accounts.recovery_config;
+    let guardian = ctx.accounts.guardian.key();
+    let clock = Clock::get()?;
+
+    recovery_config.approve(guardian, clock.unix_timestamp)?;
+    let proposed_owner = recovery_config.proposed_owner.ok_or(ErrorCode::NoPendingRecovery)?;
+
+    emit!(RecoveryApprovedEvent {
+        vault: ctx.accounts.vault.key(),
+        guardian,
+        proposed_owner,
+        approvals: recovery_config.approvals.len() as u8,
+        threshold: recovery_config.threshold,
+        recovery_ready_at: recovery_config.recovery_ready_at,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "✅ Recovery approved: {} of {}",
+        recovery_config.approvals.len(),
+        recovery_config.threshold
+    );
+
+    Ok(())
+}
+
+pub fn veto_recovery(ctx: Context<VetoRecovery>) -> Result<()> {
+    let recovery_config = &mut ctx.accounts.recovery_config;
+    let proposed_owner = recovery_config.proposed_owner.ok_or(ErrorCode::NoPendingRecovery)?;
+    recovery_config.clear_pending();
+
+    let clock = Clock::get()?;
+    emit!(RecoveryVetoedEvent {
+        vault: ctx.accounts.vault.key(),
+        owner: ctx.accounts.user.key(),
+        proposed_owner,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Recovery to {} vetoed", proposed_owner);
+
+    Ok(())
+}
+
+pub fn execute_recovery(ctx: Context<ExecuteRecovery>) -> Result<()> {
+    let recovery_config = &mut ctx.accounts.recovery_config;
+    let new_owner = ctx.accounts.new_owner.key();
+    let clock = Clock::get()?;
+
+    let proposed_owner = recovery_config.proposed_owner.ok_or(ErrorCode::NoPendingRecovery)?;
+    require!(proposed_owner == new_owner, ErrorCode::UnauthorizedPendingOwner);
+    require!(
+        recovery_config.is_ready(clock.unix_timestamp),
+        ErrorCode::RecoveryNotReady
+    );
+    recovery_config.clear_pending();
+
+    let vault = &mut ctx.accounts.vault;
+    let previous_owner = vault.owner;
+    vault.transfer_ownership(new_owner)?;
+    // The lockdown recovery key went with the other delegations, so a
+    // lockdown left in place could never be lifted.
+    vault.locked_down = false;
+    vault.lockdown_release_at = 0;
+
+    ctx.accounts.previous_owner_index.remove_subaccount(vault.key())?;
+    ctx.accounts.new_owner_index.add_subaccount(
+        new_owner,
+        vault.subaccount,
+        vault.key(),
+        ctx.bumps.new_owner_index,
+    )?;
+
+    emit!(RecoveryExecutedEvent {
+        vault: vault.key(),
+        previous_owner,
+        new_owner,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Vault recovered to new owner: {}", new_owner);
+    msg!("Previous owner: {}", previous_owner);
+
+    Ok(())
+}
@@ -0,0 +1,460 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Mint, Token, TokenAccount, Transfer},
+};
+use crate::state::{CollateralVault, RewardPool, RewardPosition, VaultAuthority};
+use crate::constants::{
+    AUTHORITY_SEED, MAX_REWARD_POSITIONS, REWARD_POOL_SEED, REWARD_POSITION_SEED, VAULT_SEED,
+};
+use crate::errors::ErrorCode;
+use crate::events::{
+    RewardPoolCreatedEvent, RewardPoolFundedEvent, RewardPositionClosedEvent,
+    RewardPositionOpenedEvent, RewardPositionSyncedEvent, RewardsClaimedEvent,
+};
+
+#[derive(Accounts)]
+pub struct CreateRewardPool<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    pub collateral_mint: Account<'info, Mint>,
+
+    pub reward_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = RewardPool::LEN,
+        seeds = [REWARD_POOL_SEED, collateral_mint.key().as_ref(), reward_mint.key().as_ref()],
+        bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        associated_token::mint = reward_mint,
+        associated_token::authority = reward_pool
+    )]
+    pub reward_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundRewardPool<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        mut,
+        seeds = [
+            REWARD_POOL_SEED,
+            reward_pool.collateral_mint.as_ref(),
+            reward_pool.reward_mint.as_ref()
+        ],
+        bump = reward_pool.bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(
+        mut,
+        constraint = reward_token_account.key() == reward_pool.reward_token_account @ ErrorCode::InvalidRewardPool
+    )]
+    pub reward_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub admin_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct OpenRewardPosition<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            REWARD_POOL_SEED,
+            reward_pool.collateral_mint.as_ref(),
+            reward_pool.reward_mint.as_ref()
+        ],
+        bump = reward_pool.bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        constraint = vault_token_account.key() == vault.token_account @ ErrorCode::UnauthorizedOwner,
+        constraint = vault_token_account.mint == reward_pool.collateral_mint @ ErrorCode::InvalidRewardPool
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = RewardPosition::LEN,
+        seeds = [REWARD_POSITION_SEED, reward_pool.key().as_ref(), vault.key().as_ref()],
+        bump
+    )]
+    pub reward_position: Account<'info, RewardPosition>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Permissionless, so anyone may bring a position's stake up to date.
+#[derive(Accounts)]
+pub struct SyncRewardPosition<'info> {
+    #[account(
+        mut,
+        seeds = [
+            REWARD_POOL_SEED,
+            reward_pool.collateral_mint.as_ref(),
+            reward_pool.reward_mint.as_ref()
+        ],
+        bump = reward_pool.bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        mut,
+        seeds = [REWARD_POSITION_SEED, reward_pool.key().as_ref(), vault.key().as_ref()],
+        bump = reward_position.bump
+    )]
+    pub reward_position: Account<'info, RewardPosition>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        mut,
+        seeds = [
+            REWARD_POOL_SEED,
+            reward_pool.collateral_mint.as_ref(),
+            reward_pool.reward_mint.as_ref()
+        ],
+        bump = reward_pool.bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(
+        mut,
+        seeds = [REWARD_POSITION_SEED, reward_pool.key().as_ref(), vault.key().as_ref()],
+        bump = reward_position.bump
+    )]
+    pub reward_position: Account<'info, RewardPosition>,
+
+    #[account(
+        mut,
+        constraint = reward_token_account.key() == reward_pool.reward_token_account @ ErrorCode::InvalidRewardPool
+    )]
+    pub reward_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_reward_token_account.mint == reward_pool.reward_mint @ ErrorCode::InvalidRewardPool
+    )]
+    pub user_reward_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Takes the position's stake out of the pool. Anything it earned must
+/// have been claimed first.
+#[derive(Accounts)]
+pub struct CloseRewardPosition<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        mut,
+        seeds = [
+            REWARD_POOL_SEED,
+            reward_pool.collateral_mint.as_ref(),
+            reward_pool.reward_mint.as_ref()
+        ],
+        bump = reward_pool.bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(
+        mut,
+        close = user,
+        seeds = [REWARD_POSITION_SEED, reward_pool.key().as_ref(), vault.key().as_ref()],
+        bump = reward_position.bump
+    )]
+    pub reward_position: Account<'info, RewardPosition>,
+}
+
+pub fn create_reward_pool(ctx: Context<CreateRewardPool>, reward_duration: i64) -> Result<()> {
+    require!(reward_duration > 0, ErrorCode::InvalidRewardDuration);
+
+    let clock = Clock::get()?;
+    let reward_pool = &mut ctx.accounts.reward_pool;
+    reward_pool.collateral_mint = ctx.accounts.collateral_mint.key();
+    reward_pool.reward_mint = ctx.accounts.reward_mint.key();
+    reward_pool.reward_token_account = ctx.accounts.reward_token_account.key();
+    reward_pool.reward_duration = reward_duration;
+    reward_pool.reward_rate = 0;
+    reward_pool.period_finish = clock.unix_timestamp;
+    reward_pool.last_update_time = clock.unix_timestamp;
+    reward_pool.reward_per_token_stored = 0;
+    reward_pool.total_staked = 0;
+    reward_pool.total_funded = 0;
+    reward_pool.total_claimed = 0;
+    reward_pool.created_at = clock.unix_timestamp;
+    reward_pool.bump = ctx.bumps.reward_pool;
+
+    emit!(RewardPoolCreatedEvent {
+        reward_pool: reward_pool.key(),
+        collateral_mint: reward_pool.collateral_mint,
+        reward_mint: reward_pool.reward_mint,
+        reward_duration,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Reward pool created for collateral mint: {}", reward_pool.collateral_mint);
+    msg!("Reward mint: {}, emission period: {}s", reward_pool.reward_mint, reward_duration);
+
+    Ok(())
+}
+
+pub fn fund_reward_pool(ctx: Context<FundRewardPool>, amount: u64) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.admin_token_account.to_account_info(),
+                to: ctx.accounts.reward_token_account.to_account_info(),
+                authority: ctx.accounts.admin.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let clock = Clock::get()?;
+    let reward_pool = &mut ctx.accounts.reward_pool;
+    reward_pool.notify_reward(amount, clock.unix_timestamp)?;
+
+    emit!(RewardPoolFundedEvent {
+        reward_pool: reward_pool.key(),
+        funder: ctx.accounts.admin.key(),
+        amount,
+        reward_rate: reward_pool.reward_rate,
+        period_finish: reward_pool.period_finish,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Funded reward pool with {} tokens", amount);
+    msg!("Emitting until: {}", reward_pool.period_finish);
+
+    Ok(())
+}
+
+pub fn open_reward_position(ctx: Context<OpenRewardPosition>) -> Result<()> {
+    let clock = Clock::get()?;
+    let vault = &mut ctx.accounts.vault;
+    require!(
+        vault.reward_positions < MAX_REWARD_POSITIONS,
+        ErrorCode::MaxRewardPositionsReached
+    );
+    vault.reward_positions += 1;
+    let staked = vault.locked_and_committed_balance()?;
+    let locked_balance_seconds = vault.locked_balance_seconds_at(clock.unix_timestamp)?;
+
+    let reward_pool = &mut ctx.accounts.reward_pool;
+    reward_pool.update(clock.unix_timestamp)?;
+    reward_pool.restake(0, staked)?;
+
+    let reward_position = &mut ctx.accounts.reward_position;
+    reward_position.reward_pool = reward_pool.key();
+    reward_position.vault = vault.key();
+    reward_position.staked = staked;
+    reward_position.reward_per_token_paid = reward_pool.reward_per_token_stored;
+    reward_position.locked_balance_seconds = locked_balance_seconds;
+    reward_position.synced_at = clock.unix_timestamp;
+    reward_position.earned = 0;
+    reward_position.total_claimed = 0;
+    reward_position.bump = ctx.bumps.reward_position;
+
+    emit!(RewardPositionOpenedEvent {
+        reward_pool: reward_position.reward_pool,
+        vault: reward_position.vault,
+        staked,
+        total_staked: reward_pool.total_staked,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Reward position opened for vault: {}", reward_position.vault);
+    msg!("Staked: {} of {}", staked, reward_pool.total_staked);
+
+    Ok(())
+}
+
+pub fn sync_reward_position(ctx: Context<SyncRewardPosition>) -> Result<()> {
+    let clock = Clock::get()?;
+    let vault = &ctx.accounts.vault;
+    let reward_pool = &mut ctx.accounts.reward_pool;
+    let reward_position = &mut ctx.accounts.reward_position;
+
+    reward_pool.update(clock.unix_timestamp)?;
+    reward_position.sync(
+        reward_pool,
+        vault.locked_and_committed_balance()?,
+        vault.locked_balance_seconds_at(clock.unix_timestamp)?,
+        clock.unix_timestamp,
+    )?;
+
+    emit!(RewardPositionSyncedEvent {
+        reward_pool: reward_pool.key(),
+        vault: vault.key(),
+        staked: reward_position.staked,
+        total_staked: reward_pool.total_staked,
+        earned: reward_position.earned,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Reward position synced for vault: {}", vault.key());
+    msg!("Staked: {}, earned: {}", reward_position.staked, reward_position.earned);
+
+    Ok(())
+}
+
+pub fn close_reward_position(ctx: Context<CloseRewardPosition>) -> Result<()> {
+    let clock = Clock::get()?;
+    let vault = &mut ctx.accounts.vault;
+    let reward_pool = &mut ctx.accounts.reward_pool;
+    let reward_position = &mut ctx.accounts.reward_position;
+
+    reward_pool.update(clock.unix_timestamp)?;
+    reward_position.sync(
+        reward_pool,
+        vault.locked_and_committed_balance()?,
+        vault.locked_balance_seconds_at(clock.unix_timestamp)?,
+        clock.unix_timestamp,
+    )?;
+    require!(reward_position.earned == 0, ErrorCode::UnclaimedRewards);
+
+    reward_pool.restake(reward_position.staked, 0)?;
+    reward_position.staked = 0;
+    vault.reward_positions = vault.reward_positions
+        .checked_sub(1)
+        .ok_or(ErrorCode::ArithmeticUnderflow)?;
+
+    emit!(RewardPositionClosedEvent {
+        reward_pool: reward_pool.key(),
+        vault: vault.key(),
+        total_staked: reward_pool.total_staked,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Reward position closed for vault: {}", vault.key());
+    msg!("Pool total staked: {}", reward_pool.total_staked);
+
+    Ok(())
+}
+
+pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+    let clock = Clock::get()?;
+    let vault = &ctx.accounts.vault;
+    let reward_pool = &mut ctx.accounts.reward_pool;
+    let reward_position = &mut ctx.accounts.reward_position;
+
+    reward_pool.update(clock.unix_timestamp)?;
+    reward_position.sync(
+        reward_pool,
+        vault.locked_and_committed_balance()?,
+        vault.locked_balance_seconds_at(clock.unix_timestamp)?,
+        clock.unix_timestamp,
+    )?;
+    let amount = reward_position.claim()?;
+    require!(amount > 0, ErrorCode::NoRewardsToClaim);
+
+    let collateral_mint = reward_pool.collateral_mint;
+    let reward_mint = reward_pool.reward_mint;
+    let seeds = &[
+        REWARD_POOL_SEED,
+        collateral_mint.as_ref(),
+        reward_mint.as_ref(),
+        &[reward_pool.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.reward_token_account.to_account_info(),
+                to: ctx.accounts.user_reward_token_account.to_account_info(),
+                authority: reward_pool.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )?;
+
+    reward_pool.record_claim(amount)?;
+
+    emit!(RewardsClaimedEvent {
+        reward_pool: reward_pool.key(),
+        vault: vault.key(),
+        owner: ctx.accounts.user.key(),
+        amount,
+        staked: reward_position.staked,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Claimed {} reward tokens", amount);
+    msg!("Staked: {}", reward_position.staked);
+
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use anchor_lang::prelude::*;
+use crate::state::{CollateralVault, SessionKey};
+use crate::constants::{VAULT_SEED, SESSION_SEED};
+use crate::errors::ErrorCode;
+use crate::events::{SessionKeyRegisteredEvent, SessionKeyRevokedEvent};
+
+#[derive(Accounts)]
+#[instruction(session_key: Pubkey)]
+pub struct RegisterSessionKey<'info> {
+    pub user: Signer<'info>,
+
+    /// Pays for the session account, which lets a multisig owner register
+    /// keys through a proposal.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = SessionKey::LEN,
+        seeds = [SESSION_SEED, vault.key().as_ref(), session_key.as_ref()],
+        bump
+    )]
+    pub session: Account<'info, SessionKey>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeSessionKey<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        mut,
+        close = user,
+        seeds = [SESSION_SEED, vault.key().as_ref(), session.session_key.as_ref()],
+        bump = session.bump
+    )]
+    pub session: Account<'info, SessionKey>,
+}
+
+pub fn register_session_key(
+    ctx: Context<RegisterSessionKey>,
+    session_key: Pubkey,
+    scopes: u8,
+    max_amount: u64,
+    expires_at: i64,
+) -> Result<()> {
+    require!(session_key != ctx.accounts.user.key(), ErrorCode::InvalidSessionKey);
+
+    let clock = Clock::get()?;
+    let vault_key = ctx.accounts.vault.key();
+    let delegation_epoch = ctx.accounts.vault.delegation_epoch;
+    ctx.accounts.session.register(
+        vault_key,
+        session_key,
+        scopes,
+        max_amount,
+        expires_at,
+        clock.unix_timestamp,
+        delegation_epoch,
+        ctx.bumps.session,
+    )?;
+
+    emit!(SessionKeyRegisteredEvent {
+        vault: vault_key,
+        session_key,
+        scopes,
+        max_amount,
+        expires_at,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Session key registered: {}", session_key);
+    msg!("Expires at: {}", expires_at);
+
+    Ok(())
+}
+
+pub fn revoke_session_key(ctx: Context<RevokeSessionKey>) -> Result<()> {
+    let session = &ctx.accounts.session;
+
+    let clock = Clock::get()?;
+    emit!(SessionKeyRevokedEvent {
+        vault: ctx.accounts.vault.key(),
+        session_key: session.session_key,
+        used_amount: session.used_amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Session key revoked: {}", session.session_key);
+
+    Ok(())
+}
@@ -0,0 +1,371 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::state::{
+    CollateralVault, MintState, ProtocolState, SettlementLeg, SharePool, VaultAuthority,
+};
+use crate::constants::{
+    VAULT_SEED, AUTHORITY_SEED, PROTOCOL_STATE_SEED, MINT_STATE_SEED, SHARE_POOL_SEED,
+};
+use crate::errors::ErrorCode;
+use crate::instructions::shared::{
+    authorized_caller, enforce_rate_limit, fee_treasury, pooled_share_pool, record_outflow,
+};
+use crate::events::{BatchSettledEvent, VaultSettledEvent};
+
+/// Settles many vault-to-vault obligations at once. The vaults taking part
+/// follow in `remaining_accounts` as writable (vault, vault token account)
+/// pairs, all in the mint of `mint_state`; legs refer to them by pair index.
+/// Pooled vaults settle through the share pool, which is then required.
+#[derive(Accounts)]
+pub struct SettleBatch<'info> {
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    /// Accounting of the mint every settled vault holds.
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, mint_state.mint.as_ref()],
+        bump = mint_state.bump
+    )]
+    pub mint_state: Account<'info, MintState>,
+
+    /// CHECK: Outflow rate limit PDA for the calling program, validated in handler
+    #[account(mut)]
+    pub rate_limit: UncheckedAccount<'info>,
+
+    /// Protocol treasury for the vault mint; required while a transfer fee is set.
+    #[account(mut)]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: Instructions sysvar used to identify the calling program
+    #[account(address = solana_instructions_sysvar::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    /// Share pool of the mint; required when any settled vault is pooled.
+    #[account(
+        mut,
+        seeds = [SHARE_POOL_SEED, share_pool.mint.as_ref()],
+        bump = share_pool.bump
+    )]
+    pub share_pool: Option<Account<'info, SharePool>>,
+
+    /// Pool token account pooled vaults settle through.
+    #[account(mut)]
+    pub pool_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// A balance that changes in the settlement. Pooled vaults all keep their
+/// tokens in the pool token account, so they are netted into one node.
+#[derive(Clone, Copy, PartialEq)]
+enum Node {
+    Vault(usize),
+    Pool,
+    Treasury,
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SettleBatch<'info>>,
+    legs: Vec<SettlementLeg>,
+) -> Result<()> {
+    require!(!legs.is_empty(), ErrorCode::InvalidSettlementLeg);
+    ctx.accounts.protocol_state.require_active()?;
+
+    let caller_program = authorized_caller(
+        &ctx.accounts.authority,
+        &ctx.accounts.instructions_sysvar,
+    )?;
+
+    let mint = ctx.accounts.mint_state.mint;
+    let remaining = ctx.remaining_accounts;
+    require!(
+        !remaining.is_empty()
+            && remaining.len().is_multiple_of(2)
+            && remaining.len() / 2 <= u8::MAX as usize + 1,
+        ErrorCode::InvalidSettlementAccounts
+    );
+
+    // Owner and discriminator checks in `Account::try_from` are enough to
+    // know each vault is one of ours; its address is never re-derived.
+    let clock = Clock::get()?;
+    let mut vaults: Vec<Account<'info, CollateralVault>> = Vec::with_capacity(remaining.len() / 2);
+    let mut token_accounts: Vec<Account<'info, TokenAccount>> = Vec::with_capacity(remaining.len() / 2);
+    for pair in remaining.chunks(2) {
+        require!(
+            pair[0].is_writable && pair[1].is_writable,
+            ErrorCode::InvalidSettlementAccounts
+        );
+        let mut vault = Account::<CollateralVault>::try_from(&pair[0])?;
+        let token_account = Account::<TokenAccount>::try_from(&pair[1])?;
+        require!(
+            token_account.key() == vault.token_account
+                && token_account.mint == mint
+                && !vaults.iter().any(|seen| seen.key() == vault.key()),
+            ErrorCode::InvalidSettlementAccounts
+        );
+        pooled_share_pool(&mut vault, ctx.accounts.share_pool.as_mut(), Some(&token_account))?;
+        vault.absorb_socialized_loss(&ctx.accounts.mint_state, &clock)?;
+        vaults.push(vault);
+        token_accounts.push(token_account);
+    }
+    let any_pooled = vaults.iter().any(|vault| vault.pooled);
+    let pool_token_account = if any_pooled {
+        let share_pool = ctx.accounts.share_pool.as_ref().ok_or(ErrorCode::InvalidSharePool)?;
+        Some(
+            ctx.accounts.pool_token_account
+                .as_ref()
+                .filter(|account| account.key() == share_pool.token_account)
+                .ok_or(ErrorCode::InvalidSharePool)?,
+        )
+    } else {
+        None
+    };
+
+    let mut debits = vec![0u64; vaults.len()];
+    let mut credits = vec![0u64; vaults.len()];
+    let mut gross_amount: u64 = 0;
+    for leg in &legs {
+        let (from, to) = (leg.from as usize, leg.to as usize);
+        require!(
+            leg.amount > 0 && from != to && from < vaults.len() && to < vaults.len(),
+            ErrorCode::InvalidSettlementLeg
+        );
+        debits[from] = debits[from]
+            .checked_add(leg.amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        credits[to] = credits[to]
+            .checked_add(leg.amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        gross_amount = gross_amount
+            .checked_add(leg.amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+    }
+
+    // Net every vault down to a single debit or credit. Receivers pay the
+    // transfer fee on their net credit, just as with `transfer_collateral`.
+    let protocol_state = &ctx.accounts.protocol_state;
+    let mut debtors: Vec<(usize, u64)> = Vec::new();
+    let mut creditors: Vec<(usize, u64)> = Vec::new();
+    let mut fees = vec![0u64; vaults.len()];
+    let mut net_amount: u64 = 0;
+    let mut total_fee: u64 = 0;
+    for (index, vault) in vaults.iter().enumerate() {
+        if debits[index] > credits[index] {
+            let debit = debits[index] - credits[index];
+            require!(
+                vault.available_balance >= debit,
+                ErrorCode::InsufficientAvailableBalance
+            );
+            enforce_rate_limit(
+                &ctx.accounts.rate_limit,
+                &caller_program,
+                vault.key(),
+                debit,
+            )?;
+            net_amount = net_amount
+                .checked_add(debit)
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+            debtors.push((index, debit));
+        } else if credits[index] > debits[index] {
+            let credit = credits[index] - debits[index];
+            let fee = protocol_state.fee_for(protocol_state.transfer_fee_bps, credit)?;
+            require!(credit > fee, ErrorCode::InvalidAmount);
+            fees[index] = fee;
+            total_fee = total_fee
+                .checked_add(fee)
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+            creditors.push((index, credit - fee));
+        }
+    }
+    require!(!debtors.is_empty(), ErrorCode::InvalidSettlementLeg);
+
+    // Unpooled vaults move their own tokens; pooled ones only move the
+    // pool's net position against the rest of the batch.
+    let mut payers: Vec<(Node, u64)> = Vec::new();
+    let mut payees: Vec<(Node, u64)> = Vec::new();
+    let (mut pool_out, mut pool_in): (u64, u64) = (0, 0);
+    for &(index, debit) in &debtors {
+        if vaults[index].pooled {
+            pool_out += debit;
+        } else {
+            payers.push((Node::Vault(index), debit));
+        }
+    }
+    for &(index, credit) in &creditors {
+        if vaults[index].pooled {
+            pool_in += credit;
+        } else {
+            payees.push((Node::Vault(index), credit));
+        }
+    }
+    match pool_out.cmp(&pool_in) {
+        std::cmp::Ordering::Greater => {
+            let pool_debit = pool_out - pool_in;
+            let share_pool = ctx.accounts.share_pool.as_ref().ok_or(ErrorCode::InvalidSharePool)?;
+            require!(
+                share_pool.idle_assets() >= pool_debit,
+                ErrorCode::InsufficientPoolLiquidity
+            );
+            payers.push((Node::Pool, pool_debit));
+        }
+        std::cmp::Ordering::Less => payees.push((Node::Pool, pool_in - pool_out)),
+        std::cmp::Ordering::Equal => {}
+    }
+
+    let treasury = if total_fee > 0 {
+        payees.push((Node::Treasury, total_fee));
+        Some(fee_treasury(
+            ctx.accounts.treasury_token_account.as_ref(),
+            &protocol_state.key(),
+            &mint,
+        )?)
+    } else {
+        None
+    };
+
+    // Walk payers and payees together, closing out at least one of them
+    // with every transfer, so the CPI count stays below the number of
+    // balances that change (treasury included).
+    let pool_bump = ctx.accounts.share_pool.as_ref().map_or(0, |share_pool| share_pool.bump);
+    let pool_seeds = &[SHARE_POOL_SEED, mint.as_ref(), &[pool_bump]];
+
+    let mut transfers: u16 = 0;
+    let (mut payer, mut payee) = (0, 0);
+    let mut payer_left = payers.first().map_or(0, |entry| entry.1);
+    let mut payee_left = payees.first().map_or(0, |entry| entry.1);
+    while payer < payers.len() && payee < payees.len() {
+        let amount = payer_left.min(payee_left);
+        let to = match payees[payee].0 {
+            Node::Vault(index) => token_accounts[index].to_account_info(),
+            Node::Pool => pool_token_account.ok_or(ErrorCode::InvalidSharePool)?.to_account_info(),
+            Node::Treasury => treasury.ok_or(ErrorCode::MissingTreasuryAccount)?.to_account_info(),
+        };
+
+        match payers[payer].0 {
+            Node::Vault(from_index) => {
+                let from_vault = &vaults[from_index];
+                let subaccount = from_vault.subaccount.to_le_bytes();
+                let seeds = &[
+                    VAULT_SEED,
+                    from_vault.seed_owner.as_ref(),
+                    subaccount.as_ref(),
+                    &[from_vault.bump],
+                ];
+                token::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: token_accounts[from_index].to_account_info(),
+                            to,
+                            authority: from_vault.to_account_info(),
+                        },
+                        &[&seeds[..]],
+                    ),
+                    amount,
+                )?;
+            }
+            Node::Pool => {
+                let share_pool = ctx.accounts.share_pool.as_ref().ok_or(ErrorCode::InvalidSharePool)?;
+                token::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: pool_token_account.ok_or(ErrorCode::InvalidSharePool)?.to_account_info(),
+                            to,
+                            authority: share_pool.to_account_info(),
+                        },
+                        &[&pool_seeds[..]],
+                    ),
+                    amount,
+                )?;
+            }
+            Node::Treasury => return err!(ErrorCode::InvalidSettlementLeg),
+        }
+        transfers += 1;
+
+        payer_left -= amount;
+        payee_left -= amount;
+        if payer_left == 0 {
+            payer += 1;
+            payer_left = payers.get(payer).map_or(0, |entry| entry.1);
+        }
+        if payee_left == 0 {
+            payee += 1;
+            payee_left = payees.get(payee).map_or(0, |entry| entry.1);
+        }
+    }
+
+    // Pooled debtors redeem before pooled creditors are issued shares, so
+    // both sides trade at the same share price.
+    let mut share_pool = ctx.accounts.share_pool.as_mut().filter(|_| any_pooled);
+    for &(index, debit) in &debtors {
+        match share_pool.as_deref_mut() {
+            Some(share_pool) if vaults[index].pooled => {
+                vaults[index].redeem_pooled(share_pool, debit, &clock)?;
+            }
+            _ => vaults[index].withdraw(debit, &clock)?,
+        }
+    }
+    for &(index, credit) in &creditors {
+        match share_pool.as_deref_mut() {
+            Some(share_pool) if vaults[index].pooled => {
+                vaults[index].issue_pooled(share_pool, credit, &clock)?;
+            }
+            _ => vaults[index].deposit(credit, &clock)?,
+        }
+    }
+    if let Some(share_pool) = share_pool {
+        share_pool.require_liquid_reserves()?;
+    }
+    for (index, vault) in vaults.iter().enumerate() {
+        let (debited, credited) = match debits[index].cmp(&credits[index]) {
+            std::cmp::Ordering::Greater => (debits[index] - credits[index], 0),
+            std::cmp::Ordering::Less => (0, credits[index] - debits[index] - fees[index]),
+            std::cmp::Ordering::Equal => continue,
+        };
+        vault.exit(&crate::ID)?;
+        emit!(VaultSettledEvent {
+            vault: vault.key(),
+            debited,
+            credited,
+            fee: fees[index],
+            caller_program,
+            timestamp: clock.unix_timestamp,
+        });
+    }
+
+    // What the receivers kept stays in the protocol; fees leave TVL.
+    let (protocol_state, mint_state) = (&mut ctx.accounts.protocol_state, &mut ctx.accounts.mint_state);
+    record_outflow(protocol_state, mint_state, net_amount - total_fee, false)?;
+    if total_fee > 0 {
+        record_outflow(protocol_state, mint_state, total_fee, true)?;
+    }
+    protocol_state.record_fee(total_fee)?;
+
+    emit!(BatchSettledEvent {
+        caller_program,
+        legs: legs.len() as u16,
+        vaults: vaults.len() as u16,
+        transfers,
+        gross_amount,
+        net_amount,
+        fee: total_fee,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Settled {} legs across {} vaults in {} transfers", legs.len(), vaults.len(), transfers);
+    msg!("Gross: {}, net: {}, fee: {}", gross_amount, net_amount, total_fee);
+
+    Ok(())
+}
@@ -0,0 +1,485 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Mint, Token, TokenAccount, Transfer},
+};
+use crate::state::{CollateralVault, MintState, ProtocolState, SharePool, VaultAuthority};
+use crate::constants::{
+    AUTHORITY_SEED, PROTOCOL_STATE_SEED, MINT_STATE_SEED, SHARE_POOL_SEED, VAULT_SEED,
+    BPS_DENOMINATOR,
+};
+use crate::errors::ErrorCode;
+use crate::instructions::shared::{
+    enforce_spending_policy, fee_treasury, record_outflow, withdrawal_cooldown,
+};
+use crate::events::{
+    PoolYieldAddedEvent, PooledAccountingUpdatedEvent, PooledDepositEvent, PooledWithdrawEvent,
+    SharePoolInitializedEvent,
+};
+
+#[derive(Accounts)]
+pub struct InitializeSharePool<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = SharePool::LEN,
+        seeds = [SHARE_POOL_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub share_pool: Account<'info, SharePool>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        associated_token::mint = mint,
+        associated_token::authority = share_pool
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPooledAccounting<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+}
+
+#[derive(Accounts)]
+pub struct DepositPooled<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        constraint = vault_token_account.key() == vault.token_account @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [SHARE_POOL_SEED, share_pool.mint.as_ref()],
+        bump = share_pool.bump,
+        constraint = share_pool.mint == vault_token_account.mint @ ErrorCode::InvalidSharePool
+    )]
+    pub share_pool: Account<'info, SharePool>,
+
+    #[account(
+        mut,
+        constraint = pool_token_account.key() == share_pool.token_account @ ErrorCode::InvalidSharePool
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, share_pool.mint.as_ref()],
+        bump = mint_state.bump
+    )]
+    pub mint_state: Account<'info, MintState>,
+
+    #[account(mut)]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    /// Protocol treasury for the vault mint; required while a deposit fee is set.
+    #[account(mut)]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawPooled<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    pub cosigner: Option<Signer<'info>>,
+
+    #[account(
+        constraint = vault_token_account.key() == vault.token_account @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [SHARE_POOL_SEED, share_pool.mint.as_ref()],
+        bump = share_pool.bump,
+        constraint = share_pool.mint == vault_token_account.mint @ ErrorCode::InvalidSharePool
+    )]
+    pub share_pool: Account<'info, SharePool>,
+
+    #[account(
+        mut,
+        constraint = pool_token_account.key() == share_pool.token_account @ ErrorCode::InvalidSharePool
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, share_pool.mint.as_ref()],
+        bump = mint_state.bump
+    )]
+    pub mint_state: Account<'info, MintState>,
+
+    #[account(mut)]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Withdrawal cooldown PDA for the vault mint, validated in handler
+    pub cooldown_config: UncheckedAccount<'info>,
+
+    /// CHECK: Owner spending policy PDA for the vault, validated in handler
+    #[account(mut)]
+    pub spending_policy: UncheckedAccount<'info>,
+
+    /// Protocol treasury for the vault mint; required while a withdraw fee is set.
+    #[account(mut)]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct AddPoolYield<'info> {
+    pub contributor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [SHARE_POOL_SEED, share_pool.mint.as_ref()],
+        bump = share_pool.bump
+    )]
+    pub share_pool: Account<'info, SharePool>,
+
+    #[account(
+        mut,
+        constraint = pool_token_account.key() == share_pool.token_account @ ErrorCode::InvalidSharePool
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, share_pool.mint.as_ref()],
+        bump = mint_state.bump
+    )]
+    pub mint_state: Account<'info, MintState>,
+
+    #[account(mut)]
+    pub contributor_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn initialize_share_pool(ctx: Context<InitializeSharePool>) -> Result<()> {
+    let share_pool = &mut ctx.accounts.share_pool;
+    share_pool.mint = ctx.accounts.mint.key();
+    share_pool.token_account = ctx.accounts.pool_token_account.key();
+    share_pool.total_shares = 0;
+    share_pool.total_assets = 0;
+    // Nothing can be deployed until the admin lowers the reserve.
+    share_pool.keeper = ctx.accounts.admin.key();
+    share_pool.reserve_bps = BPS_DENOMINATOR as u16;
+    share_pool.locked_assets = 0;
+    share_pool.committed_assets = 0;
+    share_pool.pending_assets = 0;
+    share_pool.deployed_assets = 0;
+    share_pool.total_socialized_loss = 0;
+    share_pool.bump = ctx.bumps.share_pool;
+
+    let clock = Clock::get()?;
+    emit!(SharePoolInitializedEvent {
+        pool: share_pool.key(),
+        mint: share_pool.mint,
+        admin: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Share pool initialized for mint: {}", share_pool.mint);
+
+    Ok(())
+}
+
+pub fn set_pooled_accounting(ctx: Context<SetPooledAccounting>, pooled: bool) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    // Balances in one mode mean nothing in the other, so only an empty
+    // vault may switch.
+    require!(
+        vault.total_balance == 0 && vault.shares == 0 && vault.pending_withdrawal == 0,
+        ErrorCode::VaultNotEmpty
+    );
+    vault.pooled = pooled;
+
+    let clock = Clock::get()?;
+    emit!(PooledAccountingUpdatedEvent {
+        vault: vault.key(),
+        owner: ctx.accounts.user.key(),
+        pooled,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Share accounting {}", if pooled { "enabled" } else { "disabled" });
+
+    Ok(())
+}
+
+pub fn deposit_pooled(ctx: Context<DepositPooled>, amount: u64) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    ctx.accounts.protocol_state.require_active()?;
+    require!(ctx.accounts.vault.pooled, ErrorCode::VaultNotPooled);
+
+    let protocol_state = &ctx.accounts.protocol_state;
+    let fee = protocol_state.fee_for(protocol_state.deposit_fee_bps, amount)?;
+    let credited = amount - fee;
+    require!(credited > 0, ErrorCode::InvalidAmount);
+
+    let shares = ctx.accounts.share_pool.shares_for_deposit(credited)?;
+    require!(shares > 0, ErrorCode::ZeroShares);
+
+    if fee > 0 {
+        let treasury = fee_treasury(
+            ctx.accounts.treasury_token_account.as_ref(),
+            &protocol_state.key(),
+            &ctx.accounts.vault_token_account.mint,
+        )?;
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.user_token_account.to_account_info(),
+                    to: treasury.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            fee,
+        )?;
+    }
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: ctx.accounts.pool_token_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        credited,
+    )?;
+
+    let share_pool = &mut ctx.accounts.share_pool;
+    share_pool.mint_shares(shares, credited)?;
+
+    let clock = Clock::get()?;
+    let vault = &mut ctx.accounts.vault;
+    vault.deposit_shares(shares, credited)?;
+    vault.sync_pooled_balance(share_pool, &clock)?;
+
+    ctx.accounts.mint_state.record_inflow(&ctx.accounts.protocol_state, credited, &clock)?;
+    ctx.accounts.protocol_state.record_fee(fee)?;
+    emit!(PooledDepositEvent {
+        vault: vault.key(),
+        user: ctx.accounts.user.key(),
+        amount,
+        fee,
+        shares,
+        total_shares: share_pool.total_shares,
+        total_assets: share_pool.total_assets,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Deposited {} tokens for {} shares (fee: {})", credited, shares, fee);
+    msg!("Vault shares: {}", vault.shares);
+
+    Ok(())
+}
+
+pub fn withdraw_pooled(ctx: Context<WithdrawPooled>, amount: u64) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    ctx.accounts.protocol_state.require_withdrawals_enabled()?;
+    ctx.accounts.vault.require_not_locked_down()?;
+    require!(ctx.accounts.vault.pooled, ErrorCode::VaultNotPooled);
+
+    let cooldown = withdrawal_cooldown(
+        &ctx.accounts.protocol_state,
+        &ctx.accounts.cooldown_config,
+        &ctx.accounts.vault_token_account.mint,
+    )?;
+    require!(cooldown == 0, ErrorCode::WithdrawalCooldownRequired);
+
+    let clock = Clock::get()?;
+    let share_pool = &mut ctx.accounts.share_pool;
+    let vault = &mut ctx.accounts.vault;
+    vault.sync_pooled_balance(share_pool, &clock)?;
+    require!(
+        vault.available_balance >= amount,
+        ErrorCode::InsufficientAvailableBalance
+    );
+    vault.require_cosigner(amount, ctx.accounts.cosigner.as_ref().map(|c| c.key()))?;
+
+    enforce_spending_policy(
+        &ctx.accounts.spending_policy,
+        vault.key(),
+        vault.owner,
+        vault.delegation_epoch,
+        Some(&ctx.accounts.user_token_account),
+        amount,
+        false,
+    )?;
+
+    require!(
+        share_pool.idle_assets() >= amount,
+        ErrorCode::InsufficientPoolLiquidity
+    );
+    let shares = vault.redeem_pooled(share_pool, amount, &clock)?;
+    share_pool.require_liquid_reserves()?;
+
+    let protocol_state = &ctx.accounts.protocol_state;
+    let fee = protocol_state.fee_for(protocol_state.withdraw_fee_bps, amount)?;
+    let received = amount - fee;
+    require!(received > 0, ErrorCode::InvalidAmount);
+
+    let mint = share_pool.mint;
+    let seeds = &[SHARE_POOL_SEED, mint.as_ref(), &[share_pool.bump]];
+    let signer_seeds = &[&seeds[..]];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.pool_token_account.to_account_info(),
+                to: ctx.accounts.user_token_account.to_account_info(),
+                authority: share_pool.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        received,
+    )?;
+
+    if fee > 0 {
+        let treasury = fee_treasury(
+            ctx.accounts.treasury_token_account.as_ref(),
+            &protocol_state.key(),
+            &mint,
+        )?;
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.pool_token_account.to_account_info(),
+                    to: treasury.to_account_info(),
+                    authority: share_pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            fee,
+        )?;
+    }
+
+    record_outflow(&mut ctx.accounts.protocol_state, &mut ctx.accounts.mint_state, amount, true)?;
+    ctx.accounts.protocol_state.record_fee(fee)?;
+
+    emit!(PooledWithdrawEvent {
+        vault: vault.key(),
+        user: ctx.accounts.user.key(),
+        amount,
+        fee,
+        shares,
+        total_shares: share_pool.total_shares,
+        total_assets: share_pool.total_assets,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Withdrew {} tokens for {} shares (fee: {})", received, shares, fee);
+    msg!("Vault shares: {}", vault.shares);
+
+    Ok(())
+}
+
+pub fn add_pool_yield(ctx: Context<AddPoolYield>, amount: u64) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    // With no shareholders the yield would go to whoever deposits first.
+    require!(ctx.accounts.share_pool.total_shares > 0, ErrorCode::ZeroShares);
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.contributor_token_account.to_account_info(),
+                to: ctx.accounts.pool_token_account.to_account_info(),
+                authority: ctx.accounts.contributor.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let share_pool = &mut ctx.accounts.share_pool;
+    share_pool.add_yield(amount)?;
+
+    let clock = Clock::get()?;
+    ctx.accounts.mint_state.record_inflow(&ctx.accounts.protocol_state, amount, &clock)?;
+    emit!(PoolYieldAddedEvent {
+        pool: share_pool.key(),
+        contributor: ctx.accounts.contributor.key(),
+        amount,
+        total_shares: share_pool.total_shares,
+        total_assets: share_pool.total_assets,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Added {} tokens of yield to the share pool", amount);
+
+    Ok(())
+}
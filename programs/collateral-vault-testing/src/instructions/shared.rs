@@ -0,0 +1,274 @@
+use anchor_lang::prelude::*;
+use solana_instructions_sysvar::get_instruction_relative;
+use anchor_spl::token::TokenAccount;
+use crate::state::{
+    CollateralVault, CooldownConfig, MintState, ProgramRateLimit, ProtocolState, RewardPool, RewardPosition,
+    SharePool, SpendingPolicy, VaultAuthority,
+};
+use crate::constants::{COOLDOWN_SEED, RATE_LIMIT_SEED, SPENDING_POLICY_SEED};
+use crate::errors::ErrorCode;
+use crate::events::{CircuitBreakerTrippedEvent, RateLimitExceededEvent, SpendingPolicyUpdatedEvent};
+
+/// Resolves the program driving the current top-level instruction and
+/// requires it to be on the authority's allowlist.
+pub fn authorized_caller(
+    authority: &VaultAuthority,
+    instructions_sysvar: &AccountInfo,
+) -> Result<Pubkey> {
+    let current_ix = get_instruction_relative(0, instructions_sysvar)?;
+    require!(
+        authority.is_authorized(&current_ix.program_id),
+        ErrorCode::UnauthorizedProgram
+    );
+    Ok(current_ix.program_id)
+}
+
+/// Loads a program-owned PDA if it has been created, returning `None` while
+/// the address is still empty. The address itself is always checked so a
+/// caller cannot dodge a configured account by passing a different one.
+pub fn load_pda_if_initialized<T>(
+    info: &AccountInfo,
+    seeds: &[&[u8]],
+    mismatch: ErrorCode,
+) -> Result<Option<T>>
+where
+    T: AccountDeserialize + Owner,
+{
+    let (expected, _) = Pubkey::find_program_address(seeds, &crate::ID);
+    require_keys_eq!(info.key(), expected, mismatch);
+
+    if info.owner != &T::owner() || info.data_is_empty() {
+        return Ok(None);
+    }
+    let data = info.try_borrow_data()?;
+    Ok(Some(T::try_deserialize(&mut &data[..])?))
+}
+
+/// Writes back an account previously read with `load_pda_if_initialized`.
+pub fn store_pda<T: AccountSerialize>(info: &AccountInfo, account: &T) -> Result<()> {
+    let mut data = info.try_borrow_mut_data()?;
+    let mut writer: &mut [u8] = &mut data[..];
+    account.try_serialize(&mut writer)
+}
+
+/// Restakes every reward position of `vault` after its locked or committed
+/// balance moved. The positions follow in `remaining_accounts` as writable
+/// (reward pool, reward position) pairs, one pair per open position, so no
+/// pool keeps counting stake the vault no longer has.
+pub fn sync_reward_positions<'info>(
+    vault: &Account<'info, CollateralVault>,
+    remaining_accounts: &'info [AccountInfo<'info>],
+    clock: &Clock,
+) -> Result<()> {
+    require!(
+        remaining_accounts.len() == vault.reward_positions as usize * 2,
+        ErrorCode::InvalidRewardAccounts
+    );
+
+    // Owner and discriminator checks in `Account::try_from` are enough to
+    // know each account is one of ours; a position only exists at the
+    // address derived from its pool and vault.
+    let staked = vault.locked_and_committed_balance()?;
+    let locked_balance_seconds = vault.locked_balance_seconds_at(clock.unix_timestamp)?;
+    let mut synced: Vec<Pubkey> = Vec::with_capacity(vault.reward_positions as usize);
+    for pair in remaining_accounts.chunks(2) {
+        require!(
+            pair[0].is_writable && pair[1].is_writable,
+            ErrorCode::InvalidRewardAccounts
+        );
+        let mut reward_pool = Account::<RewardPool>::try_from(&pair[0])?;
+        let mut reward_position = Account::<RewardPosition>::try_from(&pair[1])?;
+        require!(
+            reward_position.vault == vault.key()
+                && reward_position.reward_pool == reward_pool.key()
+                && !synced.contains(&reward_position.key()),
+            ErrorCode::InvalidRewardAccounts
+        );
+
+        reward_pool.update(clock.unix_timestamp)?;
+        reward_position.sync(&mut reward_pool, staked, locked_balance_seconds, clock.unix_timestamp)?;
+        reward_pool.exit(&crate::ID)?;
+        reward_position.exit(&crate::ID)?;
+        synced.push(reward_position.key());
+    }
+    Ok(())
+}
+
+/// Resolves the treasury a non-zero protocol fee is paid into. It must be
+/// held by the protocol state PDA in the same mint as the vault.
+pub fn fee_treasury<'a, 'info>(
+    treasury: Option<&'a Account<'info, TokenAccount>>,
+    protocol_state: &Pubkey,
+    mint: &Pubkey,
+) -> Result<&'a Account<'info, TokenAccount>> {
+    let treasury = treasury.ok_or(ErrorCode::MissingTreasuryAccount)?;
+    require!(
+        treasury.owner == *protocol_state && treasury.mint == *mint,
+        ErrorCode::InvalidTreasuryAccount
+    );
+    Ok(treasury)
+}
+
+/// Resolves the share pool behind a pooled vault, checked against the
+/// vault's mint, and re-prices the vault from it. Unpooled vaults need
+/// neither account.
+pub fn pooled_share_pool<'a, 'info>(
+    vault: &mut CollateralVault,
+    share_pool: Option<&'a mut Account<'info, SharePool>>,
+    vault_token_account: Option<&Account<'info, TokenAccount>>,
+) -> Result<Option<&'a mut Account<'info, SharePool>>> {
+    if !vault.pooled {
+        return Ok(None);
+    }
+    let share_pool = share_pool.ok_or(ErrorCode::InvalidSharePool)?;
+    let vault_token_account = vault_token_account.ok_or(ErrorCode::InvalidSharePool)?;
+    require!(
+        vault_token_account.key() == vault.token_account
+            && vault_token_account.mint == share_pool.mint,
+        ErrorCode::InvalidSharePool
+    );
+    let clock = Clock::get()?;
+    vault.sync_pooled_balance(share_pool, &clock)?;
+    Ok(Some(share_pool))
+}
+
+/// Draws `amount` from the calling program's outflow bucket, if the admin
+/// has configured one. Exceeding the bucket emits an alert and fails.
+pub fn enforce_rate_limit(
+    rate_limit: &AccountInfo,
+    caller_program: &Pubkey,
+    vault: Pubkey,
+    amount: u64,
+) -> Result<()> {
+    let bucket = load_pda_if_initialized::<ProgramRateLimit>(
+        rate_limit,
+        &[RATE_LIMIT_SEED, caller_program.as_ref()],
+        ErrorCode::InvalidRateLimitAccount,
+    )?;
+    let Some(mut bucket) = bucket else {
+        return Ok(());
+    };
+
+    let clock = Clock::get()?;
+    bucket.refill(clock.slot)?;
+
+    if bucket.tokens < amount {
+        emit!(RateLimitExceededEvent {
+            program_id: *caller_program,
+            vault,
+            amount,
+            remaining: bucket.tokens,
+            capacity: bucket.capacity,
+            window_slots: bucket.window_slots,
+            slot: clock.slot,
+            timestamp: clock.unix_timestamp,
+        });
+        msg!("❌ Rate limit exceeded for program: {}", caller_program);
+        return err!(ErrorCode::RateLimitExceeded);
+    }
+
+    bucket.consume(amount)?;
+    store_pda(rate_limit, &bucket)
+}
+
+/// Withdrawal delay applying to vaults of `mint`: the per-mint override if
+/// the admin configured one, otherwise the protocol-wide default, raised to
+/// the longest per-program cooldown.
+pub fn withdrawal_cooldown(
+    protocol_state: &ProtocolState,
+    cooldown_config: &AccountInfo,
+    mint: &Pubkey,
+) -> Result<i64> {
+    let config = load_pda_if_initialized::<CooldownConfig>(
+        cooldown_config,
+        &[COOLDOWN_SEED, mint.as_ref()],
+        ErrorCode::InvalidCooldownAccount,
+    )?;
+    let cooldown = match config {
+        Some(config) => config.cooldown_seconds,
+        None => protocol_state.withdrawal_cooldown_seconds,
+    };
+    Ok(cooldown.max(protocol_state.max_program_cooldown()))
+}
+
+/// Feeds an outflow into the circuit breaker of its mint. A breach in any
+/// mint trips the protocol-wide status. The tripping outflow itself
+/// completes so the new status persists; later outflows are blocked.
+pub fn record_outflow(
+    protocol_state: &mut ProtocolState,
+    mint_state: &mut MintState,
+    amount: u64,
+    leaves_protocol: bool,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let breached = mint_state.record_outflow(protocol_state, amount, leaves_protocol, &clock)?;
+
+    if breached && protocol_state.trip(mint_state.mint, clock.unix_timestamp) {
+        emit!(CircuitBreakerTrippedEvent {
+            mint: mint_state.mint,
+            status: protocol_state.status,
+            net_outflow: mint_state.net_window_outflow(),
+            threshold: mint_state.outflow_threshold(protocol_state.max_outflow_bps),
+            total_value_locked: mint_state.total_value_locked,
+            window_start: mint_state.window_start,
+            timestamp: clock.unix_timestamp,
+        });
+        msg!("🚨 Circuit breaker tripped by mint {}: {:?}", mint_state.mint, protocol_state.status);
+    }
+
+    Ok(())
+}
+
+/// Applies the owner's spending policy, if one exists, to a withdrawal of
+/// `amount` into `destination`. Token accounts owned by the vault owner are
+/// always allowed; anything else must be an active allowlisted destination.
+/// With `allowlist_required` a missing policy rejects non-owner destinations
+/// instead of leaving them unrestricted. A `destination` of `None` is a move
+/// into another of the owner's own vaults: it needs no allowlist entry but
+/// still counts toward the limits. A policy set up before the vault last
+/// changed owner no longer applies.
+pub fn enforce_spending_policy(
+    spending_policy: &AccountInfo,
+    vault: Pubkey,
+    vault_owner: Pubkey,
+    delegation_epoch: u64,
+    destination: Option<&Account<TokenAccount>>,
+    amount: u64,
+    allowlist_required: bool,
+) -> Result<()> {
+    let external_destination = destination.filter(|account| account.owner != vault_owner);
+    let policy = load_pda_if_initialized::<SpendingPolicy>(
+        spending_policy,
+        &[SPENDING_POLICY_SEED, vault.as_ref()],
+        ErrorCode::InvalidSpendingPolicyAccount,
+    )?;
+    let policy = policy.filter(|policy| policy.delegation_epoch == delegation_epoch);
+    let Some(mut policy) = policy else {
+        require!(
+            !allowlist_required || external_destination.is_none(),
+            ErrorCode::DestinationNotAllowed
+        );
+        return Ok(());
+    };
+
+    let clock = Clock::get()?;
+    if policy.apply_pending(clock.unix_timestamp) {
+        emit!(SpendingPolicyUpdatedEvent {
+            vault,
+            max_per_transaction: policy.max_per_transaction,
+            max_per_day: policy.max_per_day,
+            policy_delay: policy.policy_delay,
+            timestamp: clock.unix_timestamp,
+        });
+    }
+
+    if let Some(destination) = external_destination {
+        require!(
+            policy.is_destination_active(&destination.key(), clock.unix_timestamp),
+            ErrorCode::DestinationNotAllowed
+        );
+    }
+    policy.record_spend(amount, clock.unix_timestamp)?;
+
+    store_pda(spending_policy, &policy)
+}
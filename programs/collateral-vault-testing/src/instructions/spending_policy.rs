@@ -0,0 +1,268 @@
+use anchor_lang::prelude::*;
+use crate::state::{CollateralVault, SpendingPolicy};
+use crate::constants::{VAULT_SEED, SPENDING_POLICY_SEED};
+use crate::errors::ErrorCode;
+use crate::events::{
+    SpendingPolicyChangeCancelledEvent, SpendingPolicyChangeQueuedEvent,
+    SpendingPolicyUpdatedEvent, WithdrawalDestinationAddedEvent,
+    WithdrawalDestinationRemovedEvent,
+};
+
+#[derive(Accounts)]
+pub struct InitializeSpendingPolicy<'info> {
+    pub user: Signer<'info>,
+
+    /// Rent payer, kept apart from the owner for multisig vaults.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    /// Created on first use; a policy left by a previous owner is replaced.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = SpendingPolicy::LEN,
+        seeds = [SPENDING_POLICY_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub spending_policy: Account<'info, SpendingPolicy>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSpendingPolicy<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        mut,
+        seeds = [SPENDING_POLICY_SEED, vault.key().as_ref()],
+        bump = spending_policy.bump,
+        constraint = spending_policy.delegation_epoch == vault.delegation_epoch @ ErrorCode::DelegationRevoked
+    )]
+    pub spending_policy: Account<'info, SpendingPolicy>,
+}
+
+#[derive(Accounts)]
+pub struct CancelSpendingPolicyChange<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        mut,
+        seeds = [SPENDING_POLICY_SEED, vault.key().as_ref()],
+        bump = spending_policy.bump,
+        constraint = spending_policy.delegation_epoch == vault.delegation_epoch @ ErrorCode::DelegationRevoked
+    )]
+    pub spending_policy: Account<'info, SpendingPolicy>,
+}
+
+#[derive(Accounts)]
+pub struct AddWithdrawalDestination<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        mut,
+        seeds = [SPENDING_POLICY_SEED, vault.key().as_ref()],
+        bump = spending_policy.bump,
+        constraint = spending_policy.delegation_epoch == vault.delegation_epoch @ ErrorCode::DelegationRevoked
+    )]
+    pub spending_policy: Account<'info, SpendingPolicy>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveWithdrawalDestination<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        mut,
+        seeds = [SPENDING_POLICY_SEED, vault.key().as_ref()],
+        bump = spending_policy.bump,
+        constraint = spending_policy.delegation_epoch == vault.delegation_epoch @ ErrorCode::DelegationRevoked
+    )]
+    pub spending_policy: Account<'info, SpendingPolicy>,
+}
+
+pub fn initialize_spending_policy(
+    ctx: Context<InitializeSpendingPolicy>,
+    max_per_transaction: u64,
+    max_per_day: u64,
+    policy_delay: i64,
+) -> Result<()> {
+    require!(
+        max_per_transaction > 0 && max_per_day > 0 && policy_delay >= 0,
+        ErrorCode::InvalidSpendingPolicy
+    );
+
+    let vault = &ctx.accounts.vault;
+    let policy = &ctx.accounts.spending_policy;
+    require!(
+        policy.vault == Pubkey::default() || policy.delegation_epoch != vault.delegation_epoch,
+        ErrorCode::SpendingPolicyAlreadyExists
+    );
+
+    let clock = Clock::get()?;
+    let vault_key = vault.key();
+    let delegation_epoch = vault.delegation_epoch;
+    ctx.accounts.spending_policy.initialize(
+        vault_key,
+        max_per_transaction,
+        max_per_day,
+        policy_delay,
+        clock.unix_timestamp,
+        delegation_epoch,
+        ctx.bumps.spending_policy,
+    );
+
+    emit!(SpendingPolicyUpdatedEvent {
+        vault: vault_key,
+        max_per_transaction,
+        max_per_day,
+        policy_delay,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Spending policy initialized for vault: {}", vault_key);
+
+    Ok(())
+}
+
+pub fn update_spending_policy(
+    ctx: Context<UpdateSpendingPolicy>,
+    max_per_transaction: u64,
+    max_per_day: u64,
+    policy_delay: i64,
+) -> Result<()> {
+    require!(
+        max_per_transaction > 0 && max_per_day > 0 && policy_delay >= 0,
+        ErrorCode::InvalidSpendingPolicy
+    );
+
+    let clock = Clock::get()?;
+    let vault_key = ctx.accounts.vault.key();
+    let policy = &mut ctx.accounts.spending_policy;
+    policy.apply_pending(clock.unix_timestamp);
+
+    let queued = policy.update(
+        max_per_transaction,
+        max_per_day,
+        policy_delay,
+        clock.unix_timestamp,
+    )?;
+
+    emit!(SpendingPolicyUpdatedEvent {
+        vault: vault_key,
+        max_per_transaction: policy.max_per_transaction,
+        max_per_day: policy.max_per_day,
+        policy_delay: policy.policy_delay,
+        timestamp: clock.unix_timestamp,
+    });
+
+    if queued {
+        emit!(SpendingPolicyChangeQueuedEvent {
+            vault: vault_key,
+            max_per_transaction,
+            max_per_day,
+            policy_delay,
+            effective_at: policy.pending_effective_at,
+            timestamp: clock.unix_timestamp,
+        });
+        msg!("⏳ Loosened spending policy effective at: {}", policy.pending_effective_at);
+    }
+
+    msg!("✅ Spending policy updated for vault: {}", vault_key);
+
+    Ok(())
+}
+
+pub fn cancel_spending_policy_change(ctx: Context<CancelSpendingPolicyChange>) -> Result<()> {
+    let policy = &mut ctx.accounts.spending_policy;
+
+    require!(policy.has_pending_change(), ErrorCode::NoPendingPolicyChange);
+
+    policy.cancel_pending();
+
+    let clock = Clock::get()?;
+    emit!(SpendingPolicyChangeCancelledEvent {
+        vault: ctx.accounts.vault.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Pending spending policy change cancelled");
+
+    Ok(())
+}
+
+pub fn add_withdrawal_destination(
+    ctx: Context<AddWithdrawalDestination>,
+    destination: Pubkey,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let policy = &mut ctx.accounts.spending_policy;
+    policy.apply_pending(clock.unix_timestamp);
+
+    let active_at = policy.add_destination(destination, clock.unix_timestamp)?;
+
+    emit!(WithdrawalDestinationAddedEvent {
+        vault: ctx.accounts.vault.key(),
+        destination,
+        active_at,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Withdrawal destination added: {}", destination);
+    msg!("Active at: {}", active_at);
+
+    Ok(())
+}
+
+pub fn remove_withdrawal_destination(
+    ctx: Context<RemoveWithdrawalDestination>,
+    destination: Pubkey,
+) -> Result<()> {
+    ctx.accounts.spending_policy.remove_destination(destination)?;
+
+    let clock = Clock::get()?;
+    emit!(WithdrawalDestinationRemovedEvent {
+        vault: ctx.accounts.vault.key(),
+        destination,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Withdrawal destination removed: {}", destination);
+
+    Ok(())
+}
@@ -0,0 +1,502 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+};
+use anchor_spl::token::{Token, TokenAccount};
+use crate::state::{MintState, ProtocolState, SharePool, Strategy, VaultAuthority};
+use crate::constants::{
+    AUTHORITY_SEED, PROTOCOL_STATE_SEED, MINT_STATE_SEED, SHARE_POOL_SEED, STRATEGY_SEED,
+    BPS_DENOMINATOR, STRATEGY_DEPOSIT_DISCRIMINATOR, STRATEGY_WITHDRAW_DISCRIMINATOR,
+};
+use crate::errors::ErrorCode;
+use crate::events::{
+    PoolStrategyConfigUpdatedEvent, StrategyDeployedEvent, StrategyRecalledEvent,
+    StrategyUpdatedEvent, StrategyWrittenDownEvent,
+};
+
+#[derive(Accounts)]
+pub struct ConfigurePoolStrategies<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        mut,
+        seeds = [SHARE_POOL_SEED, share_pool.mint.as_ref()],
+        bump = share_pool.bump
+    )]
+    pub share_pool: Account<'info, SharePool>,
+}
+
+#[derive(Accounts)]
+pub struct SetStrategy<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        seeds = [SHARE_POOL_SEED, share_pool.mint.as_ref()],
+        bump = share_pool.bump
+    )]
+    pub share_pool: Account<'info, SharePool>,
+
+    /// CHECK: Strategy program being allowlisted; only its address is stored
+    #[account(constraint = strategy_program.executable @ ErrorCode::InvalidStrategyConfig)]
+    pub strategy_program: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = Strategy::LEN,
+        seeds = [STRATEGY_SEED, share_pool.mint.as_ref(), strategy_program.key().as_ref()],
+        bump
+    )]
+    pub strategy: Account<'info, Strategy>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DeployToStrategy<'info> {
+    /// The pool keeper or the admin.
+    pub keeper: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [SHARE_POOL_SEED, share_pool.mint.as_ref()],
+        bump = share_pool.bump
+    )]
+    pub share_pool: Account<'info, SharePool>,
+
+    #[account(
+        mut,
+        constraint = pool_token_account.key() == share_pool.token_account @ ErrorCode::InvalidSharePool
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [STRATEGY_SEED, share_pool.mint.as_ref(), strategy.program_id.as_ref()],
+        bump = strategy.bump
+    )]
+    pub strategy: Account<'info, Strategy>,
+
+    /// CHECK: Allowlisted strategy program, matched against the strategy account
+    #[account(address = strategy.program_id @ ErrorCode::InvalidStrategyConfig)]
+    pub strategy_program: UncheckedAccount<'info>,
+
+    /// CHECK: Strategy-owned state, validated by the strategy program
+    #[account(mut)]
+    pub strategy_state: UncheckedAccount<'info>,
+
+    /// CHECK: Strategy-owned token account, validated by the strategy program
+    #[account(mut)]
+    pub strategy_token_account: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RecallFromStrategy<'info> {
+    /// The pool keeper or the admin.
+    pub keeper: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, share_pool.mint.as_ref()],
+        bump = mint_state.bump
+    )]
+    pub mint_state: Account<'info, MintState>,
+
+    #[account(
+        mut,
+        seeds = [SHARE_POOL_SEED, share_pool.mint.as_ref()],
+        bump = share_pool.bump
+    )]
+    pub share_pool: Account<'info, SharePool>,
+
+    #[account(
+        mut,
+        constraint = pool_token_account.key() == share_pool.token_account @ ErrorCode::InvalidSharePool
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [STRATEGY_SEED, share_pool.mint.as_ref(), strategy.program_id.as_ref()],
+        bump = strategy.bump
+    )]
+    pub strategy: Account<'info, Strategy>,
+
+    /// CHECK: Allowlisted strategy program, matched against the strategy account
+    #[account(address = strategy.program_id @ ErrorCode::InvalidStrategyConfig)]
+    pub strategy_program: UncheckedAccount<'info>,
+
+    /// CHECK: Strategy-owned state, validated by the strategy program
+    #[account(mut)]
+    pub strategy_state: UncheckedAccount<'info>,
+
+    /// CHECK: Strategy-owned token account, validated by the strategy program
+    #[account(mut)]
+    pub strategy_token_account: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Strategies report no value of their own, so the admin writes down what
+/// one has lost; otherwise `deployed_assets` keeps pricing shares too high.
+#[derive(Accounts)]
+pub struct WriteDownStrategy<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [AUTHORITY_SEED],
+        bump = authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub authority: Account<'info, VaultAuthority>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, share_pool.mint.as_ref()],
+        bump = mint_state.bump
+    )]
+    pub mint_state: Account<'info, MintState>,
+
+    #[account(
+        mut,
+        seeds = [SHARE_POOL_SEED, share_pool.mint.as_ref()],
+        bump = share_pool.bump
+    )]
+    pub share_pool: Account<'info, SharePool>,
+
+    #[account(
+        mut,
+        seeds = [STRATEGY_SEED, share_pool.mint.as_ref(), strategy.program_id.as_ref()],
+        bump = strategy.bump
+    )]
+    pub strategy: Account<'info, Strategy>,
+}
+
+/// Calls `deposit` or `withdraw` on a strategy program with the share pool
+/// signing. Strategies take `[depositor, state, depositor_token_account,
+/// strategy_token_account, token_program]` and a little-endian amount.
+#[allow(clippy::too_many_arguments)]
+fn invoke_strategy<'info>(
+    discriminator: [u8; 8],
+    amount: u64,
+    share_pool: &Account<'info, SharePool>,
+    pool_token_account: &Account<'info, TokenAccount>,
+    strategy_program: &UncheckedAccount<'info>,
+    strategy_state: &UncheckedAccount<'info>,
+    strategy_token_account: &UncheckedAccount<'info>,
+    token_program: &Program<'info, Token>,
+) -> Result<()> {
+    let mut data = discriminator.to_vec();
+    data.extend_from_slice(&amount.to_le_bytes());
+
+    let ix = Instruction {
+        program_id: strategy_program.key(),
+        accounts: vec![
+            AccountMeta::new_readonly(share_pool.key(), true),
+            AccountMeta::new(strategy_state.key(), false),
+            AccountMeta::new(pool_token_account.key(), false),
+            AccountMeta::new(strategy_token_account.key(), false),
+            AccountMeta::new_readonly(token_program.key(), false),
+        ],
+        data,
+    };
+
+    let mint = share_pool.mint;
+    let seeds = &[SHARE_POOL_SEED, mint.as_ref(), &[share_pool.bump]];
+    invoke_signed(
+        &ix,
+        &[
+            share_pool.to_account_info(),
+            strategy_state.to_account_info(),
+            pool_token_account.to_account_info(),
+            strategy_token_account.to_account_info(),
+            token_program.to_account_info(),
+            strategy_program.to_account_info(),
+        ],
+        &[&seeds[..]],
+    )?;
+    Ok(())
+}
+
+/// Pulls `amount` back from a strategy into the pool token account and
+/// books it. Returns the principal and the yield earned on top of it; the
+/// caller records the yield as an inflow of the mint.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn recall_strategy_funds<'info>(
+    amount: u64,
+    share_pool: &mut Account<'info, SharePool>,
+    pool_token_account: &mut Account<'info, TokenAccount>,
+    strategy: &mut Account<'info, Strategy>,
+    strategy_program: &UncheckedAccount<'info>,
+    strategy_state: &UncheckedAccount<'info>,
+    strategy_token_account: &UncheckedAccount<'info>,
+    token_program: &Program<'info, Token>,
+) -> Result<(u64, u64)> {
+    let balance_before = pool_token_account.amount;
+    invoke_strategy(
+        STRATEGY_WITHDRAW_DISCRIMINATOR,
+        amount,
+        share_pool,
+        pool_token_account,
+        strategy_program,
+        strategy_state,
+        strategy_token_account,
+        token_program,
+    )?;
+
+    pool_token_account.reload()?;
+    let received = pool_token_account.amount
+        .checked_sub(balance_before)
+        .ok_or(ErrorCode::StrategyBalanceMismatch)?;
+    require!(received == amount, ErrorCode::StrategyBalanceMismatch);
+
+    // Anything returned beyond the allocation is yield for the shareholders.
+    let (principal, yield_amount) = strategy.deallocate(received);
+    share_pool.recall(principal)?;
+    if yield_amount > 0 {
+        share_pool.add_yield(yield_amount)?;
+    }
+    Ok((principal, yield_amount))
+}
+
+fn require_keeper(keeper: &Pubkey, share_pool: &SharePool, authority: &VaultAuthority) -> Result<()> {
+    require!(
+        *keeper == share_pool.keeper || *keeper == authority.admin,
+        ErrorCode::UnauthorizedKeeper
+    );
+    Ok(())
+}
+
+pub fn configure_pool_strategies(
+    ctx: Context<ConfigurePoolStrategies>,
+    keeper: Pubkey,
+    reserve_bps: u16,
+) -> Result<()> {
+    require!(
+        keeper != Pubkey::default() && reserve_bps as u64 <= BPS_DENOMINATOR,
+        ErrorCode::InvalidStrategyConfig
+    );
+
+    let share_pool = &mut ctx.accounts.share_pool;
+    share_pool.keeper = keeper;
+    share_pool.reserve_bps = reserve_bps;
+
+    let clock = Clock::get()?;
+    emit!(PoolStrategyConfigUpdatedEvent {
+        pool: share_pool.key(),
+        keeper,
+        reserve_bps,
+        admin: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Strategy keeper set: {}", keeper);
+    msg!("Liquidity reserve: {} bps of unlocked assets", reserve_bps);
+
+    Ok(())
+}
+
+pub fn set_strategy(
+    ctx: Context<SetStrategy>,
+    max_allocation_bps: u16,
+    active: bool,
+) -> Result<()> {
+    require!(
+        max_allocation_bps as u64 <= BPS_DENOMINATOR,
+        ErrorCode::InvalidStrategyConfig
+    );
+
+    let strategy = &mut ctx.accounts.strategy;
+    strategy.mint = ctx.accounts.share_pool.mint;
+    strategy.program_id = ctx.accounts.strategy_program.key();
+    strategy.max_allocation_bps = max_allocation_bps;
+    strategy.active = active;
+    strategy.bump = ctx.bumps.strategy;
+
+    let clock = Clock::get()?;
+    emit!(StrategyUpdatedEvent {
+        strategy: strategy.key(),
+        mint: strategy.mint,
+        program_id: strategy.program_id,
+        max_allocation_bps,
+        active,
+        admin: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Strategy {} set for mint {}", strategy.program_id, strategy.mint);
+    msg!("Max allocation: {} bps, active: {}", max_allocation_bps, active);
+
+    Ok(())
+}
+
+pub fn deploy_to_strategy(ctx: Context<DeployToStrategy>, amount: u64) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    ctx.accounts.protocol_state.require_active()?;
+    require_keeper(
+        &ctx.accounts.keeper.key(),
+        &ctx.accounts.share_pool,
+        &ctx.accounts.authority,
+    )?;
+
+    let share_pool = &ctx.accounts.share_pool;
+    require!(
+        amount <= share_pool.deployable_assets()?,
+        ErrorCode::InsufficientPoolLiquidity
+    );
+    ctx.accounts.strategy.allocate(amount, share_pool)?;
+
+    let balance_before = ctx.accounts.pool_token_account.amount;
+    invoke_strategy(
+        STRATEGY_DEPOSIT_DISCRIMINATOR,
+        amount,
+        share_pool,
+        &ctx.accounts.pool_token_account,
+        &ctx.accounts.strategy_program,
+        &ctx.accounts.strategy_state,
+        &ctx.accounts.strategy_token_account,
+        &ctx.accounts.token_program,
+    )?;
+
+    ctx.accounts.pool_token_account.reload()?;
+    require!(
+        balance_before.checked_sub(ctx.accounts.pool_token_account.amount) == Some(amount),
+        ErrorCode::StrategyBalanceMismatch
+    );
+
+    let share_pool = &mut ctx.accounts.share_pool;
+    share_pool.deploy(amount)?;
+
+    let strategy = &ctx.accounts.strategy;
+    let clock = Clock::get()?;
+    emit!(StrategyDeployedEvent {
+        strategy: strategy.key(),
+        program_id: strategy.program_id,
+        amount,
+        allocated: strategy.allocated,
+        deployed_assets: share_pool.deployed_assets,
+        keeper: ctx.accounts.keeper.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Deployed {} tokens to strategy {}", amount, strategy.program_id);
+    msg!("Pool deployed assets: {}", share_pool.deployed_assets);
+
+    Ok(())
+}
+
+pub fn recall_from_strategy(ctx: Context<RecallFromStrategy>, amount: u64) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    require_keeper(
+        &ctx.accounts.keeper.key(),
+        &ctx.accounts.share_pool,
+        &ctx.accounts.authority,
+    )?;
+
+    let (principal, yield_amount) = recall_strategy_funds(
+        amount,
+        &mut ctx.accounts.share_pool,
+        &mut ctx.accounts.pool_token_account,
+        &mut ctx.accounts.strategy,
+        &ctx.accounts.strategy_program,
+        &ctx.accounts.strategy_state,
+        &ctx.accounts.strategy_token_account,
+        &ctx.accounts.token_program,
+    )?;
+
+    let clock = Clock::get()?;
+    if yield_amount > 0 {
+        ctx.accounts.mint_state.record_inflow(&ctx.accounts.protocol_state, yield_amount, &clock)?;
+    }
+
+    let strategy = &ctx.accounts.strategy;
+    let share_pool = &ctx.accounts.share_pool;
+
+    emit!(StrategyRecalledEvent {
+        strategy: strategy.key(),
+        program_id: strategy.program_id,
+        principal,
+        yield_amount,
+        allocated: strategy.allocated,
+        deployed_assets: share_pool.deployed_assets,
+        keeper: ctx.accounts.keeper.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Recalled {} tokens from strategy {}", amount, strategy.program_id);
+    msg!("Principal: {}, yield: {}", principal, yield_amount);
+
+    Ok(())
+}
+
+pub fn write_down_strategy(ctx: Context<WriteDownStrategy>, loss: u64) -> Result<()> {
+    require!(loss > 0, ErrorCode::InvalidAmount);
+
+    let strategy = &mut ctx.accounts.strategy;
+    strategy.write_down(loss)?;
+
+    let share_pool = &mut ctx.accounts.share_pool;
+    share_pool.write_down_deployed(loss)?;
+
+    let clock = Clock::get()?;
+    ctx.accounts.mint_state.record_loss(loss, &clock)?;
+
+    emit!(StrategyWrittenDownEvent {
+        strategy: strategy.key(),
+        program_id: strategy.program_id,
+        loss,
+        allocated: strategy.allocated,
+        deployed_assets: share_pool.deployed_assets,
+        total_assets: share_pool.total_assets,
+        admin: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Wrote down {} tokens lost by strategy {}", loss, strategy.program_id);
+    msg!("Pool total assets: {}", share_pool.total_assets);
+
+    Ok(())
+}
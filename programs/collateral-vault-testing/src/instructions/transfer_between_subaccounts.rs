@@ -0,0 +1,156 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::state::{CollateralVault, MintState, ProtocolState, SessionKey};
+use crate::constants::{
+    VAULT_SEED, PROTOCOL_STATE_SEED, MINT_STATE_SEED, SESSION_SEED, SESSION_SCOPE_TRANSFER,
+};
+use crate::errors::ErrorCode;
+use crate::instructions::shared::enforce_spending_policy;
+use crate::events::SubaccountTransferEvent;
+
+#[derive(Accounts)]
+pub struct TransferBetweenSubaccounts<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, from_vault.seed_owner.as_ref(), from_vault.subaccount.to_le_bytes().as_ref()],
+        bump = from_vault.bump
+    )]
+    pub from_vault: Account<'info, CollateralVault>,
+
+    pub cosigner: Option<Signer<'info>>,
+
+    #[account(
+        mut,
+        seeds = [SESSION_SEED, from_vault.key().as_ref(), user.key().as_ref()],
+        bump = session.bump,
+        constraint = session.delegation_epoch == from_vault.delegation_epoch @ ErrorCode::DelegationRevoked
+    )]
+    pub session: Option<Account<'info, SessionKey>>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, to_vault.seed_owner.as_ref(), to_vault.subaccount.to_le_bytes().as_ref()],
+        bump = to_vault.bump,
+        constraint = to_vault.owner == from_vault.owner @ ErrorCode::UnauthorizedOwner
+    )]
+    pub to_vault: Account<'info, CollateralVault>,
+
+    #[account(
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        seeds = [MINT_STATE_SEED, from_vault_token_account.mint.as_ref()],
+        bump = mint_state.bump
+    )]
+    pub mint_state: Account<'info, MintState>,
+
+    #[account(
+        mut,
+        constraint = from_vault_token_account.key() == from_vault.token_account @ ErrorCode::UnauthorizedOwner
+    )]
+    pub from_vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = to_vault_token_account.key() == to_vault.token_account @ ErrorCode::UnauthorizedOwner
+    )]
+    pub to_vault_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Owner spending policy PDA for the source vault, validated in handler
+    #[account(mut)]
+    pub spending_policy: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<TransferBetweenSubaccounts>, amount: u64) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    require!(
+        ctx.accounts.from_vault.key() != ctx.accounts.to_vault.key(),
+        ErrorCode::SameVaultTransfer
+    );
+    ctx.accounts.protocol_state.require_active()?;
+    ctx.accounts.from_vault.require_not_locked_down()?;
+    ctx.accounts.from_vault.require_unpooled()?;
+    ctx.accounts.to_vault.require_unpooled()?;
+
+    let clock = Clock::get()?;
+    ctx.accounts.from_vault.absorb_socialized_loss(&ctx.accounts.mint_state, &clock)?;
+    ctx.accounts.to_vault.absorb_socialized_loss(&ctx.accounts.mint_state, &clock)?;
+    let from_vault = &mut ctx.accounts.from_vault;
+
+    if ctx.accounts.user.key() != from_vault.owner {
+        let session = ctx.accounts.session
+            .as_mut()
+            .ok_or(ErrorCode::UnauthorizedOwner)?;
+        session.authorize(SESSION_SCOPE_TRANSFER, amount, clock.unix_timestamp)?;
+    }
+
+    require!(
+        from_vault.available_balance >= amount,
+        ErrorCode::InsufficientAvailableBalance
+    );
+    from_vault.require_cosigner(amount, ctx.accounts.cosigner.as_ref().map(|c| c.key()))?;
+
+    // The destination belongs to the same owner, so it needs no allowlist
+    // entry, but the move still counts toward the source vault's limits.
+    enforce_spending_policy(
+        &ctx.accounts.spending_policy,
+        from_vault.key(),
+        from_vault.owner,
+        from_vault.delegation_epoch,
+        None,
+        amount,
+        false,
+    )?;
+
+    let owner = from_vault.owner;
+    let seed_owner = from_vault.seed_owner;
+    let from_subaccount = from_vault.subaccount.to_le_bytes();
+    let seeds = &[
+        VAULT_SEED,
+        seed_owner.as_ref(),
+        from_subaccount.as_ref(),
+        &[from_vault.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.from_vault_token_account.to_account_info(),
+                to: ctx.accounts.to_vault_token_account.to_account_info(),
+                authority: from_vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )?;
+
+    from_vault.withdraw(amount, &clock)?;
+
+    let to_vault = &mut ctx.accounts.to_vault;
+    to_vault.deposit(amount, &clock)?;
+
+    emit!(SubaccountTransferEvent {
+        owner,
+        from_vault: from_vault.key(),
+        to_vault: to_vault.key(),
+        from_subaccount: from_vault.subaccount,
+        to_subaccount: to_vault.subaccount,
+        amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Moved {} tokens between subaccounts", amount);
+    msg!("From subaccount: {}", from_vault.subaccount);
+    msg!("To subaccount: {}", to_vault.subaccount);
+
+    Ok(())
+}
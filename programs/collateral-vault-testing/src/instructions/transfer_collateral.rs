@@ -1,8 +1,13 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
-use crate::state::{CollateralVault, VaultAuthority};
-use crate::constants::{VAULT_SEED, AUTHORITY_SEED};
+use crate::state::{CollateralVault, MintState, ProtocolState, SharePool, VaultAuthority};
+use crate::constants::{
+    VAULT_SEED, AUTHORITY_SEED, PROTOCOL_STATE_SEED, MINT_STATE_SEED, SHARE_POOL_SEED,
+};
 use crate::errors::ErrorCode;
+use crate::instructions::shared::{
+    authorized_caller, enforce_rate_limit, fee_treasury, pooled_share_pool, record_outflow,
+};
 use crate::events::TransferEvent;
 
 #[derive(Accounts)]
@@ -15,14 +20,28 @@ pub struct TransferCollateral<'info> {
 
     #[account(
         mut,
-        seeds = [VAULT_SEED, from_vault.owner.as_ref()],
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, from_vault_token_account.mint.as_ref()],
+        bump = mint_state.bump
+    )]
+    pub mint_state: Account<'info, MintState>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, from_vault.seed_owner.as_ref(), from_vault.subaccount.to_le_bytes().as_ref()],
         bump = from_vault.bump
     )]
     pub from_vault: Account<'info, CollateralVault>,
 
     #[account(
         mut,
-        seeds = [VAULT_SEED, to_vault.owner.as_ref()],
+        seeds = [VAULT_SEED, to_vault.seed_owner.as_ref(), to_vault.subaccount.to_le_bytes().as_ref()],
         bump = to_vault.bump
     )]
     pub to_vault: Account<'info, CollateralVault>,
@@ -39,6 +58,30 @@ pub struct TransferCollateral<'info> {
     )]
     pub to_vault_token_account: Account<'info, TokenAccount>,
 
+    /// CHECK: Outflow rate limit PDA for the calling program, validated in handler
+    #[account(mut)]
+    pub rate_limit: UncheckedAccount<'info>,
+
+    /// Protocol treasury for the vault mint; required while a transfer fee is set.
+    #[account(mut)]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: Instructions sysvar used to identify the calling program
+    #[account(address = solana_instructions_sysvar::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    /// Share pool of the mint; required when either vault uses share accounting.
+    #[account(
+        mut,
+        seeds = [SHARE_POOL_SEED, share_pool.mint.as_ref()],
+        bump = share_pool.bump
+    )]
+    pub share_pool: Option<Account<'info, SharePool>>,
+
+    /// Pool token account standing in for the token account of a pooled vault.
+    #[account(mut)]
+    pub pool_token_account: Option<Account<'info, TokenAccount>>,
+
     pub token_program: Program<'info, Token>,
 }
 
@@ -49,7 +92,40 @@ pub fn handler(ctx: Context<TransferCollateral>, amount: u64) -> Result<()> {
         ctx.accounts.from_vault.key() != ctx.accounts.to_vault.key(),
         ErrorCode::SameVaultTransfer
     );
+    ctx.accounts.protocol_state.require_active()?;
 
+    let caller_program = authorized_caller(
+        &ctx.accounts.authority,
+        &ctx.accounts.instructions_sysvar,
+    )?;
+
+    // Pooled vaults keep their tokens in the pool token account, so that
+    // account stands in for theirs on either side of the transfer.
+    let from_pooled = pooled_share_pool(
+        &mut ctx.accounts.from_vault,
+        ctx.accounts.share_pool.as_mut(),
+        Some(&ctx.accounts.from_vault_token_account),
+    )?
+    .is_some();
+    let to_pooled = pooled_share_pool(
+        &mut ctx.accounts.to_vault,
+        ctx.accounts.share_pool.as_mut(),
+        Some(&ctx.accounts.to_vault_token_account),
+    )?
+    .is_some();
+    let pool_token_account = match ctx.accounts.share_pool.as_ref() {
+        Some(share_pool) if from_pooled || to_pooled => Some(
+            ctx.accounts.pool_token_account
+                .as_ref()
+                .filter(|account| account.key() == share_pool.token_account)
+                .ok_or(ErrorCode::InvalidSharePool)?,
+        ),
+        _ => None,
+    };
+
+    let clock = Clock::get()?;
+    ctx.accounts.from_vault.absorb_socialized_loss(&ctx.accounts.mint_state, &clock)?;
+    ctx.accounts.to_vault.absorb_socialized_loss(&ctx.accounts.mint_state, &clock)?;
     let from_vault = &mut ctx.accounts.from_vault;
 
     require!(
@@ -57,42 +133,135 @@ pub fn handler(ctx: Context<TransferCollateral>, amount: u64) -> Result<()> {
         ErrorCode::InsufficientAvailableBalance
     );
 
-    let from_vault_owner = from_vault.owner;
-    let seeds = &[
+    enforce_rate_limit(
+        &ctx.accounts.rate_limit,
+        &caller_program,
+        from_vault.key(),
+        amount,
+    )?;
+
+    let protocol_state = &ctx.accounts.protocol_state;
+    let fee = protocol_state.fee_for(protocol_state.transfer_fee_bps, amount)?;
+    let credited = amount - fee;
+    require!(credited > 0, ErrorCode::InvalidAmount);
+
+    if let (true, Some(share_pool)) = (from_pooled, ctx.accounts.share_pool.as_ref()) {
+        let leaving = if to_pooled { fee } else { amount };
+        require!(
+            share_pool.idle_assets() >= leaving,
+            ErrorCode::InsufficientPoolLiquidity
+        );
+    }
+
+    let mint = ctx.accounts.from_vault_token_account.mint;
+    let seed_owner = from_vault.seed_owner;
+    let from_subaccount = from_vault.subaccount.to_le_bytes();
+    let vault_seeds = &[
         VAULT_SEED,
-        from_vault_owner.as_ref(),
+        seed_owner.as_ref(),
+        from_subaccount.as_ref(),
         &[from_vault.bump],
     ];
-    let signer_seeds = &[&seeds[..]];
-
-    token::transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.from_vault_token_account.to_account_info(),
-                to: ctx.accounts.to_vault_token_account.to_account_info(),
-                authority: from_vault.to_account_info(),
-            },
-            signer_seeds,
-        ),
-        amount,
-    )?;
+    let vault_signer = &[&vault_seeds[..]];
+    let pool_bump = ctx.accounts.share_pool.as_ref().map_or(0, |share_pool| share_pool.bump);
+    let pool_seeds = &[SHARE_POOL_SEED, mint.as_ref(), &[pool_bump]];
+    let pool_signer = &[&pool_seeds[..]];
 
-    from_vault.withdraw(amount)?;
+    let (source, source_authority, signer_seeds): (AccountInfo, AccountInfo, &[&[&[u8]]]) =
+        match (from_pooled, ctx.accounts.share_pool.as_ref(), pool_token_account) {
+            (true, Some(share_pool), Some(pool_token_account)) => (
+                pool_token_account.to_account_info(),
+                share_pool.to_account_info(),
+                pool_signer,
+            ),
+            _ => (
+                ctx.accounts.from_vault_token_account.to_account_info(),
+                from_vault.to_account_info(),
+                vault_signer,
+            ),
+        };
+    let destination = match (to_pooled, pool_token_account) {
+        (true, Some(pool_token_account)) => pool_token_account.to_account_info(),
+        _ => ctx.accounts.to_vault_token_account.to_account_info(),
+    };
+
+    // Between two pooled vaults only shares change hands.
+    if !(from_pooled && to_pooled) {
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: source.clone(),
+                    to: destination,
+                    authority: source_authority.clone(),
+                },
+                signer_seeds,
+            ),
+            credited,
+        )?;
+    }
+
+    if fee > 0 {
+        let treasury = fee_treasury(
+            ctx.accounts.treasury_token_account.as_ref(),
+            &protocol_state.key(),
+            &mint,
+        )?;
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: source,
+                    to: treasury.to_account_info(),
+                    authority: source_authority,
+                },
+                signer_seeds,
+            ),
+            fee,
+        )?;
+    }
 
     let to_vault = &mut ctx.accounts.to_vault;
-    to_vault.deposit(amount)?;
+    match ctx.accounts.share_pool.as_mut() {
+        Some(share_pool) if from_pooled || to_pooled => {
+            // Redeem before issuing so the receiver gets shares at the
+            // price the sender's tokens left the pool at.
+            if from_pooled {
+                from_vault.redeem_pooled(share_pool, amount, &clock)?;
+            } else {
+                from_vault.withdraw(amount, &clock)?;
+            }
+            if to_pooled {
+                to_vault.issue_pooled(share_pool, credited, &clock)?;
+            } else {
+                to_vault.deposit(credited, &clock)?;
+            }
+            share_pool.require_liquid_reserves()?;
+        }
+        _ => {
+            from_vault.withdraw(amount, &clock)?;
+            to_vault.deposit(credited, &clock)?;
+        }
+    }
+
+    // The credited part stays in the protocol; the fee leaves TVL.
+    let (protocol_state, mint_state) = (&mut ctx.accounts.protocol_state, &mut ctx.accounts.mint_state);
+    record_outflow(protocol_state, mint_state, credited, false)?;
+    if fee > 0 {
+        record_outflow(protocol_state, mint_state, fee, true)?;
+    }
+    ctx.accounts.protocol_state.record_fee(fee)?;
 
-    let clock = Clock::get()?;
     emit!(TransferEvent {
         from_vault: from_vault.key(),
         to_vault: to_vault.key(),
         amount,
-        caller_program: *ctx.program_id,
+        fee,
+        caller_program,
         timestamp: clock.unix_timestamp,
     });
 
-    msg!("✅ Transferred {} tokens", amount);
+    msg!("✅ Transferred {} tokens (fee: {})", credited, fee);
     msg!("From vault: {}", from_vault.key());
     msg!("To vault: {}", to_vault.key());
 
@@ -1,8 +1,14 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
-use crate::state::CollateralVault;
-use crate::constants::VAULT_SEED;
+use crate::state::{CollateralVault, MintState, ProtocolState, SessionKey, VaultOperator};
+use crate::constants::{
+    VAULT_SEED, OPERATOR_SEED, PROTOCOL_STATE_SEED, MINT_STATE_SEED, SESSION_SEED,
+    SESSION_SCOPE_WITHDRAW,
+};
 use crate::errors::ErrorCode;
+use crate::instructions::shared::{
+    enforce_spending_policy, fee_treasury, record_outflow, withdrawal_cooldown,
+};
 use crate::events::WithdrawEvent;
 
 #[derive(Accounts)]
@@ -12,12 +18,43 @@ pub struct Withdraw<'info> {
 
     #[account(
         mut,
-        seeds = [VAULT_SEED, user.key().as_ref()],
-        bump = vault.bump,
-        constraint= vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump
     )]
     pub vault: Account<'info, CollateralVault>,
 
+    pub cosigner: Option<Signer<'info>>,
+
+    #[account(
+        mut,
+        seeds = [OPERATOR_SEED, vault.key().as_ref(), user.key().as_ref()],
+        bump = vault_operator.bump,
+        constraint = vault_operator.delegation_epoch == vault.delegation_epoch @ ErrorCode::DelegationRevoked
+    )]
+    pub vault_operator: Option<Account<'info, VaultOperator>>,
+
+    #[account(
+        mut,
+        seeds = [SESSION_SEED, vault.key().as_ref(), user.key().as_ref()],
+        bump = session.bump,
+        constraint = session.delegation_epoch == vault.delegation_epoch @ ErrorCode::DelegationRevoked
+    )]
+    pub session: Option<Account<'info, SessionKey>>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, vault_token_account.mint.as_ref()],
+        bump = mint_state.bump
+    )]
+    pub mint_state: Account<'info, MintState>,
+
     #[account(mut)]
     pub user_token_account: Account<'info, TokenAccount>,
 
@@ -27,22 +64,80 @@ pub struct Withdraw<'info> {
     )]
     pub vault_token_account: Account<'info, TokenAccount>,
 
+    /// CHECK: Withdrawal cooldown PDA for the vault mint, validated in handler
+    pub cooldown_config: UncheckedAccount<'info>,
+
+    /// CHECK: Owner spending policy PDA for the vault, validated in handler
+    #[account(mut)]
+    pub spending_policy: UncheckedAccount<'info>,
+
+    /// Protocol treasury for the vault mint; required while a withdraw fee is set.
+    #[account(mut)]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+
     pub token_program: Program<'info, Token>,
 }
 
 pub fn handler(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
     require!(amount > 0, ErrorCode::InvalidAmount);
+    ctx.accounts.protocol_state.require_withdrawals_enabled()?;
+    ctx.accounts.vault.require_not_locked_down()?;
+    ctx.accounts.vault.require_unpooled()?;
 
+    let cooldown = withdrawal_cooldown(
+        &ctx.accounts.protocol_state,
+        &ctx.accounts.cooldown_config,
+        &ctx.accounts.vault_token_account.mint,
+    )?;
+    require!(cooldown == 0, ErrorCode::WithdrawalCooldownRequired);
+
+    let clock = Clock::get()?;
     let vault = &mut ctx.accounts.vault;
+    vault.absorb_socialized_loss(&ctx.accounts.mint_state, &clock)?;
 
     require!(
         vault.available_balance >= amount,
         ErrorCode::InsufficientAvailableBalance
     );
-    let user_key=ctx.accounts.user.key();
+
+    let destination_is_owner = ctx.accounts.user_token_account.owner == vault.owner;
+    let mut acting_as_operator = false;
+    if ctx.accounts.user.key() != vault.owner {
+        if let Some(session) = ctx.accounts.session.as_mut() {
+            // Session keys stand in for the owner and may only pay the owner.
+            require!(destination_is_owner, ErrorCode::DestinationNotAllowed);
+            session.authorize(SESSION_SCOPE_WITHDRAW, amount, clock.unix_timestamp)?;
+        } else {
+            let operator = ctx.accounts.vault_operator
+                .as_mut()
+                .ok_or(ErrorCode::UnauthorizedOwner)?;
+            operator.authorize_withdrawal(destination_is_owner, amount)?;
+            acting_as_operator = true;
+        }
+    }
+    vault.require_cosigner(amount, ctx.accounts.cosigner.as_ref().map(|c| c.key()))?;
+
+    enforce_spending_policy(
+        &ctx.accounts.spending_policy,
+        vault.key(),
+        vault.owner,
+        vault.delegation_epoch,
+        Some(&ctx.accounts.user_token_account),
+        amount,
+        acting_as_operator,
+    )?;
+
+    let protocol_state = &ctx.accounts.protocol_state;
+    let fee = protocol_state.fee_for(protocol_state.withdraw_fee_bps, amount)?;
+    let received = amount - fee;
+    require!(received > 0, ErrorCode::InvalidAmount);
+
+    let seed_owner = vault.seed_owner;
+    let subaccount = vault.subaccount.to_le_bytes();
     let seeds = &[
         VAULT_SEED,
-        user_key.as_ref(),
+        seed_owner.as_ref(),
+        subaccount.as_ref(),
         &[vault.bump],
     ];
     let signer_seeds = &[&seeds[..]];
@@ -57,22 +152,44 @@ pub fn handler(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
             },
             signer_seeds,
         ),
-        amount,
+        received,
     )?;
 
-    vault.withdraw(amount)?;
+    if fee > 0 {
+        let treasury = fee_treasury(
+            ctx.accounts.treasury_token_account.as_ref(),
+            &protocol_state.key(),
+            &ctx.accounts.vault_token_account.mint,
+        )?;
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault_token_account.to_account_info(),
+                    to: treasury.to_account_info(),
+                    authority: vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            fee,
+        )?;
+    }
+
+    vault.withdraw(amount, &clock)?;
+    record_outflow(&mut ctx.accounts.protocol_state, &mut ctx.accounts.mint_state, amount, true)?;
+    ctx.accounts.protocol_state.record_fee(fee)?;
 
-    let clock = Clock::get()?;
     emit!(WithdrawEvent {
         vault: vault.key(),
         user: ctx.accounts.user.key(),
         amount,
+        fee,
         new_total_balance: vault.total_balance,
         new_available_balance: vault.available_balance,
         timestamp: clock.unix_timestamp,
     });
 
-    msg!("✅ Withdrew {} tokens", amount);
+    msg!("✅ Withdrew {} tokens (fee: {})", received, fee);
     msg!("New available balance: {}", vault.available_balance);
 
     Ok(())
@@ -0,0 +1,362 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::state::{CollateralVault, MintState, ProtocolState, SharePool};
+use crate::constants::{VAULT_SEED, PROTOCOL_STATE_SEED, MINT_STATE_SEED, SHARE_POOL_SEED};
+use crate::errors::ErrorCode;
+use crate::events::{WithdrawalCancelledEvent, WithdrawalExecutedEvent, WithdrawalRequestedEvent};
+use crate::instructions::shared::{
+    enforce_spending_policy, fee_treasury, pooled_share_pool, record_outflow, withdrawal_cooldown,
+};
+
+#[derive(Accounts)]
+pub struct RequestWithdrawal<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    pub cosigner: Option<Signer<'info>>,
+
+    #[account(
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        constraint = vault_token_account.key() == vault.token_account @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Withdrawal cooldown PDA for the vault mint, validated in handler
+    pub cooldown_config: UncheckedAccount<'info>,
+
+    /// Share pool behind the vault; required when the vault uses share accounting.
+    #[account(
+        mut,
+        seeds = [SHARE_POOL_SEED, share_pool.mint.as_ref()],
+        bump = share_pool.bump
+    )]
+    pub share_pool: Option<Account<'info, SharePool>>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteWithdrawal<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATE_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, vault_token_account.mint.as_ref()],
+        bump = mint_state.bump
+    )]
+    pub mint_state: Account<'info, MintState>,
+
+    #[account(mut)]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Owner spending policy PDA for the vault, validated in handler
+    #[account(mut)]
+    pub spending_policy: UncheckedAccount<'info>,
+
+    /// Share pool behind the vault; required when the vault uses share accounting.
+    #[account(
+        mut,
+        seeds = [SHARE_POOL_SEED, share_pool.mint.as_ref()],
+        bump = share_pool.bump
+    )]
+    pub share_pool: Option<Account<'info, SharePool>>,
+
+    /// Pool token account the pending amount of a pooled vault is paid from.
+    #[account(mut)]
+    pub pool_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Protocol treasury for the vault mint; required while a withdraw fee is set.
+    #[account(mut)]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelWithdrawal<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.seed_owner.as_ref(), vault.subaccount.to_le_bytes().as_ref()],
+        bump = vault.bump,
+        constraint = vault.owner == user.key() @ ErrorCode::UnauthorizedOwner
+    )]
+    pub vault: Account<'info, CollateralVault>,
+
+    /// Share pool behind the vault; required when the vault uses share accounting.
+    #[account(
+        mut,
+        seeds = [SHARE_POOL_SEED, share_pool.mint.as_ref()],
+        bump = share_pool.bump
+    )]
+    pub share_pool: Option<Account<'info, SharePool>>,
+
+    /// Vault token account, matched against the share pool mint.
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+}
+
+/// A pooled vault's pending amount is held in underlying tokens, like its
+/// locks, so later yield or losses only move its available balance.
+pub fn request_withdrawal(ctx: Context<RequestWithdrawal>, amount: u64) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    ctx.accounts.vault.require_not_locked_down()?;
+
+    let cooldown = withdrawal_cooldown(
+        &ctx.accounts.protocol_state,
+        &ctx.accounts.cooldown_config,
+        &ctx.accounts.vault_token_account.mint,
+    )?;
+
+    let vault = &mut ctx.accounts.vault;
+    let share_pool = pooled_share_pool(
+        vault,
+        ctx.accounts.share_pool.as_mut(),
+        Some(&ctx.accounts.vault_token_account),
+    )?;
+
+    require!(
+        vault.available_balance >= amount,
+        ErrorCode::InsufficientAvailableBalance
+    );
+
+    // Checked against the whole pending amount so top-ups cannot split a
+    // large withdrawal into small ones.
+    let pending_total = vault.pending_withdrawal
+        .checked_add(amount)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    vault.require_cosigner(pending_total, ctx.accounts.cosigner.as_ref().map(|c| c.key()))?;
+
+    let clock = Clock::get()?;
+    let ready_at = clock.unix_timestamp
+        .checked_add(cooldown)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    vault.request_withdrawal(amount, ready_at, &clock)?;
+    if let Some(share_pool) = share_pool {
+        share_pool.request_assets(amount)?;
+    }
+
+    emit!(WithdrawalRequestedEvent {
+        vault: vault.key(),
+        user: ctx.accounts.user.key(),
+        amount,
+        pending_withdrawal: vault.pending_withdrawal,
+        ready_at,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Requested withdrawal of {} tokens", amount);
+    msg!("Ready at: {}", ready_at);
+
+    Ok(())
+}
+
+pub fn execute_withdrawal(ctx: Context<ExecuteWithdrawal>) -> Result<()> {
+    ctx.accounts.protocol_state.require_withdrawals_enabled()?;
+    ctx.accounts.vault.require_not_locked_down()?;
+
+    let clock = Clock::get()?;
+    let vault = &mut ctx.accounts.vault;
+    // Loss the available balance cannot cover comes out of the payout.
+    vault.absorb_socialized_loss(&ctx.accounts.mint_state, &clock)?;
+
+    require!(vault.pending_withdrawal > 0, ErrorCode::NoPendingWithdrawal);
+
+    require!(
+        clock.unix_timestamp >= vault.withdrawal_ready_at,
+        ErrorCode::WithdrawalNotReady
+    );
+
+    enforce_spending_policy(
+        &ctx.accounts.spending_policy,
+        vault.key(),
+        vault.owner,
+        vault.delegation_epoch,
+        Some(&ctx.accounts.user_token_account),
+        vault.pending_withdrawal,
+        false,
+    )?;
+
+    let protocol_state = &ctx.accounts.protocol_state;
+    let fee = protocol_state.fee_for(protocol_state.withdraw_fee_bps, vault.pending_withdrawal)?;
+    let received = vault.pending_withdrawal - fee;
+    require!(received > 0, ErrorCode::InvalidAmount);
+    let treasury = if fee > 0 {
+        Some(fee_treasury(
+            ctx.accounts.treasury_token_account.as_ref(),
+            &protocol_state.key(),
+            &ctx.accounts.vault_token_account.mint,
+        )?)
+    } else {
+        None
+    };
+
+    let user_key = ctx.accounts.user.key();
+    let share_pool = pooled_share_pool(
+        vault,
+        ctx.accounts.share_pool.as_mut(),
+        Some(&ctx.accounts.vault_token_account),
+    )?;
+
+    let amount = if let Some(share_pool) = share_pool {
+        let pool_token_account = ctx.accounts.pool_token_account
+            .as_ref()
+            .filter(|account| account.key() == share_pool.token_account)
+            .ok_or(ErrorCode::InvalidSharePool)?;
+        let (amount, shares) = vault.execute_pooled_withdrawal(share_pool, &clock)?;
+
+        let mint = share_pool.mint;
+        let seeds = &[SHARE_POOL_SEED, mint.as_ref(), &[share_pool.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: pool_token_account.to_account_info(),
+                    to: ctx.accounts.user_token_account.to_account_info(),
+                    authority: share_pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            received,
+        )?;
+
+        if let Some(treasury) = treasury {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: pool_token_account.to_account_info(),
+                        to: treasury.to_account_info(),
+                        authority: share_pool.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                fee,
+            )?;
+        }
+
+        msg!("Burned {} pool shares", shares);
+        amount
+    } else {
+        let seed_owner = vault.seed_owner;
+        let subaccount = vault.subaccount.to_le_bytes();
+        let seeds = &[
+            VAULT_SEED,
+            seed_owner.as_ref(),
+            subaccount.as_ref(),
+            &[vault.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault_token_account.to_account_info(),
+                    to: ctx.accounts.user_token_account.to_account_info(),
+                    authority: vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            received,
+        )?;
+
+        if let Some(treasury) = treasury {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.vault_token_account.to_account_info(),
+                        to: treasury.to_account_info(),
+                        authority: vault.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                fee,
+            )?;
+        }
+
+        vault.execute_withdrawal(&clock)?
+    };
+    record_outflow(&mut ctx.accounts.protocol_state, &mut ctx.accounts.mint_state, amount, true)?;
+    ctx.accounts.protocol_state.record_fee(fee)?;
+
+    emit!(WithdrawalExecutedEvent {
+        vault: vault.key(),
+        user: user_key,
+        amount,
+        fee,
+        new_total_balance: vault.total_balance,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Executed withdrawal of {} tokens (fee: {})", received, fee);
+    msg!("New total balance: {}", vault.total_balance);
+
+    Ok(())
+}
+
+pub fn cancel_withdrawal(ctx: Context<CancelWithdrawal>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+
+    require!(vault.pending_withdrawal > 0, ErrorCode::NoPendingWithdrawal);
+
+    let share_pool = pooled_share_pool(
+        vault,
+        ctx.accounts.share_pool.as_mut(),
+        ctx.accounts.vault_token_account.as_ref(),
+    )?;
+
+    let clock = Clock::get()?;
+    let amount = vault.cancel_withdrawal(&clock)?;
+    if let Some(share_pool) = share_pool {
+        share_pool.release_pending_assets(amount)?;
+    }
+
+    emit!(WithdrawalCancelledEvent {
+        vault: vault.key(),
+        user: ctx.accounts.user.key(),
+        amount,
+        new_available_balance: vault.available_balance,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Cancelled withdrawal of {} tokens", amount);
+    msg!("New available balance: {}", vault.available_balance);
+
+    Ok(())
+}
@@ -6,7 +6,9 @@ pub mod events;
 pub mod instructions;
 pub mod state;
 
-pub use state::{CollateralVault, VaultAuthority};
+pub use state::{
+    CollateralVault, MultisigAction, ProposalAccount, ProtocolState, ProtocolStatus, SettlementLeg, VaultAuthority,
+};
 
 
 use instructions::*;
@@ -23,27 +25,85 @@ pub mod collateral_vault {
 
     pub fn initialize_vault(
         ctx: Context<InitializeVault>,
+        subaccount: u16,
         initial_deposit: u64,
     ) -> Result<()> {
-        instructions::initialize_vault::handler(ctx, initial_deposit)
+        instructions::initialize_vault::handler(ctx, subaccount, initial_deposit)
+    }
+
+    pub fn initialize_vault_sponsored(
+        ctx: Context<InitializeVaultSponsored>,
+        subaccount: u16,
+        initial_deposit: u64,
+    ) -> Result<()> {
+        instructions::initialize_vault_sponsored::handler(ctx, subaccount, initial_deposit)
     }
 
     pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
         instructions::deposit::handler(ctx, amount)
     }
 
+    pub fn deposit_for(
+        ctx: Context<DepositFor>,
+        beneficiary: Pubkey,
+        subaccount: u16,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::deposit_for::handler(ctx, beneficiary, subaccount, amount)
+    }
+
+    pub fn permit_deposit(
+        ctx: Context<PermitDeposit>,
+        amount: u64,
+        relayer_fee: u64,
+        nonce: u64,
+        expiry: i64,
+    ) -> Result<()> {
+        instructions::permit::permit_deposit(ctx, amount, relayer_fee, nonce, expiry)
+    }
+
+    pub fn permit_withdraw(
+        ctx: Context<PermitWithdraw>,
+        amount: u64,
+        relayer_fee: u64,
+        nonce: u64,
+        expiry: i64,
+    ) -> Result<()> {
+        instructions::permit::permit_withdraw(ctx, amount, relayer_fee, nonce, expiry)
+    }
+
     pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
         instructions::withdraw::handler(ctx, amount)
     }
 
-    pub fn lock_collateral(ctx: Context<LockCollateral>, amount: u64) -> Result<()> {
+    pub fn lock_collateral<'info>(
+        ctx: Context<'_, '_, 'info, 'info, LockCollateral<'info>>,
+        amount: u64,
+    ) -> Result<()> {
         instructions::lock_collateral::handler(ctx, amount)
     }
 
-    pub fn unlock_collateral(ctx: Context<UnlockCollateral>, amount: u64) -> Result<()> {
+    pub fn unlock_collateral<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UnlockCollateral<'info>>,
+        amount: u64,
+    ) -> Result<()> {
         instructions::unlock_collateral::handler(ctx, amount)
     }
 
+    pub fn commit_lock<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CommitLock<'info>>,
+        amount: u64,
+        until: i64,
+    ) -> Result<()> {
+        instructions::commitment::commit_lock(ctx, amount, until)
+    }
+
+    pub fn release_commitment<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReleaseCommitment<'info>>,
+    ) -> Result<()> {
+        instructions::commitment::release_commitment(ctx)
+    }
+
     pub fn transfer_collateral(
         ctx: Context<TransferCollateral>,
         amount: u64,
@@ -51,6 +111,211 @@ pub mod collateral_vault {
         instructions::transfer_collateral::handler(ctx, amount)
     }
 
+    pub fn settle_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SettleBatch<'info>>,
+        legs: Vec<SettlementLeg>,
+    ) -> Result<()> {
+        instructions::settle_batch::handler(ctx, legs)
+    }
+
+    pub fn transfer_between_subaccounts(
+        ctx: Context<TransferBetweenSubaccounts>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::transfer_between_subaccounts::handler(ctx, amount)
+    }
+
+    pub fn request_withdrawal(ctx: Context<RequestWithdrawal>, amount: u64) -> Result<()> {
+        instructions::withdrawal_cooldown::request_withdrawal(ctx, amount)
+    }
+
+    pub fn execute_withdrawal(ctx: Context<ExecuteWithdrawal>) -> Result<()> {
+        instructions::withdrawal_cooldown::execute_withdrawal(ctx)
+    }
+
+    pub fn cancel_withdrawal(ctx: Context<CancelWithdrawal>) -> Result<()> {
+        instructions::withdrawal_cooldown::cancel_withdrawal(ctx)
+    }
+
+    pub fn initialize_spending_policy(
+        ctx: Context<InitializeSpendingPolicy>,
+        max_per_transaction: u64,
+        max_per_day: u64,
+        policy_delay: i64,
+    ) -> Result<()> {
+        instructions::spending_policy::initialize_spending_policy(
+            ctx,
+            max_per_transaction,
+            max_per_day,
+            policy_delay,
+        )
+    }
+
+    pub fn update_spending_policy(
+        ctx: Context<UpdateSpendingPolicy>,
+        max_per_transaction: u64,
+        max_per_day: u64,
+        policy_delay: i64,
+    ) -> Result<()> {
+        instructions::spending_policy::update_spending_policy(
+            ctx,
+            max_per_transaction,
+            max_per_day,
+            policy_delay,
+        )
+    }
+
+    pub fn cancel_spending_policy_change(ctx: Context<CancelSpendingPolicyChange>) -> Result<()> {
+        instructions::spending_policy::cancel_spending_policy_change(ctx)
+    }
+
+    pub fn add_withdrawal_destination(
+        ctx: Context<AddWithdrawalDestination>,
+        destination: Pubkey,
+    ) -> Result<()> {
+        instructions::spending_policy::add_withdrawal_destination(ctx, destination)
+    }
+
+    pub fn remove_withdrawal_destination(
+        ctx: Context<RemoveWithdrawalDestination>,
+        destination: Pubkey,
+    ) -> Result<()> {
+        instructions::spending_policy::remove_withdrawal_destination(ctx, destination)
+    }
+
+    pub fn grant_operator(
+        ctx: Context<GrantOperator>,
+        operator: Pubkey,
+        scopes: u8,
+        allowance: Option<u64>,
+    ) -> Result<()> {
+        instructions::manage_operator::grant_operator(ctx, operator, scopes, allowance)
+    }
+
+    pub fn revoke_operator(ctx: Context<RevokeOperator>) -> Result<()> {
+        instructions::manage_operator::revoke_operator(ctx)
+    }
+
+    pub fn register_session_key(
+        ctx: Context<RegisterSessionKey>,
+        session_key: Pubkey,
+        scopes: u8,
+        max_amount: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        instructions::session::register_session_key(ctx, session_key, scopes, max_amount, expires_at)
+    }
+
+    pub fn revoke_session_key(ctx: Context<RevokeSessionKey>) -> Result<()> {
+        instructions::session::revoke_session_key(ctx)
+    }
+
+    pub fn close_vault(ctx: Context<CloseVault>) -> Result<()> {
+        instructions::close_vault::handler(ctx)
+    }
+
+    pub fn propose_owner_transfer(
+        ctx: Context<ProposeOwnerTransfer>,
+        new_owner: Pubkey,
+    ) -> Result<()> {
+        instructions::owner_transfer::propose_owner_transfer(ctx, new_owner)
+    }
+
+    pub fn cancel_owner_transfer(ctx: Context<CancelOwnerTransfer>) -> Result<()> {
+        instructions::owner_transfer::cancel_owner_transfer(ctx)
+    }
+
+    pub fn accept_owner_transfer(ctx: Context<AcceptOwnerTransfer>) -> Result<()> {
+        instructions::owner_transfer::accept_owner_transfer(ctx)
+    }
+
+    pub fn configure_recovery(
+        ctx: Context<ConfigureRecovery>,
+        guardians: Vec<Pubkey>,
+        threshold: u8,
+        recovery_delay: i64,
+    ) -> Result<()> {
+        instructions::recovery::configure_recovery(ctx, guardians, threshold, recovery_delay)
+    }
+
+    pub fn initiate_recovery(ctx: Context<InitiateRecovery>, new_owner: Pubkey) -> Result<()> {
+        instructions::recovery::initiate_recovery(ctx, new_owner)
+    }
+
+    pub fn approve_recovery(ctx: Context<ApproveRecovery>) -> Result<()> {
+        instructions::recovery::approve_recovery(ctx)
+    }
+
+    pub fn veto_recovery(ctx: Context<VetoRecovery>) -> Result<()> {
+        instructions::recovery::veto_recovery(ctx)
+    }
+
+    pub fn execute_recovery(ctx: Context<ExecuteRecovery>) -> Result<()> {
+        instructions::recovery::execute_recovery(ctx)
+    }
+
+    pub fn configure_lockdown(
+        ctx: Context<ConfigureLockdown>,
+        recovery_key: Pubkey,
+        lockdown_delay: i64,
+    ) -> Result<()> {
+        instructions::lockdown::configure_lockdown(ctx, recovery_key, lockdown_delay)
+    }
+
+    pub fn owner_lockdown(ctx: Context<OwnerLockdown>) -> Result<()> {
+        instructions::lockdown::owner_lockdown(ctx)
+    }
+
+    pub fn request_lockdown_release(ctx: Context<RequestLockdownRelease>) -> Result<()> {
+        instructions::lockdown::request_lockdown_release(ctx)
+    }
+
+    pub fn lift_lockdown(ctx: Context<LiftLockdown>) -> Result<()> {
+        instructions::lockdown::lift_lockdown(ctx)
+    }
+
+    pub fn set_cosigner(
+        ctx: Context<SetCosigner>,
+        cosigner: Option<Pubkey>,
+        cosigner_threshold: u64,
+    ) -> Result<()> {
+        instructions::cosigner::set_cosigner(ctx, cosigner, cosigner_threshold)
+    }
+
+    pub fn create_multisig(
+        ctx: Context<CreateMultisig>,
+        create_key: Pubkey,
+        members: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        instructions::multisig::create_multisig(ctx, create_key, members, threshold)
+    }
+
+    pub fn initialize_multisig_vault(
+        ctx: Context<InitializeMultisigVault>,
+        subaccount: u16,
+        initial_deposit: u64,
+    ) -> Result<()> {
+        instructions::multisig::initialize_multisig_vault(ctx, subaccount, initial_deposit)
+    }
+
+    pub fn create_multisig_proposal(
+        ctx: Context<CreateMultisigProposal>,
+        action: MultisigAction,
+    ) -> Result<()> {
+        instructions::multisig::create_multisig_proposal(ctx, action)
+    }
+
+    pub fn approve_multisig_proposal(ctx: Context<ApproveMultisigProposal>) -> Result<()> {
+        instructions::multisig::approve_multisig_proposal(ctx)
+    }
+
+    pub fn execute_multisig_proposal<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteMultisigProposal<'info>>,
+    ) -> Result<()> {
+        instructions::multisig::execute_multisig_proposal(ctx)
+    }
+
     pub fn add_authorized_program(
         ctx: Context<AddAuthorizedProgram>,
         program_id: Pubkey,
@@ -64,4 +329,232 @@ pub mod collateral_vault {
     ) -> Result<()> {
         instructions::manage_authority::remove_authorized_program(ctx, program_id)
     }
+
+    pub fn set_rate_limit(
+        ctx: Context<SetRateLimit>,
+        program_id: Pubkey,
+        capacity: u64,
+        window_slots: u64,
+    ) -> Result<()> {
+        instructions::manage_rate_limit::set_rate_limit(ctx, program_id, capacity, window_slots)
+    }
+
+    pub fn remove_rate_limit(
+        ctx: Context<RemoveRateLimit>,
+        program_id: Pubkey,
+    ) -> Result<()> {
+        instructions::manage_rate_limit::remove_rate_limit(ctx, program_id)
+    }
+
+    pub fn set_withdrawal_cooldown(
+        ctx: Context<SetWithdrawalCooldown>,
+        cooldown_seconds: i64,
+    ) -> Result<()> {
+        instructions::manage_cooldown::set_withdrawal_cooldown(ctx, cooldown_seconds)
+    }
+
+    pub fn set_mint_cooldown(ctx: Context<SetMintCooldown>, cooldown_seconds: i64) -> Result<()> {
+        instructions::manage_cooldown::set_mint_cooldown(ctx, cooldown_seconds)
+    }
+
+    pub fn remove_mint_cooldown(ctx: Context<RemoveMintCooldown>) -> Result<()> {
+        instructions::manage_cooldown::remove_mint_cooldown(ctx)
+    }
+
+    pub fn set_program_cooldown(
+        ctx: Context<SetProgramCooldown>,
+        program_id: Pubkey,
+        cooldown_seconds: i64,
+    ) -> Result<()> {
+        instructions::manage_cooldown::set_program_cooldown(ctx, program_id, cooldown_seconds)
+    }
+
+    pub fn remove_program_cooldown(
+        ctx: Context<RemoveProgramCooldown>,
+        program_id: Pubkey,
+    ) -> Result<()> {
+        instructions::manage_cooldown::remove_program_cooldown(ctx, program_id)
+    }
+
+    pub fn configure_circuit_breaker(
+        ctx: Context<ConfigureCircuitBreaker>,
+        max_outflow_bps: u16,
+        window_seconds: i64,
+        trip_status: ProtocolStatus,
+    ) -> Result<()> {
+        instructions::circuit_breaker::configure_circuit_breaker(
+            ctx,
+            max_outflow_bps,
+            window_seconds,
+            trip_status,
+        )
+    }
+
+    pub fn reset_circuit_breaker(ctx: Context<ResetCircuitBreaker>) -> Result<()> {
+        instructions::circuit_breaker::reset_circuit_breaker(ctx)
+    }
+
+    pub fn set_protocol_status(
+        ctx: Context<SetProtocolStatus>,
+        status: ProtocolStatus,
+    ) -> Result<()> {
+        instructions::circuit_breaker::set_protocol_status(ctx, status)
+    }
+
+    pub fn initialize_share_pool(ctx: Context<InitializeSharePool>) -> Result<()> {
+        instructions::share_pool::initialize_share_pool(ctx)
+    }
+
+    pub fn set_pooled_accounting(ctx: Context<SetPooledAccounting>, pooled: bool) -> Result<()> {
+        instructions::share_pool::set_pooled_accounting(ctx, pooled)
+    }
+
+    pub fn deposit_pooled(ctx: Context<DepositPooled>, amount: u64) -> Result<()> {
+        instructions::share_pool::deposit_pooled(ctx, amount)
+    }
+
+    pub fn withdraw_pooled(ctx: Context<WithdrawPooled>, amount: u64) -> Result<()> {
+        instructions::share_pool::withdraw_pooled(ctx, amount)
+    }
+
+    pub fn add_pool_yield(ctx: Context<AddPoolYield>, amount: u64) -> Result<()> {
+        instructions::share_pool::add_pool_yield(ctx, amount)
+    }
+
+    pub fn configure_pool_strategies(
+        ctx: Context<ConfigurePoolStrategies>,
+        keeper: Pubkey,
+        reserve_bps: u16,
+    ) -> Result<()> {
+        instructions::strategy::configure_pool_strategies(ctx, keeper, reserve_bps)
+    }
+
+    pub fn set_strategy(
+        ctx: Context<SetStrategy>,
+        max_allocation_bps: u16,
+        active: bool,
+    ) -> Result<()> {
+        instructions::strategy::set_strategy(ctx, max_allocation_bps, active)
+    }
+
+    pub fn deploy_to_strategy(ctx: Context<DeployToStrategy>, amount: u64) -> Result<()> {
+        instructions::strategy::deploy_to_strategy(ctx, amount)
+    }
+
+    pub fn recall_from_strategy(ctx: Context<RecallFromStrategy>, amount: u64) -> Result<()> {
+        instructions::strategy::recall_from_strategy(ctx, amount)
+    }
+
+    pub fn write_down_strategy(ctx: Context<WriteDownStrategy>, loss: u64) -> Result<()> {
+        instructions::strategy::write_down_strategy(ctx, loss)
+    }
+
+    pub fn initialize_insurance_fund(ctx: Context<InitializeInsuranceFund>) -> Result<()> {
+        instructions::insurance::initialize_insurance_fund(ctx)
+    }
+
+    pub fn contribute_insurance(ctx: Context<ContributeInsurance>, amount: u64) -> Result<()> {
+        instructions::insurance::contribute_insurance(ctx, amount)
+    }
+
+    pub fn fund_insurance_from_fees(ctx: Context<FundInsuranceFromFees>, amount: u64) -> Result<()> {
+        instructions::insurance::fund_insurance_from_fees(ctx, amount)
+    }
+
+    pub fn record_bad_debt(ctx: Context<RecordBadDebt>, shortfall: u64) -> Result<()> {
+        instructions::insurance::record_bad_debt(ctx, shortfall)
+    }
+
+    pub fn cover_bad_debt(ctx: Context<CoverBadDebt>) -> Result<()> {
+        instructions::insurance::cover_bad_debt(ctx)
+    }
+
+    pub fn socialize_bad_debt(ctx: Context<SocializeBadDebt>) -> Result<()> {
+        instructions::insurance::socialize_bad_debt(ctx)
+    }
+
+    pub fn collect_socialized_loss(ctx: Context<CollectSocializedLoss>) -> Result<()> {
+        instructions::insurance::collect_socialized_loss(ctx)
+    }
+
+    pub fn create_reward_pool(ctx: Context<CreateRewardPool>, reward_duration: i64) -> Result<()> {
+        instructions::rewards::create_reward_pool(ctx, reward_duration)
+    }
+
+    pub fn fund_reward_pool(ctx: Context<FundRewardPool>, amount: u64) -> Result<()> {
+        instructions::rewards::fund_reward_pool(ctx, amount)
+    }
+
+    pub fn open_reward_position(ctx: Context<OpenRewardPosition>) -> Result<()> {
+        instructions::rewards::open_reward_position(ctx)
+    }
+
+    pub fn sync_reward_position(ctx: Context<SyncRewardPosition>) -> Result<()> {
+        instructions::rewards::sync_reward_position(ctx)
+    }
+
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        instructions::rewards::claim_rewards(ctx)
+    }
+
+    pub fn close_reward_position(ctx: Context<CloseRewardPosition>) -> Result<()> {
+        instructions::rewards::close_reward_position(ctx)
+    }
+
+    pub fn balance_at(ctx: Context<BalanceAt>, slot: u64) -> Result<u64> {
+        instructions::checkpoints::balance_at(ctx, slot)
+    }
+
+    pub fn total_value_locked_at(ctx: Context<TotalValueLockedAt>, slot: u64) -> Result<u64> {
+        instructions::checkpoints::total_value_locked_at(ctx, slot)
+    }
+
+    pub fn configure_fees(
+        ctx: Context<ConfigureFees>,
+        deposit_fee_bps: u16,
+        withdraw_fee_bps: u16,
+        transfer_fee_bps: u16,
+        min_fee: u64,
+    ) -> Result<()> {
+        instructions::manage_fees::configure_fees(
+            ctx,
+            deposit_fee_bps,
+            withdraw_fee_bps,
+            transfer_fee_bps,
+            min_fee,
+        )
+    }
+
+    pub fn set_fee_manager(ctx: Context<SetFeeManager>, fee_manager: Pubkey) -> Result<()> {
+        instructions::manage_fees::set_fee_manager(ctx, fee_manager)
+    }
+
+    pub fn withdraw_fees(ctx: Context<WithdrawFees>, amount: u64) -> Result<()> {
+        instructions::manage_fees::withdraw_fees(ctx, amount)
+    }
+
+    pub fn declare_emergency(ctx: Context<DeclareEmergency>) -> Result<()> {
+        instructions::emergency::declare_emergency(ctx)
+    }
+
+    pub fn configure_emergency(
+        ctx: Context<ConfigureEmergency>,
+        emergency_pause_seconds: i64,
+    ) -> Result<()> {
+        instructions::emergency::configure_emergency(ctx, emergency_pause_seconds)
+    }
+
+    pub fn mark_program_broken(ctx: Context<MarkProgramBroken>, program_id: Pubkey) -> Result<()> {
+        instructions::emergency::mark_program_broken(ctx, program_id)
+    }
+
+    pub fn clear_program_broken(ctx: Context<ClearProgramBroken>) -> Result<()> {
+        instructions::emergency::clear_program_broken(ctx)
+    }
+
+    pub fn emergency_withdraw<'info>(
+        ctx: Context<'_, '_, 'info, 'info, EmergencyWithdraw<'info>>,
+    ) -> Result<()> {
+        instructions::emergency::emergency_withdraw(ctx)
+    }
 }
\ No newline at end of file
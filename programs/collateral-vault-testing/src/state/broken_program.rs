@@ -0,0 +1,19 @@
+use anchor_lang::prelude::*;
+
+/// Admin report that an authorized program can no longer release the
+/// collateral it locked. Once the report is old enough, owners may use
+/// the emergency exit even while the protocol itself is running.
+#[account]
+pub struct BrokenProgram {
+    pub program_id: Pubkey,
+    pub broken_since: i64,
+    pub bump: u8,
+}
+
+impl BrokenProgram {
+    pub const LEN: usize = 8 + 32 + 8 + 1;
+
+    pub fn opens_emergency(&self, emergency_pause_seconds: i64, now: i64) -> bool {
+        now.saturating_sub(self.broken_since) >= emergency_pause_seconds
+    }
+}
@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+use crate::constants::MAX_BALANCE_CHECKPOINTS;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct BalanceCheckpoint {
+    pub slot: u64,
+    pub balance: u64,
+}
+
+impl BalanceCheckpoint {
+    pub const LEN: usize = 8 + 8;
+}
+
+/// Bounded history of a balance keyed by slot. Once full, each new
+/// checkpoint overwrites the oldest one.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct CheckpointRing {
+    pub checkpoints: Vec<BalanceCheckpoint>,
+    /// Index the next checkpoint is written to once the ring is full.
+    pub next: u8,
+}
+
+impl CheckpointRing {
+    pub const LEN: usize = 4 + (BalanceCheckpoint::LEN * MAX_BALANCE_CHECKPOINTS) + 1;
+
+    fn latest_index(&self) -> Option<usize> {
+        if self.checkpoints.is_empty() {
+            return None;
+        }
+        if self.checkpoints.len() < MAX_BALANCE_CHECKPOINTS {
+            return Some(self.checkpoints.len() - 1);
+        }
+        Some((self.next as usize + MAX_BALANCE_CHECKPOINTS - 1) % MAX_BALANCE_CHECKPOINTS)
+    }
+
+    /// Records `balance` as of `slot`. Several changes in one slot collapse
+    /// into a single checkpoint, and unchanged balances are not re-recorded;
+    /// an empty ring already reads as a zero balance.
+    pub fn record(&mut self, slot: u64, balance: u64) {
+        match self.latest_index() {
+            Some(index) => {
+                let latest = &mut self.checkpoints[index];
+                if latest.balance == balance {
+                    return;
+                }
+                if latest.slot == slot {
+                    latest.balance = balance;
+                    return;
+                }
+            }
+            None if balance == 0 => return,
+            None => {}
+        }
+
+        let checkpoint = BalanceCheckpoint { slot, balance };
+        if self.checkpoints.len() < MAX_BALANCE_CHECKPOINTS {
+            self.checkpoints.push(checkpoint);
+        } else {
+            self.checkpoints[self.next as usize] = checkpoint;
+            self.next = ((self.next as usize + 1) % MAX_BALANCE_CHECKPOINTS) as u8;
+        }
+    }
+
+    /// Balance held at the end of `slot`. Slots before the first checkpoint
+    /// read as zero unless older history has already been overwritten.
+    pub fn balance_at(&self, slot: u64) -> Result<u64> {
+        let len = self.checkpoints.len();
+        let Some(latest) = self.latest_index() else {
+            return Ok(0);
+        };
+        for offset in 0..len {
+            let checkpoint = &self.checkpoints[(latest + len - offset) % len];
+            if checkpoint.slot <= slot {
+                return Ok(checkpoint.balance);
+            }
+        }
+        require!(
+            len < MAX_BALANCE_CHECKPOINTS,
+            crate::errors::ErrorCode::CheckpointUnavailable
+        );
+        Ok(0)
+    }
+}
@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+/// Per-mint withdrawal delay overriding the protocol-wide default.
+#[account]
+pub struct CooldownConfig {
+    pub mint: Pubkey,
+    pub cooldown_seconds: i64,
+    pub bump: u8,
+}
+
+impl CooldownConfig {
+    pub const LEN: usize = 8 + 32 + 8 + 1;
+}
+
+/// Withdrawal delay an authorized program needs to react before collateral
+/// leaves. It applies to every vault, whether or not the program holds a
+/// lock there.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct ProgramCooldown {
+    pub program_id: Pubkey,
+    pub cooldown_seconds: i64,
+}
+
+impl ProgramCooldown {
+    pub const LEN: usize = 32 + 8;
+}
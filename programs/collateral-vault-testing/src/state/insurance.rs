@@ -0,0 +1,121 @@
+use anchor_lang::prelude::*;
+
+/// Per-mint backstop that pays creditors when a liquidation leaves a
+/// shortfall. Funded by direct contributions and protocol fees, and by the
+/// socialized loss collected from unpooled vaults.
+#[account]
+pub struct InsuranceFund {
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub balance: u64,
+    pub total_contributed: u64,
+    pub total_covered: u64,
+    pub total_loss_collected: u64,
+    pub bad_debt_count: u64,
+    pub bump: u8,
+}
+
+impl InsuranceFund {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 1;
+
+    pub fn contribute(&mut self, amount: u64) -> Result<()> {
+        self.balance = self.balance
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        self.total_contributed = self.total_contributed
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        Ok(())
+    }
+
+    /// Takes in loss a vault wrote off, to be paid out to the creditors
+    /// whose bad debt was assessed on vault balances.
+    pub fn collect_loss(&mut self, amount: u64) -> Result<()> {
+        self.balance = self.balance
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        self.total_loss_collected = self.total_loss_collected
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        Ok(())
+    }
+
+    /// Pays as much of `amount` as the fund holds and returns the payout.
+    pub fn cover(&mut self, amount: u64) -> Result<u64> {
+        let covered = amount.min(self.balance);
+        self.balance -= covered;
+        self.total_covered = self.total_covered
+            .checked_add(covered)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        Ok(covered)
+    }
+
+    pub fn next_bad_debt_index(&mut self) -> Result<u64> {
+        let index = self.bad_debt_count;
+        self.bad_debt_count = self.bad_debt_count
+            .checked_add(1)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        Ok(index)
+    }
+}
+
+/// A shortfall reported by an authorized program. It is paid down first by
+/// the insurance fund. Once the fund runs dry the rest is split across the
+/// mint's depositors: the share pool pays its part straight away, while the
+/// part assessed on unpooled vaults reaches the creditor through the fund
+/// as those vaults' write-offs are collected.
+#[account]
+pub struct BadDebt {
+    pub insurance_fund: Pubkey,
+    pub index: u64,
+    pub debtor_vault: Pubkey,
+    pub creditor_vault: Pubkey,
+    pub caller_program: Pubkey,
+    pub shortfall: u64,
+    pub covered: u64,
+    pub socialized: u64,
+    /// Assessed on unpooled vaults and not yet paid out by the fund.
+    pub assessed: u64,
+    pub recorded_at: i64,
+    pub bump: u8,
+}
+
+impl BadDebt {
+    pub const LEN: usize = 8 + 32 + 8 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 1;
+
+    /// What the creditor has not received yet.
+    pub fn outstanding(&self) -> u64 {
+        self.shortfall
+            .saturating_sub(self.covered)
+            .saturating_sub(self.socialized)
+    }
+
+    /// What nobody has been charged for yet.
+    pub fn remaining(&self) -> u64 {
+        self.outstanding().saturating_sub(self.assessed)
+    }
+
+    /// Fund payouts settle the assessed part first; it is what the
+    /// collected write-offs in the fund are for.
+    pub fn record_covered(&mut self, amount: u64) -> Result<()> {
+        self.covered = self.covered
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        self.assessed = self.assessed.saturating_sub(amount);
+        Ok(())
+    }
+
+    pub fn record_assessed(&mut self, amount: u64) -> Result<()> {
+        self.assessed = self.assessed
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        Ok(())
+    }
+
+    pub fn record_socialized(&mut self, amount: u64) -> Result<()> {
+        self.socialized = self.socialized
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        Ok(())
+    }
+}
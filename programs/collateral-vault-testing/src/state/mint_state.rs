@@ -0,0 +1,139 @@
+use anchor_lang::prelude::*;
+use crate::constants::{BPS_DENOMINATOR, LOSS_PRECISION};
+use crate::state::{CheckpointRing, ProtocolState};
+
+/// Per-mint value accounting for the outflow circuit breaker. Amounts of
+/// different mints are never summed; each mint is measured against its own
+/// TVL while the breaker configuration and status stay protocol-wide.
+#[account]
+pub struct MintState {
+    pub mint: Pubkey,
+    pub total_value_locked: u64,
+    pub window_start: i64,
+    pub window_start_tvl: u64,
+    pub window_outflow: u64,
+    pub window_inflow: u64,
+    /// TVL at the end of each slot it changed in, coalesced the same way
+    /// as a vault's balance checkpoints.
+    pub tvl_checkpoints: CheckpointRing,
+    /// Bad debt charged to unpooled vaults per token of their balance,
+    /// scaled by `LOSS_PRECISION`. Each vault writes off the growth since
+    /// it last caught up the next time its balance is touched.
+    pub loss_per_token: u128,
+    pub bump: u8,
+}
+
+impl MintState {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 8 + 8 + CheckpointRing::LEN + 16 + 1;
+
+    /// Sets up the account on first use; later calls leave it untouched.
+    pub fn initialize(&mut self, mint: Pubkey, now: i64, bump: u8) {
+        if self.mint != Pubkey::default() {
+            return;
+        }
+        self.mint = mint;
+        self.total_value_locked = 0;
+        self.window_start = now;
+        self.window_start_tvl = 0;
+        self.window_outflow = 0;
+        self.window_inflow = 0;
+        self.tvl_checkpoints = CheckpointRing::default();
+        self.loss_per_token = 0;
+        self.bump = bump;
+    }
+
+    /// Starts a fresh accounting window once the current one has elapsed.
+    pub fn roll_window(&mut self, window_seconds: i64, now: i64) {
+        if now.saturating_sub(self.window_start) >= window_seconds {
+            self.reset_window(now);
+        }
+    }
+
+    pub fn reset_window(&mut self, now: i64) {
+        self.window_start = now;
+        self.window_start_tvl = self.total_value_locked;
+        self.window_outflow = 0;
+        self.window_inflow = 0;
+    }
+
+    pub fn record_inflow(
+        &mut self,
+        protocol_state: &ProtocolState,
+        amount: u64,
+        clock: &Clock,
+    ) -> Result<()> {
+        self.roll_window(protocol_state.window_seconds, clock.unix_timestamp);
+        self.total_value_locked = self.total_value_locked
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        self.checkpoint_tvl(clock);
+        self.window_inflow = self.window_inflow
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        Ok(())
+    }
+
+    /// Records tokens moving out of a vault. `leaves_protocol` is false for
+    /// vault-to-vault transfers, which count toward the window but keep TVL.
+    /// Returns true if the window's net outflow is now above the threshold.
+    pub fn record_outflow(
+        &mut self,
+        protocol_state: &ProtocolState,
+        amount: u64,
+        leaves_protocol: bool,
+        clock: &Clock,
+    ) -> Result<bool> {
+        self.roll_window(protocol_state.window_seconds, clock.unix_timestamp);
+        if leaves_protocol {
+            self.total_value_locked = self.total_value_locked
+                .checked_sub(amount)
+                .ok_or(error!(crate::errors::ErrorCode::ArithmeticUnderflow))?;
+            self.checkpoint_tvl(clock);
+        }
+        self.window_outflow = self.window_outflow
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+
+        Ok(self.net_window_outflow() > self.outflow_threshold(protocol_state.max_outflow_bps))
+    }
+
+    /// Removes value that was lost rather than withdrawn. It leaves TVL
+    /// without counting toward the window's outflow.
+    pub fn record_loss(&mut self, amount: u64, clock: &Clock) -> Result<()> {
+        self.total_value_locked = self.total_value_locked
+            .checked_sub(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticUnderflow))?;
+        self.checkpoint_tvl(clock);
+        Ok(())
+    }
+
+    /// Charges `amount` of bad debt to the unpooled vaults of the mint, pro
+    /// rata to the `unpooled_balance` they hold between them. The index
+    /// rounds up so the vaults' shares add up to at least `amount`.
+    pub fn socialize_loss(&mut self, amount: u64, unpooled_balance: u64, clock: &Clock) -> Result<()> {
+        require!(unpooled_balance > 0, crate::errors::ErrorCode::InvalidAmount);
+        let increment = (amount as u128)
+            .checked_mul(LOSS_PRECISION)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?
+            .div_ceil(unpooled_balance as u128);
+        self.loss_per_token = self.loss_per_token
+            .checked_add(increment)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        self.record_loss(amount, clock)
+    }
+
+    fn checkpoint_tvl(&mut self, clock: &Clock) {
+        self.tvl_checkpoints.record(clock.slot, self.total_value_locked);
+    }
+
+    pub fn net_window_outflow(&self) -> u64 {
+        self.window_outflow.saturating_sub(self.window_inflow)
+    }
+
+    /// Maximum net outflow tolerated in the current window, measured against
+    /// the value of this mint that was in the protocol at any point during it.
+    pub fn outflow_threshold(&self, max_outflow_bps: u16) -> u64 {
+        let base = self.window_start_tvl as u128 + self.window_inflow as u128;
+        (base * max_outflow_bps as u128 / BPS_DENOMINATOR as u128) as u64
+    }
+}
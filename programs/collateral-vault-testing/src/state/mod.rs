@@ -1,5 +1,41 @@
 pub mod authority;
+pub mod broken_program;
+pub mod checkpoint;
+pub mod cooldown;
+pub mod insurance;
+pub mod mint_state;
+pub mod multisig;
+pub mod operator;
+pub mod owner_index;
+pub mod permit;
+pub mod protocol;
+pub mod rate_limit;
+pub mod recovery;
+pub mod rewards;
+pub mod session;
+pub mod settlement;
+pub mod share_pool;
+pub mod spending_policy;
+pub mod strategy;
 pub mod vault;
 
 pub use authority::*;
+pub use broken_program::*;
+pub use checkpoint::*;
+pub use cooldown::*;
+pub use insurance::*;
+pub use mint_state::*;
+pub use multisig::*;
+pub use operator::*;
+pub use owner_index::*;
+pub use permit::*;
+pub use protocol::*;
+pub use rate_limit::*;
+pub use recovery::*;
+pub use rewards::*;
+pub use session::*;
+pub use settlement::*;
+pub use share_pool::*;
+pub use spending_policy::*;
+pub use strategy::*;
 pub use vault::*;
\ No newline at end of file
@@ -0,0 +1,112 @@
+use anchor_lang::prelude::*;
+use crate::constants::{MAX_MULTISIG_MEMBERS, MAX_PROPOSAL_ACCOUNTS, MAX_PROPOSAL_DATA_LEN};
+
+/// Account passed to a vault instruction run by a proposal.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct ProposalAccount {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl ProposalAccount {
+    pub const LEN: usize = 32 + 1 + 1;
+}
+
+/// Vault actions a multisig carries out once enough members approve.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum MultisigAction {
+    /// Pays out directly, or through a withdrawal request while the mint
+    /// has a cooldown.
+    Withdraw { destination: Pubkey, amount: u64 },
+    ChangeMembers { members: Vec<Pubkey>, threshold: u8 },
+    /// Calls a vault program instruction with the multisig signing as the
+    /// vault owner, for owner actions without a dedicated variant.
+    VaultInstruction { accounts: Vec<ProposalAccount>, data: Vec<u8> },
+}
+
+impl MultisigAction {
+    pub const MAX_LEN: usize = 1
+        + (4 + ProposalAccount::LEN * MAX_PROPOSAL_ACCOUNTS)
+        + (4 + MAX_PROPOSAL_DATA_LEN);
+}
+
+/// On-chain member set that can own vaults. The PDA is the vault owner, so
+/// owner actions happen only through approved proposals.
+#[account]
+pub struct Multisig {
+    pub create_key: Pubkey,
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub proposal_count: u64,
+    pub bump: u8,
+}
+
+impl Multisig {
+    pub const LEN: usize = 8 + 32 + (4 + 32 * MAX_MULTISIG_MEMBERS) + 1 + 8 + 1;
+
+    pub fn set_members(&mut self, members: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        let unique = members
+            .iter()
+            .enumerate()
+            .all(|(i, m)| *m != Pubkey::default() && !members[..i].contains(m));
+        require!(
+            !members.is_empty()
+                && members.len() <= MAX_MULTISIG_MEMBERS
+                && unique
+                && threshold > 0
+                && threshold as usize <= members.len(),
+            crate::errors::ErrorCode::InvalidMultisigConfig
+        );
+        self.members = members;
+        self.threshold = threshold;
+        Ok(())
+    }
+
+    pub fn is_member(&self, key: &Pubkey) -> bool {
+        self.members.contains(key)
+    }
+
+    /// Counts approvals from keys that are still members, so removed
+    /// members stop counting toward open proposals.
+    pub fn count_approvals(&self, approvals: &[Pubkey]) -> usize {
+        approvals.iter().filter(|a| self.is_member(a)).count()
+    }
+}
+
+#[account]
+pub struct MultisigProposal {
+    pub multisig: Pubkey,
+    pub vault: Pubkey,
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub action: MultisigAction,
+    pub approvals: Vec<Pubkey>,
+    pub executed: bool,
+    pub created_at: i64,
+    /// When the withdrawal this proposal requested can be paid out; zero
+    /// until a `Withdraw` under a cooldown has been requested.
+    pub withdrawal_ready_at: i64,
+    pub bump: u8,
+}
+
+impl MultisigProposal {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 32 + MultisigAction::MAX_LEN
+        + (4 + 32 * MAX_MULTISIG_MEMBERS) + 1 + 8 + 8 + 1;
+
+    pub fn approve(&mut self, member: Pubkey, members: &[Pubkey]) -> Result<()> {
+        require!(
+            !self.executed,
+            crate::errors::ErrorCode::ProposalAlreadyExecuted
+        );
+        require!(
+            !self.approvals.contains(&member),
+            crate::errors::ErrorCode::MemberAlreadyApproved
+        );
+        // Dropping approvals from removed members keeps the list within the
+        // member cap.
+        self.approvals.retain(|a| members.contains(a));
+        self.approvals.push(member);
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use crate::constants::{
+    OPERATOR_SCOPE_ALL, OPERATOR_SCOPE_WITHDRAW_TO_ALLOWLIST, OPERATOR_SCOPE_WITHDRAW_TO_OWNER,
+};
+
+/// Owner-granted delegation letting another key act on a vault within
+/// `scopes`, optionally capped by a remaining withdrawal `allowance`.
+#[account]
+pub struct VaultOperator {
+    pub vault: Pubkey,
+    pub operator: Pubkey,
+    pub scopes: u8,
+    pub allowance: Option<u64>,
+    pub granted_at: i64,
+    /// Vault delegation epoch the grant was made in.
+    pub delegation_epoch: u64,
+    pub bump: u8,
+}
+
+impl VaultOperator {
+    pub const LEN: usize = 8 + 32 + 32 + 1 + (1 + 8) + 8 + 8 + 1;
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn grant(
+        &mut self,
+        vault: Pubkey,
+        operator: Pubkey,
+        scopes: u8,
+        allowance: Option<u64>,
+        granted_at: i64,
+        delegation_epoch: u64,
+        bump: u8,
+    ) -> Result<()> {
+        require!(
+            scopes != 0 && scopes & !OPERATOR_SCOPE_ALL == 0,
+            crate::errors::ErrorCode::InvalidOperatorScopes
+        );
+        self.vault = vault;
+        self.operator = operator;
+        self.scopes = scopes;
+        self.allowance = allowance;
+        self.granted_at = granted_at;
+        self.delegation_epoch = delegation_epoch;
+        self.bump = bump;
+        Ok(())
+    }
+
+    pub fn has_scope(&self, scope: u8) -> bool {
+        self.scopes & scope != 0
+    }
+
+    /// Checks the operator may send `amount` to the given destination and
+    /// draws it from the allowance. Allowlist rights include the owner's
+    /// own accounts.
+    pub fn authorize_withdrawal(&mut self, destination_is_owner: bool, amount: u64) -> Result<()> {
+        let required = if destination_is_owner {
+            OPERATOR_SCOPE_WITHDRAW_TO_OWNER | OPERATOR_SCOPE_WITHDRAW_TO_ALLOWLIST
+        } else {
+            OPERATOR_SCOPE_WITHDRAW_TO_ALLOWLIST
+        };
+        require!(
+            self.has_scope(required),
+            crate::errors::ErrorCode::UnauthorizedOperator
+        );
+
+        if let Some(allowance) = self.allowance {
+            self.allowance = Some(
+                allowance
+                    .checked_sub(amount)
+                    .ok_or(error!(crate::errors::ErrorCode::OperatorAllowanceExceeded))?,
+            );
+        }
+        Ok(())
+    }
+}
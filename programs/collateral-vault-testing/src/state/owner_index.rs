@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use crate::constants::MAX_SUBACCOUNTS;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct SubaccountEntry {
+    pub index: u16,
+    pub vault: Pubkey,
+}
+
+impl SubaccountEntry {
+    pub const LEN: usize = 2 + 32;
+}
+
+/// Lists the open subaccount vaults of one owner so clients can discover
+/// them without scanning program accounts.
+#[account]
+pub struct OwnerIndex {
+    pub owner: Pubkey,
+    pub subaccounts: Vec<SubaccountEntry>,
+    pub bump: u8,
+}
+
+impl OwnerIndex {
+    pub const LEN: usize = 8 + 32 + 4 + (SubaccountEntry::LEN * MAX_SUBACCOUNTS) + 1;
+
+    /// Records a newly opened subaccount, initializing the index on first use.
+    pub fn add_subaccount(&mut self, owner: Pubkey, index: u16, vault: Pubkey, bump: u8) -> Result<()> {
+        if self.owner == Pubkey::default() {
+            self.owner = owner;
+            self.subaccounts = Vec::new();
+            self.bump = bump;
+        }
+        require!(
+            self.subaccounts.len() < MAX_SUBACCOUNTS,
+            crate::errors::ErrorCode::MaxSubaccountsReached
+        );
+        self.subaccounts.push(SubaccountEntry { index, vault });
+        Ok(())
+    }
+
+    pub fn remove_subaccount(&mut self, vault: Pubkey) -> Result<()> {
+        let position = self.subaccounts
+            .iter()
+            .position(|s| s.vault == vault)
+            .ok_or(crate::errors::ErrorCode::SubaccountNotFound)?;
+        self.subaccounts.remove(position);
+        Ok(())
+    }
+}
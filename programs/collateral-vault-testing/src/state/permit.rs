@@ -0,0 +1,19 @@
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PermitAction {
+    Deposit,
+    Withdraw,
+}
+
+/// Payload the vault owner signs off-chain to authorize a relayed action.
+/// Its borsh encoding is the exact message checked by the Ed25519 program.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PermitMessage {
+    pub vault: Pubkey,
+    pub action: PermitAction,
+    pub amount: u64,
+    pub relayer_fee: u64,
+    pub nonce: u64,
+    pub expiry: i64,
+}
@@ -0,0 +1,200 @@
+use anchor_lang::prelude::*;
+use crate::constants::{BPS_DENOMINATOR, MAX_AUTHORIZED_PROGRAMS};
+use crate::state::ProgramCooldown;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ProtocolStatus {
+    Active,
+    WithdrawOnly,
+    Paused,
+}
+
+/// Protocol-wide status and configuration. Value accounting for the
+/// outflow circuit breaker is kept per mint in `MintState`.
+#[account]
+pub struct ProtocolState {
+    pub status: ProtocolStatus,
+    pub status_updated_at: i64,
+    pub max_outflow_bps: u16,
+    pub window_seconds: i64,
+    pub trip_status: ProtocolStatus,
+    /// Mint whose outflow last tripped the breaker, cleared on reset.
+    pub tripped_mint: Pubkey,
+    pub emergency_pause_seconds: i64,
+    pub emergency_declared_at: i64,
+    pub withdrawal_cooldown_seconds: i64,
+    pub program_cooldowns: Vec<ProgramCooldown>,
+    pub fee_manager: Pubkey,
+    pub deposit_fee_bps: u16,
+    pub withdraw_fee_bps: u16,
+    pub transfer_fee_bps: u16,
+    pub min_fee: u64,
+    pub total_fees_collected: u64,
+    pub bump: u8,
+}
+
+impl ProtocolState {
+    pub const LEN: usize = 8 + 1 + 8 + 2 + 8 + 1 + 32 + 8 + 8 + 8
+        + 4 + (ProgramCooldown::LEN * MAX_AUTHORIZED_PROGRAMS) + 32 + 2 + 2 + 2 + 8 + 8 + 1;
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize(
+        &mut self,
+        max_outflow_bps: u16,
+        window_seconds: i64,
+        trip_status: ProtocolStatus,
+        emergency_pause_seconds: i64,
+        fee_manager: Pubkey,
+        now: i64,
+        bump: u8,
+    ) {
+        self.status = ProtocolStatus::Active;
+        self.status_updated_at = now;
+        self.max_outflow_bps = max_outflow_bps;
+        self.window_seconds = window_seconds;
+        self.trip_status = trip_status;
+        self.tripped_mint = Pubkey::default();
+        self.emergency_pause_seconds = emergency_pause_seconds;
+        self.emergency_declared_at = 0;
+        self.withdrawal_cooldown_seconds = 0;
+        self.program_cooldowns = Vec::new();
+        self.fee_manager = fee_manager;
+        self.deposit_fee_bps = 0;
+        self.withdraw_fee_bps = 0;
+        self.transfer_fee_bps = 0;
+        self.min_fee = 0;
+        self.total_fees_collected = 0;
+        self.bump = bump;
+    }
+
+    pub fn configure(
+        &mut self,
+        max_outflow_bps: u16,
+        window_seconds: i64,
+        trip_status: ProtocolStatus,
+    ) {
+        self.max_outflow_bps = max_outflow_bps;
+        self.window_seconds = window_seconds;
+        self.trip_status = trip_status;
+    }
+
+    pub fn set_program_cooldown(&mut self, program_id: Pubkey, cooldown_seconds: i64) -> Result<()> {
+        if let Some(entry) = self.program_cooldowns.iter_mut().find(|c| c.program_id == program_id) {
+            entry.cooldown_seconds = cooldown_seconds;
+            return Ok(());
+        }
+        require!(
+            self.program_cooldowns.len() < MAX_AUTHORIZED_PROGRAMS,
+            crate::errors::ErrorCode::MaxAuthorizedProgramsReached
+        );
+        self.program_cooldowns.push(ProgramCooldown { program_id, cooldown_seconds });
+        Ok(())
+    }
+
+    /// Drops the program's cooldown, returning whether it had one.
+    pub fn remove_program_cooldown(&mut self, program_id: &Pubkey) -> bool {
+        let before = self.program_cooldowns.len();
+        self.program_cooldowns.retain(|c| c.program_id != *program_id);
+        self.program_cooldowns.len() != before
+    }
+
+    /// Longest delay any authorized program asked for.
+    pub fn max_program_cooldown(&self) -> i64 {
+        self.program_cooldowns
+            .iter()
+            .map(|c| c.cooldown_seconds)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn configure_fees(
+        &mut self,
+        deposit_fee_bps: u16,
+        withdraw_fee_bps: u16,
+        transfer_fee_bps: u16,
+        min_fee: u64,
+    ) {
+        self.deposit_fee_bps = deposit_fee_bps;
+        self.withdraw_fee_bps = withdraw_fee_bps;
+        self.transfer_fee_bps = transfer_fee_bps;
+        self.min_fee = min_fee;
+    }
+
+    /// Fee owed on `amount` at `fee_bps`, rounded up and raised to the
+    /// minimum fee but never above `amount`. A zero rate charges nothing.
+    pub fn fee_for(&self, fee_bps: u16, amount: u64) -> Result<u64> {
+        if fee_bps == 0 {
+            return Ok(0);
+        }
+        let fee = (amount as u128)
+            .checked_mul(fee_bps as u128)
+            .and_then(|v| v.checked_add(BPS_DENOMINATOR as u128 - 1))
+            .map(|v| v / BPS_DENOMINATOR as u128)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))? as u64;
+        Ok(fee.max(self.min_fee).min(amount))
+    }
+
+    pub fn record_fee(&mut self, fee: u64) -> Result<()> {
+        self.total_fees_collected = self.total_fees_collected
+            .checked_add(fee)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        Ok(())
+    }
+
+    pub fn require_active(&self) -> Result<()> {
+        match self.status {
+            ProtocolStatus::Active => Ok(()),
+            ProtocolStatus::WithdrawOnly => err!(crate::errors::ErrorCode::ProtocolWithdrawOnly),
+            ProtocolStatus::Paused => err!(crate::errors::ErrorCode::ProtocolPaused),
+        }
+    }
+
+    pub fn require_withdrawals_enabled(&self) -> Result<()> {
+        require!(
+            self.status != ProtocolStatus::Paused,
+            crate::errors::ErrorCode::ProtocolPaused
+        );
+        Ok(())
+    }
+
+    pub fn set_status(&mut self, status: ProtocolStatus, now: i64) {
+        if status != self.status {
+            self.status = status;
+            self.status_updated_at = now;
+        }
+        if status == ProtocolStatus::Active {
+            self.emergency_declared_at = 0;
+            self.tripped_mint = Pubkey::default();
+        }
+    }
+
+    /// Declaring an emergency also pauses the protocol; it ends when the
+    /// admin sets the protocol active again.
+    pub fn declare_emergency(&mut self, now: i64) {
+        self.set_status(ProtocolStatus::Paused, now);
+        self.emergency_declared_at = now;
+    }
+
+    /// Emergency mode is either declared by the admin or entered
+    /// automatically once the protocol has been paused for too long.
+    pub fn is_emergency(&self, now: i64) -> bool {
+        if self.emergency_declared_at != 0 {
+            return true;
+        }
+        self.status == ProtocolStatus::Paused
+            && now.saturating_sub(self.status_updated_at) >= self.emergency_pause_seconds
+    }
+
+    /// Moves an active protocol into the configured trip status after an
+    /// outflow of `mint` broke its window threshold. Returns true if the
+    /// status changed.
+    pub fn trip(&mut self, mint: Pubkey, now: i64) -> bool {
+        if self.status != ProtocolStatus::Active {
+            return false;
+        }
+        self.status = self.trip_status;
+        self.status_updated_at = now;
+        self.tripped_mint = mint;
+        true
+    }
+}
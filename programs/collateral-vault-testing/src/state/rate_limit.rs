@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+
+/// Token bucket bounding how much a single authorized program may move out
+/// of vaults (via transfers and unlocks) within a rolling window of slots.
+#[account]
+pub struct ProgramRateLimit {
+    pub program_id: Pubkey,
+    pub capacity: u64,
+    pub window_slots: u64,
+    pub tokens: u64,
+    pub last_refill_slot: u64,
+    pub bump: u8,
+}
+
+impl ProgramRateLimit {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 8 + 1;
+
+    pub fn configure(
+        &mut self,
+        program_id: Pubkey,
+        capacity: u64,
+        window_slots: u64,
+        slot: u64,
+        bump: u8,
+    ) {
+        self.program_id = program_id;
+        self.capacity = capacity;
+        self.window_slots = window_slots;
+        self.tokens = capacity;
+        self.last_refill_slot = slot;
+        self.bump = bump;
+    }
+
+    /// Credits the bucket for the slots elapsed since the last refill,
+    /// accruing `capacity / window_slots` tokens per slot up to `capacity`.
+    pub fn refill(&mut self, slot: u64) -> Result<()> {
+        let elapsed = slot.saturating_sub(self.last_refill_slot);
+        let accrued = (self.capacity as u128)
+            .checked_mul(elapsed as u128)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?
+            / self.window_slots as u128;
+        let tokens = (self.tokens as u128)
+            .saturating_add(accrued)
+            .min(self.capacity as u128);
+        self.tokens = tokens as u64;
+        self.last_refill_slot = slot;
+        Ok(())
+    }
+
+    pub fn consume(&mut self, amount: u64) -> Result<()> {
+        self.tokens = self.tokens
+            .checked_sub(amount)
+            .ok_or(error!(crate::errors::ErrorCode::RateLimitExceeded))?;
+        Ok(())
+    }
+}
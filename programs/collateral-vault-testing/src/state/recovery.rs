@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+use crate::constants::MAX_GUARDIANS;
+
+/// Owner-chosen guardians that can hand a vault to a new key when the owner
+/// loses theirs. `threshold` guardians must back the same new owner, after
+/// which the recovery can run once `recovery_delay` seconds have passed
+/// unless the current owner vetoes it.
+#[account]
+pub struct RecoveryConfig {
+    pub vault: Pubkey,
+    pub guardians: Vec<Pubkey>,
+    pub threshold: u8,
+    pub recovery_delay: i64,
+    pub proposed_owner: Option<Pubkey>,
+    pub approvals: Vec<Pubkey>,
+    pub recovery_ready_at: i64,
+    /// Vault delegation epoch the guardians were chosen in.
+    pub delegation_epoch: u64,
+    pub bump: u8,
+}
+
+impl RecoveryConfig {
+    pub const LEN: usize = 8 + 32 + (4 + 32 * MAX_GUARDIANS) + 1 + 8 + (1 + 32)
+        + (4 + 32 * MAX_GUARDIANS) + 8 + 8 + 1;
+
+    pub fn configure(
+        &mut self,
+        vault: Pubkey,
+        guardians: Vec<Pubkey>,
+        threshold: u8,
+        recovery_delay: i64,
+        delegation_epoch: u64,
+        bump: u8,
+    ) -> Result<()> {
+        let unique = guardians
+            .iter()
+            .enumerate()
+            .all(|(i, g)| *g != Pubkey::default() && !guardians[..i].contains(g));
+        require!(
+            !guardians.is_empty()
+                && guardians.len() <= MAX_GUARDIANS
+                && unique
+                && threshold > 0
+                && threshold as usize <= guardians.len()
+                && recovery_delay > 0,
+            crate::errors::ErrorCode::InvalidRecoveryConfig
+        );
+        self.vault = vault;
+        self.guardians = guardians;
+        self.threshold = threshold;
+        self.recovery_delay = recovery_delay;
+        self.delegation_epoch = delegation_epoch;
+        self.bump = bump;
+        self.clear_pending();
+        Ok(())
+    }
+
+    pub fn has_pending_recovery(&self) -> bool {
+        self.proposed_owner.is_some()
+    }
+
+    pub fn is_guardian(&self, key: &Pubkey) -> bool {
+        self.guardians.contains(key)
+    }
+
+    pub fn clear_pending(&mut self) {
+        self.proposed_owner = None;
+        self.approvals = Vec::new();
+        self.recovery_ready_at = 0;
+    }
+
+    pub fn initiate(&mut self, guardian: Pubkey, proposed_owner: Pubkey, now: i64) -> Result<()> {
+        require!(
+            !self.has_pending_recovery(),
+            crate::errors::ErrorCode::RecoveryAlreadyPending
+        );
+        self.proposed_owner = Some(proposed_owner);
+        self.approve(guardian, now)
+    }
+
+    /// Counts a guardian's approval. The delay starts once the threshold is
+    /// first reached.
+    pub fn approve(&mut self, guardian: Pubkey, now: i64) -> Result<()> {
+        require!(
+            self.has_pending_recovery(),
+            crate::errors::ErrorCode::NoPendingRecovery
+        );
+        require!(
+            !self.approvals.contains(&guardian),
+            crate::errors::ErrorCode::GuardianAlreadyApproved
+        );
+        self.approvals.push(guardian);
+
+        if self.recovery_ready_at == 0 && self.approvals.len() >= self.threshold as usize {
+            self.recovery_ready_at = now
+                .checked_add(self.recovery_delay)
+                .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        }
+        Ok(())
+    }
+
+    pub fn is_ready(&self, now: i64) -> bool {
+        self.recovery_ready_at != 0 && now >= self.recovery_ready_at
+    }
+}
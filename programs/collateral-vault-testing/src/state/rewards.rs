@@ -0,0 +1,183 @@
+use anchor_lang::prelude::*;
+use crate::constants::REWARD_PRECISION;
+
+/// Streams `reward_mint` to vaults of `collateral_mint` in proportion to
+/// their locked balance. Each funding is emitted evenly over
+/// `reward_duration` seconds and split across `total_staked` through a
+/// reward-per-token accumulator, so every position is owed its share no
+/// matter who claims first.
+#[account]
+pub struct RewardPool {
+    pub collateral_mint: Pubkey,
+    pub reward_mint: Pubkey,
+    pub reward_token_account: Pubkey,
+    /// Length of an emission period in seconds.
+    pub reward_duration: i64,
+    /// Reward base units emitted per second, scaled by `REWARD_PRECISION`.
+    pub reward_rate: u128,
+    pub period_finish: i64,
+    pub last_update_time: i64,
+    /// Rewards per staked base unit accumulated up to `last_update_time`,
+    /// scaled by `REWARD_PRECISION`.
+    pub reward_per_token_stored: u128,
+    /// Sum of the stakes of all positions.
+    pub total_staked: u64,
+    pub total_funded: u64,
+    pub total_claimed: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl RewardPool {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + 16 + 8 + 8 + 16 + 8 + 8 + 8 + 8 + 1;
+
+    fn last_time_applicable(&self, now: i64) -> i64 {
+        now.min(self.period_finish)
+    }
+
+    /// Reward per staked base unit as of `now`. Time in which nothing was
+    /// staked emits nothing; those rewards stay in the pool.
+    pub fn reward_per_token(&self, now: i64) -> Result<u128> {
+        if self.total_staked == 0 {
+            return Ok(self.reward_per_token_stored);
+        }
+        let elapsed = self
+            .last_time_applicable(now)
+            .saturating_sub(self.last_update_time)
+            .max(0) as u128;
+        let emitted = elapsed
+            .checked_mul(self.reward_rate)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        self.reward_per_token_stored
+            .checked_add(emitted / self.total_staked as u128)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))
+    }
+
+    /// Advances the accumulator to `now`. Called before the stake or the
+    /// rate changes.
+    pub fn update(&mut self, now: i64) -> Result<()> {
+        self.reward_per_token_stored = self.reward_per_token(now)?;
+        self.last_update_time = self.last_time_applicable(now).max(self.last_update_time);
+        Ok(())
+    }
+
+    /// Starts a new emission period for `amount` plus whatever the running
+    /// period had not emitted yet.
+    pub fn notify_reward(&mut self, amount: u64, now: i64) -> Result<()> {
+        self.update(now)?;
+
+        let mut undistributed = (amount as u128)
+            .checked_mul(REWARD_PRECISION)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        if now < self.period_finish {
+            let leftover = ((self.period_finish - now) as u128)
+                .checked_mul(self.reward_rate)
+                .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+            undistributed = undistributed
+                .checked_add(leftover)
+                .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        }
+
+        self.reward_rate = undistributed / self.reward_duration as u128;
+        require!(self.reward_rate > 0, crate::errors::ErrorCode::InvalidRewardRate);
+        self.last_update_time = now;
+        self.period_finish = now
+            .checked_add(self.reward_duration)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+
+        self.total_funded = self.total_funded
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        Ok(())
+    }
+
+    pub fn restake(&mut self, old_stake: u64, new_stake: u64) -> Result<()> {
+        self.total_staked = self.total_staked
+            .checked_sub(old_stake)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticUnderflow))?
+            .checked_add(new_stake)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        Ok(())
+    }
+
+    pub fn record_claim(&mut self, amount: u64) -> Result<()> {
+        self.total_claimed = self.total_claimed
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        Ok(())
+    }
+}
+
+/// A vault's enrollment in a reward pool.
+///
+/// Every instruction that moves the vault's locked or committed balance
+/// resyncs its positions, so the stake always matches the vault. Between
+/// syncs the position earns on the smaller of its stake and the vault's
+/// time-weighted locked balance.
+#[account]
+pub struct RewardPosition {
+    pub reward_pool: Pubkey,
+    pub vault: Pubkey,
+    /// Locked balance counted in the pool's `total_staked`.
+    pub staked: u64,
+    /// Pool accumulator at the last sync.
+    pub reward_per_token_paid: u128,
+    /// Vault's cumulative locked token-seconds at the last sync.
+    pub locked_balance_seconds: u128,
+    pub synced_at: i64,
+    /// Rewards earned but not yet claimed.
+    pub earned: u64,
+    pub total_claimed: u64,
+    pub bump: u8,
+}
+
+impl RewardPosition {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 16 + 16 + 8 + 8 + 8 + 1;
+
+    /// Credits rewards up to `now` and restakes the vault's current locked
+    /// balance. The pool must be updated to `now` first.
+    pub fn sync(
+        &mut self,
+        pool: &mut RewardPool,
+        locked_balance: u64,
+        locked_balance_seconds: u128,
+        now: i64,
+    ) -> Result<()> {
+        let elapsed = now.saturating_sub(self.synced_at).max(0) as u128;
+        let average_locked = locked_balance_seconds
+            .saturating_sub(self.locked_balance_seconds)
+            .checked_div(elapsed)
+            .unwrap_or(0);
+        let counted = average_locked.min(self.staked as u128);
+
+        let earned = counted
+            .checked_mul(
+                pool.reward_per_token_stored
+                    .checked_sub(self.reward_per_token_paid)
+                    .ok_or(error!(crate::errors::ErrorCode::ArithmeticUnderflow))?,
+            )
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?
+            / REWARD_PRECISION;
+        self.earned = u64::try_from(earned)
+            .ok()
+            .and_then(|earned| self.earned.checked_add(earned))
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+
+        pool.restake(self.staked, locked_balance)?;
+        self.staked = locked_balance;
+        self.reward_per_token_paid = pool.reward_per_token_stored;
+        self.locked_balance_seconds = locked_balance_seconds;
+        self.synced_at = now;
+        Ok(())
+    }
+
+    /// Pays out everything earned so far.
+    pub fn claim(&mut self) -> Result<u64> {
+        let amount = self.earned;
+        self.earned = 0;
+        self.total_claimed = self.total_claimed
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        Ok(amount)
+    }
+}
@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+use crate::constants::SESSION_SCOPE_ALL;
+
+/// Short-lived key the owner approves once so a trading UI can act on the
+/// vault without a wallet prompt per action. Every action it takes counts
+/// toward `max_amount` until `expires_at`.
+#[account]
+pub struct SessionKey {
+    pub vault: Pubkey,
+    pub session_key: Pubkey,
+    pub scopes: u8,
+    pub max_amount: u64,
+    pub used_amount: u64,
+    pub expires_at: i64,
+    pub created_at: i64,
+    /// Vault delegation epoch the session was registered in.
+    pub delegation_epoch: u64,
+    pub bump: u8,
+}
+
+impl SessionKey {
+    pub const LEN: usize = 8 + 32 + 32 + 1 + 8 + 8 + 8 + 8 + 8 + 1;
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn register(
+        &mut self,
+        vault: Pubkey,
+        session_key: Pubkey,
+        scopes: u8,
+        max_amount: u64,
+        expires_at: i64,
+        now: i64,
+        delegation_epoch: u64,
+        bump: u8,
+    ) -> Result<()> {
+        require!(
+            scopes != 0 && scopes & !SESSION_SCOPE_ALL == 0 && max_amount > 0 && expires_at > now,
+            crate::errors::ErrorCode::InvalidSessionKey
+        );
+        self.vault = vault;
+        self.session_key = session_key;
+        self.scopes = scopes;
+        self.max_amount = max_amount;
+        self.used_amount = 0;
+        self.expires_at = expires_at;
+        self.created_at = now;
+        self.delegation_epoch = delegation_epoch;
+        self.bump = bump;
+        Ok(())
+    }
+
+    /// Checks the session is live and allowed `scope`, then draws `amount`
+    /// from its budget.
+    pub fn authorize(&mut self, scope: u8, amount: u64, now: i64) -> Result<()> {
+        require!(now < self.expires_at, crate::errors::ErrorCode::SessionExpired);
+        require!(
+            self.scopes & scope != 0,
+            crate::errors::ErrorCode::SessionScopeNotAllowed
+        );
+        let used_amount = self.used_amount
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        require!(
+            used_amount <= self.max_amount,
+            crate::errors::ErrorCode::SessionLimitExceeded
+        );
+        self.used_amount = used_amount;
+        Ok(())
+    }
+}
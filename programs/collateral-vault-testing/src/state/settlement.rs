@@ -0,0 +1,10 @@
+use anchor_lang::prelude::*;
+
+/// One obligation in a batch settlement. `from` and `to` index the vaults
+/// passed to `settle_batch` through its remaining accounts.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct SettlementLeg {
+    pub from: u8,
+    pub to: u8,
+    pub amount: u64,
+}
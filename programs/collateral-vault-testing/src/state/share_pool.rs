@@ -0,0 +1,212 @@
+use anchor_lang::prelude::*;
+use crate::constants::BPS_DENOMINATOR;
+
+/// Per-mint pool backing vaults that opted into share accounting. Pooled
+/// vaults hold shares; `total_assets` grows with yield so every share is
+/// worth more underlying tokens over time. Locked, committed and pending
+/// amounts of pooled vaults are held in underlying tokens and must stay
+/// liquid in the pool token account.
+#[account]
+pub struct SharePool {
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub total_shares: u64,
+    pub total_assets: u64,
+    pub keeper: Pubkey,
+    pub reserve_bps: u16,
+    pub locked_assets: u64,
+    pub committed_assets: u64,
+    pub pending_assets: u64,
+    pub deployed_assets: u64,
+    pub total_socialized_loss: u64,
+    pub bump: u8,
+}
+
+impl SharePool {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 32 + 2 + 8 + 8 + 8 + 8 + 8 + 1;
+
+    // One virtual share and asset keep the first depositor from inflating
+    // the exchange rate by donating tokens to an empty pool.
+    fn convert(amount: u64, numerator: u64, denominator: u64, round_up: bool) -> Result<u64> {
+        let numerator = (numerator as u128) + 1;
+        let denominator = (denominator as u128) + 1;
+        let product = (amount as u128)
+            .checked_mul(numerator)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        let quotient = if round_up {
+            product.div_ceil(denominator)
+        } else {
+            product / denominator
+        };
+        u64::try_from(quotient).map_err(|_| error!(crate::errors::ErrorCode::ArithmeticOverflow))
+    }
+
+    /// Shares minted for depositing `assets`, rounded down.
+    pub fn shares_for_deposit(&self, assets: u64) -> Result<u64> {
+        Self::convert(assets, self.total_shares, self.total_assets, false)
+    }
+
+    /// Shares burned to withdraw `assets`, rounded up.
+    pub fn shares_for_withdrawal(&self, assets: u64) -> Result<u64> {
+        Self::convert(assets, self.total_shares, self.total_assets, true)
+    }
+
+    /// Underlying tokens `shares` can be redeemed for, rounded down.
+    pub fn assets_for_shares(&self, shares: u64) -> Result<u64> {
+        Self::convert(shares, self.total_assets, self.total_shares, false)
+    }
+
+    pub fn mint_shares(&mut self, shares: u64, assets: u64) -> Result<()> {
+        self.total_shares = self.total_shares
+            .checked_add(shares)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        self.total_assets = self.total_assets
+            .checked_add(assets)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        Ok(())
+    }
+
+    pub fn burn_shares(&mut self, shares: u64, assets: u64) -> Result<()> {
+        self.total_shares = self.total_shares
+            .checked_sub(shares)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticUnderflow))?;
+        self.total_assets = self.total_assets
+            .checked_sub(assets)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticUnderflow))?;
+        Ok(())
+    }
+
+    /// Tokens held in the pool's own token account rather than a strategy.
+    pub fn idle_assets(&self) -> u64 {
+        self.total_assets.saturating_sub(self.deployed_assets)
+    }
+
+    /// Assets pooled vaults hold as locked, committed or pending balances.
+    pub fn reserved_assets(&self) -> u64 {
+        self.locked_assets
+            .saturating_add(self.committed_assets)
+            .saturating_add(self.pending_assets)
+    }
+
+    pub fn unlocked_assets(&self) -> u64 {
+        self.total_assets.saturating_sub(self.reserved_assets())
+    }
+
+    /// Idle tokens a keeper may send to strategies. Every reserved token plus
+    /// `reserve_bps` of the unlocked ones stay liquid for withdrawals.
+    pub fn deployable_assets(&self) -> Result<u64> {
+        let reserve = (self.unlocked_assets() as u128)
+            .checked_mul(self.reserve_bps as u128)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?
+            .div_ceil(BPS_DENOMINATOR as u128) as u64;
+        let required = self.reserved_assets()
+            .checked_add(reserve)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        Ok(self.idle_assets().saturating_sub(required))
+    }
+
+    /// Reserved collateral must always be redeemable without a strategy recall.
+    pub fn require_liquid_reserves(&self) -> Result<()> {
+        require!(
+            self.idle_assets() >= self.reserved_assets(),
+            crate::errors::ErrorCode::InsufficientPoolLiquidity
+        );
+        Ok(())
+    }
+
+    pub fn lock_assets(&mut self, amount: u64) -> Result<()> {
+        self.locked_assets = self.locked_assets
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        self.require_liquid_reserves()
+    }
+
+    pub fn unlock_assets(&mut self, amount: u64) -> Result<()> {
+        self.locked_assets = self.locked_assets
+            .checked_sub(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticUnderflow))?;
+        Ok(())
+    }
+
+    pub fn commit_assets(&mut self, amount: u64) -> Result<()> {
+        self.committed_assets = self.committed_assets
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        self.require_liquid_reserves()
+    }
+
+    pub fn release_committed_assets(&mut self, amount: u64) -> Result<()> {
+        self.committed_assets = self.committed_assets
+            .checked_sub(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticUnderflow))?;
+        Ok(())
+    }
+
+    /// Drops a vault's reserves when it exits in an emergency. Saturates so
+    /// that reserve bookkeeping can never block the exit.
+    pub fn release_reserves(&mut self, locked: u64, committed: u64, pending: u64) {
+        self.locked_assets = self.locked_assets.saturating_sub(locked);
+        self.committed_assets = self.committed_assets.saturating_sub(committed);
+        self.pending_assets = self.pending_assets.saturating_sub(pending);
+    }
+
+    pub fn request_assets(&mut self, amount: u64) -> Result<()> {
+        self.pending_assets = self.pending_assets
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        self.require_liquid_reserves()
+    }
+
+    pub fn release_pending_assets(&mut self, amount: u64) -> Result<()> {
+        self.pending_assets = self.pending_assets
+            .checked_sub(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticUnderflow))?;
+        Ok(())
+    }
+
+    pub fn deploy(&mut self, amount: u64) -> Result<()> {
+        self.deployed_assets = self.deployed_assets
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        Ok(())
+    }
+
+    pub fn recall(&mut self, principal: u64) -> Result<()> {
+        self.deployed_assets = self.deployed_assets
+            .checked_sub(principal)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticUnderflow))?;
+        Ok(())
+    }
+
+    /// Writes off deployed assets a strategy has lost. Like a socialized
+    /// loss it lowers every share's value and reserves are not re-checked.
+    pub fn write_down_deployed(&mut self, assets: u64) -> Result<()> {
+        self.deployed_assets = self.deployed_assets
+            .checked_sub(assets)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticUnderflow))?;
+        self.total_assets = self.total_assets
+            .checked_sub(assets)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticUnderflow))?;
+        Ok(())
+    }
+
+    /// Writes `assets` off every share pro rata. Reserves are not
+    /// re-checked: a realized loss cannot be refused.
+    pub fn socialize_loss(&mut self, assets: u64) -> Result<()> {
+        self.total_assets = self.total_assets
+            .checked_sub(assets)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticUnderflow))?;
+        self.total_socialized_loss = self.total_socialized_loss
+            .checked_add(assets)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        Ok(())
+    }
+
+    /// Credits yield to every shareholder by raising the exchange rate.
+    pub fn add_yield(&mut self, assets: u64) -> Result<()> {
+        self.total_assets = self.total_assets
+            .checked_add(assets)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        Ok(())
+    }
+}
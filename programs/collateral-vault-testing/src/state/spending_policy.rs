@@ -0,0 +1,177 @@
+use anchor_lang::prelude::*;
+use crate::constants::{MAX_WITHDRAWAL_DESTINATIONS, SECONDS_PER_DAY};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct WithdrawalDestination {
+    pub token_account: Pubkey,
+    pub active_at: i64,
+}
+
+impl WithdrawalDestination {
+    pub const LEN: usize = 32 + 8;
+}
+
+/// Owner-configured limits on how collateral may leave a vault. Changes that
+/// tighten the policy apply at once; changes that loosen it are queued for
+/// `policy_delay` seconds so a stolen key cannot drain the vault right away.
+#[account]
+pub struct SpendingPolicy {
+    pub vault: Pubkey,
+    pub max_per_transaction: u64,
+    pub max_per_day: u64,
+    pub policy_delay: i64,
+    pub day_start: i64,
+    pub spent_today: u64,
+    pub pending_max_per_transaction: u64,
+    pub pending_max_per_day: u64,
+    pub pending_policy_delay: i64,
+    pub pending_effective_at: i64,
+    pub destinations: Vec<WithdrawalDestination>,
+    /// Vault delegation epoch the policy was set up in. A policy from a
+    /// previous owner no longer applies.
+    pub delegation_epoch: u64,
+    pub bump: u8,
+}
+
+impl SpendingPolicy {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8
+        + 4 + (WithdrawalDestination::LEN * MAX_WITHDRAWAL_DESTINATIONS) + 8 + 1;
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize(
+        &mut self,
+        vault: Pubkey,
+        max_per_transaction: u64,
+        max_per_day: u64,
+        policy_delay: i64,
+        now: i64,
+        delegation_epoch: u64,
+        bump: u8,
+    ) {
+        self.vault = vault;
+        self.max_per_transaction = max_per_transaction;
+        self.max_per_day = max_per_day;
+        self.policy_delay = policy_delay;
+        self.day_start = now;
+        self.spent_today = 0;
+        self.destinations = Vec::new();
+        self.delegation_epoch = delegation_epoch;
+        self.bump = bump;
+        self.clear_pending();
+    }
+
+    pub fn has_pending_change(&self) -> bool {
+        self.pending_effective_at != 0
+    }
+
+    fn clear_pending(&mut self) {
+        self.pending_max_per_transaction = 0;
+        self.pending_max_per_day = 0;
+        self.pending_policy_delay = 0;
+        self.pending_effective_at = 0;
+    }
+
+    /// Applies the tightening part of a change immediately. If any field is
+    /// loosened, the full requested policy is queued behind the (possibly
+    /// just increased) policy delay. Returns true if a change was queued.
+    pub fn update(
+        &mut self,
+        max_per_transaction: u64,
+        max_per_day: u64,
+        policy_delay: i64,
+        now: i64,
+    ) -> Result<bool> {
+        let loosens = max_per_transaction > self.max_per_transaction
+            || max_per_day > self.max_per_day
+            || policy_delay < self.policy_delay;
+
+        self.max_per_transaction = self.max_per_transaction.min(max_per_transaction);
+        self.max_per_day = self.max_per_day.min(max_per_day);
+        self.policy_delay = self.policy_delay.max(policy_delay);
+
+        if !loosens {
+            self.clear_pending();
+            return Ok(false);
+        }
+
+        self.pending_max_per_transaction = max_per_transaction;
+        self.pending_max_per_day = max_per_day;
+        self.pending_policy_delay = policy_delay;
+        self.pending_effective_at = now
+            .checked_add(self.policy_delay)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        Ok(true)
+    }
+
+    pub fn cancel_pending(&mut self) {
+        self.clear_pending();
+    }
+
+    /// Promotes a queued change once its delay has passed. Returns true if
+    /// the policy changed.
+    pub fn apply_pending(&mut self, now: i64) -> bool {
+        if !self.has_pending_change() || now < self.pending_effective_at {
+            return false;
+        }
+        self.max_per_transaction = self.pending_max_per_transaction;
+        self.max_per_day = self.pending_max_per_day;
+        self.policy_delay = self.pending_policy_delay;
+        self.clear_pending();
+        true
+    }
+
+    pub fn add_destination(&mut self, token_account: Pubkey, now: i64) -> Result<i64> {
+        require!(
+            !self.destinations.iter().any(|d| d.token_account == token_account),
+            crate::errors::ErrorCode::DestinationAlreadyAllowed
+        );
+        require!(
+            self.destinations.len() < MAX_WITHDRAWAL_DESTINATIONS,
+            crate::errors::ErrorCode::MaxWithdrawalDestinationsReached
+        );
+        let active_at = now
+            .checked_add(self.policy_delay)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        self.destinations.push(WithdrawalDestination { token_account, active_at });
+        Ok(active_at)
+    }
+
+    pub fn remove_destination(&mut self, token_account: Pubkey) -> Result<()> {
+        let position = self.destinations
+            .iter()
+            .position(|d| d.token_account == token_account)
+            .ok_or(crate::errors::ErrorCode::DestinationNotAllowed)?;
+        self.destinations.remove(position);
+        Ok(())
+    }
+
+    pub fn is_destination_active(&self, token_account: &Pubkey, now: i64) -> bool {
+        self.destinations
+            .iter()
+            .any(|d| d.token_account == *token_account && d.active_at <= now)
+    }
+
+    /// Checks `amount` against the per-transaction and rolling daily limits
+    /// and counts it toward today's spend.
+    pub fn record_spend(&mut self, amount: u64, now: i64) -> Result<()> {
+        require!(
+            amount <= self.max_per_transaction,
+            crate::errors::ErrorCode::WithdrawalExceedsTransactionLimit
+        );
+
+        if now.saturating_sub(self.day_start) >= SECONDS_PER_DAY {
+            self.day_start = now;
+            self.spent_today = 0;
+        }
+
+        let spent_today = self.spent_today
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        require!(
+            spent_today <= self.max_per_day,
+            crate::errors::ErrorCode::WithdrawalExceedsDailyLimit
+        );
+        self.spent_today = spent_today;
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+use crate::constants::BPS_DENOMINATOR;
+use crate::state::SharePool;
+
+/// Admin-approved yield strategy for one mint's share pool.
+#[account]
+pub struct Strategy {
+    pub mint: Pubkey,
+    pub program_id: Pubkey,
+    pub max_allocation_bps: u16,
+    pub allocated: u64,
+    pub active: bool,
+    pub bump: u8,
+}
+
+impl Strategy {
+    pub const LEN: usize = 8 + 32 + 32 + 2 + 8 + 1 + 1;
+
+    /// Most this strategy may hold: `max_allocation_bps` of the pool's
+    /// unlocked assets.
+    pub fn allocation_cap(&self, pool: &SharePool) -> Result<u64> {
+        let cap = (pool.unlocked_assets() as u128)
+            .checked_mul(self.max_allocation_bps as u128)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?
+            / BPS_DENOMINATOR as u128;
+        Ok(cap as u64)
+    }
+
+    pub fn allocate(&mut self, amount: u64, pool: &SharePool) -> Result<()> {
+        require!(self.active, crate::errors::ErrorCode::StrategyInactive);
+        let allocated = self.allocated
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        require!(
+            allocated <= self.allocation_cap(pool)?,
+            crate::errors::ErrorCode::StrategyAllocationExceeded
+        );
+        self.allocated = allocated;
+        Ok(())
+    }
+
+    /// Drops `loss` from the allocation once the strategy has lost it.
+    pub fn write_down(&mut self, loss: u64) -> Result<()> {
+        self.allocated = self.allocated
+            .checked_sub(loss)
+            .ok_or(error!(crate::errors::ErrorCode::StrategyLossExceedsAllocation))?;
+        Ok(())
+    }
+
+    /// Splits `received` tokens from a recall into returned principal and
+    /// yield earned on top of it.
+    pub fn deallocate(&mut self, received: u64) -> (u64, u64) {
+        let principal = received.min(self.allocated);
+        self.allocated -= principal;
+        (principal, received - principal)
+    }
+}
@@ -1,41 +1,227 @@
 use anchor_lang::prelude::*;
+use crate::constants::{LOSS_PRECISION, MAX_AUTHORIZED_PROGRAMS};
+use crate::state::{CheckpointRing, MintState, SharePool};
+
+/// Collateral one authorized program holds locked in a vault.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct ProgramLock {
+    pub program_id: Pubkey,
+    pub amount: u64,
+}
+
+impl ProgramLock {
+    pub const LEN: usize = 32 + 8;
+}
 
 #[account]
 pub struct CollateralVault {
     pub owner: Pubkey,
+    pub seed_owner: Pubkey,
+    pub pending_owner: Option<Pubkey>,
+    pub subaccount: u16,
     pub token_account: Pubkey,
+    pub rent_payer: Pubkey,
     pub total_balance: u64,
     pub locked_balance: u64,
+    /// `locked_balance` broken down by the program that locked it.
+    pub program_locks: Vec<ProgramLock>,
     pub available_balance: u64,
+    /// Collateral the owner locked themselves until `committed_until`.
+    pub committed_balance: u64,
+    pub committed_until: i64,
     pub total_deposited: u64,
     pub total_withdrawn: u64,
+    /// Shortfalls authorized programs recorded against this vault.
+    pub bad_debt_recorded: u64,
+    /// The mint's `loss_per_token` as of the vault's last write-off.
+    pub loss_index: u128,
+    /// Socialized loss the available and pending balances could not cover
+    /// yet. It comes out of the next collateral that frees up.
+    pub loss_owed: u64,
+    /// Written-off loss whose tokens still sit in the vault token account
+    /// until they are collected into the insurance fund.
+    pub loss_uncollected: u64,
+    pub pending_withdrawal: u64,
+    pub withdrawal_ready_at: i64,
+    pub permit_nonce: u64,
+    pub lockdown_recovery_key: Option<Pubkey>,
+    pub lockdown_delay: i64,
+    pub locked_down: bool,
+    pub lockdown_release_at: i64,
+    pub cosigner: Option<Pubkey>,
+    pub cosigner_threshold: u64,
+    /// Bumped whenever the vault changes owner. Operators, session keys,
+    /// spending policies and guardians record the epoch they were set up
+    /// in and stop counting once it moves on.
+    pub delegation_epoch: u64,
+    /// Reward positions open on the vault. Every one of them is resynced
+    /// whenever the locked or committed balance moves.
+    pub reward_positions: u8,
+    pub pooled: bool,
+    pub shares: u64,
+    pub available_balance_seconds: u128,
+    pub locked_balance_seconds: u128,
+    pub balance_seconds_updated_at: i64,
+    pub balance_checkpoints: CheckpointRing,
     pub created_at: i64,
     pub bump: u8,
 }
 
 impl CollateralVault {
-    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 1;
+    pub const LEN: usize = 8 + 32 + 32 + (1 + 32) + 2 + 32 + 32 + 8 + 8
+        + 4 + (ProgramLock::LEN * MAX_AUTHORIZED_PROGRAMS) + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8
+        + 16 + 8 + 8 + (1 + 32) + 8 + 1 + 8 + (1 + 32) + 8 + 8 + 1 + 1 + 8 + 16 + 16 + 8 + CheckpointRing::LEN + 8 + 1;
 
+    #[allow(clippy::too_many_arguments)]
     pub fn initialize(
         &mut self,
         owner: Pubkey,
+        subaccount: u16,
         token_account: Pubkey,
+        rent_payer: Pubkey,
         initial_deposit: u64,
-        created_at: i64,
+        loss_index: u128,
+        clock: &Clock,
         bump: u8,
     ) {
         self.owner = owner;
+        self.seed_owner = owner;
+        self.pending_owner = None;
+        self.subaccount = subaccount;
         self.token_account = token_account;
+        self.rent_payer = rent_payer;
         self.total_balance = initial_deposit;
         self.locked_balance = 0;
+        self.program_locks = Vec::new();
         self.available_balance = initial_deposit;
+        self.committed_balance = 0;
+        self.committed_until = 0;
         self.total_deposited = initial_deposit;
         self.total_withdrawn = 0;
-        self.created_at = created_at;
+        self.bad_debt_recorded = 0;
+        self.loss_index = loss_index;
+        self.loss_owed = 0;
+        self.loss_uncollected = 0;
+        self.pending_withdrawal = 0;
+        self.withdrawal_ready_at = 0;
+        self.permit_nonce = 0;
+        self.lockdown_recovery_key = None;
+        self.lockdown_delay = 0;
+        self.locked_down = false;
+        self.lockdown_release_at = 0;
+        self.cosigner = None;
+        self.cosigner_threshold = 0;
+        self.delegation_epoch = 0;
+        self.reward_positions = 0;
+        self.pooled = false;
+        self.shares = 0;
+        self.available_balance_seconds = 0;
+        self.locked_balance_seconds = 0;
+        self.balance_seconds_updated_at = clock.unix_timestamp;
+        self.balance_checkpoints = CheckpointRing::default();
+        self.balance_checkpoints.record(clock.slot, initial_deposit);
+        self.created_at = clock.unix_timestamp;
         self.bump = bump;
     }
 
-    pub fn deposit(&mut self, amount: u64) -> Result<()> {
+    fn balance_seconds_at(accumulated: u128, balance: u64, since: i64, now: i64) -> Result<u128> {
+        let elapsed = now.saturating_sub(since).max(0) as u128;
+        accumulated
+            .checked_add(
+                (balance as u128)
+                    .checked_mul(elapsed)
+                    .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?,
+            )
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))
+    }
+
+    /// Cumulative available token-seconds as of `now`.
+    pub fn available_balance_seconds_at(&self, now: i64) -> Result<u128> {
+        Self::balance_seconds_at(
+            self.available_balance_seconds,
+            self.available_balance,
+            self.balance_seconds_updated_at,
+            now,
+        )
+    }
+
+    /// Program locks plus the owner's own commitments.
+    pub fn locked_and_committed_balance(&self) -> Result<u64> {
+        self.locked_balance
+            .checked_add(self.committed_balance)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))
+    }
+
+    /// Cumulative locked token-seconds as of `now`, counting the owner's
+    /// own commitments alongside program locks.
+    pub fn locked_balance_seconds_at(&self, now: i64) -> Result<u128> {
+        Self::balance_seconds_at(
+            self.locked_balance_seconds,
+            self.locked_and_committed_balance()?,
+            self.balance_seconds_updated_at,
+            now,
+        )
+    }
+
+    /// Records the vault's total balance for historical lookups.
+    fn checkpoint_balance(&mut self, clock: &Clock) {
+        self.balance_checkpoints.record(clock.slot, self.total_balance);
+    }
+
+    /// Credits the time the current balances were held before they change.
+    /// Every method that moves available or locked balance calls this first.
+    fn accrue_balance_seconds(&mut self, now: i64) -> Result<()> {
+        self.available_balance_seconds = self.available_balance_seconds_at(now)?;
+        self.locked_balance_seconds = self.locked_balance_seconds_at(now)?;
+        self.balance_seconds_updated_at = self.balance_seconds_updated_at.max(now);
+        Ok(())
+    }
+
+    /// Writes the vault's share of bad debt socialized across the mint
+    /// since its last write-off off the available balance, then off a
+    /// pending withdrawal. Callers run this before any change to the total
+    /// balance, so the share is measured on what the vault held while the
+    /// index grew. Pooled vaults bear their share through the pool and only
+    /// catch up with the index.
+    pub fn absorb_socialized_loss(&mut self, mint_state: &MintState, clock: &Clock) -> Result<()> {
+        let delta = mint_state.loss_per_token.saturating_sub(self.loss_index);
+        self.loss_index = mint_state.loss_per_token;
+        if self.pooled {
+            return Ok(());
+        }
+        let charged = self.total_balance.saturating_sub(self.loss_owed) as u128;
+        let share = charged
+            .checked_mul(delta)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?
+            / LOSS_PRECISION;
+        let share = u64::try_from(share)
+            .map_err(|_| error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        self.loss_owed = self.loss_owed
+            .checked_add(share)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        if self.loss_owed == 0 {
+            return Ok(());
+        }
+
+        self.accrue_balance_seconds(clock.unix_timestamp)?;
+        let from_available = self.loss_owed.min(self.available_balance);
+        self.available_balance -= from_available;
+        let from_pending = (self.loss_owed - from_available).min(self.pending_withdrawal);
+        self.pending_withdrawal -= from_pending;
+        let written_off = from_available + from_pending;
+        self.loss_owed -= written_off;
+        self.total_balance = self.total_balance
+            .checked_sub(written_off)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticUnderflow))?;
+        self.loss_uncollected = self.loss_uncollected
+            .checked_add(written_off)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        self.checkpoint_balance(clock);
+        Ok(())
+    }
+
+    pub fn deposit(&mut self, amount: u64, clock: &Clock) -> Result<()> {
+        self.accrue_balance_seconds(clock.unix_timestamp)?;
         self.total_balance = self.total_balance
             .checked_add(amount)
             .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
@@ -45,10 +231,12 @@ impl CollateralVault {
         self.total_deposited = self.total_deposited
             .checked_add(amount)
             .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        self.checkpoint_balance(clock);
         Ok(())
     }
 
-    pub fn withdraw(&mut self, amount: u64) -> Result<()> {
+    pub fn withdraw(&mut self, amount: u64, clock: &Clock) -> Result<()> {
+        self.accrue_balance_seconds(clock.unix_timestamp)?;
         self.total_balance = self.total_balance
             .checked_sub(amount)
             .ok_or(error!(crate::errors::ErrorCode::ArithmeticUnderflow))?;
@@ -58,26 +246,318 @@ impl CollateralVault {
         self.total_withdrawn = self.total_withdrawn
             .checked_add(amount)
             .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        self.checkpoint_balance(clock);
         Ok(())
     }
 
-    pub fn lock(&mut self, amount: u64) -> Result<()> {
+    /// Collateral `program_id` currently holds locked in this vault.
+    pub fn program_locked_balance(&self, program_id: &Pubkey) -> u64 {
+        self.program_locks
+            .iter()
+            .find(|lock| lock.program_id == *program_id)
+            .map_or(0, |lock| lock.amount)
+    }
+
+    pub fn lock(&mut self, program_id: Pubkey, amount: u64, clock: &Clock) -> Result<()> {
+        if let Some(lock) = self.program_locks.iter_mut().find(|lock| lock.program_id == program_id) {
+            lock.amount = lock.amount
+                .checked_add(amount)
+                .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        } else {
+            require!(
+                self.program_locks.len() < MAX_AUTHORIZED_PROGRAMS,
+                crate::errors::ErrorCode::MaxAuthorizedProgramsReached
+            );
+            self.program_locks.push(ProgramLock { program_id, amount });
+        }
+        self.accrue_balance_seconds(clock.unix_timestamp)?;
         self.locked_balance = self.locked_balance
             .checked_add(amount)
             .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
         self.available_balance = self.available_balance
             .checked_sub(amount)
             .ok_or(error!(crate::errors::ErrorCode::ArithmeticUnderflow))?;
+        self.checkpoint_balance(clock);
+        Ok(())
+    }
+
+    /// Commits `amount` of available collateral until `until`. Topping up
+    /// may push the release time out but never bring it forward.
+    pub fn commit_lock(&mut self, amount: u64, until: i64, clock: &Clock) -> Result<()> {
+        require!(
+            until > clock.unix_timestamp && until >= self.committed_until,
+            crate::errors::ErrorCode::InvalidCommitment
+        );
+        self.accrue_balance_seconds(clock.unix_timestamp)?;
+        self.committed_balance = self.committed_balance
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        self.available_balance = self.available_balance
+            .checked_sub(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticUnderflow))?;
+        self.committed_until = until;
+        Ok(())
+    }
+
+    /// Returns the whole commitment to available once it has expired.
+    pub fn release_commitment(&mut self, clock: &Clock) -> Result<u64> {
+        require!(
+            clock.unix_timestamp >= self.committed_until,
+            crate::errors::ErrorCode::CommitmentActive
+        );
+        self.accrue_balance_seconds(clock.unix_timestamp)?;
+        let amount = self.committed_balance;
+        self.available_balance = self.available_balance
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        self.committed_balance = 0;
+        self.committed_until = 0;
+        Ok(amount)
+    }
+
+    /// Hands the vault to `new_owner`. The PDA stays derived from
+    /// `seed_owner`, so balances, locks and history carry over unchanged,
+    /// but every key the previous owner empowered loses its rights.
+    pub fn transfer_ownership(&mut self, new_owner: Pubkey) -> Result<()> {
+        self.owner = new_owner;
+        self.pending_owner = None;
+        self.cosigner = None;
+        self.cosigner_threshold = 0;
+        self.lockdown_recovery_key = None;
+        self.delegation_epoch = self.delegation_epoch
+            .checked_add(1)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        Ok(())
+    }
+
+    /// Rejects owner-driven outflows while the owner has frozen the vault.
+    pub fn require_not_locked_down(&self) -> Result<()> {
+        require!(!self.locked_down, crate::errors::ErrorCode::VaultLockedDown);
+        Ok(())
+    }
+
+    /// Outflows above `cosigner_threshold` also need the configured
+    /// co-signer's signature.
+    pub fn require_cosigner(&self, amount: u64, cosigner: Option<Pubkey>) -> Result<()> {
+        if let Some(expected) = self.cosigner {
+            if amount > self.cosigner_threshold {
+                require!(
+                    cosigner == Some(expected),
+                    crate::errors::ErrorCode::CosignerRequired
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Instructions that move tokens through the vault's own token account
+    /// cannot run on a vault whose collateral sits in the mint's share pool.
+    pub fn require_unpooled(&self) -> Result<()> {
+        require!(!self.pooled, crate::errors::ErrorCode::VaultPooled);
+        Ok(())
+    }
+
+    /// Locked, committed and pending amounts. A pooled vault holds these in
+    /// underlying tokens, mirrored by the share pool's reserves.
+    pub fn reserved_balance(&self) -> u64 {
+        self.locked_balance
+            .saturating_add(self.committed_balance)
+            .saturating_add(self.pending_withdrawal)
+    }
+
+    /// Re-prices a pooled vault from its shares. Reserved amounts stay in
+    /// underlying tokens, so yield only ever lands in available.
+    pub fn sync_pooled_balance(&mut self, pool: &SharePool, clock: &Clock) -> Result<()> {
+        self.accrue_balance_seconds(clock.unix_timestamp)?;
+        let value = pool.assets_for_shares(self.shares)?;
+        self.total_balance = value;
+        self.available_balance = value.saturating_sub(self.reserved_balance());
+        self.checkpoint_balance(clock);
+        Ok(())
+    }
+
+    /// Burns the shares behind `amount` of a pooled vault's balance and
+    /// returns how many were burned. The caller moves the tokens out of the
+    /// pool token account.
+    pub fn redeem_pooled(&mut self, pool: &mut SharePool, amount: u64, clock: &Clock) -> Result<u64> {
+        let shares = pool.shares_for_withdrawal(amount)?;
+        require!(
+            shares <= self.shares,
+            crate::errors::ErrorCode::InsufficientAvailableBalance
+        );
+        pool.burn_shares(shares, amount)?;
+        self.withdraw_shares(shares, amount)?;
+        self.sync_pooled_balance(pool, clock)?;
+        // Burning rounds up, so the remaining shares must still cover the reserves.
+        require!(
+            self.total_balance >= self.reserved_balance(),
+            crate::errors::ErrorCode::InsufficientAvailableBalance
+        );
+        Ok(shares)
+    }
+
+    /// Mints shares for `amount` tokens that have landed in the pool token
+    /// account and returns how many were minted.
+    pub fn issue_pooled(&mut self, pool: &mut SharePool, amount: u64, clock: &Clock) -> Result<u64> {
+        let shares = pool.shares_for_deposit(amount)?;
+        require!(shares > 0, crate::errors::ErrorCode::ZeroShares);
+        pool.mint_shares(shares, amount)?;
+        self.deposit_shares(shares, amount)?;
+        self.sync_pooled_balance(pool, clock)?;
+        Ok(shares)
+    }
+
+    pub fn deposit_shares(&mut self, shares: u64, amount: u64) -> Result<()> {
+        self.shares = self.shares
+            .checked_add(shares)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        self.total_deposited = self.total_deposited
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        Ok(())
+    }
+
+    pub fn withdraw_shares(&mut self, shares: u64, amount: u64) -> Result<()> {
+        self.shares = self.shares
+            .checked_sub(shares)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticUnderflow))?;
+        self.total_withdrawn = self.total_withdrawn
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        Ok(())
+    }
+
+    /// Records a shortfall left once the vault has been emptied. A position
+    /// backed by the vault cannot have owed more than the collateral it ever
+    /// received, so all its shortfalls together stay within its deposits.
+    pub fn record_bad_debt(&mut self, shortfall: u64) -> Result<()> {
+        require!(
+            self.total_balance == 0 && self.shares == 0,
+            crate::errors::ErrorCode::DebtorVaultNotEmpty
+        );
+        let recorded = self.bad_debt_recorded
+            .checked_add(shortfall)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        require!(
+            recorded <= self.total_deposited,
+            crate::errors::ErrorCode::BadDebtExceedsObligation
+        );
+        self.bad_debt_recorded = recorded;
+        Ok(())
+    }
+
+    /// Consumes the next permit nonce so a signed permit runs at most once.
+    pub fn use_permit_nonce(&mut self, nonce: u64) -> Result<()> {
+        require!(
+            nonce == self.permit_nonce,
+            crate::errors::ErrorCode::InvalidPermitNonce
+        );
+        self.permit_nonce = self.permit_nonce
+            .checked_add(1)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        Ok(())
+    }
+
+    /// Moves `amount` out of the available balance into the pending bucket.
+    /// Topping up an existing request restarts its cooldown.
+    pub fn request_withdrawal(&mut self, amount: u64, ready_at: i64, clock: &Clock) -> Result<()> {
+        self.accrue_balance_seconds(clock.unix_timestamp)?;
+        self.available_balance = self.available_balance
+            .checked_sub(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticUnderflow))?;
+        self.pending_withdrawal = self.pending_withdrawal
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        self.withdrawal_ready_at = ready_at;
         Ok(())
     }
 
-    pub fn unlock(&mut self, amount: u64) -> Result<()> {
+    pub fn cancel_withdrawal(&mut self, clock: &Clock) -> Result<u64> {
+        self.accrue_balance_seconds(clock.unix_timestamp)?;
+        let amount = self.pending_withdrawal;
+        self.available_balance = self.available_balance
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        self.pending_withdrawal = 0;
+        self.withdrawal_ready_at = 0;
+        Ok(amount)
+    }
+
+    /// Pays out a pooled vault's pending request by burning the shares
+    /// behind it. Returns the amount and the shares burned.
+    pub fn execute_pooled_withdrawal(&mut self, pool: &mut SharePool, clock: &Clock) -> Result<(u64, u64)> {
+        self.accrue_balance_seconds(clock.unix_timestamp)?;
+        let amount = self.pending_withdrawal;
+        pool.release_pending_assets(amount)?;
+        self.pending_withdrawal = 0;
+        self.withdrawal_ready_at = 0;
+        let shares = self.redeem_pooled(pool, amount, clock)?;
+        Ok((amount, shares))
+    }
+
+    pub fn execute_withdrawal(&mut self, clock: &Clock) -> Result<u64> {
+        self.accrue_balance_seconds(clock.unix_timestamp)?;
+        let amount = self.pending_withdrawal;
+        self.total_balance = self.total_balance
+            .checked_sub(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticUnderflow))?;
+        self.total_withdrawn = self.total_withdrawn
+            .checked_add(amount)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        self.pending_withdrawal = 0;
+        self.withdrawal_ready_at = 0;
+        self.checkpoint_balance(clock);
+        Ok(amount)
+    }
+
+    /// Empties the vault regardless of locks and commitments, returning the
+    /// program locks that were released along with the withdrawal.
+    pub fn emergency_withdraw(&mut self, clock: &Clock) -> Result<Vec<ProgramLock>> {
+        self.accrue_balance_seconds(clock.unix_timestamp)?;
+        let released_locks = std::mem::take(&mut self.program_locks);
+        self.total_withdrawn = self.total_withdrawn
+            .checked_add(self.total_balance)
+            .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        self.total_balance = 0;
+        self.locked_balance = 0;
+        self.available_balance = 0;
+        self.committed_balance = 0;
+        self.committed_until = 0;
+        self.pending_withdrawal = 0;
+        self.withdrawal_ready_at = 0;
+        self.shares = 0;
+        self.checkpoint_balance(clock);
+        Ok(released_locks)
+    }
+
+    /// Returns everything `program_id` holds locked to the available
+    /// balance, leaving other programs' locks in place.
+    pub fn release_program_lock(&mut self, program_id: &Pubkey, clock: &Clock) -> Result<u64> {
+        let amount = self.program_locked_balance(program_id);
+        require!(amount > 0, crate::errors::ErrorCode::InsufficientLockedBalance);
+        self.unlock(*program_id, amount, clock)?;
+        Ok(amount)
+    }
+
+    /// Releases `amount` of the collateral `program_id` locked. A program
+    /// can only unlock what it locked itself.
+    pub fn unlock(&mut self, program_id: Pubkey, amount: u64, clock: &Clock) -> Result<()> {
+        let position = self.program_locks
+            .iter()
+            .position(|lock| lock.program_id == program_id && lock.amount >= amount)
+            .ok_or(error!(crate::errors::ErrorCode::InsufficientLockedBalance))?;
+        self.program_locks[position].amount -= amount;
+        if self.program_locks[position].amount == 0 {
+            self.program_locks.remove(position);
+        }
+        self.accrue_balance_seconds(clock.unix_timestamp)?;
         self.locked_balance = self.locked_balance
             .checked_sub(amount)
             .ok_or(error!(crate::errors::ErrorCode::ArithmeticUnderflow))?;
         self.available_balance = self.available_balance
             .checked_add(amount)
             .ok_or(error!(crate::errors::ErrorCode::ArithmeticOverflow))?;
+        self.checkpoint_balance(clock);
         Ok(())
     }
 }
\ No newline at end of file
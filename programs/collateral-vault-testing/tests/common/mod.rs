@@ -1,12 +1,28 @@
+// Shared by every integration test binary; not each one uses every helper.
+#![allow(dead_code)]
+
 use anchor_lang::{
     prelude::*,
     solana_program::{system_program, program_pack::Pack},
     InstructionData, ToAccountMetas,
 };
+use base64::Engine;
+use anchor_spl::associated_token::{self, get_associated_token_address};
 use collateral_vault_testing::{
     self,
-    constants::{AUTHORITY_SEED, VAULT_SEED},
-    state::{CollateralVault, VaultAuthority},
+    constants::{
+        AUTHORITY_SEED, BAD_DEBT_SEED, BROKEN_PROGRAM_SEED, COOLDOWN_SEED, INSURANCE_SEED,
+        MINT_STATE_SEED, MULTISIG_SEED, OPERATOR_SEED, OWNER_INDEX_SEED, PROPOSAL_SEED,
+        PROTOCOL_STATE_SEED, RATE_LIMIT_SEED, RECOVERY_SEED, REWARD_POOL_SEED, REWARD_POSITION_SEED,
+        SESSION_SEED, SHARE_POOL_SEED, SPENDING_POLICY_SEED, STRATEGY_SEED, VAULT_SEED,
+    },
+    errors,
+    state::{
+        BadDebt, BrokenProgram, CollateralVault, CooldownConfig, InsuranceFund, MintState, Multisig,
+        MultisigAction, MultisigProposal, OwnerIndex, PermitMessage, ProgramRateLimit,
+        ProposalAccount, ProtocolState, ProtocolStatus, RecoveryConfig, RewardPool, RewardPosition, SessionKey, SettlementLeg,
+        SharePool, SpendingPolicy, Strategy, VaultAuthority, VaultOperator,
+    },
 };
 
 // Use the Solana 2.0 library versions
@@ -14,19 +30,32 @@ use solana_program_test::*;
 use solana_sdk::{
     account::Account,
     clock::Clock,
-    hash::Hash,
-    instruction::Instruction,
+    instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
     signature::{Keypair, Signer},
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
 };
-use spl_token_2::{
+use spl_token::{
     self,
-    state::{Account as TokenAccount, Mint},
+    state::Mint,
 };
 
 pub const USER_STARTING_USDT: u64 = 1_000_000_000; // 1000 USDT with 6 decimals
 
+/// Builds a vault program instruction from Anchor's generated instruction
+/// data and accounts structs.
+pub trait ToInstruction: InstructionData + Sized {
+    fn to_instruction(self, accounts: impl ToAccountMetas) -> Instruction {
+        Instruction {
+            program_id: collateral_vault_testing::id(),
+            accounts: accounts.to_account_metas(None),
+            data: self.data(),
+        }
+    }
+}
+
+impl<T: InstructionData> ToInstruction for T {}
+
 pub struct CollateralVaultProgramTest {
     pub context: ProgramTestContext,
     pub program_id: Pubkey,
@@ -34,6 +63,7 @@ pub struct CollateralVaultProgramTest {
     pub usdt_mint: Pubkey,
     pub authority_pda: Pubkey,
     pub authority_bump: u8,
+    pub protocol_state_pda: Pubkey,
 }
 
 impl CollateralVaultProgramTest {
@@ -42,14 +72,14 @@ impl CollateralVaultProgramTest {
         let mut pt = ProgramTest::new(
             "collateral_vault_testing",
             program_id,
-            processor!(collateral_vault_testing::entry),
+            processor!(process_vault_instruction),
         );
 
-        // Add SPL Token program
+        // Add mock yield strategy program
         pt.add_program(
-            "spl_token_2",
-            spl_token_2::id(),
-            processor!(spl_token_2::processor::Processor::process),
+            "mock_strategy",
+            mock_strategy::id(),
+            processor!(process_mock_strategy_instruction),
         );
 
         // Add user account
@@ -73,10 +103,12 @@ impl CollateralVaultProgramTest {
         );
 
         // Start the test context
-        let mut context = pt.start_with_context().await;
+        let context = pt.start_with_context().await;
 
         let (authority_pda, authority_bump) =
             Pubkey::find_program_address(&[AUTHORITY_SEED], &program_id);
+        let (protocol_state_pda, _) =
+            Pubkey::find_program_address(&[PROTOCOL_STATE_SEED], &program_id);
 
         Self {
             context,
@@ -85,6 +117,7 @@ impl CollateralVaultProgramTest {
             usdt_mint: usdt_mint.pubkey(),
             authority_pda,
             authority_bump,
+            protocol_state_pda,
         }
     }
 
@@ -93,25 +126,155 @@ impl CollateralVaultProgramTest {
     }
 
     pub fn find_vault_pda(&self, user: &Pubkey) -> (Pubkey, u8) {
-        Pubkey::find_program_address(&[VAULT_SEED, user.as_ref()], &self.program_id)
+        self.find_subaccount_vault_pda(user, 0)
+    }
+
+    pub fn find_subaccount_vault_pda(&self, user: &Pubkey, subaccount: u16) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[VAULT_SEED, user.as_ref(), &subaccount.to_le_bytes()],
+            &self.program_id,
+        )
+    }
+
+    pub fn find_owner_index_pda(&self, user: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[OWNER_INDEX_SEED, user.as_ref()], &self.program_id)
+    }
+
+    pub fn find_mint_state_pda(&self, mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[MINT_STATE_SEED, mint.as_ref()], &self.program_id)
+    }
+
+    pub fn find_share_pool_pda(&self, mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[SHARE_POOL_SEED, mint.as_ref()], &self.program_id)
+    }
+
+    pub fn find_strategy_pda(&self, mint: &Pubkey, strategy_program: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[STRATEGY_SEED, mint.as_ref(), strategy_program.as_ref()],
+            &self.program_id,
+        )
+    }
+
+    pub fn find_pool_token_account(&self) -> Pubkey {
+        let (share_pool, _) = self.find_share_pool_pda(&self.usdt_mint);
+        get_associated_token_address(&share_pool, &self.usdt_mint)
+    }
+
+    pub fn find_mock_strategy_state(&self) -> Pubkey {
+        Pubkey::find_program_address(
+            &[mock_strategy::STRATEGY_SEED, self.usdt_mint.as_ref()],
+            &mock_strategy::id(),
+        )
+        .0
+    }
+
+    pub fn find_mock_strategy_token_account(&self) -> Pubkey {
+        get_associated_token_address(&self.find_mock_strategy_state(), &self.usdt_mint)
+    }
+
+    pub fn find_vault_token_account(&self, vault_pda: &Pubkey) -> Pubkey {
+        get_associated_token_address(vault_pda, &self.usdt_mint)
+    }
+
+    pub fn find_rate_limit_pda(&self, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[RATE_LIMIT_SEED, program_id.as_ref()], &self.program_id)
+    }
+
+    pub fn find_insurance_fund_pda(&self, mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[INSURANCE_SEED, mint.as_ref()], &self.program_id)
+    }
+
+    pub fn find_insurance_token_account(&self) -> Pubkey {
+        let (insurance_fund, _) = self.find_insurance_fund_pda(&self.usdt_mint);
+        get_associated_token_address(&insurance_fund, &self.usdt_mint)
+    }
+
+    pub fn find_bad_debt_pda(&self, index: u64) -> (Pubkey, u8) {
+        let (insurance_fund, _) = self.find_insurance_fund_pda(&self.usdt_mint);
+        Pubkey::find_program_address(
+            &[BAD_DEBT_SEED, insurance_fund.as_ref(), &index.to_le_bytes()],
+            &self.program_id,
+        )
+    }
+
+    /// Reward pool paying out the test mint to vaults of the test mint.
+    pub fn find_reward_pool_pda(&self) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[REWARD_POOL_SEED, self.usdt_mint.as_ref(), self.usdt_mint.as_ref()],
+            &self.program_id,
+        )
+    }
+
+    pub fn find_reward_token_account(&self) -> Pubkey {
+        let (reward_pool, _) = self.find_reward_pool_pda();
+        get_associated_token_address(&reward_pool, &self.usdt_mint)
+    }
+
+    pub fn find_reward_position_pda(&self, vault: &Pubkey) -> (Pubkey, u8) {
+        let (reward_pool, _) = self.find_reward_pool_pda();
+        Pubkey::find_program_address(
+            &[REWARD_POSITION_SEED, reward_pool.as_ref(), vault.as_ref()],
+            &self.program_id,
+        )
+    }
+
+    pub fn find_broken_program_pda(&self, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[BROKEN_PROGRAM_SEED, program_id.as_ref()], &self.program_id)
+    }
+
+    pub fn find_operator_pda(&self, vault_pda: &Pubkey, operator: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[OPERATOR_SEED, vault_pda.as_ref(), operator.as_ref()],
+            &self.program_id,
+        )
+    }
+
+    pub fn find_recovery_pda(&self, vault_pda: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[RECOVERY_SEED, vault_pda.as_ref()], &self.program_id)
+    }
+
+    pub fn find_session_pda(&self, vault_pda: &Pubkey, session_key: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[SESSION_SEED, vault_pda.as_ref(), session_key.as_ref()],
+            &self.program_id,
+        )
+    }
+
+    pub fn find_multisig_pda(&self, create_key: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[MULTISIG_SEED, create_key.as_ref()], &self.program_id)
+    }
+
+    pub fn find_proposal_pda(&self, multisig: &Pubkey, index: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[PROPOSAL_SEED, multisig.as_ref(), &index.to_le_bytes()],
+            &self.program_id,
+        )
+    }
+
+    pub fn find_cooldown_pda(&self, mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[COOLDOWN_SEED, mint.as_ref()], &self.program_id)
+    }
+
+    pub fn find_spending_policy_pda(&self, vault: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[SPENDING_POLICY_SEED, vault.as_ref()], &self.program_id)
     }
 
     pub async fn create_token_account(&mut self, owner: &Pubkey) -> Pubkey {
         let token_account = Keypair::new();
         let rent = self.context.banks_client.get_rent().await.unwrap();
-        let rent = rent.minimum_balance(spl_token_2::state::Account::LEN);
+        let rent = rent.minimum_balance(spl_token::state::Account::LEN);
 
         let tx = Transaction::new_signed_with_payer(
             &[
-                system_program::create_account(
+                system_instruction::create_account(
                     &self.context.payer.pubkey(),
                     &token_account.pubkey(),
                     rent,
-                    spl_token_2::state::Account::LEN as u64,
-                    &spl_token_2::id(),
+                    spl_token::state::Account::LEN as u64,
+                    &spl_token::id(),
                 ),
-                spl_token_2::instruction::initialize_account(
-                    &spl_token_2::id(),
+                spl_token::instruction::initialize_account(
+                    &spl_token::id(),
                     &token_account.pubkey(),
                     &self.usdt_mint,
                     owner,
@@ -132,8 +295,8 @@ impl CollateralVaultProgramTest {
 
     pub async fn mint_tokens(&mut self, token_account: &Pubkey, amount: u64) {
         let tx = Transaction::new_signed_with_payer(
-            &[spl_token_2::instruction::mint_to(
-                &spl_token_2::id(),
+            &[spl_token::instruction::mint_to(
+                &spl_token::id(),
                 &self.usdt_mint,
                 token_account,
                 &self.user_keypair.pubkey(), // Mint authority
@@ -169,33 +332,147 @@ impl CollateralVaultProgramTest {
 
     pub async fn get_vault_account(&mut self, vault_pda: &Pubkey) -> CollateralVault {
         let data = self.get_account_data(vault_pda).await.unwrap();
-        CollateralVault::try_from_slice(&data[8..]).unwrap()
+        CollateralVault::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    pub async fn get_share_pool_account(&mut self) -> SharePool {
+        let (share_pool, _) = self.find_share_pool_pda(&self.usdt_mint);
+        let data = self.get_account_data(&share_pool).await.unwrap();
+        SharePool::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    pub async fn get_strategy_account(&mut self) -> Strategy {
+        let (strategy, _) = self.find_strategy_pda(&self.usdt_mint, &mock_strategy::id());
+        let data = self.get_account_data(&strategy).await.unwrap();
+        Strategy::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    pub async fn get_mint_state_account(&mut self) -> MintState {
+        let (mint_state, _) = self.find_mint_state_pda(&self.usdt_mint);
+        let data = self.get_account_data(&mint_state).await.unwrap();
+        MintState::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    pub async fn get_insurance_fund_account(&mut self) -> InsuranceFund {
+        let (insurance_fund, _) = self.find_insurance_fund_pda(&self.usdt_mint);
+        let data = self.get_account_data(&insurance_fund).await.unwrap();
+        InsuranceFund::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    pub async fn get_bad_debt_account(&mut self, index: u64) -> BadDebt {
+        let (bad_debt, _) = self.find_bad_debt_pda(index);
+        let data = self.get_account_data(&bad_debt).await.unwrap();
+        BadDebt::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    pub async fn get_reward_pool_account(&mut self) -> RewardPool {
+        let (reward_pool, _) = self.find_reward_pool_pda();
+        let data = self.get_account_data(&reward_pool).await.unwrap();
+        RewardPool::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    pub async fn get_reward_position_account(&mut self, vault: &Pubkey) -> RewardPosition {
+        let (reward_position, _) = self.find_reward_position_pda(vault);
+        let data = self.get_account_data(&reward_position).await.unwrap();
+        RewardPosition::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    pub async fn get_protocol_state_account(&mut self) -> ProtocolState {
+        let protocol_state_pda = self.protocol_state_pda;
+        let data = self.get_account_data(&protocol_state_pda).await.unwrap();
+        ProtocolState::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    pub async fn get_broken_program_account(&mut self, program_id: &Pubkey) -> BrokenProgram {
+        let (broken_program, _) = self.find_broken_program_pda(program_id);
+        let data = self.get_account_data(&broken_program).await.unwrap();
+        BrokenProgram::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    pub async fn get_cooldown_config_account(&mut self) -> CooldownConfig {
+        let (cooldown_config, _) = self.find_cooldown_pda(&self.usdt_mint);
+        let data = self.get_account_data(&cooldown_config).await.unwrap();
+        CooldownConfig::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    pub async fn get_spending_policy_account(&mut self, vault_pda: &Pubkey) -> SpendingPolicy {
+        let (spending_policy, _) = self.find_spending_policy_pda(vault_pda);
+        let data = self.get_account_data(&spending_policy).await.unwrap();
+        SpendingPolicy::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    pub async fn get_vault_operator_account(&mut self, vault_pda: &Pubkey, operator: &Pubkey) -> VaultOperator {
+        let (vault_operator, _) = self.find_operator_pda(vault_pda, operator);
+        let data = self.get_account_data(&vault_operator).await.unwrap();
+        VaultOperator::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    pub async fn get_owner_index_account(&mut self, owner: &Pubkey) -> OwnerIndex {
+        let (owner_index, _) = self.find_owner_index_pda(owner);
+        let data = self.get_account_data(&owner_index).await.unwrap();
+        OwnerIndex::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    pub async fn get_recovery_config_account(&mut self, vault_pda: &Pubkey) -> RecoveryConfig {
+        let (recovery_config, _) = self.find_recovery_pda(vault_pda);
+        let data = self.get_account_data(&recovery_config).await.unwrap();
+        RecoveryConfig::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    pub async fn get_session_key_account(&mut self, vault_pda: &Pubkey, session_key: &Pubkey) -> SessionKey {
+        let (session, _) = self.find_session_pda(vault_pda, session_key);
+        let data = self.get_account_data(&session).await.unwrap();
+        SessionKey::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    pub async fn get_multisig_account(&mut self, multisig: &Pubkey) -> Multisig {
+        let data = self.get_account_data(multisig).await.unwrap();
+        Multisig::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    pub async fn get_multisig_proposal_account(&mut self, proposal: &Pubkey) -> MultisigProposal {
+        let data = self.get_account_data(proposal).await.unwrap();
+        MultisigProposal::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    pub async fn get_rate_limit_account(&mut self, program_id: &Pubkey) -> ProgramRateLimit {
+        let (rate_limit, _) = self.find_rate_limit_pda(program_id);
+        let data = self.get_account_data(&rate_limit).await.unwrap();
+        ProgramRateLimit::try_deserialize(&mut data.as_slice()).unwrap()
     }
 
     pub async fn get_authority_account(&mut self) -> VaultAuthority {
-        let data = self.get_account_data(&self.authority_pda).await.unwrap();
-        VaultAuthority::try_from_slice(&data[8..]).unwrap()
+        let authority_pda = self.authority_pda;
+        let data = self.get_account_data(&authority_pda).await.unwrap();
+        VaultAuthority::try_deserialize(&mut data.as_slice()).unwrap()
     }
 
     pub async fn get_token_balance(&mut self, token_account_pubkey: &Pubkey) -> u64 {
         let data = self.get_account_data(token_account_pubkey).await.unwrap();
-        let token_account = spl_token_2::state::Account::unpack(&data).unwrap();
+        let token_account = spl_token::state::Account::unpack(&data).unwrap();
         token_account.amount
     }
 
     pub async fn warp_to_slot(&mut self, slot: u64) {
-        self.context.warp_to_slot(slot).await.unwrap();
+        self.context.warp_to_slot(slot).unwrap();
     }
     
     pub async fn get_clock(&mut self) -> Clock {
-        self.context.banks_client.get_clock().await.unwrap()
+        self.context.banks_client.get_sysvar::<Clock>().await.unwrap()
+    }
+
+    /// Moves the clock's unix timestamp forward without changing the slot.
+    pub async fn advance_clock(&mut self, seconds: i64) {
+        let mut clock = self.get_clock().await;
+        clock.unix_timestamp += seconds;
+        self.context.set_sysvar(&clock);
     }
 
     pub async fn process_transaction(
         &mut self,
         instructions: &[Instruction],
         signers: &[&Keypair],
-    ) -> Result<(), BanksClientError> {
+    ) -> std::result::Result<(), BanksClientError> {
         let mut all_signers = vec![&self.context.payer];
         all_signers.extend(signers);
 
@@ -208,18 +485,197 @@ impl CollateralVaultProgramTest {
         self.context.banks_client.process_transaction(tx).await
     }
 
+    /// Simulates the transaction and returns its program log. Unlike the
+    /// account state, the log keeps events emitted before a failing
+    /// instruction reverted.
+    pub async fn simulate_transaction_logs(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Vec<String> {
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend(signers);
+
+        let tx = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.context.payer.pubkey()),
+            &all_signers,
+            self.context.last_blockhash,
+        );
+        let simulation = self.context.banks_client.simulate_transaction(tx).await.unwrap();
+        simulation.simulation_details.unwrap().logs
+    }
+
+    // --- Scenario Helpers ---
+
+    /// Initializes the authority and a funded vault for the test user.
+    /// Returns the vault, its token account and the user's token account.
+    pub async fn setup_vault(&mut self, initial_deposit: u64) -> (Pubkey, Pubkey, Pubkey) {
+        let user_pubkey = self.user_pubkey();
+        let user_ata = self.create_and_fund_user_ata(&user_pubkey).await;
+        let (vault_pda, _) = self.find_vault_pda(&user_pubkey);
+        let vault_ata = self.find_vault_token_account(&vault_pda);
+
+        let init_auth_ix = self.initialize_authority_ix();
+        let init_vault_ix = self.initialize_vault_ix(
+            &user_pubkey,
+            &vault_pda,
+            &vault_ata,
+            &user_ata,
+            initial_deposit,
+        );
+        let user_keypair = self.user_keypair.insecure_clone();
+        self.process_transaction(&[init_auth_ix, init_vault_ix], &[&user_keypair])
+            .await
+            .unwrap();
+
+        (vault_pda, vault_ata, user_ata)
+    }
+
+    /// Opens a further subaccount vault for the test user, funded from
+    /// `user_ata`. Expects the authority to be initialized already.
+    pub async fn setup_subaccount_vault(
+        &mut self,
+        subaccount: u16,
+        user_ata: &Pubkey,
+        initial_deposit: u64,
+    ) -> (Pubkey, Pubkey) {
+        let user_pubkey = self.user_pubkey();
+        let (vault_pda, _) = self.find_subaccount_vault_pda(&user_pubkey, subaccount);
+        let vault_ata = self.find_vault_token_account(&vault_pda);
+
+        let init_vault_ix = self.initialize_subaccount_vault_ix(
+            &user_pubkey,
+            subaccount,
+            &vault_pda,
+            &vault_ata,
+            user_ata,
+            initial_deposit,
+        );
+        let user_keypair = self.user_keypair.insecure_clone();
+        self.process_transaction(&[init_vault_ix], &[&user_keypair])
+            .await
+            .unwrap();
+
+        (vault_pda, vault_ata)
+    }
+
+    /// Initializes the authority and a multisig of `members` that owns a
+    /// funded vault, paid for by the test user, who must be a member.
+    /// Returns the multisig, its vault and the user's token account.
+    pub async fn setup_multisig_vault(
+        &mut self,
+        members: Vec<Pubkey>,
+        threshold: u8,
+        initial_deposit: u64,
+    ) -> (Pubkey, Pubkey, Pubkey) {
+        let user_pubkey = self.user_pubkey();
+        let user_ata = self.create_and_fund_user_ata(&user_pubkey).await;
+        let create_key = Pubkey::new_unique();
+        let (multisig, _) = self.find_multisig_pda(&create_key);
+        let (vault_pda, _) = self.find_subaccount_vault_pda(&multisig, 0);
+
+        let init_auth_ix = self.initialize_authority_ix();
+        let create_ix = self.create_multisig_ix(&user_pubkey, &create_key, members, threshold);
+        let init_vault_ix =
+            self.initialize_multisig_vault_ix(&user_pubkey, &multisig, 0, &user_ata, initial_deposit);
+        let user_keypair = self.user_keypair.insecure_clone();
+        self.process_transaction(&[init_auth_ix, create_ix, init_vault_ix], &[&user_keypair])
+            .await
+            .unwrap();
+
+        (multisig, vault_pda, user_ata)
+    }
+
+    /// Allowlists this program itself, so tests may call the instructions
+    /// reserved for authorized programs directly.
+    pub async fn authorize_test_caller(&mut self) {
+        let add_program_ix = self.add_authorized_program_ix(&self.program_id);
+        self.process_transaction(&[add_program_ix], &[]).await.unwrap();
+    }
+
+    /// Initializes the insurance fund of the test mint and pays
+    /// `contribution` into it from `contributor_ata`.
+    pub async fn setup_insurance_fund(&mut self, contributor_ata: &Pubkey, contribution: u64) {
+        let init_ix = self.initialize_insurance_fund_ix();
+        self.process_transaction(&[init_ix], &[]).await.unwrap();
+        if contribution > 0 {
+            let contribute_ix = self.contribute_insurance_ix(contributor_ata, contribution);
+            let user_keypair = self.user_keypair.insecure_clone();
+            self.process_transaction(&[contribute_ix], &[&user_keypair])
+                .await
+                .unwrap();
+        }
+    }
+
+    /// Funds the reward pool with `amount` freshly minted tokens held by the
+    /// test payer as admin.
+    pub async fn fund_reward_pool(&mut self, amount: u64) {
+        let admin = self.context.payer.pubkey();
+        let admin_ata = self.create_token_account(&admin).await;
+        self.mint_tokens(&admin_ata, amount).await;
+
+        let fund_ix = self.fund_reward_pool_ix(&admin_ata, amount);
+        self.process_transaction(&[fund_ix], &[]).await.unwrap();
+    }
+
+    /// Sets up a vault whose `deposit` is held as shares of the mint's
+    /// share pool. The minimum initial deposit is withdrawn again so the
+    /// empty vault may switch to share accounting.
+    pub async fn setup_pooled_vault(&mut self, deposit: u64) -> (Pubkey, Pubkey, Pubkey) {
+        let user_pubkey = self.user_pubkey();
+        let initial_deposit = collateral_vault_testing::constants::MIN_DEPOSIT_AMOUNT;
+        let (vault_pda, vault_ata, user_ata) = self.setup_vault(initial_deposit).await;
+
+        let withdraw_ix = self.withdraw_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, initial_deposit);
+        let init_pool_ix = self.initialize_share_pool_ix();
+        let pooled_ix = self.set_pooled_accounting_ix(&user_pubkey, &vault_pda, true);
+        let deposit_ix = self.deposit_pooled_ix(&user_pubkey, &user_ata, &vault_pda, &vault_ata, deposit);
+        let user_keypair = self.user_keypair.insecure_clone();
+        self.process_transaction(
+            &[withdraw_ix, init_pool_ix, pooled_ix, deposit_ix],
+            &[&user_keypair],
+        )
+        .await
+        .unwrap();
+
+        (vault_pda, vault_ata, user_ata)
+    }
+
+    /// Allowlists the mock strategy for the share pool with the test payer
+    /// as keeper.
+    pub async fn setup_mock_strategy(&mut self, reserve_bps: u16, max_allocation_bps: u16) {
+        let keeper = self.context.payer.pubkey();
+        let init_strategy_ix = self.initialize_mock_strategy_ix();
+        let configure_ix = self.configure_pool_strategies_ix(&keeper, reserve_bps);
+        let set_strategy_ix = self.set_strategy_ix(max_allocation_bps, true);
+        self.process_transaction(&[init_strategy_ix, configure_ix, set_strategy_ix], &[])
+            .await
+            .unwrap();
+    }
+
     // --- Instruction Helper ---
 
+    /// Rebuilds an admin instruction with `signer` in place of the test
+    /// payer, for exercising admin checks.
+    pub fn as_signer(&self, mut instruction: Instruction, signer: &Pubkey) -> Instruction {
+        let admin = self.context.payer.pubkey();
+        for meta in instruction.accounts.iter_mut().filter(|meta| meta.pubkey == admin) {
+            meta.pubkey = *signer;
+        }
+        instruction
+    }
+
     pub fn initialize_authority_ix(&self) -> Instruction {
         collateral_vault_testing::instruction::InitializeAuthority {}
             .to_instruction(
                 collateral_vault_testing::accounts::InitializeAuthority {
                     admin: self.context.payer.pubkey(), // Use test payer as admin
                     authority: self.authority_pda,
+                    protocol_state: self.protocol_state_pda,
                     system_program: system_program::id(),
                 },
             )
-            .unwrap()
     }
     
     pub fn initialize_vault_ix(
@@ -230,20 +686,71 @@ impl CollateralVaultProgramTest {
         user_token_account: &Pubkey,
         initial_deposit: u64,
     ) -> Instruction {
-        collateral_vault_testing::instruction::InitializeVault { initial_deposit }
+        self.initialize_subaccount_vault_ix(
+            user,
+            0,
+            vault_pda,
+            vault_token_account,
+            user_token_account,
+            initial_deposit,
+        )
+    }
+
+    pub fn initialize_subaccount_vault_ix(
+        &self,
+        user: &Pubkey,
+        subaccount: u16,
+        vault_pda: &Pubkey,
+        vault_token_account: &Pubkey,
+        user_token_account: &Pubkey,
+        initial_deposit: u64,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::InitializeVault { subaccount, initial_deposit }
             .to_instruction(
                 collateral_vault_testing::accounts::InitializeVault {
                     user: *user,
                     vault: *vault_pda,
-                    authority: self.authority_pda,
+                    owner_index: self.find_owner_index_pda(user).0,
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
                     vault_token_account: *vault_token_account,
                     user_token_account: *user_token_account,
                     mint: self.usdt_mint,
-                    token_program: spl_token_2::id(),
+                    treasury_token_account: None,
+                    token_program: spl_token::id(),
+                    associated_token_program: associated_token::ID,
+                    system_program: system_program::id(),
+                },
+            )
+    }
+
+    pub fn initialize_vault_sponsored_ix(
+        &self,
+        sponsor: &Pubkey,
+        user: &Pubkey,
+        subaccount: u16,
+        user_token_account: &Pubkey,
+        initial_deposit: u64,
+    ) -> Instruction {
+        let (vault_pda, _) = self.find_subaccount_vault_pda(user, subaccount);
+        collateral_vault_testing::instruction::InitializeVaultSponsored { subaccount, initial_deposit }
+            .to_instruction(
+                collateral_vault_testing::accounts::InitializeVaultSponsored {
+                    sponsor: *sponsor,
+                    user: *user,
+                    vault: vault_pda,
+                    owner_index: self.find_owner_index_pda(user).0,
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                    vault_token_account: self.find_vault_token_account(&vault_pda),
+                    user_token_account: *user_token_account,
+                    mint: self.usdt_mint,
+                    treasury_token_account: None,
+                    token_program: spl_token::id(),
+                    associated_token_program: associated_token::ID,
                     system_program: system_program::id(),
                 },
             )
-            .unwrap()
     }
 
     pub fn deposit_ix(
@@ -260,15 +767,2022 @@ impl CollateralVaultProgramTest {
                     user: *user,
                     user_token_account: *user_token_account,
                     vault: *vault_pda,
+                    vault_operator: None,
+                    multisig: None,
+                    session: None,
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
                     vault_token_account: *vault_token_account,
-                    token_program: spl_token_2::id(),
+                    treasury_token_account: None,
+                    token_program: spl_token::id(),
                 },
             )
-            .unwrap()
     }
-}
 
-// --- Private Helpers ---
+    pub fn deposit_for_ix(
+        &self,
+        payer: &Pubkey,
+        payer_token_account: &Pubkey,
+        beneficiary: &Pubkey,
+        subaccount: u16,
+        amount: u64,
+    ) -> Instruction {
+        let (vault_pda, _) = self.find_subaccount_vault_pda(beneficiary, subaccount);
+        collateral_vault_testing::instruction::DepositFor {
+            beneficiary: *beneficiary,
+            subaccount,
+            amount,
+        }
+        .to_instruction(
+            collateral_vault_testing::accounts::DepositFor {
+                payer: *payer,
+                vault: vault_pda,
+                owner_index: self.find_owner_index_pda(beneficiary).0,
+                protocol_state: self.protocol_state_pda,
+                mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                vault_token_account: self.find_vault_token_account(&vault_pda),
+                payer_token_account: *payer_token_account,
+                mint: self.usdt_mint,
+                treasury_token_account: None,
+                token_program: spl_token::id(),
+                associated_token_program: associated_token::ID,
+                system_program: system_program::id(),
+            },
+        )
+    }
+
+    /// Deposits for `beneficiary` while a deposit fee is set, paying the fee
+    /// into `treasury_token_account`.
+    pub fn deposit_for_with_fee_ix(
+        &self,
+        payer: &Pubkey,
+        payer_token_account: &Pubkey,
+        beneficiary: &Pubkey,
+        treasury_token_account: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        let (vault_pda, _) = self.find_subaccount_vault_pda(beneficiary, 0);
+        collateral_vault_testing::instruction::DepositFor {
+            beneficiary: *beneficiary,
+            subaccount: 0,
+            amount,
+        }
+        .to_instruction(
+            collateral_vault_testing::accounts::DepositFor {
+                payer: *payer,
+                vault: vault_pda,
+                owner_index: self.find_owner_index_pda(beneficiary).0,
+                protocol_state: self.protocol_state_pda,
+                mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                vault_token_account: self.find_vault_token_account(&vault_pda),
+                payer_token_account: *payer_token_account,
+                mint: self.usdt_mint,
+                treasury_token_account: Some(*treasury_token_account),
+                token_program: spl_token::id(),
+                associated_token_program: associated_token::ID,
+                system_program: system_program::id(),
+            },
+        )
+    }
+
+    /// Ed25519 program check of `signer`'s signature over the borsh encoded
+    /// `message`, with all offsets pointing into the instruction itself.
+    pub fn permit_signature_ix(&self, signer: &Keypair, message: &PermitMessage) -> Instruction {
+        let message = message.try_to_vec().unwrap();
+        let signature = signer.sign_message(&message);
+
+        let pubkey_offset: u16 = 2 + 14;
+        let signature_offset = pubkey_offset + 32;
+        let message_offset = signature_offset + 64;
+
+        let mut data = vec![1, 0];
+        for value in [
+            signature_offset,
+            u16::MAX,
+            pubkey_offset,
+            u16::MAX,
+            message_offset,
+            message.len() as u16,
+            u16::MAX,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(signer.pubkey().as_ref());
+        data.extend_from_slice(signature.as_ref());
+        data.extend_from_slice(&message);
+
+        Instruction {
+            program_id: solana_sdk::ed25519_program::id(),
+            accounts: vec![],
+            data,
+        }
+    }
+
+    /// Approves the vault PDA to move up to `amount` from the owner's token
+    /// account, as a permit deposit requires.
+    pub fn approve_vault_delegate_ix(
+        &self,
+        owner: &Pubkey,
+        owner_token_account: &Pubkey,
+        vault_pda: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        spl_token::instruction::approve(
+            &spl_token::id(),
+            owner_token_account,
+            vault_pda,
+            owner,
+            &[],
+            amount,
+        )
+        .unwrap()
+    }
+
+    pub fn permit_deposit_ix(
+        &self,
+        relayer: &Pubkey,
+        vault_pda: &Pubkey,
+        owner_token_account: &Pubkey,
+        vault_token_account: &Pubkey,
+        relayer_token_account: Option<&Pubkey>,
+        message: &PermitMessage,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::PermitDeposit {
+            amount: message.amount,
+            relayer_fee: message.relayer_fee,
+            nonce: message.nonce,
+            expiry: message.expiry,
+        }
+        .to_instruction(
+            collateral_vault_testing::accounts::PermitDeposit {
+                relayer: *relayer,
+                vault: *vault_pda,
+                protocol_state: self.protocol_state_pda,
+                mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                owner_token_account: *owner_token_account,
+                vault_token_account: *vault_token_account,
+                relayer_token_account: relayer_token_account.copied(),
+                instructions_sysvar: solana_sdk::sysvar::instructions::id(),
+                treasury_token_account: None,
+                token_program: spl_token::id(),
+            },
+        )
+    }
+
+    pub fn permit_withdraw_ix(
+        &self,
+        relayer: &Pubkey,
+        vault_pda: &Pubkey,
+        owner_token_account: &Pubkey,
+        vault_token_account: &Pubkey,
+        relayer_token_account: Option<&Pubkey>,
+        message: &PermitMessage,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::PermitWithdraw {
+            amount: message.amount,
+            relayer_fee: message.relayer_fee,
+            nonce: message.nonce,
+            expiry: message.expiry,
+        }
+        .to_instruction(
+            collateral_vault_testing::accounts::PermitWithdraw {
+                relayer: *relayer,
+                vault: *vault_pda,
+                cosigner: None,
+                protocol_state: self.protocol_state_pda,
+                mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                owner_token_account: *owner_token_account,
+                vault_token_account: *vault_token_account,
+                relayer_token_account: relayer_token_account.copied(),
+                cooldown_config: self.find_cooldown_pda(&self.usdt_mint).0,
+                spending_policy: self.find_spending_policy_pda(vault_pda).0,
+                instructions_sysvar: solana_sdk::sysvar::instructions::id(),
+                treasury_token_account: None,
+                token_program: spl_token::id(),
+            },
+        )
+    }
+
+    pub fn close_vault_ix(
+        &self,
+        user: &Pubkey,
+        vault_pda: &Pubkey,
+        user_token_account: &Pubkey,
+        vault_token_account: &Pubkey,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::CloseVault {}
+            .to_instruction(
+                collateral_vault_testing::accounts::CloseVault {
+                    user: *user,
+                    vault: *vault_pda,
+                    cosigner: None,
+                    rent_payer: *user,
+                    owner_index: self.find_owner_index_pda(user).0,
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                    user_token_account: *user_token_account,
+                    vault_token_account: *vault_token_account,
+                    cooldown_config: self.find_cooldown_pda(&self.usdt_mint).0,
+                    spending_policy: self.find_spending_policy_pda(vault_pda).0,
+                    treasury_token_account: None,
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    /// Closes `vault_pda` while a withdraw fee is set, paying the fee on its
+    /// balance into `treasury_token_account`.
+    pub fn close_vault_with_fee_ix(
+        &self,
+        user: &Pubkey,
+        vault_pda: &Pubkey,
+        user_token_account: &Pubkey,
+        vault_token_account: &Pubkey,
+        treasury_token_account: &Pubkey,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::CloseVault {}
+            .to_instruction(
+                collateral_vault_testing::accounts::CloseVault {
+                    user: *user,
+                    vault: *vault_pda,
+                    cosigner: None,
+                    rent_payer: *user,
+                    owner_index: self.find_owner_index_pda(user).0,
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                    user_token_account: *user_token_account,
+                    vault_token_account: *vault_token_account,
+                    cooldown_config: self.find_cooldown_pda(&self.usdt_mint).0,
+                    spending_policy: self.find_spending_policy_pda(vault_pda).0,
+                    treasury_token_account: Some(*treasury_token_account),
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    pub fn withdraw_ix(
+        &self,
+        user: &Pubkey,
+        vault_pda: &Pubkey,
+        vault_token_account: &Pubkey,
+        user_token_account: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::Withdraw { amount }
+            .to_instruction(
+                collateral_vault_testing::accounts::Withdraw {
+                    user: *user,
+                    vault: *vault_pda,
+                    cosigner: None,
+                    vault_operator: None,
+                    session: None,
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                    user_token_account: *user_token_account,
+                    vault_token_account: *vault_token_account,
+                    cooldown_config: self.find_cooldown_pda(&self.usdt_mint).0,
+                    spending_policy: self.find_spending_policy_pda(vault_pda).0,
+                    treasury_token_account: None,
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    pub fn initialize_share_pool_ix(&self) -> Instruction {
+        collateral_vault_testing::instruction::InitializeSharePool {}
+            .to_instruction(
+                collateral_vault_testing::accounts::InitializeSharePool {
+                    admin: self.context.payer.pubkey(),
+                    authority: self.authority_pda,
+                    mint: self.usdt_mint,
+                    share_pool: self.find_share_pool_pda(&self.usdt_mint).0,
+                    pool_token_account: self.find_pool_token_account(),
+                    token_program: spl_token::id(),
+                    associated_token_program: associated_token::ID,
+                    system_program: system_program::id(),
+                },
+            )
+    }
+
+    pub fn set_pooled_accounting_ix(&self, user: &Pubkey, vault_pda: &Pubkey, pooled: bool) -> Instruction {
+        collateral_vault_testing::instruction::SetPooledAccounting { pooled }
+            .to_instruction(
+                collateral_vault_testing::accounts::SetPooledAccounting {
+                    user: *user,
+                    vault: *vault_pda,
+                },
+            )
+    }
+
+    pub fn deposit_pooled_ix(
+        &self,
+        user: &Pubkey,
+        user_token_account: &Pubkey,
+        vault_pda: &Pubkey,
+        vault_token_account: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::DepositPooled { amount }
+            .to_instruction(
+                collateral_vault_testing::accounts::DepositPooled {
+                    user: *user,
+                    vault: *vault_pda,
+                    vault_token_account: *vault_token_account,
+                    share_pool: self.find_share_pool_pda(&self.usdt_mint).0,
+                    pool_token_account: self.find_pool_token_account(),
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                    user_token_account: *user_token_account,
+                    treasury_token_account: None,
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    pub fn withdraw_pooled_ix(
+        &self,
+        user: &Pubkey,
+        vault_pda: &Pubkey,
+        vault_token_account: &Pubkey,
+        user_token_account: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::WithdrawPooled { amount }
+            .to_instruction(
+                collateral_vault_testing::accounts::WithdrawPooled {
+                    user: *user,
+                    vault: *vault_pda,
+                    cosigner: None,
+                    vault_token_account: *vault_token_account,
+                    share_pool: self.find_share_pool_pda(&self.usdt_mint).0,
+                    pool_token_account: self.find_pool_token_account(),
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                    user_token_account: *user_token_account,
+                    cooldown_config: self.find_cooldown_pda(&self.usdt_mint).0,
+                    spending_policy: self.find_spending_policy_pda(vault_pda).0,
+                    treasury_token_account: None,
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    pub fn add_pool_yield_ix(&self, contributor: &Pubkey, contributor_token_account: &Pubkey, amount: u64) -> Instruction {
+        collateral_vault_testing::instruction::AddPoolYield { amount }
+            .to_instruction(
+                collateral_vault_testing::accounts::AddPoolYield {
+                    contributor: *contributor,
+                    share_pool: self.find_share_pool_pda(&self.usdt_mint).0,
+                    pool_token_account: self.find_pool_token_account(),
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                    contributor_token_account: *contributor_token_account,
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    pub fn initialize_mock_strategy_ix(&self) -> Instruction {
+        Instruction {
+            program_id: mock_strategy::id(),
+            accounts: mock_strategy::accounts::Initialize {
+                payer: self.context.payer.pubkey(),
+                mint: self.usdt_mint,
+                state: self.find_mock_strategy_state(),
+                strategy_token_account: self.find_mock_strategy_token_account(),
+                token_program: spl_token::id(),
+                associated_token_program: associated_token::ID,
+                system_program: system_program::id(),
+            }
+            .to_account_metas(None),
+            data: mock_strategy::instruction::Initialize {
+                depositor: self.find_share_pool_pda(&self.usdt_mint).0,
+            }
+            .data(),
+        }
+    }
+
+    pub fn configure_pool_strategies_ix(&self, keeper: &Pubkey, reserve_bps: u16) -> Instruction {
+        collateral_vault_testing::instruction::ConfigurePoolStrategies {
+            keeper: *keeper,
+            reserve_bps,
+        }
+        .to_instruction(
+            collateral_vault_testing::accounts::ConfigurePoolStrategies {
+                admin: self.context.payer.pubkey(),
+                authority: self.authority_pda,
+                share_pool: self.find_share_pool_pda(&self.usdt_mint).0,
+            },
+        )
+    }
+
+    pub fn set_strategy_ix(&self, max_allocation_bps: u16, active: bool) -> Instruction {
+        collateral_vault_testing::instruction::SetStrategy { max_allocation_bps, active }
+            .to_instruction(
+                collateral_vault_testing::accounts::SetStrategy {
+                    admin: self.context.payer.pubkey(),
+                    authority: self.authority_pda,
+                    share_pool: self.find_share_pool_pda(&self.usdt_mint).0,
+                    strategy_program: mock_strategy::id(),
+                    strategy: self.find_strategy_pda(&self.usdt_mint, &mock_strategy::id()).0,
+                    system_program: system_program::id(),
+                },
+            )
+    }
+
+    pub fn deploy_to_strategy_ix(&self, amount: u64) -> Instruction {
+        collateral_vault_testing::instruction::DeployToStrategy { amount }
+            .to_instruction(
+                collateral_vault_testing::accounts::DeployToStrategy {
+                    keeper: self.context.payer.pubkey(),
+                    authority: self.authority_pda,
+                    protocol_state: self.protocol_state_pda,
+                    share_pool: self.find_share_pool_pda(&self.usdt_mint).0,
+                    pool_token_account: self.find_pool_token_account(),
+                    strategy: self.find_strategy_pda(&self.usdt_mint, &mock_strategy::id()).0,
+                    strategy_program: mock_strategy::id(),
+                    strategy_state: self.find_mock_strategy_state(),
+                    strategy_token_account: self.find_mock_strategy_token_account(),
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    pub fn recall_from_strategy_ix(&self, amount: u64) -> Instruction {
+        collateral_vault_testing::instruction::RecallFromStrategy { amount }
+            .to_instruction(
+                collateral_vault_testing::accounts::RecallFromStrategy {
+                    keeper: self.context.payer.pubkey(),
+                    authority: self.authority_pda,
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                    share_pool: self.find_share_pool_pda(&self.usdt_mint).0,
+                    pool_token_account: self.find_pool_token_account(),
+                    strategy: self.find_strategy_pda(&self.usdt_mint, &mock_strategy::id()).0,
+                    strategy_program: mock_strategy::id(),
+                    strategy_state: self.find_mock_strategy_state(),
+                    strategy_token_account: self.find_mock_strategy_token_account(),
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    pub fn write_down_strategy_ix(&self, loss: u64) -> Instruction {
+        collateral_vault_testing::instruction::WriteDownStrategy { loss }
+            .to_instruction(
+                collateral_vault_testing::accounts::WriteDownStrategy {
+                    admin: self.context.payer.pubkey(),
+                    authority: self.authority_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                    share_pool: self.find_share_pool_pda(&self.usdt_mint).0,
+                    strategy: self.find_strategy_pda(&self.usdt_mint, &mock_strategy::id()).0,
+                },
+            )
+    }
+
+    pub fn add_authorized_program_ix(&self, program_id: &Pubkey) -> Instruction {
+        collateral_vault_testing::instruction::AddAuthorizedProgram { program_id: *program_id }
+            .to_instruction(
+                collateral_vault_testing::accounts::AddAuthorizedProgram {
+                    admin: self.context.payer.pubkey(),
+                    authority: self.authority_pda,
+                },
+            )
+    }
+
+    pub fn set_rate_limit_ix(&self, program_id: &Pubkey, capacity: u64, window_slots: u64) -> Instruction {
+        collateral_vault_testing::instruction::SetRateLimit {
+            program_id: *program_id,
+            capacity,
+            window_slots,
+        }
+        .to_instruction(
+            collateral_vault_testing::accounts::SetRateLimit {
+                admin: self.context.payer.pubkey(),
+                authority: self.authority_pda,
+                rate_limit: self.find_rate_limit_pda(program_id).0,
+                system_program: system_program::id(),
+            },
+        )
+    }
+
+    pub fn remove_rate_limit_ix(&self, program_id: &Pubkey) -> Instruction {
+        collateral_vault_testing::instruction::RemoveRateLimit { program_id: *program_id }
+            .to_instruction(
+                collateral_vault_testing::accounts::RemoveRateLimit {
+                    admin: self.context.payer.pubkey(),
+                    authority: self.authority_pda,
+                    rate_limit: self.find_rate_limit_pda(program_id).0,
+                },
+            )
+    }
+
+    pub fn configure_circuit_breaker_ix(
+        &self,
+        max_outflow_bps: u16,
+        window_seconds: i64,
+        trip_status: ProtocolStatus,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::ConfigureCircuitBreaker {
+            max_outflow_bps,
+            window_seconds,
+            trip_status,
+        }
+        .to_instruction(
+            collateral_vault_testing::accounts::ConfigureCircuitBreaker {
+                admin: self.context.payer.pubkey(),
+                authority: self.authority_pda,
+                protocol_state: self.protocol_state_pda,
+            },
+        )
+    }
+
+    /// `with_mint_state` passes the test mint's accounting so its window
+    /// restarts along with the breaker.
+    pub fn reset_circuit_breaker_ix(&self, with_mint_state: bool) -> Instruction {
+        collateral_vault_testing::instruction::ResetCircuitBreaker {}
+            .to_instruction(
+                collateral_vault_testing::accounts::ResetCircuitBreaker {
+                    admin: self.context.payer.pubkey(),
+                    authority: self.authority_pda,
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: with_mint_state.then(|| self.find_mint_state_pda(&self.usdt_mint).0),
+                },
+            )
+    }
+
+    pub fn set_protocol_status_ix(&self, status: ProtocolStatus) -> Instruction {
+        collateral_vault_testing::instruction::SetProtocolStatus { status }
+            .to_instruction(
+                collateral_vault_testing::accounts::SetProtocolStatus {
+                    admin: self.context.payer.pubkey(),
+                    authority: self.authority_pda,
+                    protocol_state: self.protocol_state_pda,
+                },
+            )
+    }
+
+    pub fn configure_fees_ix(
+        &self,
+        deposit_fee_bps: u16,
+        withdraw_fee_bps: u16,
+        transfer_fee_bps: u16,
+        min_fee: u64,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::ConfigureFees {
+            deposit_fee_bps,
+            withdraw_fee_bps,
+            transfer_fee_bps,
+            min_fee,
+        }
+        .to_instruction(
+            collateral_vault_testing::accounts::ConfigureFees {
+                admin: self.context.payer.pubkey(),
+                authority: self.authority_pda,
+                protocol_state: self.protocol_state_pda,
+            },
+        )
+    }
+
+    pub fn set_fee_manager_ix(&self, fee_manager: &Pubkey) -> Instruction {
+        collateral_vault_testing::instruction::SetFeeManager { fee_manager: *fee_manager }
+            .to_instruction(
+                collateral_vault_testing::accounts::SetFeeManager {
+                    admin: self.context.payer.pubkey(),
+                    authority: self.authority_pda,
+                    protocol_state: self.protocol_state_pda,
+                },
+            )
+    }
+
+    pub fn withdraw_fees_ix(
+        &self,
+        signer: &Pubkey,
+        treasury_token_account: &Pubkey,
+        destination: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::WithdrawFees { amount }
+            .to_instruction(
+                collateral_vault_testing::accounts::WithdrawFees {
+                    signer: *signer,
+                    authority: self.authority_pda,
+                    protocol_state: self.protocol_state_pda,
+                    treasury_token_account: *treasury_token_account,
+                    destination: *destination,
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    pub fn declare_emergency_ix(&self) -> Instruction {
+        collateral_vault_testing::instruction::DeclareEmergency {}
+            .to_instruction(
+                collateral_vault_testing::accounts::DeclareEmergency {
+                    admin: self.context.payer.pubkey(),
+                    authority: self.authority_pda,
+                    protocol_state: self.protocol_state_pda,
+                },
+            )
+    }
+
+    pub fn configure_emergency_ix(&self, emergency_pause_seconds: i64) -> Instruction {
+        collateral_vault_testing::instruction::ConfigureEmergency { emergency_pause_seconds }
+            .to_instruction(
+                collateral_vault_testing::accounts::ConfigureEmergency {
+                    admin: self.context.payer.pubkey(),
+                    authority: self.authority_pda,
+                    protocol_state: self.protocol_state_pda,
+                },
+            )
+    }
+
+    pub fn mark_program_broken_ix(&self, program_id: &Pubkey) -> Instruction {
+        collateral_vault_testing::instruction::MarkProgramBroken { program_id: *program_id }
+            .to_instruction(
+                collateral_vault_testing::accounts::MarkProgramBroken {
+                    admin: self.context.payer.pubkey(),
+                    authority: self.authority_pda,
+                    broken_program: self.find_broken_program_pda(program_id).0,
+                    system_program: system_program::id(),
+                },
+            )
+    }
+
+    pub fn clear_program_broken_ix(&self, program_id: &Pubkey) -> Instruction {
+        collateral_vault_testing::instruction::ClearProgramBroken {}
+            .to_instruction(
+                collateral_vault_testing::accounts::ClearProgramBroken {
+                    admin: self.context.payer.pubkey(),
+                    authority: self.authority_pda,
+                    broken_program: self.find_broken_program_pda(program_id).0,
+                },
+            )
+    }
+
+    /// Emergency exit of an unpooled vault, optionally through the broken
+    /// report of `broken_program_id`.
+    pub fn emergency_withdraw_ix(
+        &self,
+        user: &Pubkey,
+        vault_pda: &Pubkey,
+        vault_token_account: &Pubkey,
+        user_token_account: &Pubkey,
+        broken_program_id: Option<&Pubkey>,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::EmergencyWithdraw {}
+            .to_instruction(
+                collateral_vault_testing::accounts::EmergencyWithdraw {
+                    user: *user,
+                    vault: *vault_pda,
+                    authority: self.authority_pda,
+                    broken_program: broken_program_id.map(|program_id| self.find_broken_program_pda(program_id).0),
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                    user_token_account: *user_token_account,
+                    vault_token_account: *vault_token_account,
+                    share_pool: None,
+                    pool_token_account: None,
+                    strategy: None,
+                    strategy_program: None,
+                    strategy_state: None,
+                    strategy_token_account: None,
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    /// Emergency exit of a pooled vault, paid from idle pool tokens without
+    /// a strategy recall.
+    pub fn emergency_withdraw_pooled_ix(
+        &self,
+        user: &Pubkey,
+        vault_pda: &Pubkey,
+        vault_token_account: &Pubkey,
+        user_token_account: &Pubkey,
+        broken_program_id: Option<&Pubkey>,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::EmergencyWithdraw {}
+            .to_instruction(
+                collateral_vault_testing::accounts::EmergencyWithdraw {
+                    user: *user,
+                    vault: *vault_pda,
+                    authority: self.authority_pda,
+                    broken_program: broken_program_id.map(|program_id| self.find_broken_program_pda(program_id).0),
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                    user_token_account: *user_token_account,
+                    vault_token_account: *vault_token_account,
+                    share_pool: Some(self.find_share_pool_pda(&self.usdt_mint).0),
+                    pool_token_account: Some(self.find_pool_token_account()),
+                    strategy: None,
+                    strategy_program: None,
+                    strategy_state: None,
+                    strategy_token_account: None,
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    pub fn set_withdrawal_cooldown_ix(&self, cooldown_seconds: i64) -> Instruction {
+        collateral_vault_testing::instruction::SetWithdrawalCooldown { cooldown_seconds }
+            .to_instruction(
+                collateral_vault_testing::accounts::SetWithdrawalCooldown {
+                    admin: self.context.payer.pubkey(),
+                    authority: self.authority_pda,
+                    protocol_state: self.protocol_state_pda,
+                },
+            )
+    }
+
+    pub fn set_mint_cooldown_ix(&self, cooldown_seconds: i64) -> Instruction {
+        collateral_vault_testing::instruction::SetMintCooldown { cooldown_seconds }
+            .to_instruction(
+                collateral_vault_testing::accounts::SetMintCooldown {
+                    admin: self.context.payer.pubkey(),
+                    authority: self.authority_pda,
+                    mint: self.usdt_mint,
+                    cooldown_config: self.find_cooldown_pda(&self.usdt_mint).0,
+                    system_program: system_program::id(),
+                },
+            )
+    }
+
+    pub fn remove_mint_cooldown_ix(&self) -> Instruction {
+        collateral_vault_testing::instruction::RemoveMintCooldown {}
+            .to_instruction(
+                collateral_vault_testing::accounts::RemoveMintCooldown {
+                    admin: self.context.payer.pubkey(),
+                    authority: self.authority_pda,
+                    protocol_state: self.protocol_state_pda,
+                    cooldown_config: self.find_cooldown_pda(&self.usdt_mint).0,
+                },
+            )
+    }
+
+    pub fn set_program_cooldown_ix(&self, program_id: &Pubkey, cooldown_seconds: i64) -> Instruction {
+        collateral_vault_testing::instruction::SetProgramCooldown {
+            program_id: *program_id,
+            cooldown_seconds,
+        }
+        .to_instruction(
+            collateral_vault_testing::accounts::SetProgramCooldown {
+                admin: self.context.payer.pubkey(),
+                authority: self.authority_pda,
+                protocol_state: self.protocol_state_pda,
+            },
+        )
+    }
+
+    pub fn remove_program_cooldown_ix(&self, program_id: &Pubkey) -> Instruction {
+        collateral_vault_testing::instruction::RemoveProgramCooldown { program_id: *program_id }
+            .to_instruction(
+                collateral_vault_testing::accounts::RemoveProgramCooldown {
+                    admin: self.context.payer.pubkey(),
+                    authority: self.authority_pda,
+                    protocol_state: self.protocol_state_pda,
+                },
+            )
+    }
+
+    pub fn request_withdrawal_ix(
+        &self,
+        user: &Pubkey,
+        vault_pda: &Pubkey,
+        vault_token_account: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::RequestWithdrawal { amount }
+            .to_instruction(
+                collateral_vault_testing::accounts::RequestWithdrawal {
+                    user: *user,
+                    vault: *vault_pda,
+                    cosigner: None,
+                    protocol_state: self.protocol_state_pda,
+                    vault_token_account: *vault_token_account,
+                    cooldown_config: self.find_cooldown_pda(&self.usdt_mint).0,
+                    share_pool: None,
+                },
+            )
+    }
+
+    pub fn execute_withdrawal_ix(
+        &self,
+        user: &Pubkey,
+        vault_pda: &Pubkey,
+        vault_token_account: &Pubkey,
+        user_token_account: &Pubkey,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::ExecuteWithdrawal {}
+            .to_instruction(
+                collateral_vault_testing::accounts::ExecuteWithdrawal {
+                    user: *user,
+                    vault: *vault_pda,
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                    user_token_account: *user_token_account,
+                    vault_token_account: *vault_token_account,
+                    spending_policy: self.find_spending_policy_pda(vault_pda).0,
+                    share_pool: None,
+                    pool_token_account: None,
+                    treasury_token_account: None,
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    /// Pays out a ready withdrawal while a withdraw fee is set, paying the
+    /// fee into `treasury_token_account`.
+    pub fn execute_withdrawal_with_fee_ix(
+        &self,
+        user: &Pubkey,
+        vault_pda: &Pubkey,
+        vault_token_account: &Pubkey,
+        user_token_account: &Pubkey,
+        treasury_token_account: &Pubkey,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::ExecuteWithdrawal {}
+            .to_instruction(
+                collateral_vault_testing::accounts::ExecuteWithdrawal {
+                    user: *user,
+                    vault: *vault_pda,
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                    user_token_account: *user_token_account,
+                    vault_token_account: *vault_token_account,
+                    spending_policy: self.find_spending_policy_pda(vault_pda).0,
+                    share_pool: None,
+                    pool_token_account: None,
+                    treasury_token_account: Some(*treasury_token_account),
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    /// Queues a withdrawal from a vault using share accounting.
+    pub fn request_pooled_withdrawal_ix(
+        &self,
+        user: &Pubkey,
+        vault_pda: &Pubkey,
+        vault_token_account: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::RequestWithdrawal { amount }
+            .to_instruction(
+                collateral_vault_testing::accounts::RequestWithdrawal {
+                    user: *user,
+                    vault: *vault_pda,
+                    cosigner: None,
+                    protocol_state: self.protocol_state_pda,
+                    vault_token_account: *vault_token_account,
+                    cooldown_config: self.find_cooldown_pda(&self.usdt_mint).0,
+                    share_pool: Some(self.find_share_pool_pda(&self.usdt_mint).0),
+                },
+            )
+    }
+
+    /// Pays out the queued withdrawal of a pooled vault from the pool token account.
+    pub fn execute_pooled_withdrawal_ix(
+        &self,
+        user: &Pubkey,
+        vault_pda: &Pubkey,
+        vault_token_account: &Pubkey,
+        user_token_account: &Pubkey,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::ExecuteWithdrawal {}
+            .to_instruction(
+                collateral_vault_testing::accounts::ExecuteWithdrawal {
+                    user: *user,
+                    vault: *vault_pda,
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                    user_token_account: *user_token_account,
+                    vault_token_account: *vault_token_account,
+                    spending_policy: self.find_spending_policy_pda(vault_pda).0,
+                    share_pool: Some(self.find_share_pool_pda(&self.usdt_mint).0),
+                    pool_token_account: Some(self.find_pool_token_account()),
+                    treasury_token_account: None,
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    pub fn cancel_withdrawal_ix(&self, user: &Pubkey, vault_pda: &Pubkey) -> Instruction {
+        collateral_vault_testing::instruction::CancelWithdrawal {}
+            .to_instruction(
+                collateral_vault_testing::accounts::CancelWithdrawal {
+                    user: *user,
+                    vault: *vault_pda,
+                    share_pool: None,
+                    vault_token_account: None,
+                },
+            )
+    }
+
+    pub fn initialize_spending_policy_ix(
+        &self,
+        user: &Pubkey,
+        vault_pda: &Pubkey,
+        max_per_transaction: u64,
+        max_per_day: u64,
+        policy_delay: i64,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::InitializeSpendingPolicy {
+            max_per_transaction,
+            max_per_day,
+            policy_delay,
+        }
+        .to_instruction(
+            collateral_vault_testing::accounts::InitializeSpendingPolicy {
+                user: *user,
+                payer: *user,
+                vault: *vault_pda,
+                spending_policy: self.find_spending_policy_pda(vault_pda).0,
+                system_program: system_program::id(),
+            },
+        )
+    }
+
+    pub fn update_spending_policy_ix(
+        &self,
+        user: &Pubkey,
+        vault_pda: &Pubkey,
+        max_per_transaction: u64,
+        max_per_day: u64,
+        policy_delay: i64,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::UpdateSpendingPolicy {
+            max_per_transaction,
+            max_per_day,
+            policy_delay,
+        }
+        .to_instruction(
+            collateral_vault_testing::accounts::UpdateSpendingPolicy {
+                user: *user,
+                vault: *vault_pda,
+                spending_policy: self.find_spending_policy_pda(vault_pda).0,
+            },
+        )
+    }
+
+    pub fn cancel_spending_policy_change_ix(&self, user: &Pubkey, vault_pda: &Pubkey) -> Instruction {
+        collateral_vault_testing::instruction::CancelSpendingPolicyChange {}
+            .to_instruction(
+                collateral_vault_testing::accounts::CancelSpendingPolicyChange {
+                    user: *user,
+                    vault: *vault_pda,
+                    spending_policy: self.find_spending_policy_pda(vault_pda).0,
+                },
+            )
+    }
+
+    pub fn add_withdrawal_destination_ix(
+        &self,
+        user: &Pubkey,
+        vault_pda: &Pubkey,
+        destination: &Pubkey,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::AddWithdrawalDestination { destination: *destination }
+            .to_instruction(
+                collateral_vault_testing::accounts::AddWithdrawalDestination {
+                    user: *user,
+                    vault: *vault_pda,
+                    spending_policy: self.find_spending_policy_pda(vault_pda).0,
+                },
+            )
+    }
+
+    pub fn remove_withdrawal_destination_ix(
+        &self,
+        user: &Pubkey,
+        vault_pda: &Pubkey,
+        destination: &Pubkey,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::RemoveWithdrawalDestination { destination: *destination }
+            .to_instruction(
+                collateral_vault_testing::accounts::RemoveWithdrawalDestination {
+                    user: *user,
+                    vault: *vault_pda,
+                    spending_policy: self.find_spending_policy_pda(vault_pda).0,
+                },
+            )
+    }
+
+    /// Withdraws from `vault_pda` while a withdraw fee is set, paying the
+    /// fee into `treasury_token_account`.
+    pub fn withdraw_with_fee_ix(
+        &self,
+        user: &Pubkey,
+        vault_pda: &Pubkey,
+        vault_token_account: &Pubkey,
+        user_token_account: &Pubkey,
+        treasury_token_account: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::Withdraw { amount }
+            .to_instruction(
+                collateral_vault_testing::accounts::Withdraw {
+                    user: *user,
+                    vault: *vault_pda,
+                    cosigner: None,
+                    vault_operator: None,
+                    session: None,
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                    user_token_account: *user_token_account,
+                    vault_token_account: *vault_token_account,
+                    cooldown_config: self.find_cooldown_pda(&self.usdt_mint).0,
+                    spending_policy: self.find_spending_policy_pda(vault_pda).0,
+                    treasury_token_account: Some(*treasury_token_account),
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    /// Withdraws from `vault_pda` signed by a granted operator.
+    pub fn operator_withdraw_ix(
+        &self,
+        operator: &Pubkey,
+        vault_pda: &Pubkey,
+        vault_token_account: &Pubkey,
+        destination: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::Withdraw { amount }
+            .to_instruction(
+                collateral_vault_testing::accounts::Withdraw {
+                    user: *operator,
+                    vault: *vault_pda,
+                    cosigner: None,
+                    vault_operator: Some(self.find_operator_pda(vault_pda, operator).0),
+                    session: None,
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                    user_token_account: *destination,
+                    vault_token_account: *vault_token_account,
+                    cooldown_config: self.find_cooldown_pda(&self.usdt_mint).0,
+                    spending_policy: self.find_spending_policy_pda(vault_pda).0,
+                    treasury_token_account: None,
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    /// Withdraws from `vault_pda` signed by a registered session key.
+    pub fn session_withdraw_ix(
+        &self,
+        session_key: &Pubkey,
+        vault_pda: &Pubkey,
+        vault_token_account: &Pubkey,
+        destination: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::Withdraw { amount }
+            .to_instruction(
+                collateral_vault_testing::accounts::Withdraw {
+                    user: *session_key,
+                    vault: *vault_pda,
+                    cosigner: None,
+                    vault_operator: None,
+                    session: Some(self.find_session_pda(vault_pda, session_key).0),
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                    user_token_account: *destination,
+                    vault_token_account: *vault_token_account,
+                    cooldown_config: self.find_cooldown_pda(&self.usdt_mint).0,
+                    spending_policy: self.find_spending_policy_pda(vault_pda).0,
+                    treasury_token_account: None,
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    pub fn grant_operator_ix(
+        &self,
+        user: &Pubkey,
+        vault_pda: &Pubkey,
+        operator: &Pubkey,
+        scopes: u8,
+        allowance: Option<u64>,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::GrantOperator {
+            operator: *operator,
+            scopes,
+            allowance,
+        }
+        .to_instruction(
+            collateral_vault_testing::accounts::GrantOperator {
+                user: *user,
+                payer: *user,
+                vault: *vault_pda,
+                vault_operator: self.find_operator_pda(vault_pda, operator).0,
+                system_program: system_program::id(),
+            },
+        )
+    }
+
+    pub fn revoke_operator_ix(&self, user: &Pubkey, vault_pda: &Pubkey, operator: &Pubkey) -> Instruction {
+        collateral_vault_testing::instruction::RevokeOperator {}
+            .to_instruction(
+                collateral_vault_testing::accounts::RevokeOperator {
+                    user: *user,
+                    vault: *vault_pda,
+                    vault_operator: self.find_operator_pda(vault_pda, operator).0,
+                },
+            )
+    }
+
+    pub fn register_session_key_ix(
+        &self,
+        user: &Pubkey,
+        vault_pda: &Pubkey,
+        session_key: &Pubkey,
+        scopes: u8,
+        max_amount: u64,
+        expires_at: i64,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::RegisterSessionKey {
+            session_key: *session_key,
+            scopes,
+            max_amount,
+            expires_at,
+        }
+        .to_instruction(
+            collateral_vault_testing::accounts::RegisterSessionKey {
+                user: *user,
+                payer: *user,
+                vault: *vault_pda,
+                session: self.find_session_pda(vault_pda, session_key).0,
+                system_program: system_program::id(),
+            },
+        )
+    }
+
+    pub fn revoke_session_key_ix(&self, user: &Pubkey, vault_pda: &Pubkey, session_key: &Pubkey) -> Instruction {
+        collateral_vault_testing::instruction::RevokeSessionKey {}
+            .to_instruction(
+                collateral_vault_testing::accounts::RevokeSessionKey {
+                    user: *user,
+                    vault: *vault_pda,
+                    session: self.find_session_pda(vault_pda, session_key).0,
+                },
+            )
+    }
+
+    pub fn transfer_between_subaccounts_ix(
+        &self,
+        user: &Pubkey,
+        from_vault: &Pubkey,
+        to_vault: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::TransferBetweenSubaccounts { amount }
+            .to_instruction(
+                collateral_vault_testing::accounts::TransferBetweenSubaccounts {
+                    user: *user,
+                    from_vault: *from_vault,
+                    cosigner: None,
+                    session: None,
+                    to_vault: *to_vault,
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                    from_vault_token_account: self.find_vault_token_account(from_vault),
+                    to_vault_token_account: self.find_vault_token_account(to_vault),
+                    spending_policy: self.find_spending_policy_pda(from_vault).0,
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    pub fn propose_owner_transfer_ix(&self, user: &Pubkey, vault_pda: &Pubkey, new_owner: &Pubkey) -> Instruction {
+        collateral_vault_testing::instruction::ProposeOwnerTransfer { new_owner: *new_owner }
+            .to_instruction(
+                collateral_vault_testing::accounts::ProposeOwnerTransfer {
+                    user: *user,
+                    vault: *vault_pda,
+                },
+            )
+    }
+
+    pub fn cancel_owner_transfer_ix(&self, user: &Pubkey, vault_pda: &Pubkey) -> Instruction {
+        collateral_vault_testing::instruction::CancelOwnerTransfer {}
+            .to_instruction(
+                collateral_vault_testing::accounts::CancelOwnerTransfer {
+                    user: *user,
+                    vault: *vault_pda,
+                },
+            )
+    }
+
+    pub fn accept_owner_transfer_ix(
+        &self,
+        new_owner: &Pubkey,
+        vault_pda: &Pubkey,
+        previous_owner: &Pubkey,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::AcceptOwnerTransfer {}
+            .to_instruction(
+                collateral_vault_testing::accounts::AcceptOwnerTransfer {
+                    new_owner: *new_owner,
+                    vault: *vault_pda,
+                    previous_owner_index: self.find_owner_index_pda(previous_owner).0,
+                    new_owner_index: self.find_owner_index_pda(new_owner).0,
+                    system_program: system_program::id(),
+                },
+            )
+    }
+
+    pub fn configure_recovery_ix(
+        &self,
+        user: &Pubkey,
+        vault_pda: &Pubkey,
+        guardians: Vec<Pubkey>,
+        threshold: u8,
+        recovery_delay: i64,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::ConfigureRecovery {
+            guardians,
+            threshold,
+            recovery_delay,
+        }
+        .to_instruction(
+            collateral_vault_testing::accounts::ConfigureRecovery {
+                user: *user,
+                vault: *vault_pda,
+                recovery_config: self.find_recovery_pda(vault_pda).0,
+                system_program: system_program::id(),
+            },
+        )
+    }
+
+    pub fn initiate_recovery_ix(&self, guardian: &Pubkey, vault_pda: &Pubkey, new_owner: &Pubkey) -> Instruction {
+        collateral_vault_testing::instruction::InitiateRecovery { new_owner: *new_owner }
+            .to_instruction(
+                collateral_vault_testing::accounts::InitiateRecovery {
+                    guardian: *guardian,
+                    vault: *vault_pda,
+                    recovery_config: self.find_recovery_pda(vault_pda).0,
+                },
+            )
+    }
+
+    pub fn approve_recovery_ix(&self, guardian: &Pubkey, vault_pda: &Pubkey) -> Instruction {
+        collateral_vault_testing::instruction::ApproveRecovery {}
+            .to_instruction(
+                collateral_vault_testing::accounts::ApproveRecovery {
+                    guardian: *guardian,
+                    vault: *vault_pda,
+                    recovery_config: self.find_recovery_pda(vault_pda).0,
+                },
+            )
+    }
+
+    pub fn veto_recovery_ix(&self, user: &Pubkey, vault_pda: &Pubkey) -> Instruction {
+        collateral_vault_testing::instruction::VetoRecovery {}
+            .to_instruction(
+                collateral_vault_testing::accounts::VetoRecovery {
+                    user: *user,
+                    vault: *vault_pda,
+                    recovery_config: self.find_recovery_pda(vault_pda).0,
+                },
+            )
+    }
+
+    pub fn execute_recovery_ix(
+        &self,
+        new_owner: &Pubkey,
+        vault_pda: &Pubkey,
+        previous_owner: &Pubkey,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::ExecuteRecovery {}
+            .to_instruction(
+                collateral_vault_testing::accounts::ExecuteRecovery {
+                    new_owner: *new_owner,
+                    vault: *vault_pda,
+                    recovery_config: self.find_recovery_pda(vault_pda).0,
+                    previous_owner_index: self.find_owner_index_pda(previous_owner).0,
+                    new_owner_index: self.find_owner_index_pda(new_owner).0,
+                    system_program: system_program::id(),
+                },
+            )
+    }
+
+    pub fn configure_lockdown_ix(
+        &self,
+        user: &Pubkey,
+        vault_pda: &Pubkey,
+        recovery_key: &Pubkey,
+        lockdown_delay: i64,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::ConfigureLockdown {
+            recovery_key: *recovery_key,
+            lockdown_delay,
+        }
+        .to_instruction(
+            collateral_vault_testing::accounts::ConfigureLockdown {
+                user: *user,
+                vault: *vault_pda,
+            },
+        )
+    }
+
+    pub fn owner_lockdown_ix(&self, user: &Pubkey, vault_pda: &Pubkey) -> Instruction {
+        collateral_vault_testing::instruction::OwnerLockdown {}
+            .to_instruction(
+                collateral_vault_testing::accounts::OwnerLockdown {
+                    user: *user,
+                    vault: *vault_pda,
+                },
+            )
+    }
+
+    pub fn request_lockdown_release_ix(&self, recovery_key: &Pubkey, vault_pda: &Pubkey) -> Instruction {
+        collateral_vault_testing::instruction::RequestLockdownRelease {}
+            .to_instruction(
+                collateral_vault_testing::accounts::RequestLockdownRelease {
+                    recovery_key: *recovery_key,
+                    vault: *vault_pda,
+                },
+            )
+    }
+
+    pub fn lift_lockdown_ix(&self, recovery_key: &Pubkey, vault_pda: &Pubkey) -> Instruction {
+        collateral_vault_testing::instruction::LiftLockdown {}
+            .to_instruction(
+                collateral_vault_testing::accounts::LiftLockdown {
+                    recovery_key: *recovery_key,
+                    vault: *vault_pda,
+                },
+            )
+    }
+
+    pub fn set_cosigner_ix(
+        &self,
+        user: &Pubkey,
+        vault_pda: &Pubkey,
+        current_cosigner: Option<&Pubkey>,
+        cosigner: Option<Pubkey>,
+        cosigner_threshold: u64,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::SetCosigner { cosigner, cosigner_threshold }
+            .to_instruction(
+                collateral_vault_testing::accounts::SetCosigner {
+                    user: *user,
+                    vault: *vault_pda,
+                    current_cosigner: current_cosigner.copied(),
+                },
+            )
+    }
+
+    pub fn create_multisig_ix(
+        &self,
+        payer: &Pubkey,
+        create_key: &Pubkey,
+        members: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::CreateMultisig {
+            create_key: *create_key,
+            members,
+            threshold,
+        }
+        .to_instruction(
+            collateral_vault_testing::accounts::CreateMultisig {
+                payer: *payer,
+                multisig: self.find_multisig_pda(create_key).0,
+                system_program: system_program::id(),
+            },
+        )
+    }
+
+    pub fn initialize_multisig_vault_ix(
+        &self,
+        member: &Pubkey,
+        multisig: &Pubkey,
+        subaccount: u16,
+        member_token_account: &Pubkey,
+        initial_deposit: u64,
+    ) -> Instruction {
+        let (vault_pda, _) = self.find_subaccount_vault_pda(multisig, subaccount);
+        collateral_vault_testing::instruction::InitializeMultisigVault { subaccount, initial_deposit }
+            .to_instruction(
+                collateral_vault_testing::accounts::InitializeMultisigVault {
+                    member: *member,
+                    multisig: *multisig,
+                    vault: vault_pda,
+                    owner_index: self.find_owner_index_pda(multisig).0,
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                    vault_token_account: self.find_vault_token_account(&vault_pda),
+                    member_token_account: *member_token_account,
+                    mint: self.usdt_mint,
+                    treasury_token_account: None,
+                    token_program: spl_token::id(),
+                    associated_token_program: associated_token::ID,
+                    system_program: system_program::id(),
+                },
+            )
+    }
+
+    pub fn create_multisig_proposal_ix(
+        &self,
+        member: &Pubkey,
+        multisig: &Pubkey,
+        vault_pda: &Pubkey,
+        index: u64,
+        action: MultisigAction,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::CreateMultisigProposal { action }
+            .to_instruction(
+                collateral_vault_testing::accounts::CreateMultisigProposal {
+                    member: *member,
+                    multisig: *multisig,
+                    vault: *vault_pda,
+                    proposal: self.find_proposal_pda(multisig, index).0,
+                    system_program: system_program::id(),
+                },
+            )
+    }
+
+    pub fn approve_multisig_proposal_ix(&self, member: &Pubkey, multisig: &Pubkey, proposal: &Pubkey) -> Instruction {
+        collateral_vault_testing::instruction::ApproveMultisigProposal {}
+            .to_instruction(
+                collateral_vault_testing::accounts::ApproveMultisigProposal {
+                    member: *member,
+                    multisig: *multisig,
+                    proposal: *proposal,
+                },
+            )
+    }
+
+    pub fn execute_multisig_proposal_ix(
+        &self,
+        member: &Pubkey,
+        multisig: &Pubkey,
+        proposal: &Pubkey,
+        vault_pda: &Pubkey,
+        destination: Option<&Pubkey>,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::ExecuteMultisigProposal {}
+            .to_instruction(
+                collateral_vault_testing::accounts::ExecuteMultisigProposal {
+                    member: *member,
+                    multisig: *multisig,
+                    proposal: *proposal,
+                    vault: *vault_pda,
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                    vault_token_account: self.find_vault_token_account(vault_pda),
+                    destination: destination.copied(),
+                    cosigner: None,
+                    cooldown_config: self.find_cooldown_pda(&self.usdt_mint).0,
+                    spending_policy: self.find_spending_policy_pda(vault_pda).0,
+                    treasury_token_account: None,
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    /// Proposal action running `ix`, a vault instruction built with the
+    /// multisig as its owner.
+    pub fn vault_instruction_action(&self, ix: &Instruction) -> MultisigAction {
+        MultisigAction::VaultInstruction {
+            accounts: ix
+                .accounts
+                .iter()
+                .map(|meta| ProposalAccount {
+                    pubkey: meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: ix.data.clone(),
+        }
+    }
+
+    /// Executes a `VaultInstruction` proposal, passing the accounts of `ix`
+    /// and the vault program after the named ones.
+    pub fn execute_multisig_vault_instruction_ix(
+        &self,
+        member: &Pubkey,
+        multisig: &Pubkey,
+        proposal: &Pubkey,
+        vault_pda: &Pubkey,
+        ix: &Instruction,
+    ) -> Instruction {
+        let mut execute_ix = self.execute_multisig_proposal_ix(member, multisig, proposal, vault_pda, None);
+        execute_ix.accounts.extend(
+            ix.accounts
+                .iter()
+                .filter(|meta| meta.pubkey != *multisig)
+                .map(|meta| AccountMeta {
+                    pubkey: meta.pubkey,
+                    is_signer: false,
+                    is_writable: meta.is_writable,
+                }),
+        );
+        execute_ix.accounts.push(AccountMeta::new_readonly(self.program_id, false));
+        execute_ix
+    }
+
+    pub fn lock_collateral_ix(&self, vault_owner: &Pubkey, vault_pda: &Pubkey, amount: u64) -> Instruction {
+        collateral_vault_testing::instruction::LockCollateral { amount }
+            .to_instruction(
+                collateral_vault_testing::accounts::LockCollateral {
+                    authority: self.authority_pda,
+                    vault: *vault_pda,
+                    vault_owner: *vault_owner,
+                    share_pool: None,
+                    vault_token_account: None,
+                    instructions_sysvar: solana_sdk::sysvar::instructions::id(),
+                },
+            )
+    }
+
+    /// Locks collateral of a vault using share accounting.
+    pub fn lock_pooled_collateral_ix(
+        &self,
+        vault_owner: &Pubkey,
+        vault_pda: &Pubkey,
+        vault_token_account: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::LockCollateral { amount }
+            .to_instruction(
+                collateral_vault_testing::accounts::LockCollateral {
+                    authority: self.authority_pda,
+                    vault: *vault_pda,
+                    vault_owner: *vault_owner,
+                    share_pool: Some(self.find_share_pool_pda(&self.usdt_mint).0),
+                    vault_token_account: Some(*vault_token_account),
+                    instructions_sysvar: solana_sdk::sysvar::instructions::id(),
+                },
+            )
+    }
+
+    pub fn unlock_collateral_ix(&self, vault_owner: &Pubkey, vault_pda: &Pubkey, amount: u64) -> Instruction {
+        collateral_vault_testing::instruction::UnlockCollateral { amount }
+            .to_instruction(
+                collateral_vault_testing::accounts::UnlockCollateral {
+                    authority: self.authority_pda,
+                    vault: *vault_pda,
+                    vault_owner: *vault_owner,
+                    share_pool: None,
+                    vault_token_account: None,
+                    rate_limit: self.find_rate_limit_pda(&self.program_id).0,
+                    instructions_sysvar: solana_sdk::sysvar::instructions::id(),
+                },
+            )
+    }
+
+    pub fn transfer_collateral_ix(
+        &self,
+        from_vault: &Pubkey,
+        from_vault_token_account: &Pubkey,
+        to_vault: &Pubkey,
+        to_vault_token_account: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::TransferCollateral { amount }
+            .to_instruction(
+                collateral_vault_testing::accounts::TransferCollateral {
+                    authority: self.authority_pda,
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                    from_vault: *from_vault,
+                    to_vault: *to_vault,
+                    from_vault_token_account: *from_vault_token_account,
+                    to_vault_token_account: *to_vault_token_account,
+                    rate_limit: self.find_rate_limit_pda(&self.program_id).0,
+                    treasury_token_account: None,
+                    instructions_sysvar: solana_sdk::sysvar::instructions::id(),
+                    share_pool: None,
+                    pool_token_account: None,
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    /// Transfer where either vault uses share accounting; the pool token
+    /// account stands in for the pooled side.
+    pub fn transfer_pooled_collateral_ix(
+        &self,
+        from_vault: &Pubkey,
+        from_vault_token_account: &Pubkey,
+        to_vault: &Pubkey,
+        to_vault_token_account: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::TransferCollateral { amount }
+            .to_instruction(
+                collateral_vault_testing::accounts::TransferCollateral {
+                    authority: self.authority_pda,
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                    from_vault: *from_vault,
+                    to_vault: *to_vault,
+                    from_vault_token_account: *from_vault_token_account,
+                    to_vault_token_account: *to_vault_token_account,
+                    rate_limit: self.find_rate_limit_pda(&self.program_id).0,
+                    treasury_token_account: None,
+                    instructions_sysvar: solana_sdk::sysvar::instructions::id(),
+                    share_pool: Some(self.find_share_pool_pda(&self.usdt_mint).0),
+                    pool_token_account: Some(self.find_pool_token_account()),
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    /// Settles `legs` between `vaults`, given as (vault, vault token
+    /// account) pairs that the legs index into. Set `pooled` when any of
+    /// the vaults uses share accounting.
+    pub fn settle_batch_ix(
+        &self,
+        vaults: &[(Pubkey, Pubkey)],
+        legs: Vec<SettlementLeg>,
+        pooled: bool,
+    ) -> Instruction {
+        let mut instruction = collateral_vault_testing::instruction::SettleBatch { legs }
+            .to_instruction(
+                collateral_vault_testing::accounts::SettleBatch {
+                    authority: self.authority_pda,
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                    rate_limit: self.find_rate_limit_pda(&self.program_id).0,
+                    treasury_token_account: None,
+                    instructions_sysvar: solana_sdk::sysvar::instructions::id(),
+                    share_pool: pooled.then(|| self.find_share_pool_pda(&self.usdt_mint).0),
+                    pool_token_account: pooled.then(|| self.find_pool_token_account()),
+                    token_program: spl_token::id(),
+                },
+            );
+        for (vault, vault_token_account) in vaults {
+            instruction.accounts.push(AccountMeta::new(*vault, false));
+            instruction.accounts.push(AccountMeta::new(*vault_token_account, false));
+        }
+        instruction
+    }
+
+    pub fn commit_lock_ix(
+        &self,
+        user: &Pubkey,
+        vault_pda: &Pubkey,
+        vault_token_account: &Pubkey,
+        pooled: bool,
+        amount: u64,
+        until: i64,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::CommitLock { amount, until }
+            .to_instruction(
+                collateral_vault_testing::accounts::CommitLock {
+                    user: *user,
+                    vault: *vault_pda,
+                    share_pool: pooled.then(|| self.find_share_pool_pda(&self.usdt_mint).0),
+                    vault_token_account: pooled.then_some(*vault_token_account),
+                },
+            )
+    }
+
+    pub fn release_commitment_ix(
+        &self,
+        user: &Pubkey,
+        vault_pda: &Pubkey,
+        vault_token_account: &Pubkey,
+        pooled: bool,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::ReleaseCommitment {}
+            .to_instruction(
+                collateral_vault_testing::accounts::ReleaseCommitment {
+                    user: *user,
+                    vault: *vault_pda,
+                    share_pool: pooled.then(|| self.find_share_pool_pda(&self.usdt_mint).0),
+                    vault_token_account: pooled.then_some(*vault_token_account),
+                },
+            )
+    }
+
+    pub fn initialize_insurance_fund_ix(&self) -> Instruction {
+        collateral_vault_testing::instruction::InitializeInsuranceFund {}
+            .to_instruction(
+                collateral_vault_testing::accounts::InitializeInsuranceFund {
+                    admin: self.context.payer.pubkey(),
+                    authority: self.authority_pda,
+                    mint: self.usdt_mint,
+                    insurance_fund: self.find_insurance_fund_pda(&self.usdt_mint).0,
+                    fund_token_account: self.find_insurance_token_account(),
+                    token_program: spl_token::id(),
+                    associated_token_program: associated_token::ID,
+                    system_program: system_program::id(),
+                },
+            )
+    }
+
+    pub fn contribute_insurance_ix(&self, contributor_token_account: &Pubkey, amount: u64) -> Instruction {
+        collateral_vault_testing::instruction::ContributeInsurance { amount }
+            .to_instruction(
+                collateral_vault_testing::accounts::ContributeInsurance {
+                    contributor: self.user_pubkey(),
+                    insurance_fund: self.find_insurance_fund_pda(&self.usdt_mint).0,
+                    fund_token_account: self.find_insurance_token_account(),
+                    contributor_token_account: *contributor_token_account,
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    pub fn fund_insurance_from_fees_ix(
+        &self,
+        signer: &Pubkey,
+        treasury_token_account: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::FundInsuranceFromFees { amount }
+            .to_instruction(
+                collateral_vault_testing::accounts::FundInsuranceFromFees {
+                    signer: *signer,
+                    authority: self.authority_pda,
+                    protocol_state: self.protocol_state_pda,
+                    treasury_token_account: *treasury_token_account,
+                    insurance_fund: self.find_insurance_fund_pda(&self.usdt_mint).0,
+                    fund_token_account: self.find_insurance_token_account(),
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    pub fn record_bad_debt_ix(
+        &self,
+        index: u64,
+        debtor_vault: &Pubkey,
+        debtor_vault_token_account: &Pubkey,
+        creditor_vault: &Pubkey,
+        creditor_vault_token_account: &Pubkey,
+        shortfall: u64,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::RecordBadDebt { shortfall }
+            .to_instruction(
+                collateral_vault_testing::accounts::RecordBadDebt {
+                    payer: self.context.payer.pubkey(),
+                    authority: self.authority_pda,
+                    insurance_fund: self.find_insurance_fund_pda(&self.usdt_mint).0,
+                    debtor_vault: *debtor_vault,
+                    debtor_vault_token_account: *debtor_vault_token_account,
+                    creditor_vault: *creditor_vault,
+                    creditor_vault_token_account: *creditor_vault_token_account,
+                    bad_debt: self.find_bad_debt_pda(index).0,
+                    instructions_sysvar: solana_sdk::sysvar::instructions::id(),
+                    system_program: system_program::id(),
+                },
+            )
+    }
+
+    pub fn cover_bad_debt_ix(
+        &self,
+        index: u64,
+        creditor_vault: &Pubkey,
+        creditor_vault_token_account: &Pubkey,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::CoverBadDebt {}
+            .to_instruction(
+                collateral_vault_testing::accounts::CoverBadDebt {
+                    authority: self.authority_pda,
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                    insurance_fund: self.find_insurance_fund_pda(&self.usdt_mint).0,
+                    fund_token_account: self.find_insurance_token_account(),
+                    bad_debt: self.find_bad_debt_pda(index).0,
+                    creditor_vault: *creditor_vault,
+                    creditor_vault_token_account: *creditor_vault_token_account,
+                    rate_limit: self.find_rate_limit_pda(&self.program_id).0,
+                    instructions_sysvar: solana_sdk::sysvar::instructions::id(),
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    pub fn socialize_bad_debt_ix(
+        &self,
+        index: u64,
+        creditor_vault: &Pubkey,
+        creditor_vault_token_account: &Pubkey,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::SocializeBadDebt {}
+            .to_instruction(
+                collateral_vault_testing::accounts::SocializeBadDebt {
+                    authority: self.authority_pda,
+                    protocol_state: self.protocol_state_pda,
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                    insurance_fund: self.find_insurance_fund_pda(&self.usdt_mint).0,
+                    share_pool: self.find_share_pool_pda(&self.usdt_mint).0,
+                    pool_token_account: Some(self.find_pool_token_account()),
+                    bad_debt: self.find_bad_debt_pda(index).0,
+                    creditor_vault: *creditor_vault,
+                    creditor_vault_token_account: *creditor_vault_token_account,
+                    rate_limit: self.find_rate_limit_pda(&self.program_id).0,
+                    instructions_sysvar: solana_sdk::sysvar::instructions::id(),
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    pub fn collect_socialized_loss_ix(&self, vault_pda: &Pubkey) -> Instruction {
+        collateral_vault_testing::instruction::CollectSocializedLoss {}
+            .to_instruction(
+                collateral_vault_testing::accounts::CollectSocializedLoss {
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                    insurance_fund: self.find_insurance_fund_pda(&self.usdt_mint).0,
+                    fund_token_account: self.find_insurance_token_account(),
+                    vault: *vault_pda,
+                    vault_token_account: self.find_vault_token_account(vault_pda),
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    /// Socializes bad debt on a mint that has no share pool, leaving the
+    /// optional pool token account out.
+    pub fn socialize_unpooled_bad_debt_ix(
+        &self,
+        index: u64,
+        creditor_vault: &Pubkey,
+        creditor_vault_token_account: &Pubkey,
+    ) -> Instruction {
+        let mut instruction = self.socialize_bad_debt_ix(index, creditor_vault, creditor_vault_token_account);
+        let pool_token_account = self.find_pool_token_account();
+        for meta in instruction.accounts.iter_mut().filter(|meta| meta.pubkey == pool_token_account) {
+            meta.pubkey = self.program_id;
+            meta.is_writable = false;
+        }
+        instruction
+    }
+
+    pub fn balance_at_ix(&self, vault_pda: &Pubkey, slot: u64) -> Instruction {
+        collateral_vault_testing::instruction::BalanceAt { slot }
+            .to_instruction(
+                collateral_vault_testing::accounts::BalanceAt {
+                    vault: *vault_pda,
+                },
+            )
+    }
+
+    pub fn total_value_locked_at_ix(&self, slot: u64) -> Instruction {
+        collateral_vault_testing::instruction::TotalValueLockedAt { slot }
+            .to_instruction(
+                collateral_vault_testing::accounts::TotalValueLockedAt {
+                    mint_state: self.find_mint_state_pda(&self.usdt_mint).0,
+                },
+            )
+    }
+
+    pub fn create_reward_pool_ix(&self, reward_duration: i64) -> Instruction {
+        collateral_vault_testing::instruction::CreateRewardPool { reward_duration }
+            .to_instruction(
+                collateral_vault_testing::accounts::CreateRewardPool {
+                    admin: self.context.payer.pubkey(),
+                    authority: self.authority_pda,
+                    collateral_mint: self.usdt_mint,
+                    reward_mint: self.usdt_mint,
+                    reward_pool: self.find_reward_pool_pda().0,
+                    reward_token_account: self.find_reward_token_account(),
+                    token_program: spl_token::id(),
+                    associated_token_program: associated_token::ID,
+                    system_program: system_program::id(),
+                },
+            )
+    }
+
+    pub fn fund_reward_pool_ix(&self, admin_token_account: &Pubkey, amount: u64) -> Instruction {
+        collateral_vault_testing::instruction::FundRewardPool { amount }
+            .to_instruction(
+                collateral_vault_testing::accounts::FundRewardPool {
+                    admin: self.context.payer.pubkey(),
+                    authority: self.authority_pda,
+                    reward_pool: self.find_reward_pool_pda().0,
+                    reward_token_account: self.find_reward_token_account(),
+                    admin_token_account: *admin_token_account,
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+
+    pub fn open_reward_position_ix(&self, vault_pda: &Pubkey, vault_token_account: &Pubkey) -> Instruction {
+        collateral_vault_testing::instruction::OpenRewardPosition {}
+            .to_instruction(
+                collateral_vault_testing::accounts::OpenRewardPosition {
+                    payer: self.context.payer.pubkey(),
+                    reward_pool: self.find_reward_pool_pda().0,
+                    vault: *vault_pda,
+                    vault_token_account: *vault_token_account,
+                    reward_position: self.find_reward_position_pda(vault_pda).0,
+                    system_program: system_program::id(),
+                },
+            )
+    }
+
+    /// Appends the vault's position in the test reward pool to an
+    /// instruction that moves its locked or committed balance.
+    pub fn with_reward_position(&self, mut instruction: Instruction, vault_pda: &Pubkey) -> Instruction {
+        instruction.accounts.push(AccountMeta::new(self.find_reward_pool_pda().0, false));
+        instruction.accounts.push(AccountMeta::new(self.find_reward_position_pda(vault_pda).0, false));
+        instruction
+    }
+
+    pub fn close_reward_position_ix(&self, user: &Pubkey, vault_pda: &Pubkey) -> Instruction {
+        collateral_vault_testing::instruction::CloseRewardPosition {}
+            .to_instruction(
+                collateral_vault_testing::accounts::CloseRewardPosition {
+                    user: *user,
+                    vault: *vault_pda,
+                    reward_pool: self.find_reward_pool_pda().0,
+                    reward_position: self.find_reward_position_pda(vault_pda).0,
+                },
+            )
+    }
+
+    pub fn sync_reward_position_ix(&self, vault_pda: &Pubkey) -> Instruction {
+        collateral_vault_testing::instruction::SyncRewardPosition {}
+            .to_instruction(
+                collateral_vault_testing::accounts::SyncRewardPosition {
+                    reward_pool: self.find_reward_pool_pda().0,
+                    vault: *vault_pda,
+                    reward_position: self.find_reward_position_pda(vault_pda).0,
+                },
+            )
+    }
+
+    pub fn claim_rewards_ix(
+        &self,
+        user: &Pubkey,
+        vault_pda: &Pubkey,
+        user_reward_token_account: &Pubkey,
+    ) -> Instruction {
+        collateral_vault_testing::instruction::ClaimRewards {}
+            .to_instruction(
+                collateral_vault_testing::accounts::ClaimRewards {
+                    user: *user,
+                    vault: *vault_pda,
+                    reward_pool: self.find_reward_pool_pda().0,
+                    reward_position: self.find_reward_position_pda(vault_pda).0,
+                    reward_token_account: self.find_reward_token_account(),
+                    user_reward_token_account: *user_reward_token_account,
+                    token_program: spl_token::id(),
+                },
+            )
+    }
+}
+
+/// Asserts that a transaction failed with the given program error.
+pub fn assert_program_error(result: std::result::Result<(), BanksClientError>, expected: errors::ErrorCode) {
+    match result {
+        Err(BanksClientError::TransactionError(TransactionError::InstructionError(_, ix_err))) => {
+            match ix_err {
+                solana_sdk::instruction::InstructionError::Custom(code) => {
+                    assert_eq!(code, u32::from(expected), "Wrong program error");
+                }
+                other => panic!("Wrong instruction error type: {:?}", other),
+            }
+        }
+        Err(BanksClientError::TransactionError(tx_err)) => {
+            panic!("Wrong transaction error type: {:?}", tx_err)
+        }
+        Err(err) => panic!("Wrong error type: {:?}", err),
+        Ok(()) => panic!("Transaction succeeded, expected {:?}", expected),
+    }
+}
+
+/// Returns the first `T` event in a program log.
+pub fn find_event<T: anchor_lang::Event>(logs: &[String]) -> Option<T> {
+    logs.iter()
+        .filter_map(|line| line.strip_prefix("Program data: "))
+        .filter_map(|data| base64::engine::general_purpose::STANDARD.decode(data).ok())
+        .find_map(|data| {
+            data.strip_prefix(T::DISCRIMINATOR)
+                .and_then(|mut event| T::deserialize(&mut event).ok())
+        })
+}
+
+// --- Private Helpers ---
+
+// Anchor entrypoints tie the account slice to the accounts' own lifetime,
+// which the builtin processor signature does not promise; the leaked copy
+// lives for the rest of the test.
+fn process_vault_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> anchor_lang::solana_program::entrypoint::ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    collateral_vault_testing::entry(program_id, accounts, data)
+}
+
+fn process_mock_strategy_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> anchor_lang::solana_program::entrypoint::ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    mock_strategy::entry(program_id, accounts, data)
+}
 
 fn create_mint_account(mint_authority: &Pubkey, decimals: u8) -> Account {
     let mut mint_data = vec![0; Mint::LEN];
@@ -284,7 +2798,7 @@ fn create_mint_account(mint_authority: &Pubkey, decimals: u8) -> Account {
     Account {
         lamports: 1_000_000_000, // Rent
         data: mint_data,
-        owner: spl_token_2::id(),
+        owner: spl_token::id(),
         executable: false,
         rent_epoch: 0,
     }
@@ -1,14 +1,22 @@
 // This is your new, working test file.
 // Run this with `cargo test-sbf`
+#![cfg(feature = "test-sbf")]
 
 // Use the common helper module
 mod common;
-use common::CollateralVaultProgramTest;
+use common::{assert_program_error, find_event, CollateralVaultProgramTest};
 
-use anchor_lang::prelude::{AccountDeserialize, ErrorCode};
-use solana_program_test_2::BanksClientError;
-use solana_sdk_2::transport::TransportError;
-use collateral_vault_testing::errors;
+use anchor_lang::prelude::Pubkey;
+use solana_sdk::{
+    instruction::AccountMeta,
+    signature::{Keypair, Signer},
+};
+use collateral_vault_testing::{
+    constants::{OPERATOR_SCOPE_WITHDRAW_TO_OWNER, SESSION_SCOPE_DEPOSIT, SESSION_SCOPE_WITHDRAW},
+    errors,
+    events::{DepositForEvent, RateLimitExceededEvent, VaultClosedEvent, WithdrawalExecutedEvent},
+    state::{MultisigAction, PermitAction, PermitMessage, ProtocolStatus, SettlementLeg},
+};
 
 // Use tokio::test for async tests
 #[tokio::test]
@@ -20,7 +28,7 @@ async fn test_initialize_vault_success() {
     // Create ATAs
     let user_ata = test.create_and_fund_user_ata(&user_pubkey).await;
     let (vault_pda, _vault_bump) = test.find_vault_pda(&user_pubkey);
-    let vault_ata = test.create_token_account(&vault_pda).await;
+    let vault_ata = test.find_vault_token_account(&vault_pda);
     
     let initial_deposit = 100_000_000; // 100 USDT
 
@@ -41,7 +49,7 @@ async fn test_initialize_vault_success() {
     
     // The user must sign this transaction
     let result = test
-        .process_transaction(&[init_vault_ix], &[&test.user_keypair])
+        .process_transaction(&[init_vault_ix], &[&test.user_keypair.insecure_clone()])
         .await;
     
     assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
@@ -73,7 +81,7 @@ async fn test_deposit_success() {
     let user_pubkey = test.user_pubkey();
     let user_ata = test.create_and_fund_user_ata(&user_pubkey).await;
     let (vault_pda, _vault_bump) = test.find_vault_pda(&user_pubkey);
-    let vault_ata = test.create_token_account(&vault_pda).await;
+    let vault_ata = test.find_vault_token_account(&vault_pda);
     let initial_deposit = 100_000_000; // 100 USDT
 
     // Init Authority
@@ -90,7 +98,7 @@ async fn test_deposit_success() {
         &user_ata,
         initial_deposit,
     );
-    test.process_transaction(&[init_vault_ix], &[&test.user_keypair])
+    test.process_transaction(&[init_vault_ix], &[&test.user_keypair.insecure_clone()])
         .await
         .unwrap();
 
@@ -105,7 +113,7 @@ async fn test_deposit_success() {
     );
 
     let result = test
-        .process_transaction(&[deposit_ix], &[&test.user_keypair])
+        .process_transaction(&[deposit_ix], &[&test.user_keypair.insecure_clone()])
         .await;
 
     assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
@@ -132,11 +140,11 @@ async fn test_deposit_error_zero_amount() {
     let user_pubkey = test.user_pubkey();
     let user_ata = test.create_and_fund_user_ata(&user_pubkey).await;
     let (vault_pda, _vault_bump) = test.find_vault_pda(&user_pubkey);
-    let vault_ata = test.create_token_account(&vault_pda).await;
+    let vault_ata = test.find_vault_token_account(&vault_pda);
 
     let init_auth_ix = test.initialize_authority_ix();
-    let init_vault_ix = test.initialize_vault_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, 100);
-    test.process_transaction(&[init_auth_ix, init_vault_ix], &[&test.user_keypair])
+    let init_vault_ix = test.initialize_vault_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, 100_000_000);
+    test.process_transaction(&[init_auth_ix, init_vault_ix], &[&test.user_keypair.insecure_clone()])
         .await
         .unwrap();
 
@@ -151,25 +159,4983 @@ async fn test_deposit_error_zero_amount() {
     );
 
     let result = test
-        .process_transaction(&[deposit_ix], &[&test.user_keypair])
+        .process_transaction(&[deposit_ix], &[&test.user_keypair.insecure_clone()])
         .await;
 
     // 3. Verify
-    assert!(result.is_err());
-    let err = result.unwrap_err();
-    
-    // Check for specific program error
-    match err {
-        BanksClientError::TransactionError(TransportError::TransactionError(tx_err)) => {
-            let inner_err = tx_err.unwrap_instruction_error();
-            match inner_err {
-                solana_sdk_2::transaction::TransactionError::InstructionError(_, ix_err) => {
-                     let program_error = u32::from(ix_err);
-                     assert_eq!(program_error, errors::ErrorCode::InvalidAmount.into());
-                }
-                _ => panic!("Wrong instruction error type"),
-            }
-        }
-        _ => panic!("Wrong error type: {:?}", err),
-    }
+    assert_program_error(result, errors::ErrorCode::InvalidAmount);
+}
+
+#[tokio::test]
+async fn test_close_vault_success() {
+    // 1. Setup (Initialize Vault)
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let user_ata = test.create_and_fund_user_ata(&user_pubkey).await;
+    let (vault_pda, _vault_bump) = test.find_vault_pda(&user_pubkey);
+    let vault_ata = test.find_vault_token_account(&vault_pda);
+    let initial_deposit = 100_000_000; // 100 USDT
+
+    let init_auth_ix = test.initialize_authority_ix();
+    let init_vault_ix = test.initialize_vault_ix(
+        &user_pubkey,
+        &vault_pda,
+        &vault_ata,
+        &user_ata,
+        initial_deposit,
+    );
+    test.process_transaction(&[init_auth_ix, init_vault_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Close Vault
+    let close_ix = test.close_vault_ix(&user_pubkey, &vault_pda, &user_ata, &vault_ata);
+    let result = test
+        .process_transaction(&[close_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    let user_token_balance = test.get_token_balance(&user_ata).await;
+    assert_eq!(user_token_balance, common::USER_STARTING_USDT);
+    assert!(test.get_account_data(&vault_pda).await.is_none());
+    assert!(test.get_account_data(&vault_ata).await.is_none());
+}
+
+#[tokio::test]
+async fn test_deploy_and_recall_strategy_success() {
+    // 1. Setup: 100 USDT in the pool, 20% kept liquid, strategy capped at 50%
+    let mut test = CollateralVaultProgramTest::new().await;
+    let pooled_deposit = 100_000_000;
+    test.setup_pooled_vault(pooled_deposit).await;
+    test.setup_mock_strategy(2_000, 5_000).await;
+
+    let pool_ata = test.find_pool_token_account();
+    let strategy_ata = test.find_mock_strategy_token_account();
+
+    // 2. Deploy
+    let deploy_amount = 40_000_000;
+    let deploy_ix = test.deploy_to_strategy_ix(deploy_amount);
+    let result = test.process_transaction(&[deploy_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    let share_pool = test.get_share_pool_account().await;
+    let strategy = test.get_strategy_account().await;
+    assert_eq!(share_pool.deployed_assets, deploy_amount);
+    assert_eq!(share_pool.total_assets, pooled_deposit);
+    assert_eq!(strategy.allocated, deploy_amount);
+    assert_eq!(test.get_token_balance(&pool_ata).await, pooled_deposit - deploy_amount);
+    assert_eq!(test.get_token_balance(&strategy_ata).await, deploy_amount);
+
+    // 3. Strategy earns 1 USDT, then everything is recalled
+    let strategy_yield = 1_000_000;
+    test.mint_tokens(&strategy_ata, strategy_yield).await;
+    let recall_ix = test.recall_from_strategy_ix(deploy_amount + strategy_yield);
+    let result = test.process_transaction(&[recall_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 4. Verify: principal returned, yield credited to every share
+    let share_pool = test.get_share_pool_account().await;
+    let strategy = test.get_strategy_account().await;
+    let mint_state = test.get_mint_state_account().await;
+    assert_eq!(share_pool.deployed_assets, 0);
+    assert_eq!(share_pool.total_assets, pooled_deposit + strategy_yield);
+    assert_eq!(strategy.allocated, 0);
+    assert_eq!(mint_state.total_value_locked, pooled_deposit + strategy_yield);
+    assert_eq!(test.get_token_balance(&pool_ata).await, pooled_deposit + strategy_yield);
+    assert_eq!(test.get_token_balance(&strategy_ata).await, 0);
+}
+
+#[tokio::test]
+async fn test_deploy_to_strategy_error_allocation_exceeded() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_pooled_vault(100_000_000).await;
+    test.setup_mock_strategy(2_000, 5_000).await;
+
+    // 2. Deployable liquidity is 80 USDT, but the strategy may hold only 50
+    let deploy_ix = test.deploy_to_strategy_ix(60_000_000);
+    let result = test.process_transaction(&[deploy_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::StrategyAllocationExceeded);
+    let share_pool = test.get_share_pool_account().await;
+    assert_eq!(share_pool.deployed_assets, 0);
+}
+
+#[tokio::test]
+async fn test_write_down_strategy_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let pooled_deposit = 100_000_000;
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_pooled_vault(pooled_deposit).await;
+    test.setup_mock_strategy(2_000, 5_000).await;
+
+    let deploy_amount = 40_000_000;
+    let deploy_ix = test.deploy_to_strategy_ix(deploy_amount);
+    test.process_transaction(&[deploy_ix], &[]).await.unwrap();
+
+    let outflow_before = test.get_mint_state_account().await.window_outflow;
+
+    // 2. The strategy lost 10 USDT
+    let loss = 10_000_000;
+    let write_down_ix = test.write_down_strategy_ix(loss);
+    let result = test.process_transaction(&[write_down_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: the loss comes off the shares, not off the idle tokens
+    let share_pool = test.get_share_pool_account().await;
+    let strategy = test.get_strategy_account().await;
+    let mint_state = test.get_mint_state_account().await;
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(share_pool.deployed_assets, deploy_amount - loss);
+    assert_eq!(share_pool.total_assets, pooled_deposit - loss);
+    assert_eq!(share_pool.total_shares, vault_state.shares);
+    assert_eq!(strategy.allocated, deploy_amount - loss);
+    assert_eq!(mint_state.total_value_locked, pooled_deposit - loss);
+    assert_eq!(mint_state.window_outflow, outflow_before);
+}
+
+#[tokio::test]
+async fn test_write_down_strategy_error_exceeds_allocation() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_pooled_vault(100_000_000).await;
+    test.setup_mock_strategy(2_000, 5_000).await;
+
+    let deploy_ix = test.deploy_to_strategy_ix(40_000_000);
+    test.process_transaction(&[deploy_ix], &[]).await.unwrap();
+
+    // 2. Write down more than was ever allocated
+    let write_down_ix = test.write_down_strategy_ix(50_000_000);
+    let result = test.process_transaction(&[write_down_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::StrategyLossExceedsAllocation);
+    let share_pool = test.get_share_pool_account().await;
+    assert_eq!(share_pool.deployed_assets, 40_000_000);
+}
+
+#[tokio::test]
+async fn test_record_and_cover_bad_debt_success() {
+    // 1. Setup: an emptied debtor vault, a creditor vault and 20 USDT of insurance
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let debtor_deposit = 10_000_000;
+    let (debtor_vault, debtor_ata, user_ata) = test.setup_vault(debtor_deposit).await;
+    let creditor_deposit = 5_000_000;
+    let (creditor_vault, creditor_ata) = test.setup_subaccount_vault(1, &user_ata, creditor_deposit).await;
+    test.authorize_test_caller().await;
+    let contribution = 20_000_000;
+    test.setup_insurance_fund(&user_ata, contribution).await;
+
+    let withdraw_ix = test.withdraw_ix(&user_pubkey, &debtor_vault, &debtor_ata, &user_ata, debtor_deposit);
+    test.process_transaction(&[withdraw_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Record a 4 USDT shortfall and cover it from the fund
+    let shortfall = 4_000_000;
+    let record_ix = test.record_bad_debt_ix(0, &debtor_vault, &debtor_ata, &creditor_vault, &creditor_ata, shortfall);
+    let result = test.process_transaction(&[record_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    let cover_ix = test.cover_bad_debt_ix(0, &creditor_vault, &creditor_ata);
+    let result = test.process_transaction(&[cover_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    let bad_debt = test.get_bad_debt_account(0).await;
+    let insurance_fund = test.get_insurance_fund_account().await;
+    let debtor_state = test.get_vault_account(&debtor_vault).await;
+    let creditor_state = test.get_vault_account(&creditor_vault).await;
+    assert_eq!(bad_debt.caller_program, test.program_id);
+    assert_eq!(bad_debt.shortfall, shortfall);
+    assert_eq!(bad_debt.covered, shortfall);
+    assert_eq!(debtor_state.bad_debt_recorded, shortfall);
+    assert_eq!(insurance_fund.balance, contribution - shortfall);
+    assert_eq!(creditor_state.total_balance, creditor_deposit + shortfall);
+    assert_eq!(test.get_token_balance(&creditor_ata).await, creditor_deposit + shortfall);
+    assert_eq!(
+        test.get_token_balance(&test.find_insurance_token_account()).await,
+        contribution - shortfall
+    );
+}
+
+#[tokio::test]
+async fn test_record_bad_debt_error_debtor_not_empty() {
+    // 1. Setup: the debtor still holds its collateral
+    let mut test = CollateralVaultProgramTest::new().await;
+    let (debtor_vault, debtor_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let (creditor_vault, creditor_ata) = test.setup_subaccount_vault(1, &user_ata, 5_000_000).await;
+    test.authorize_test_caller().await;
+    test.setup_insurance_fund(&user_ata, 20_000_000).await;
+
+    // 2. Try to record a shortfall the debtor could still pay
+    let record_ix = test.record_bad_debt_ix(0, &debtor_vault, &debtor_ata, &creditor_vault, &creditor_ata, 4_000_000);
+    let result = test.process_transaction(&[record_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::DebtorVaultNotEmpty);
+    assert_eq!(test.get_insurance_fund_account().await.bad_debt_count, 0);
+}
+
+#[tokio::test]
+async fn test_record_bad_debt_error_exceeds_obligation() {
+    // 1. Setup: an emptied debtor that only ever deposited 10 USDT
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let debtor_deposit = 10_000_000;
+    let (debtor_vault, debtor_ata, user_ata) = test.setup_vault(debtor_deposit).await;
+    let (creditor_vault, creditor_ata) = test.setup_subaccount_vault(1, &user_ata, 5_000_000).await;
+    test.authorize_test_caller().await;
+    test.setup_insurance_fund(&user_ata, 20_000_000).await;
+
+    let withdraw_ix = test.withdraw_ix(&user_pubkey, &debtor_vault, &debtor_ata, &user_ata, debtor_deposit);
+    test.process_transaction(&[withdraw_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Record a shortfall larger than the debtor's obligation
+    let record_ix = test.record_bad_debt_ix(
+        0,
+        &debtor_vault,
+        &debtor_ata,
+        &creditor_vault,
+        &creditor_ata,
+        debtor_deposit + 1,
+    );
+    let result = test.process_transaction(&[record_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::BadDebtExceedsObligation);
+    assert_eq!(test.get_vault_account(&debtor_vault).await.bad_debt_recorded, 0);
+}
+
+#[tokio::test]
+async fn test_cover_bad_debt_error_rate_limit_exceeded() {
+    // 1. Setup: the recording program may only move 1 USDT per window
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let debtor_deposit = 10_000_000;
+    let (debtor_vault, debtor_ata, user_ata) = test.setup_vault(debtor_deposit).await;
+    let creditor_deposit = 5_000_000;
+    let (creditor_vault, creditor_ata) = test.setup_subaccount_vault(1, &user_ata, creditor_deposit).await;
+    test.authorize_test_caller().await;
+    test.setup_insurance_fund(&user_ata, 20_000_000).await;
+
+    let program_id = test.program_id;
+    let withdraw_ix = test.withdraw_ix(&user_pubkey, &debtor_vault, &debtor_ata, &user_ata, debtor_deposit);
+    let rate_limit_ix = test.set_rate_limit_ix(&program_id, 1_000_000, 1_000);
+    let record_ix = test.record_bad_debt_ix(0, &debtor_vault, &debtor_ata, &creditor_vault, &creditor_ata, 4_000_000);
+    test.process_transaction(
+        &[withdraw_ix, rate_limit_ix, record_ix],
+        &[&test.user_keypair.insecure_clone()],
+    )
+    .await
+    .unwrap();
+
+    // 2. Cover the 4 USDT shortfall
+    let cover_ix = test.cover_bad_debt_ix(0, &creditor_vault, &creditor_ata);
+    let result = test.process_transaction(&[cover_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::RateLimitExceeded);
+    assert_eq!(test.get_bad_debt_account(0).await.covered, 0);
+    assert_eq!(test.get_vault_account(&creditor_vault).await.total_balance, creditor_deposit);
+}
+
+#[tokio::test]
+async fn test_socialize_bad_debt_success() {
+    // 1. Setup: 100 USDT pooled, an emptied debtor and an empty insurance fund
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let pooled_deposit = 100_000_000;
+    let (_pooled_vault, _pooled_ata, user_ata) = test.setup_pooled_vault(pooled_deposit).await;
+    let debtor_deposit = 2_000_000;
+    let (debtor_vault, debtor_ata) = test.setup_subaccount_vault(1, &user_ata, debtor_deposit).await;
+    let creditor_deposit = 1_000_000;
+    let (creditor_vault, creditor_ata) = test.setup_subaccount_vault(2, &user_ata, creditor_deposit).await;
+    test.authorize_test_caller().await;
+    test.setup_insurance_fund(&user_ata, 0).await;
+
+    let withdraw_ix = test.withdraw_ix(&user_pubkey, &debtor_vault, &debtor_ata, &user_ata, debtor_deposit);
+    let record_ix = test.record_bad_debt_ix(0, &debtor_vault, &debtor_ata, &creditor_vault, &creditor_ata, debtor_deposit);
+    test.process_transaction(&[withdraw_ix, record_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Socialize the shortfall across the pool and the creditor, the
+    //    only unpooled depositor left
+    let socialize_ix = test.socialize_bad_debt_ix(0, &creditor_vault, &creditor_ata);
+    let result = test.process_transaction(&[socialize_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: each side bears its share of the 101 USDT locked
+    let pool_part = debtor_deposit * pooled_deposit / (pooled_deposit + creditor_deposit);
+    let assessed = debtor_deposit - pool_part;
+    let share_pool = test.get_share_pool_account().await;
+    let bad_debt = test.get_bad_debt_account(0).await;
+    let creditor_state = test.get_vault_account(&creditor_vault).await;
+    assert_eq!(share_pool.total_assets, pooled_deposit - pool_part);
+    assert_eq!(bad_debt.socialized, pool_part);
+    assert_eq!(bad_debt.assessed, assessed);
+    assert_eq!(bad_debt.remaining(), 0);
+    assert_eq!(creditor_state.loss_uncollected, assessed);
+    assert_eq!(creditor_state.total_balance, creditor_deposit - assessed + pool_part);
+    assert_eq!(
+        test.get_token_balance(&test.find_pool_token_account()).await,
+        pooled_deposit - pool_part
+    );
+}
+
+#[tokio::test]
+async fn test_socialize_bad_debt_unpooled_vaults_success() {
+    // 1. Setup: 30 USDT in a bystander vault, 10 USDT with the creditor,
+    //    an emptied debtor and no share pool
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let bystander_deposit = 30_000_000;
+    let (bystander_vault, bystander_ata, user_ata) = test.setup_vault(bystander_deposit).await;
+    let debtor_deposit = 4_000_000;
+    let (debtor_vault, debtor_ata) = test.setup_subaccount_vault(1, &user_ata, debtor_deposit).await;
+    let creditor_deposit = 10_000_000;
+    let (creditor_vault, creditor_ata) = test.setup_subaccount_vault(2, &user_ata, creditor_deposit).await;
+    test.authorize_test_caller().await;
+    test.setup_insurance_fund(&user_ata, 0).await;
+
+    let withdraw_ix = test.withdraw_ix(&user_pubkey, &debtor_vault, &debtor_ata, &user_ata, debtor_deposit);
+    let record_ix = test.record_bad_debt_ix(0, &debtor_vault, &debtor_ata, &creditor_vault, &creditor_ata, debtor_deposit);
+    test.process_transaction(&[withdraw_ix, record_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Socialize the shortfall, then try to withdraw the bystander's
+    //    full deposit
+    let socialize_ix = test.socialize_unpooled_bad_debt_ix(0, &creditor_vault, &creditor_ata);
+    let result = test.process_transaction(&[socialize_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    let withdraw_ix = test.withdraw_ix(&user_pubkey, &bystander_vault, &bystander_ata, &user_ata, bystander_deposit);
+    let result = test
+        .process_transaction(&[withdraw_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify: the bystander lost 3 of its 30 USDT, the creditor 1 of 10
+    assert_program_error(result, errors::ErrorCode::InsufficientAvailableBalance);
+    let bad_debt = test.get_bad_debt_account(0).await;
+    assert_eq!(bad_debt.assessed, debtor_deposit);
+    assert_eq!(bad_debt.outstanding(), debtor_deposit);
+    assert_eq!(bad_debt.remaining(), 0);
+    let creditor_state = test.get_vault_account(&creditor_vault).await;
+    assert_eq!(creditor_state.total_balance, creditor_deposit - 1_000_000);
+    assert_eq!(creditor_state.loss_uncollected, 1_000_000);
+
+    let withdraw_ix = test.withdraw_ix(&user_pubkey, &bystander_vault, &bystander_ata, &user_ata, 27_000_000);
+    test.process_transaction(&[withdraw_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+    let bystander_state = test.get_vault_account(&bystander_vault).await;
+    assert_eq!(bystander_state.total_balance, 0);
+    assert_eq!(bystander_state.loss_uncollected, 3_000_000);
+    assert_eq!(test.get_token_balance(&bystander_ata).await, 3_000_000);
+}
+
+#[tokio::test]
+async fn test_collect_socialized_loss_success() {
+    // 1. Setup: a 4 USDT shortfall assessed on 30 + 10 USDT of unpooled vaults
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (bystander_vault, bystander_ata, user_ata) = test.setup_vault(30_000_000).await;
+    let debtor_deposit = 4_000_000;
+    let (debtor_vault, debtor_ata) = test.setup_subaccount_vault(1, &user_ata, debtor_deposit).await;
+    let creditor_deposit = 10_000_000;
+    let (creditor_vault, creditor_ata) = test.setup_subaccount_vault(2, &user_ata, creditor_deposit).await;
+    test.authorize_test_caller().await;
+    test.setup_insurance_fund(&user_ata, 0).await;
+
+    let withdraw_ix = test.withdraw_ix(&user_pubkey, &debtor_vault, &debtor_ata, &user_ata, debtor_deposit);
+    let record_ix = test.record_bad_debt_ix(0, &debtor_vault, &debtor_ata, &creditor_vault, &creditor_ata, debtor_deposit);
+    let socialize_ix = test.socialize_unpooled_bad_debt_ix(0, &creditor_vault, &creditor_ata);
+    test.process_transaction(
+        &[withdraw_ix, record_ix, socialize_ix],
+        &[&test.user_keypair.insecure_clone()],
+    )
+    .await
+    .unwrap();
+
+    // 2. Collect both vaults' write-offs, then pay the creditor from the fund
+    let collect_bystander_ix = test.collect_socialized_loss_ix(&bystander_vault);
+    let collect_creditor_ix = test.collect_socialized_loss_ix(&creditor_vault);
+    let cover_ix = test.cover_bad_debt_ix(0, &creditor_vault, &creditor_ata);
+    let result = test
+        .process_transaction(&[collect_bystander_ix, collect_creditor_ix, cover_ix], &[])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    let bystander_state = test.get_vault_account(&bystander_vault).await;
+    assert_eq!(bystander_state.total_balance, 27_000_000);
+    assert_eq!(bystander_state.loss_uncollected, 0);
+    assert_eq!(test.get_token_balance(&bystander_ata).await, 27_000_000);
+    let insurance_fund = test.get_insurance_fund_account().await;
+    assert_eq!(insurance_fund.total_loss_collected, debtor_deposit);
+    assert_eq!(insurance_fund.balance, 0);
+    let bad_debt = test.get_bad_debt_account(0).await;
+    assert_eq!(bad_debt.covered, debtor_deposit);
+    assert_eq!(bad_debt.assessed, 0);
+    assert_eq!(bad_debt.outstanding(), 0);
+    let creditor_state = test.get_vault_account(&creditor_vault).await;
+    assert_eq!(creditor_state.total_balance, creditor_deposit - 1_000_000 + debtor_deposit);
+    assert_eq!(test.get_token_balance(&creditor_ata).await, creditor_state.total_balance);
+}
+
+#[tokio::test]
+async fn test_collect_socialized_loss_error_nothing_to_collect() {
+    // 1. Setup: a vault and an insurance fund, but no socialized loss
+    let mut test = CollateralVaultProgramTest::new().await;
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    test.setup_insurance_fund(&user_ata, 0).await;
+
+    // 2. Collect from the vault
+    let collect_ix = test.collect_socialized_loss_ix(&vault_pda);
+    let result = test.process_transaction(&[collect_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::NoSocializedLoss);
+    assert_eq!(test.get_token_balance(&vault_ata).await, 10_000_000);
+}
+
+#[tokio::test]
+async fn test_close_vault_error_socialized_loss_uncollected() {
+    // 1. Setup: a bystander vault that has written off and withdrawn
+    //    everything else
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (bystander_vault, bystander_ata, user_ata) = test.setup_vault(30_000_000).await;
+    let debtor_deposit = 4_000_000;
+    let (debtor_vault, debtor_ata) = test.setup_subaccount_vault(1, &user_ata, debtor_deposit).await;
+    let (creditor_vault, creditor_ata) = test.setup_subaccount_vault(2, &user_ata, 10_000_000).await;
+    test.authorize_test_caller().await;
+    test.setup_insurance_fund(&user_ata, 0).await;
+
+    let withdraw_ix = test.withdraw_ix(&user_pubkey, &debtor_vault, &debtor_ata, &user_ata, debtor_deposit);
+    let record_ix = test.record_bad_debt_ix(0, &debtor_vault, &debtor_ata, &creditor_vault, &creditor_ata, debtor_deposit);
+    let socialize_ix = test.socialize_unpooled_bad_debt_ix(0, &creditor_vault, &creditor_ata);
+    let drain_ix = test.withdraw_ix(&user_pubkey, &bystander_vault, &bystander_ata, &user_ata, 27_000_000);
+    test.process_transaction(
+        &[withdraw_ix, record_ix, socialize_ix, drain_ix],
+        &[&test.user_keypair.insecure_clone()],
+    )
+    .await
+    .unwrap();
+
+    // 2. Close the vault before its write-off was collected
+    let close_ix = test.close_vault_ix(&user_pubkey, &bystander_vault, &user_ata, &bystander_ata);
+    let result = test
+        .process_transaction(&[close_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::SocializedLossUncollected);
+    assert_eq!(test.get_token_balance(&bystander_ata).await, 3_000_000);
+}
+
+#[tokio::test]
+async fn test_close_vault_error_reward_position_open() {
+    // 1. Setup: the vault is enrolled in a reward pool but has nothing locked
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let create_pool_ix = test.create_reward_pool_ix(1_000);
+    let open_ix = test.open_reward_position_ix(&vault_pda, &vault_ata);
+    test.process_transaction(&[create_pool_ix, open_ix], &[]).await.unwrap();
+
+    // 2. Close the vault with the position still open
+    let close_ix = test.close_vault_ix(&user_pubkey, &vault_pda, &user_ata, &vault_ata);
+    let result = test
+        .process_transaction(&[close_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::RewardPositionsOpen);
+    assert_eq!(test.get_vault_account(&vault_pda).await.reward_positions, 1);
+}
+
+#[tokio::test]
+async fn test_socialize_bad_debt_error_reserved_liquidity() {
+    // 1. Setup: every pooled token is committed
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let pooled_deposit = 100_000_000;
+    let (pooled_vault, pooled_ata, user_ata) = test.setup_pooled_vault(pooled_deposit).await;
+    let debtor_deposit = 2_000_000;
+    let (debtor_vault, debtor_ata) = test.setup_subaccount_vault(1, &user_ata, debtor_deposit).await;
+    let (creditor_vault, creditor_ata) = test.setup_subaccount_vault(2, &user_ata, 1_000_000).await;
+    test.authorize_test_caller().await;
+    test.setup_insurance_fund(&user_ata, 0).await;
+
+    // The creditor withdraws too, so the whole loss falls on the pool.
+    let until = test.get_clock().await.unix_timestamp + 86_400;
+    let commit_ix = test.commit_lock_ix(&user_pubkey, &pooled_vault, &pooled_ata, true, pooled_deposit, until);
+    let withdraw_ix = test.withdraw_ix(&user_pubkey, &debtor_vault, &debtor_ata, &user_ata, debtor_deposit);
+    let record_ix = test.record_bad_debt_ix(0, &debtor_vault, &debtor_ata, &creditor_vault, &creditor_ata, debtor_deposit);
+    let drain_ix = test.withdraw_ix(&user_pubkey, &creditor_vault, &creditor_ata, &user_ata, 1_000_000);
+    test.process_transaction(
+        &[commit_ix, withdraw_ix, record_ix, drain_ix],
+        &[&test.user_keypair.insecure_clone()],
+    )
+    .await
+    .unwrap();
+
+    // 2. Socialize while nothing in the pool is unreserved
+    let socialize_ix = test.socialize_bad_debt_ix(0, &creditor_vault, &creditor_ata);
+    let result = test.process_transaction(&[socialize_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InsufficientPoolLiquidity);
+    let share_pool = test.get_share_pool_account().await;
+    assert_eq!(share_pool.total_assets, pooled_deposit);
+    assert_eq!(share_pool.committed_assets, pooled_deposit);
+}
+
+#[tokio::test]
+async fn test_claim_rewards_split_by_locked_balance() {
+    // 1. Setup: two vaults commit 30 and 10 USDT and enroll in the reward pool
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (first_vault, first_ata, user_ata) = test.setup_vault(30_000_000).await;
+    let (second_vault, second_ata) = test.setup_subaccount_vault(1, &user_ata, 10_000_000).await;
+
+    let until = test.get_clock().await.unix_timestamp + 86_400;
+    let reward_duration = 1_000;
+    let create_pool_ix = test.create_reward_pool_ix(reward_duration);
+    let first_commit_ix = test.commit_lock_ix(&user_pubkey, &first_vault, &first_ata, false, 30_000_000, until);
+    let second_commit_ix = test.commit_lock_ix(&user_pubkey, &second_vault, &second_ata, false, 10_000_000, until);
+    let first_open_ix = test.open_reward_position_ix(&first_vault, &first_ata);
+    let second_open_ix = test.open_reward_position_ix(&second_vault, &second_ata);
+    test.process_transaction(
+        &[create_pool_ix, first_commit_ix, second_commit_ix, first_open_ix, second_open_ix],
+        &[&test.user_keypair.insecure_clone()],
+    )
+    .await
+    .unwrap();
+
+    // 2. Emit 1 USDT over the period, then claim the smaller position first
+    let emission = 1_000_000;
+    test.fund_reward_pool(emission).await;
+    test.advance_clock(2 * reward_duration).await;
+
+    let user_balance_before = test.get_token_balance(&user_ata).await;
+    let second_claim_ix = test.claim_rewards_ix(&user_pubkey, &second_vault, &user_ata);
+    let result = test
+        .process_transaction(&[second_claim_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    let first_claim_ix = test.claim_rewards_ix(&user_pubkey, &first_vault, &user_ata);
+    let result = test
+        .process_transaction(&[first_claim_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: each position got its share of the emission
+    let reward_pool = test.get_reward_pool_account().await;
+    let first_position = test.get_reward_position_account(&first_vault).await;
+    let second_position = test.get_reward_position_account(&second_vault).await;
+    assert_eq!(reward_pool.total_staked, 40_000_000);
+    assert_eq!(reward_pool.total_funded, emission);
+    assert_eq!(reward_pool.total_claimed, emission);
+    assert_eq!(first_position.total_claimed, 750_000);
+    assert_eq!(second_position.total_claimed, 250_000);
+    assert_eq!(first_position.earned, 0);
+    assert_eq!(test.get_token_balance(&user_ata).await, user_balance_before + emission);
+    assert_eq!(test.get_token_balance(&test.find_reward_token_account()).await, 0);
+}
+
+#[tokio::test]
+async fn test_sync_reward_position_success() {
+    // 1. Setup: the position is opened before anything is locked
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(50_000_000).await;
+
+    let reward_duration = 1_000;
+    let create_pool_ix = test.create_reward_pool_ix(reward_duration);
+    let open_ix = test.open_reward_position_ix(&vault_pda, &vault_ata);
+    test.process_transaction(&[create_pool_ix, open_ix], &[]).await.unwrap();
+    assert_eq!(test.get_reward_position_account(&vault_pda).await.staked, 0);
+
+    // 2. Commit 50 USDT, which restakes the position, and sync it again
+    let until = test.get_clock().await.unix_timestamp + 86_400;
+    let commit_ix = test.commit_lock_ix(&user_pubkey, &vault_pda, &vault_ata, false, 50_000_000, until);
+    let commit_ix = test.with_reward_position(commit_ix, &vault_pda);
+    let sync_ix = test.sync_reward_position_ix(&vault_pda);
+    let result = test
+        .process_transaction(&[commit_ix, sync_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    let reward_pool = test.get_reward_pool_account().await;
+    let reward_position = test.get_reward_position_account(&vault_pda).await;
+    assert_eq!(reward_position.staked, 50_000_000);
+    assert_eq!(reward_pool.total_staked, 50_000_000);
+
+    // 3. Verify: the synced stake earns the whole emission
+    let emission = 1_000_000;
+    test.fund_reward_pool(emission).await;
+    test.advance_clock(2 * reward_duration).await;
+
+    let user_balance_before = test.get_token_balance(&user_ata).await;
+    let claim_ix = test.claim_rewards_ix(&user_pubkey, &vault_pda, &user_ata);
+    test.process_transaction(&[claim_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+    assert_eq!(test.get_token_balance(&user_ata).await, user_balance_before + emission);
+}
+
+#[tokio::test]
+async fn test_create_reward_pool_error_zero_duration() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+
+    // 2. Create a pool without an emission period
+    let create_pool_ix = test.create_reward_pool_ix(0);
+    let result = test.process_transaction(&[create_pool_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidRewardDuration);
+    let (reward_pool, _) = test.find_reward_pool_pda();
+    assert!(test.get_account_data(&reward_pool).await.is_none());
+}
+
+#[tokio::test]
+async fn test_fund_reward_pool_error_amount_too_small() {
+    // 1. Setup: a period so long that a single token emits nothing per second
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+    let create_pool_ix = test.create_reward_pool_ix(10_000_000_000_000);
+    test.process_transaction(&[create_pool_ix], &[]).await.unwrap();
+
+    let admin = test.context.payer.pubkey();
+    let admin_ata = test.create_token_account(&admin).await;
+    test.mint_tokens(&admin_ata, 1).await;
+
+    // 2. Fund it
+    let fund_ix = test.fund_reward_pool_ix(&admin_ata, 1);
+    let result = test.process_transaction(&[fund_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidRewardRate);
+    assert_eq!(test.get_reward_pool_account().await.total_funded, 0);
+}
+
+#[tokio::test]
+async fn test_claim_rewards_error_nothing_locked() {
+    // 1. Setup: a funded pool, but the vault never locks anything
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+
+    let create_pool_ix = test.create_reward_pool_ix(1_000);
+    let open_ix = test.open_reward_position_ix(&vault_pda, &vault_ata);
+    test.process_transaction(&[create_pool_ix, open_ix], &[]).await.unwrap();
+    test.fund_reward_pool(1_000_000).await;
+    test.advance_clock(500).await;
+
+    // 2. Claim
+    let claim_ix = test.claim_rewards_ix(&user_pubkey, &vault_pda, &user_ata);
+    let result = test
+        .process_transaction(&[claim_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::NoRewardsToClaim);
+    assert_eq!(test.get_reward_pool_account().await.total_claimed, 0);
+}
+
+#[tokio::test]
+async fn test_total_value_locked_at_past_slots_success() {
+    // 1. Setup: 100 USDT at setup, then a deposit and withdrawal in one slot
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let initial_deposit = 100_000_000;
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(initial_deposit).await;
+    let setup_slot = test.get_clock().await.slot;
+
+    test.warp_to_slot(setup_slot + 10).await;
+    let deposit_amount = 50_000_000;
+    let deposit_ix = test.deposit_ix(&user_pubkey, &user_ata, &vault_pda, &vault_ata, deposit_amount);
+    let withdraw_ix = test.withdraw_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, 20_000_000);
+    test.process_transaction(&[deposit_ix, withdraw_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+    let change_slot = test.get_clock().await.slot;
+    test.warp_to_slot(change_slot + 10).await;
+
+    // 2. Query a slot between the two changes
+    let tvl_ix = test.total_value_locked_at_ix(setup_slot + 5);
+    let result = test.process_transaction(&[tvl_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: the changes within one slot collapsed into one checkpoint
+    let checkpoints = test.get_mint_state_account().await.tvl_checkpoints;
+    assert_eq!(checkpoints.checkpoints.len(), 2);
+    assert_eq!(checkpoints.balance_at(setup_slot + 5).unwrap(), initial_deposit);
+    assert_eq!(checkpoints.balance_at(change_slot).unwrap(), initial_deposit + 30_000_000);
+}
+
+#[tokio::test]
+async fn test_total_value_locked_at_error_current_slot() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(100_000_000).await;
+    let current_slot = test.get_clock().await.slot;
+
+    // 2. Ask for a slot that has not finished
+    let tvl_ix = test.total_value_locked_at_ix(current_slot);
+    let result = test.process_transaction(&[tvl_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::CheckpointUnavailable);
+}
+
+#[tokio::test]
+async fn test_set_rate_limit_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+    let program_id = test.program_id;
+
+    // 2. Allow 5 USDT per 1000 slots
+    let rate_limit_ix = test.set_rate_limit_ix(&program_id, 5_000_000, 1_000);
+    let result = test.process_transaction(&[rate_limit_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: the bucket starts full
+    let rate_limit = test.get_rate_limit_account(&program_id).await;
+    assert_eq!(rate_limit.program_id, program_id);
+    assert_eq!(rate_limit.capacity, 5_000_000);
+    assert_eq!(rate_limit.window_slots, 1_000);
+    assert_eq!(rate_limit.tokens, 5_000_000);
+}
+
+#[tokio::test]
+async fn test_set_rate_limit_error_invalid_config() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+    let program_id = test.program_id;
+
+    // 2. A window of zero slots never refills
+    let rate_limit_ix = test.set_rate_limit_ix(&program_id, 5_000_000, 0);
+    let result = test.process_transaction(&[rate_limit_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidRateLimitConfig);
+    let (rate_limit, _) = test.find_rate_limit_pda(&program_id);
+    assert!(test.get_account_data(&rate_limit).await.is_none());
+}
+
+#[tokio::test]
+async fn test_remove_rate_limit_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+    let program_id = test.program_id;
+    let rate_limit_ix = test.set_rate_limit_ix(&program_id, 5_000_000, 1_000);
+    test.process_transaction(&[rate_limit_ix], &[]).await.unwrap();
+
+    // 2. Remove it
+    let remove_ix = test.remove_rate_limit_ix(&program_id);
+    let result = test.process_transaction(&[remove_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    let (rate_limit, _) = test.find_rate_limit_pda(&program_id);
+    assert!(test.get_account_data(&rate_limit).await.is_none());
+}
+
+#[tokio::test]
+async fn test_remove_rate_limit_error_unauthorized_admin() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+    let program_id = test.program_id;
+    let rate_limit_ix = test.set_rate_limit_ix(&program_id, 5_000_000, 1_000);
+    test.process_transaction(&[rate_limit_ix], &[]).await.unwrap();
+
+    // 2. The vault owner tries to lift the limit
+    let user_pubkey = test.user_pubkey();
+    let remove_ix = test.as_signer(test.remove_rate_limit_ix(&program_id), &user_pubkey);
+    let result = test
+        .process_transaction(&[remove_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::UnauthorizedAdmin);
+    assert_eq!(test.get_rate_limit_account(&program_id).await.capacity, 5_000_000);
+}
+
+#[tokio::test]
+async fn test_transfer_collateral_within_rate_limit() {
+    // 1. Setup: two vaults and a 5 USDT bucket for the calling program
+    let mut test = CollateralVaultProgramTest::new().await;
+    let (from_vault, from_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let (to_vault, to_ata) = test.setup_subaccount_vault(1, &user_ata, 5_000_000).await;
+    test.authorize_test_caller().await;
+    let program_id = test.program_id;
+
+    // 2. Transfer 2 USDT in the slot the bucket is filled
+    let rate_limit_ix = test.set_rate_limit_ix(&program_id, 5_000_000, 1_000);
+    let transfer_ix = test.transfer_collateral_ix(&from_vault, &from_ata, &to_vault, &to_ata, 2_000_000);
+    let result = test.process_transaction(&[rate_limit_ix, transfer_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    assert_eq!(test.get_rate_limit_account(&program_id).await.tokens, 3_000_000);
+    assert_eq!(test.get_vault_account(&from_vault).await.total_balance, 8_000_000);
+    assert_eq!(test.get_vault_account(&to_vault).await.total_balance, 7_000_000);
+    assert_eq!(test.get_token_balance(&to_ata).await, 7_000_000);
+}
+
+#[tokio::test]
+async fn test_lock_collateral_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    test.authorize_test_caller().await;
+
+    // 2. Lock 4 USDT
+    let lock_ix = test.lock_collateral_ix(&user_pubkey, &vault_pda, 4_000_000);
+    let result = test.process_transaction(&[lock_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: the lock is attributed to the calling program
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.locked_balance, 4_000_000);
+    assert_eq!(vault_state.available_balance, 6_000_000);
+    assert_eq!(vault_state.program_locks.len(), 1);
+    assert_eq!(vault_state.program_locked_balance(&test.program_id), 4_000_000);
+}
+
+#[tokio::test]
+async fn test_lock_collateral_error_unauthorized_program() {
+    // 1. Setup: the calling program is not on the allowlist
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+
+    // 2. Try to lock 4 USDT
+    let lock_ix = test.lock_collateral_ix(&user_pubkey, &vault_pda, 4_000_000);
+    let result = test.process_transaction(&[lock_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::UnauthorizedProgram);
+    assert_eq!(test.get_vault_account(&vault_pda).await.locked_balance, 0);
+}
+
+#[tokio::test]
+async fn test_unlock_collateral_error_rate_limit_exceeded() {
+    // 1. Setup: 10 USDT locked, but the caller may only release 1 USDT per window
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    test.authorize_test_caller().await;
+    let program_id = test.program_id;
+
+    let lock_ix = test.lock_collateral_ix(&user_pubkey, &vault_pda, 10_000_000);
+    let rate_limit_ix = test.set_rate_limit_ix(&program_id, 1_000_000, 1_000);
+    test.process_transaction(&[lock_ix, rate_limit_ix], &[]).await.unwrap();
+
+    // 2. Unlock 5 USDT
+    let unlock_ix = test.unlock_collateral_ix(&user_pubkey, &vault_pda, 5_000_000);
+    let result = test.process_transaction(&[unlock_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::RateLimitExceeded);
+    assert_eq!(test.get_vault_account(&vault_pda).await.locked_balance, 10_000_000);
+}
+
+#[tokio::test]
+async fn test_unlock_collateral_rate_limit_exceeded_emits_alert() {
+    // 1. Setup: 10 USDT locked, but the caller may only release 1 USDT per window
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    test.authorize_test_caller().await;
+    let program_id = test.program_id;
+
+    let lock_ix = test.lock_collateral_ix(&user_pubkey, &vault_pda, 10_000_000);
+    let rate_limit_ix = test.set_rate_limit_ix(&program_id, 1_000_000, 1_000);
+    test.process_transaction(&[lock_ix, rate_limit_ix], &[]).await.unwrap();
+
+    // 2. Unlock 5 USDT
+    let unlock_ix = test.unlock_collateral_ix(&user_pubkey, &vault_pda, 5_000_000);
+    let logs = test.simulate_transaction_logs(&[unlock_ix], &[]).await;
+
+    // 3. Verify
+    let alert: RateLimitExceededEvent = find_event(&logs).expect("no rate limit alert emitted");
+    assert_eq!(alert.program_id, program_id);
+    assert_eq!(alert.vault, vault_pda);
+    assert_eq!(alert.amount, 5_000_000);
+    assert_eq!(alert.remaining, 1_000_000);
+    assert_eq!(alert.capacity, 1_000_000);
+    assert_eq!(alert.window_slots, 1_000);
+}
+
+#[tokio::test]
+async fn test_configure_circuit_breaker_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+
+    // 2. Pause on a 5% net outflow per hour
+    let configure_ix = test.configure_circuit_breaker_ix(500, 3_600, ProtocolStatus::Paused);
+    let result = test.process_transaction(&[configure_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    let protocol_state = test.get_protocol_state_account().await;
+    assert_eq!(protocol_state.max_outflow_bps, 500);
+    assert_eq!(protocol_state.window_seconds, 3_600);
+    assert_eq!(protocol_state.trip_status, ProtocolStatus::Paused);
+    assert_eq!(protocol_state.status, ProtocolStatus::Active);
+}
+
+#[tokio::test]
+async fn test_configure_circuit_breaker_error_active_trip_status() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+
+    // 2. A breaker that trips into Active would never stop anything
+    let configure_ix = test.configure_circuit_breaker_ix(500, 3_600, ProtocolStatus::Active);
+    let result = test.process_transaction(&[configure_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidCircuitBreakerConfig);
+    assert_eq!(test.get_protocol_state_account().await.trip_status, ProtocolStatus::WithdrawOnly);
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_trips_on_net_outflow() {
+    // 1. Setup: 100 USDT locked before the current window starts
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(100_000_000).await;
+    let window_seconds = test.get_protocol_state_account().await.window_seconds;
+    test.advance_clock(window_seconds).await;
+
+    // 2. Withdraw 30% against the default 20% threshold
+    let withdraw_ix = test.withdraw_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, 30_000_000);
+    let result = test
+        .process_transaction(&[withdraw_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: the tripping withdrawal completed, new deposits are blocked
+    let protocol_state = test.get_protocol_state_account().await;
+    assert_eq!(protocol_state.status, ProtocolStatus::WithdrawOnly);
+    assert_eq!(protocol_state.tripped_mint, test.usdt_mint);
+    assert_eq!(test.get_vault_account(&vault_pda).await.total_balance, 70_000_000);
+
+    let deposit_ix = test.deposit_ix(&user_pubkey, &user_ata, &vault_pda, &vault_ata, 1_000_000);
+    let result = test
+        .process_transaction(&[deposit_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert_program_error(result, errors::ErrorCode::ProtocolWithdrawOnly);
+}
+
+#[tokio::test]
+async fn test_reset_circuit_breaker_success() {
+    // 1. Setup: trip the breaker
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(100_000_000).await;
+    let window_seconds = test.get_protocol_state_account().await.window_seconds;
+    test.advance_clock(window_seconds).await;
+    let withdraw_ix = test.withdraw_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, 30_000_000);
+    test.process_transaction(&[withdraw_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Reset it
+    let reset_ix = test.reset_circuit_breaker_ix(true);
+    let result = test.process_transaction(&[reset_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: active again with a fresh window at the reduced TVL
+    let protocol_state = test.get_protocol_state_account().await;
+    let mint_state = test.get_mint_state_account().await;
+    assert_eq!(protocol_state.status, ProtocolStatus::Active);
+    assert_eq!(protocol_state.tripped_mint, Default::default());
+    assert_eq!(mint_state.window_outflow, 0);
+    assert_eq!(mint_state.window_start_tvl, 70_000_000);
+}
+
+#[tokio::test]
+async fn test_reset_circuit_breaker_error_missing_mint_state() {
+    // 1. Setup: trip the breaker
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(100_000_000).await;
+    let window_seconds = test.get_protocol_state_account().await.window_seconds;
+    test.advance_clock(window_seconds).await;
+    let withdraw_ix = test.withdraw_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, 30_000_000);
+    test.process_transaction(&[withdraw_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Reset without restarting the tripped mint's window
+    let reset_ix = test.reset_circuit_breaker_ix(false);
+    let result = test.process_transaction(&[reset_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidMintState);
+    assert_eq!(test.get_protocol_state_account().await.status, ProtocolStatus::WithdrawOnly);
+}
+
+#[tokio::test]
+async fn test_set_protocol_status_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+
+    // 2. Pause the protocol
+    let status_ix = test.set_protocol_status_ix(ProtocolStatus::Paused);
+    let result = test.process_transaction(&[status_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: withdrawals are stopped
+    assert_eq!(test.get_protocol_state_account().await.status, ProtocolStatus::Paused);
+    let withdraw_ix = test.withdraw_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, 1_000_000);
+    let result = test
+        .process_transaction(&[withdraw_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert_program_error(result, errors::ErrorCode::ProtocolPaused);
+}
+
+#[tokio::test]
+async fn test_set_protocol_status_error_unauthorized_admin() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+
+    // 2. The vault owner tries to pause the protocol
+    let user_pubkey = test.user_pubkey();
+    let status_ix = test.as_signer(test.set_protocol_status_ix(ProtocolStatus::Paused), &user_pubkey);
+    let result = test
+        .process_transaction(&[status_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::UnauthorizedAdmin);
+    assert_eq!(test.get_protocol_state_account().await.status, ProtocolStatus::Active);
+}
+
+#[tokio::test]
+async fn test_declare_emergency_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+
+    // 2. Declare
+    let declare_ix = test.declare_emergency_ix();
+    let result = test.process_transaction(&[declare_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: declaring pauses the protocol
+    let protocol_state = test.get_protocol_state_account().await;
+    let clock = test.get_clock().await;
+    assert_eq!(protocol_state.status, ProtocolStatus::Paused);
+    assert_eq!(protocol_state.emergency_declared_at, clock.unix_timestamp);
+}
+
+#[tokio::test]
+async fn test_declare_emergency_error_unauthorized_admin() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+
+    // 2. The vault owner tries to declare an emergency
+    let user_pubkey = test.user_pubkey();
+    let declare_ix = test.as_signer(test.declare_emergency_ix(), &user_pubkey);
+    let result = test
+        .process_transaction(&[declare_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::UnauthorizedAdmin);
+    assert_eq!(test.get_protocol_state_account().await.emergency_declared_at, 0);
+}
+
+#[tokio::test]
+async fn test_configure_emergency_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+
+    // 2. Open emergency mode after one hour of pause
+    let configure_ix = test.configure_emergency_ix(3_600);
+    let result = test.process_transaction(&[configure_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    assert_eq!(test.get_protocol_state_account().await.emergency_pause_seconds, 3_600);
+}
+
+#[tokio::test]
+async fn test_configure_emergency_error_zero_pause() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+    let pause_before = test.get_protocol_state_account().await.emergency_pause_seconds;
+
+    // 2. A zero pause would turn every pause into an emergency
+    let configure_ix = test.configure_emergency_ix(0);
+    let result = test.process_transaction(&[configure_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidEmergencyConfig);
+    assert_eq!(test.get_protocol_state_account().await.emergency_pause_seconds, pause_before);
+}
+
+#[tokio::test]
+async fn test_emergency_withdraw_success_ignores_locks() {
+    // 1. Setup: 4 of 10 USDT locked, then an emergency is declared
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let deposit = 10_000_000;
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(deposit).await;
+    test.authorize_test_caller().await;
+    let lock_ix = test.lock_collateral_ix(&user_pubkey, &vault_pda, 4_000_000);
+    let declare_ix = test.declare_emergency_ix();
+    test.process_transaction(&[lock_ix, declare_ix], &[]).await.unwrap();
+
+    // 2. Exit
+    let emergency_ix = test.emergency_withdraw_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, None);
+    let result = test
+        .process_transaction(&[emergency_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: locked collateral left too
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.total_balance, 0);
+    assert_eq!(vault_state.locked_balance, 0);
+    assert_eq!(test.get_token_balance(&vault_ata).await, 0);
+    assert_eq!(test.get_token_balance(&user_ata).await, common::USER_STARTING_USDT);
+}
+
+#[tokio::test]
+async fn test_emergency_withdraw_after_prolonged_pause() {
+    // 1. Setup: paused for as long as the emergency pause period
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let configure_ix = test.configure_emergency_ix(3_600);
+    let status_ix = test.set_protocol_status_ix(ProtocolStatus::Paused);
+    test.process_transaction(&[configure_ix, status_ix], &[]).await.unwrap();
+    test.advance_clock(3_600).await;
+
+    // 2. Exit
+    let emergency_ix = test.emergency_withdraw_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, None);
+    let result = test
+        .process_transaction(&[emergency_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    assert_eq!(test.get_vault_account(&vault_pda).await.total_balance, 0);
+    assert_eq!(test.get_token_balance(&user_ata).await, common::USER_STARTING_USDT);
+}
+
+#[tokio::test]
+async fn test_emergency_withdraw_error_inactive() {
+    // 1. Setup: paused, but not for long enough
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let deposit = 10_000_000;
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(deposit).await;
+    let configure_ix = test.configure_emergency_ix(3_600);
+    let status_ix = test.set_protocol_status_ix(ProtocolStatus::Paused);
+    test.process_transaction(&[configure_ix, status_ix], &[]).await.unwrap();
+    test.advance_clock(1_800).await;
+
+    // 2. Try to exit
+    let emergency_ix = test.emergency_withdraw_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, None);
+    let result = test
+        .process_transaction(&[emergency_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::EmergencyModeInactive);
+    assert_eq!(test.get_vault_account(&vault_pda).await.total_balance, deposit);
+}
+
+#[tokio::test]
+async fn test_mark_program_broken_success() {
+    // 1. Setup: an authorized program holds 4 of 10 USDT locked
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    test.authorize_test_caller().await;
+    let broken_program_id = test.program_id;
+    let configure_ix = test.configure_emergency_ix(3_600);
+    let lock_ix = test.lock_collateral_ix(&user_pubkey, &vault_pda, 4_000_000);
+    test.process_transaction(&[configure_ix, lock_ix], &[]).await.unwrap();
+
+    // 2. Mark it broken
+    let mark_ix = test.mark_program_broken_ix(&broken_program_id);
+    let result = test.process_transaction(&[mark_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    let broken_program = test.get_broken_program_account(&broken_program_id).await;
+    assert_eq!(broken_program.program_id, broken_program_id);
+    assert_eq!(broken_program.broken_since, test.get_clock().await.unix_timestamp);
+
+    // 3. Verify: after the pause period the program's lock can leave while
+    // the protocol stays active
+    test.advance_clock(3_600).await;
+    let emergency_ix = test.emergency_withdraw_ix(
+        &user_pubkey,
+        &vault_pda,
+        &vault_ata,
+        &user_ata,
+        Some(&broken_program_id),
+    );
+    let result = test
+        .process_transaction(&[emergency_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+    assert_eq!(test.get_protocol_state_account().await.status, ProtocolStatus::Active);
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.total_balance, 6_000_000);
+    assert_eq!(vault_state.locked_balance, 0);
+    assert_eq!(vault_state.available_balance, 6_000_000);
+    assert!(vault_state.program_locks.is_empty());
+    assert_eq!(test.get_token_balance(&vault_ata).await, 6_000_000);
+}
+
+#[tokio::test]
+async fn test_emergency_withdraw_broken_program_error_no_lock() {
+    // 1. Setup: a healthy program holds 4 USDT locked, another one is broken
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    test.authorize_test_caller().await;
+    let broken_program_id = Pubkey::new_unique();
+    let add_program_ix = test.add_authorized_program_ix(&broken_program_id);
+    let configure_ix = test.configure_emergency_ix(3_600);
+    let lock_ix = test.lock_collateral_ix(&user_pubkey, &vault_pda, 4_000_000);
+    let mark_ix = test.mark_program_broken_ix(&broken_program_id);
+    test.process_transaction(&[add_program_ix, configure_ix, lock_ix, mark_ix], &[])
+        .await
+        .unwrap();
+    test.advance_clock(3_600).await;
+
+    // 2. Try to exit through the broken program's report
+    let emergency_ix = test.emergency_withdraw_ix(
+        &user_pubkey,
+        &vault_pda,
+        &vault_ata,
+        &user_ata,
+        Some(&broken_program_id),
+    );
+    let result = test
+        .process_transaction(&[emergency_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify: the healthy program's lock stays in place
+    assert_program_error(result, errors::ErrorCode::InvalidAmount);
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.total_balance, 10_000_000);
+    assert_eq!(vault_state.locked_balance, 4_000_000);
+    assert_eq!(vault_state.program_locked_balance(&test.program_id), 4_000_000);
+}
+
+#[tokio::test]
+async fn test_mark_program_broken_error_not_authorized() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+
+    // 2. Mark a program that was never authorized
+    let unknown_program_id = Pubkey::new_unique();
+    let mark_ix = test.mark_program_broken_ix(&unknown_program_id);
+    let result = test.process_transaction(&[mark_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::ProgramNotAuthorized);
+    let (broken_program, _) = test.find_broken_program_pda(&unknown_program_id);
+    assert!(test.get_account_data(&broken_program).await.is_none());
+}
+
+#[tokio::test]
+async fn test_clear_program_broken_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+    let broken_program_id = Pubkey::new_unique();
+    let add_program_ix = test.add_authorized_program_ix(&broken_program_id);
+    let mark_ix = test.mark_program_broken_ix(&broken_program_id);
+    test.process_transaction(&[add_program_ix, mark_ix], &[]).await.unwrap();
+
+    // 2. Clear the report
+    let clear_ix = test.clear_program_broken_ix(&broken_program_id);
+    let result = test.process_transaction(&[clear_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    let (broken_program, _) = test.find_broken_program_pda(&broken_program_id);
+    assert!(test.get_account_data(&broken_program).await.is_none());
+}
+
+#[tokio::test]
+async fn test_clear_program_broken_error_unauthorized_admin() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+    let broken_program_id = Pubkey::new_unique();
+    let add_program_ix = test.add_authorized_program_ix(&broken_program_id);
+    let mark_ix = test.mark_program_broken_ix(&broken_program_id);
+    test.process_transaction(&[add_program_ix, mark_ix], &[]).await.unwrap();
+
+    // 2. The vault owner tries to clear it
+    let user_pubkey = test.user_pubkey();
+    let clear_ix = test.as_signer(test.clear_program_broken_ix(&broken_program_id), &user_pubkey);
+    let result = test
+        .process_transaction(&[clear_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::UnauthorizedAdmin);
+    assert_eq!(
+        test.get_broken_program_account(&broken_program_id).await.program_id,
+        broken_program_id
+    );
+}
+
+#[tokio::test]
+async fn test_close_vault_error_locked_balance() {
+    // 1. Setup: part of the collateral is locked by an authorized program
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let deposit = 10_000_000;
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(deposit).await;
+    test.authorize_test_caller().await;
+    let lock_ix = test.lock_collateral_ix(&user_pubkey, &vault_pda, 1_000_000);
+    test.process_transaction(&[lock_ix], &[]).await.unwrap();
+
+    // 2. Try to close
+    let close_ix = test.close_vault_ix(&user_pubkey, &vault_pda, &user_ata, &vault_ata);
+    let result = test
+        .process_transaction(&[close_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify: the vault and its tokens are untouched
+    assert_program_error(result, errors::ErrorCode::VaultHasLockedBalance);
+    assert_eq!(test.get_vault_account(&vault_pda).await.total_balance, deposit);
+    assert_eq!(test.get_token_balance(&vault_ata).await, deposit);
+}
+
+#[tokio::test]
+async fn test_set_withdrawal_cooldown_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+
+    // 2. Require a one hour cooldown for every mint
+    let cooldown_ix = test.set_withdrawal_cooldown_ix(3_600);
+    let result = test.process_transaction(&[cooldown_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: instant withdrawals are closed
+    assert_eq!(test.get_protocol_state_account().await.withdrawal_cooldown_seconds, 3_600);
+    let withdraw_ix = test.withdraw_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, 1_000_000);
+    let result = test
+        .process_transaction(&[withdraw_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert_program_error(result, errors::ErrorCode::WithdrawalCooldownRequired);
+}
+
+#[tokio::test]
+async fn test_set_withdrawal_cooldown_error_negative() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+
+    // 2. Try a negative cooldown
+    let cooldown_ix = test.set_withdrawal_cooldown_ix(-1);
+    let result = test.process_transaction(&[cooldown_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidCooldownConfig);
+    assert_eq!(test.get_protocol_state_account().await.withdrawal_cooldown_seconds, 0);
+}
+
+#[tokio::test]
+async fn test_set_mint_cooldown_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+
+    // 2. Override the cooldown for USDT only
+    let cooldown_ix = test.set_mint_cooldown_ix(600);
+    let result = test.process_transaction(&[cooldown_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    let cooldown_config = test.get_cooldown_config_account().await;
+    assert_eq!(cooldown_config.mint, test.usdt_mint);
+    assert_eq!(cooldown_config.cooldown_seconds, 600);
+    let withdraw_ix = test.withdraw_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, 1_000_000);
+    let result = test
+        .process_transaction(&[withdraw_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert_program_error(result, errors::ErrorCode::WithdrawalCooldownRequired);
+}
+
+#[tokio::test]
+async fn test_set_mint_cooldown_error_negative() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+
+    // 2. Try a negative cooldown
+    let cooldown_ix = test.set_mint_cooldown_ix(-600);
+    let result = test.process_transaction(&[cooldown_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidCooldownConfig);
+    let (cooldown_config, _) = test.find_cooldown_pda(&test.usdt_mint);
+    assert!(test.get_account_data(&cooldown_config).await.is_none());
+}
+
+#[tokio::test]
+async fn test_remove_mint_cooldown_success() {
+    // 1. Setup: USDT has a cooldown override
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let cooldown_ix = test.set_mint_cooldown_ix(600);
+    test.process_transaction(&[cooldown_ix], &[]).await.unwrap();
+
+    // 2. Remove it
+    let remove_ix = test.remove_mint_cooldown_ix();
+    let result = test.process_transaction(&[remove_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: USDT falls back to the zero default
+    let (cooldown_config, _) = test.find_cooldown_pda(&test.usdt_mint);
+    assert!(test.get_account_data(&cooldown_config).await.is_none());
+    let withdraw_ix = test.withdraw_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, 1_000_000);
+    let result = test
+        .process_transaction(&[withdraw_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+}
+
+#[tokio::test]
+async fn test_remove_mint_cooldown_error_unauthorized_admin() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+    let cooldown_ix = test.set_mint_cooldown_ix(600);
+    test.process_transaction(&[cooldown_ix], &[]).await.unwrap();
+
+    // 2. The vault owner tries to remove the override
+    let user_pubkey = test.user_pubkey();
+    let remove_ix = test.as_signer(test.remove_mint_cooldown_ix(), &user_pubkey);
+    let result = test
+        .process_transaction(&[remove_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::UnauthorizedAdmin);
+    assert_eq!(test.get_cooldown_config_account().await.cooldown_seconds, 600);
+}
+
+#[tokio::test]
+async fn test_set_program_cooldown_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let program_id = Pubkey::new_unique();
+    let add_program_ix = test.add_authorized_program_ix(&program_id);
+    test.process_transaction(&[add_program_ix], &[]).await.unwrap();
+
+    // 2. The program needs an hour to react to withdrawals
+    let cooldown_ix = test.set_program_cooldown_ix(&program_id, 3_600);
+    let result = test.process_transaction(&[cooldown_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: the longest program cooldown applies to every withdrawal
+    let protocol_state = test.get_protocol_state_account().await;
+    assert_eq!(protocol_state.program_cooldowns.len(), 1);
+    assert_eq!(protocol_state.program_cooldowns[0].program_id, program_id);
+    assert_eq!(protocol_state.program_cooldowns[0].cooldown_seconds, 3_600);
+    let withdraw_ix = test.withdraw_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, 1_000_000);
+    let result = test
+        .process_transaction(&[withdraw_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert_program_error(result, errors::ErrorCode::WithdrawalCooldownRequired);
+}
+
+#[tokio::test]
+async fn test_set_program_cooldown_error_not_authorized() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+
+    // 2. Set a cooldown for a program that was never authorized
+    let cooldown_ix = test.set_program_cooldown_ix(&Pubkey::new_unique(), 3_600);
+    let result = test.process_transaction(&[cooldown_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::ProgramNotAuthorized);
+    assert!(test.get_protocol_state_account().await.program_cooldowns.is_empty());
+}
+
+#[tokio::test]
+async fn test_remove_program_cooldown_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+    let program_id = Pubkey::new_unique();
+    let add_program_ix = test.add_authorized_program_ix(&program_id);
+    let cooldown_ix = test.set_program_cooldown_ix(&program_id, 3_600);
+    test.process_transaction(&[add_program_ix, cooldown_ix], &[]).await.unwrap();
+
+    // 2. Remove it
+    let remove_ix = test.remove_program_cooldown_ix(&program_id);
+    let result = test.process_transaction(&[remove_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    assert!(test.get_protocol_state_account().await.program_cooldowns.is_empty());
+}
+
+#[tokio::test]
+async fn test_remove_program_cooldown_error_not_set() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+
+    // 2. Remove a cooldown that was never set
+    let remove_ix = test.remove_program_cooldown_ix(&Pubkey::new_unique());
+    let result = test.process_transaction(&[remove_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::ProgramCooldownNotSet);
+}
+
+#[tokio::test]
+async fn test_request_and_execute_withdrawal_success() {
+    // 1. Setup: one hour cooldown
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let deposit = 10_000_000;
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(deposit).await;
+    let cooldown_ix = test.set_withdrawal_cooldown_ix(3_600);
+    test.process_transaction(&[cooldown_ix], &[]).await.unwrap();
+
+    // 2. Request 4 USDT
+    let amount = 4_000_000;
+    let request_ix = test.request_withdrawal_ix(&user_pubkey, &vault_pda, &vault_ata, amount);
+    let result = test
+        .process_transaction(&[request_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    let clock = test.get_clock().await;
+    assert_eq!(vault_state.pending_withdrawal, amount);
+    assert_eq!(vault_state.available_balance, deposit - amount);
+    assert_eq!(vault_state.withdrawal_ready_at, clock.unix_timestamp + 3_600);
+
+    // 3. Execute once the cooldown has passed
+    test.advance_clock(3_600).await;
+    let execute_ix = test.execute_withdrawal_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata);
+    let result = test
+        .process_transaction(&[execute_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.pending_withdrawal, 0);
+    assert_eq!(vault_state.total_balance, deposit - amount);
+    assert_eq!(test.get_token_balance(&vault_ata).await, deposit - amount);
+    assert_eq!(
+        test.get_token_balance(&user_ata).await,
+        common::USER_STARTING_USDT - deposit + amount
+    );
+}
+
+#[tokio::test]
+async fn test_request_withdrawal_error_insufficient_available_balance() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let deposit = 10_000_000;
+    let (vault_pda, vault_ata, _user_ata) = test.setup_vault(deposit).await;
+
+    // 2. Request more than the vault holds
+    let request_ix = test.request_withdrawal_ix(&user_pubkey, &vault_pda, &vault_ata, deposit + 1);
+    let result = test
+        .process_transaction(&[request_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InsufficientAvailableBalance);
+    assert_eq!(test.get_vault_account(&vault_pda).await.pending_withdrawal, 0);
+}
+
+#[tokio::test]
+async fn test_execute_withdrawal_error_not_ready() {
+    // 1. Setup: a request still inside its cooldown
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let deposit = 10_000_000;
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(deposit).await;
+    let cooldown_ix = test.set_withdrawal_cooldown_ix(3_600);
+    test.process_transaction(&[cooldown_ix], &[]).await.unwrap();
+    let request_ix = test.request_withdrawal_ix(&user_pubkey, &vault_pda, &vault_ata, 4_000_000);
+    test.process_transaction(&[request_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+    test.advance_clock(1_800).await;
+
+    // 2. Try to execute early
+    let execute_ix = test.execute_withdrawal_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata);
+    let result = test
+        .process_transaction(&[execute_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::WithdrawalNotReady);
+    assert_eq!(test.get_vault_account(&vault_pda).await.total_balance, deposit);
+}
+
+#[tokio::test]
+async fn test_cancel_withdrawal_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let deposit = 10_000_000;
+    let (vault_pda, vault_ata, _user_ata) = test.setup_vault(deposit).await;
+    let cooldown_ix = test.set_withdrawal_cooldown_ix(3_600);
+    test.process_transaction(&[cooldown_ix], &[]).await.unwrap();
+    let request_ix = test.request_withdrawal_ix(&user_pubkey, &vault_pda, &vault_ata, 4_000_000);
+    test.process_transaction(&[request_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Cancel
+    let cancel_ix = test.cancel_withdrawal_ix(&user_pubkey, &vault_pda);
+    let result = test
+        .process_transaction(&[cancel_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: the amount is available again
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.pending_withdrawal, 0);
+    assert_eq!(vault_state.withdrawal_ready_at, 0);
+    assert_eq!(vault_state.available_balance, deposit);
+}
+
+#[tokio::test]
+async fn test_cancel_withdrawal_error_no_pending_withdrawal() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+
+    // 2. Cancel without a request
+    let cancel_ix = test.cancel_withdrawal_ix(&user_pubkey, &vault_pda);
+    let result = test
+        .process_transaction(&[cancel_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::NoPendingWithdrawal);
+}
+
+#[tokio::test]
+async fn test_initialize_spending_policy_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+
+    // 2. At most 5 USDT per withdrawal and 8 USDT per day
+    let policy_ix = test.initialize_spending_policy_ix(&user_pubkey, &vault_pda, 5_000_000, 8_000_000, 3_600);
+    let result = test
+        .process_transaction(&[policy_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    let policy = test.get_spending_policy_account(&vault_pda).await;
+    assert_eq!(policy.vault, vault_pda);
+    assert_eq!(policy.max_per_transaction, 5_000_000);
+    assert_eq!(policy.max_per_day, 8_000_000);
+    assert_eq!(policy.policy_delay, 3_600);
+
+    let withdraw_ix = test.withdraw_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, 6_000_000);
+    let result = test
+        .process_transaction(&[withdraw_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert_program_error(result, errors::ErrorCode::WithdrawalExceedsTransactionLimit);
+}
+
+#[tokio::test]
+async fn test_initialize_spending_policy_error_zero_limit() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+
+    // 2. A zero daily limit would freeze the vault
+    let policy_ix = test.initialize_spending_policy_ix(&user_pubkey, &vault_pda, 5_000_000, 0, 3_600);
+    let result = test
+        .process_transaction(&[policy_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidSpendingPolicy);
+    let (spending_policy, _) = test.find_spending_policy_pda(&vault_pda);
+    assert!(test.get_account_data(&spending_policy).await.is_none());
+}
+
+#[tokio::test]
+async fn test_initialize_spending_policy_error_already_exists() {
+    // 1. Setup: the owner already has a policy with a delay on loosening
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let policy_ix = test.initialize_spending_policy_ix(&user_pubkey, &vault_pda, 5_000_000, 8_000_000, 3_600);
+    test.process_transaction(&[policy_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Try to skip the delay by initializing a looser policy
+    let policy_ix = test.initialize_spending_policy_ix(&user_pubkey, &vault_pda, 9_000_000, 9_000_000, 0);
+    let result = test
+        .process_transaction(&[policy_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::SpendingPolicyAlreadyExists);
+    assert_eq!(test.get_spending_policy_account(&vault_pda).await.max_per_transaction, 5_000_000);
+}
+
+#[tokio::test]
+async fn test_update_spending_policy_tightens_immediately_and_queues_loosening() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let policy_ix = test.initialize_spending_policy_ix(&user_pubkey, &vault_pda, 5_000_000, 8_000_000, 3_600);
+    test.process_transaction(&[policy_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Tighten the per-transaction limit
+    let update_ix = test.update_spending_policy_ix(&user_pubkey, &vault_pda, 2_000_000, 8_000_000, 3_600);
+    let result = test
+        .process_transaction(&[update_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    let policy = test.get_spending_policy_account(&vault_pda).await;
+    assert_eq!(policy.max_per_transaction, 2_000_000);
+    assert!(!policy.has_pending_change());
+
+    // 3. Loosen it again: queued behind the policy delay
+    let update_ix = test.update_spending_policy_ix(&user_pubkey, &vault_pda, 5_000_000, 8_000_000, 3_600);
+    let result = test
+        .process_transaction(&[update_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    let policy = test.get_spending_policy_account(&vault_pda).await;
+    let clock = test.get_clock().await;
+    assert_eq!(policy.max_per_transaction, 2_000_000);
+    assert_eq!(policy.pending_max_per_transaction, 5_000_000);
+    assert_eq!(policy.pending_effective_at, clock.unix_timestamp + 3_600);
+}
+
+#[tokio::test]
+async fn test_update_spending_policy_error_negative_delay() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let policy_ix = test.initialize_spending_policy_ix(&user_pubkey, &vault_pda, 5_000_000, 8_000_000, 3_600);
+    test.process_transaction(&[policy_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Try a negative delay
+    let update_ix = test.update_spending_policy_ix(&user_pubkey, &vault_pda, 5_000_000, 8_000_000, -1);
+    let result = test
+        .process_transaction(&[update_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidSpendingPolicy);
+    assert_eq!(test.get_spending_policy_account(&vault_pda).await.policy_delay, 3_600);
+}
+
+#[tokio::test]
+async fn test_cancel_spending_policy_change_success() {
+    // 1. Setup: a loosening is queued
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let policy_ix = test.initialize_spending_policy_ix(&user_pubkey, &vault_pda, 5_000_000, 8_000_000, 3_600);
+    let update_ix = test.update_spending_policy_ix(&user_pubkey, &vault_pda, 10_000_000, 8_000_000, 3_600);
+    test.process_transaction(&[policy_ix, update_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Cancel it
+    let cancel_ix = test.cancel_spending_policy_change_ix(&user_pubkey, &vault_pda);
+    let result = test
+        .process_transaction(&[cancel_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: the queued limit never applies
+    test.advance_clock(3_600).await;
+    let policy = test.get_spending_policy_account(&vault_pda).await;
+    assert!(!policy.has_pending_change());
+    assert_eq!(policy.max_per_transaction, 5_000_000);
+}
+
+#[tokio::test]
+async fn test_cancel_spending_policy_change_error_nothing_pending() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let policy_ix = test.initialize_spending_policy_ix(&user_pubkey, &vault_pda, 5_000_000, 8_000_000, 3_600);
+    test.process_transaction(&[policy_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Cancel with nothing queued
+    let cancel_ix = test.cancel_spending_policy_change_ix(&user_pubkey, &vault_pda);
+    let result = test
+        .process_transaction(&[cancel_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::NoPendingPolicyChange);
+}
+
+#[tokio::test]
+async fn test_add_withdrawal_destination_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let policy_ix = test.initialize_spending_policy_ix(&user_pubkey, &vault_pda, 5_000_000, 8_000_000, 3_600);
+    test.process_transaction(&[policy_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Allow a new destination
+    let destination = Pubkey::new_unique();
+    let add_ix = test.add_withdrawal_destination_ix(&user_pubkey, &vault_pda, &destination);
+    let result = test
+        .process_transaction(&[add_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: it only becomes active after the policy delay
+    let policy = test.get_spending_policy_account(&vault_pda).await;
+    let clock = test.get_clock().await;
+    assert_eq!(policy.destinations.len(), 1);
+    assert!(!policy.is_destination_active(&destination, clock.unix_timestamp));
+    assert!(policy.is_destination_active(&destination, clock.unix_timestamp + 3_600));
+}
+
+#[tokio::test]
+async fn test_add_withdrawal_destination_error_already_allowed() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let destination = Pubkey::new_unique();
+    let policy_ix = test.initialize_spending_policy_ix(&user_pubkey, &vault_pda, 5_000_000, 8_000_000, 3_600);
+    let add_ix = test.add_withdrawal_destination_ix(&user_pubkey, &vault_pda, &destination);
+    test.process_transaction(&[policy_ix, add_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Add it again
+    let add_ix = test.add_withdrawal_destination_ix(&user_pubkey, &vault_pda, &destination);
+    let result = test
+        .process_transaction(&[add_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::DestinationAlreadyAllowed);
+    assert_eq!(test.get_spending_policy_account(&vault_pda).await.destinations.len(), 1);
+}
+
+#[tokio::test]
+async fn test_remove_withdrawal_destination_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let destination = Pubkey::new_unique();
+    let policy_ix = test.initialize_spending_policy_ix(&user_pubkey, &vault_pda, 5_000_000, 8_000_000, 3_600);
+    let add_ix = test.add_withdrawal_destination_ix(&user_pubkey, &vault_pda, &destination);
+    test.process_transaction(&[policy_ix, add_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Remove it
+    let remove_ix = test.remove_withdrawal_destination_ix(&user_pubkey, &vault_pda, &destination);
+    let result = test
+        .process_transaction(&[remove_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    assert!(test.get_spending_policy_account(&vault_pda).await.destinations.is_empty());
+}
+
+#[tokio::test]
+async fn test_remove_withdrawal_destination_error_not_allowed() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let policy_ix = test.initialize_spending_policy_ix(&user_pubkey, &vault_pda, 5_000_000, 8_000_000, 3_600);
+    test.process_transaction(&[policy_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Remove a destination that was never added
+    let remove_ix = test.remove_withdrawal_destination_ix(&user_pubkey, &vault_pda, &Pubkey::new_unique());
+    let result = test
+        .process_transaction(&[remove_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::DestinationNotAllowed);
+}
+
+#[tokio::test]
+async fn test_grant_operator_withdraws_within_allowance() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let deposit = 10_000_000;
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(deposit).await;
+    let operator = Keypair::new();
+
+    // 2. Grant owner-only withdrawals capped at 3 USDT
+    let grant_ix = test.grant_operator_ix(
+        &user_pubkey,
+        &vault_pda,
+        &operator.pubkey(),
+        OPERATOR_SCOPE_WITHDRAW_TO_OWNER,
+        Some(3_000_000),
+    );
+    let result = test
+        .process_transaction(&[grant_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: the operator pays 2 USDT out to the owner
+    let withdraw_ix = test.operator_withdraw_ix(&operator.pubkey(), &vault_pda, &vault_ata, &user_ata, 2_000_000);
+    let result = test.process_transaction(&[withdraw_ix], &[&operator]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    let vault_operator = test.get_vault_operator_account(&vault_pda, &operator.pubkey()).await;
+    assert_eq!(vault_operator.scopes, OPERATOR_SCOPE_WITHDRAW_TO_OWNER);
+    assert_eq!(vault_operator.allowance, Some(1_000_000));
+    assert_eq!(test.get_vault_account(&vault_pda).await.total_balance, deposit - 2_000_000);
+    assert_eq!(
+        test.get_token_balance(&user_ata).await,
+        common::USER_STARTING_USDT - deposit + 2_000_000
+    );
+}
+
+#[tokio::test]
+async fn test_grant_operator_error_no_scopes() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let operator = Pubkey::new_unique();
+
+    // 2. Grant nothing
+    let grant_ix = test.grant_operator_ix(&user_pubkey, &vault_pda, &operator, 0, None);
+    let result = test
+        .process_transaction(&[grant_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidOperatorScopes);
+    let (vault_operator, _) = test.find_operator_pda(&vault_pda, &operator);
+    assert!(test.get_account_data(&vault_operator).await.is_none());
+}
+
+#[tokio::test]
+async fn test_operator_withdraw_error_allowance_exceeded() {
+    // 1. Setup: 3 USDT allowance
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let deposit = 10_000_000;
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(deposit).await;
+    let operator = Keypair::new();
+    let grant_ix = test.grant_operator_ix(
+        &user_pubkey,
+        &vault_pda,
+        &operator.pubkey(),
+        OPERATOR_SCOPE_WITHDRAW_TO_OWNER,
+        Some(3_000_000),
+    );
+    test.process_transaction(&[grant_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Withdraw more than allowed
+    let withdraw_ix = test.operator_withdraw_ix(&operator.pubkey(), &vault_pda, &vault_ata, &user_ata, 4_000_000);
+    let result = test.process_transaction(&[withdraw_ix], &[&operator]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::OperatorAllowanceExceeded);
+    assert_eq!(test.get_vault_account(&vault_pda).await.total_balance, deposit);
+}
+
+#[tokio::test]
+async fn test_revoke_operator_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let operator = Pubkey::new_unique();
+    let grant_ix = test.grant_operator_ix(&user_pubkey, &vault_pda, &operator, OPERATOR_SCOPE_WITHDRAW_TO_OWNER, None);
+    test.process_transaction(&[grant_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Revoke
+    let revoke_ix = test.revoke_operator_ix(&user_pubkey, &vault_pda, &operator);
+    let result = test
+        .process_transaction(&[revoke_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    let (vault_operator, _) = test.find_operator_pda(&vault_pda, &operator);
+    assert!(test.get_account_data(&vault_operator).await.is_none());
+}
+
+#[tokio::test]
+async fn test_revoke_operator_error_unauthorized_owner() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let operator = Keypair::new();
+    let grant_ix = test.grant_operator_ix(
+        &user_pubkey,
+        &vault_pda,
+        &operator.pubkey(),
+        OPERATOR_SCOPE_WITHDRAW_TO_OWNER,
+        None,
+    );
+    test.process_transaction(&[grant_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. The operator tries to revoke itself on the owner's behalf
+    let revoke_ix = test.revoke_operator_ix(&operator.pubkey(), &vault_pda, &operator.pubkey());
+    let result = test.process_transaction(&[revoke_ix], &[&operator]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::UnauthorizedOwner);
+    let vault_operator = test.get_vault_operator_account(&vault_pda, &operator.pubkey()).await;
+    assert_eq!(vault_operator.operator, operator.pubkey());
+}
+
+#[tokio::test]
+async fn test_deposit_for_creates_vault_success() {
+    // 1. Setup: the test payer funds a vault for someone else
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+    let payer = test.context.payer.pubkey();
+    let payer_ata = test.create_and_fund_user_ata(&payer).await;
+    let beneficiary = Pubkey::new_unique();
+
+    // 2. Deposit
+    let amount = 5_000_000;
+    let deposit_for_ix = test.deposit_for_ix(&payer, &payer_ata, &beneficiary, 0, amount);
+    let result = test.process_transaction(&[deposit_for_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: the beneficiary owns the vault, the payer gets its rent back
+    let (vault_pda, _) = test.find_vault_pda(&beneficiary);
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.owner, beneficiary);
+    assert_eq!(vault_state.rent_payer, payer);
+    assert_eq!(vault_state.total_balance, amount);
+    assert_eq!(test.get_token_balance(&test.find_vault_token_account(&vault_pda)).await, amount);
+    assert_eq!(test.get_token_balance(&payer_ata).await, common::USER_STARTING_USDT - amount);
+}
+
+#[tokio::test]
+async fn test_deposit_for_existing_vault_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let deposit = 10_000_000;
+    let (vault_pda, vault_ata, _user_ata) = test.setup_vault(deposit).await;
+    let payer = test.context.payer.pubkey();
+    let payer_ata = test.create_and_fund_user_ata(&payer).await;
+
+    // 2. Top up the user's vault
+    let deposit_for_ix = test.deposit_for_ix(&payer, &payer_ata, &user_pubkey, 0, 500_000);
+    let result = test.process_transaction(&[deposit_for_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.owner, user_pubkey);
+    assert_eq!(vault_state.total_balance, deposit + 500_000);
+    assert_eq!(test.get_token_balance(&vault_ata).await, deposit + 500_000);
+}
+
+#[tokio::test]
+async fn test_deposit_for_error_below_minimum() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+    let payer = test.context.payer.pubkey();
+    let payer_ata = test.create_and_fund_user_ata(&payer).await;
+    let beneficiary = Pubkey::new_unique();
+
+    // 2. Create a vault with less than the minimum deposit
+    let deposit_for_ix = test.deposit_for_ix(&payer, &payer_ata, &beneficiary, 0, 500_000);
+    let result = test.process_transaction(&[deposit_for_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::DepositBelowMinimum);
+    let (vault_pda, _) = test.find_vault_pda(&beneficiary);
+    assert!(test.get_account_data(&vault_pda).await.is_none());
+    assert_eq!(test.get_token_balance(&payer_ata).await, common::USER_STARTING_USDT);
+}
+
+#[tokio::test]
+async fn test_permit_deposit_success() {
+    // 1. Setup: the owner approves the vault and signs a permit off-chain
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let deposit = 10_000_000;
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(deposit).await;
+    let relayer = test.context.payer.pubkey();
+    let relayer_ata = test.create_and_fund_user_ata(&relayer).await;
+
+    let amount = 5_000_000;
+    let relayer_fee = 100_000;
+    let approve_ix = test.approve_vault_delegate_ix(&user_pubkey, &user_ata, &vault_pda, amount);
+    test.process_transaction(&[approve_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    let clock = test.get_clock().await;
+    let message = PermitMessage {
+        vault: vault_pda,
+        action: PermitAction::Deposit,
+        amount,
+        relayer_fee,
+        nonce: 0,
+        expiry: clock.unix_timestamp + 3_600,
+    };
+
+    // 2. The relayer submits it
+    let signature_ix = test.permit_signature_ix(&test.user_keypair.insecure_clone(), &message);
+    let permit_ix = test.permit_deposit_ix(&relayer, &vault_pda, &user_ata, &vault_ata, Some(&relayer_ata), &message);
+    let result = test.process_transaction(&[signature_ix, permit_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: the fee comes out of the deposit and the nonce is burned
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.total_balance, deposit + amount - relayer_fee);
+    assert_eq!(vault_state.permit_nonce, 1);
+    assert_eq!(test.get_token_balance(&vault_ata).await, deposit + amount - relayer_fee);
+    assert_eq!(test.get_token_balance(&relayer_ata).await, common::USER_STARTING_USDT + relayer_fee);
+}
+
+#[tokio::test]
+async fn test_permit_deposit_error_wrong_signer() {
+    // 1. Setup: the permit is signed by someone other than the owner
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let deposit = 10_000_000;
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(deposit).await;
+    let relayer = test.context.payer.pubkey();
+    let approve_ix = test.approve_vault_delegate_ix(&user_pubkey, &user_ata, &vault_pda, 5_000_000);
+    test.process_transaction(&[approve_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    let clock = test.get_clock().await;
+    let message = PermitMessage {
+        vault: vault_pda,
+        action: PermitAction::Deposit,
+        amount: 5_000_000,
+        relayer_fee: 0,
+        nonce: 0,
+        expiry: clock.unix_timestamp + 3_600,
+    };
+
+    // 2. Submit it
+    let signature_ix = test.permit_signature_ix(&Keypair::new(), &message);
+    let permit_ix = test.permit_deposit_ix(&relayer, &vault_pda, &user_ata, &vault_ata, None, &message);
+    let result = test.process_transaction(&[signature_ix, permit_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidPermitSignature);
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.total_balance, deposit);
+    assert_eq!(vault_state.permit_nonce, 0);
+}
+
+#[tokio::test]
+async fn test_permit_withdraw_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let deposit = 10_000_000;
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(deposit).await;
+    let relayer = test.context.payer.pubkey();
+
+    let clock = test.get_clock().await;
+    let message = PermitMessage {
+        vault: vault_pda,
+        action: PermitAction::Withdraw,
+        amount: 3_000_000,
+        relayer_fee: 0,
+        nonce: 0,
+        expiry: clock.unix_timestamp + 3_600,
+    };
+
+    // 2. The relayer withdraws to the owner
+    let signature_ix = test.permit_signature_ix(&test.user_keypair.insecure_clone(), &message);
+    let permit_ix = test.permit_withdraw_ix(&relayer, &vault_pda, &user_ata, &vault_ata, None, &message);
+    let result = test.process_transaction(&[signature_ix, permit_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.total_balance, deposit - 3_000_000);
+    assert_eq!(vault_state.permit_nonce, 1);
+    assert_eq!(
+        test.get_token_balance(&user_ata).await,
+        common::USER_STARTING_USDT - deposit + 3_000_000
+    );
+}
+
+#[tokio::test]
+async fn test_permit_withdraw_error_expired() {
+    // 1. Setup: a permit that expired a second ago
+    let mut test = CollateralVaultProgramTest::new().await;
+    let deposit = 10_000_000;
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(deposit).await;
+    let relayer = test.context.payer.pubkey();
+
+    let clock = test.get_clock().await;
+    let message = PermitMessage {
+        vault: vault_pda,
+        action: PermitAction::Withdraw,
+        amount: 3_000_000,
+        relayer_fee: 0,
+        nonce: 0,
+        expiry: clock.unix_timestamp - 1,
+    };
+
+    // 2. Submit it
+    let signature_ix = test.permit_signature_ix(&test.user_keypair.insecure_clone(), &message);
+    let permit_ix = test.permit_withdraw_ix(&relayer, &vault_pda, &user_ata, &vault_ata, None, &message);
+    let result = test.process_transaction(&[signature_ix, permit_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::PermitExpired);
+    assert_eq!(test.get_vault_account(&vault_pda).await.total_balance, deposit);
+}
+
+#[tokio::test]
+async fn test_permit_withdraw_error_invalid_nonce() {
+    // 1. Setup: a permit for a nonce the vault has not reached
+    let mut test = CollateralVaultProgramTest::new().await;
+    let deposit = 10_000_000;
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(deposit).await;
+    let relayer = test.context.payer.pubkey();
+
+    let clock = test.get_clock().await;
+    let message = PermitMessage {
+        vault: vault_pda,
+        action: PermitAction::Withdraw,
+        amount: 3_000_000,
+        relayer_fee: 0,
+        nonce: 5,
+        expiry: clock.unix_timestamp + 3_600,
+    };
+
+    // 2. Submit it
+    let signature_ix = test.permit_signature_ix(&test.user_keypair.insecure_clone(), &message);
+    let permit_ix = test.permit_withdraw_ix(&relayer, &vault_pda, &user_ata, &vault_ata, None, &message);
+    let result = test.process_transaction(&[signature_ix, permit_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidPermitNonce);
+    assert_eq!(test.get_vault_account(&vault_pda).await.permit_nonce, 0);
+}
+
+#[tokio::test]
+async fn test_initialize_vault_sponsored_success() {
+    // 1. Setup: the test payer sponsors the rent of a second subaccount
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (_vault_pda, _vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let sponsor = test.context.payer.pubkey();
+    let user_lamports_before = test.context.banks_client.get_balance(user_pubkey).await.unwrap();
+
+    // 2. Initialize
+    let deposit = 5_000_000;
+    let sponsored_ix = test.initialize_vault_sponsored_ix(&sponsor, &user_pubkey, 1, &user_ata, deposit);
+    let result = test
+        .process_transaction(&[sponsored_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: the user owns the vault without paying its rent
+    let (vault_pda, _) = test.find_subaccount_vault_pda(&user_pubkey, 1);
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.owner, user_pubkey);
+    assert_eq!(vault_state.subaccount, 1);
+    assert_eq!(vault_state.rent_payer, sponsor);
+    assert_eq!(vault_state.total_balance, deposit);
+    assert_eq!(test.get_token_balance(&test.find_vault_token_account(&vault_pda)).await, deposit);
+    assert_eq!(
+        test.context.banks_client.get_balance(user_pubkey).await.unwrap(),
+        user_lamports_before
+    );
+}
+
+#[tokio::test]
+async fn test_initialize_vault_sponsored_error_below_minimum() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (_vault_pda, _vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let sponsor = test.context.payer.pubkey();
+
+    // 2. Initialize with less than the minimum deposit
+    let sponsored_ix = test.initialize_vault_sponsored_ix(&sponsor, &user_pubkey, 1, &user_ata, 500_000);
+    let result = test
+        .process_transaction(&[sponsored_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::DepositBelowMinimum);
+    let (vault_pda, _) = test.find_subaccount_vault_pda(&user_pubkey, 1);
+    assert!(test.get_account_data(&vault_pda).await.is_none());
+}
+
+#[tokio::test]
+async fn test_transfer_between_subaccounts_success() {
+    // 1. Setup: two subaccounts of the same owner
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let (second_vault_pda, second_vault_ata) = test.setup_subaccount_vault(1, &user_ata, 2_000_000).await;
+
+    // 2. Move 3 USDT into subaccount 1
+    let transfer_ix = test.transfer_between_subaccounts_ix(&user_pubkey, &vault_pda, &second_vault_pda, 3_000_000);
+    let result = test
+        .process_transaction(&[transfer_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    assert_eq!(test.get_vault_account(&vault_pda).await.total_balance, 7_000_000);
+    assert_eq!(test.get_vault_account(&second_vault_pda).await.total_balance, 5_000_000);
+    assert_eq!(test.get_token_balance(&vault_ata).await, 7_000_000);
+    assert_eq!(test.get_token_balance(&second_vault_ata).await, 5_000_000);
+}
+
+#[tokio::test]
+async fn test_transfer_between_subaccounts_error_other_owner() {
+    // 1. Setup: a vault that belongs to someone else
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let payer = test.context.payer.pubkey();
+    let payer_ata = test.create_and_fund_user_ata(&payer).await;
+    let other_owner = Pubkey::new_unique();
+    let deposit_for_ix = test.deposit_for_ix(&payer, &payer_ata, &other_owner, 0, 2_000_000);
+    test.process_transaction(&[deposit_for_ix], &[]).await.unwrap();
+    let (other_vault_pda, _) = test.find_vault_pda(&other_owner);
+
+    // 2. Try to move collateral into it
+    let transfer_ix = test.transfer_between_subaccounts_ix(&user_pubkey, &vault_pda, &other_vault_pda, 3_000_000);
+    let result = test
+        .process_transaction(&[transfer_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::UnauthorizedOwner);
+    assert_eq!(test.get_vault_account(&vault_pda).await.total_balance, 10_000_000);
+    assert_eq!(test.get_vault_account(&other_vault_pda).await.total_balance, 2_000_000);
+}
+
+#[tokio::test]
+async fn test_transfer_between_subaccounts_counts_toward_spending_policy() {
+    // 1. Setup: the source subaccount may move 5 USDT per transaction
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let (second_vault_pda, _second_vault_ata) = test.setup_subaccount_vault(1, &user_ata, 2_000_000).await;
+    let policy_ix = test.initialize_spending_policy_ix(&user_pubkey, &vault_pda, 5_000_000, 8_000_000, 3_600);
+    test.process_transaction(&[policy_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Move 3 USDT into subaccount 1
+    let transfer_ix = test.transfer_between_subaccounts_ix(&user_pubkey, &vault_pda, &second_vault_pda, 3_000_000);
+    let result = test
+        .process_transaction(&[transfer_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    assert_eq!(test.get_spending_policy_account(&vault_pda).await.spent_today, 3_000_000);
+    assert_eq!(test.get_vault_account(&second_vault_pda).await.total_balance, 5_000_000);
+}
+
+#[tokio::test]
+async fn test_transfer_between_subaccounts_error_exceeds_spending_policy() {
+    // 1. Setup: the source subaccount may move 5 USDT per transaction
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let (second_vault_pda, _second_vault_ata) = test.setup_subaccount_vault(1, &user_ata, 2_000_000).await;
+    let policy_ix = test.initialize_spending_policy_ix(&user_pubkey, &vault_pda, 5_000_000, 8_000_000, 3_600);
+    test.process_transaction(&[policy_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Move 6 USDT into subaccount 1
+    let transfer_ix = test.transfer_between_subaccounts_ix(&user_pubkey, &vault_pda, &second_vault_pda, 6_000_000);
+    let result = test
+        .process_transaction(&[transfer_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::WithdrawalExceedsTransactionLimit);
+    assert_eq!(test.get_vault_account(&vault_pda).await.total_balance, 10_000_000);
+}
+
+#[tokio::test]
+async fn test_propose_and_accept_owner_transfer_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let new_owner = test.context.payer.pubkey();
+
+    // 2. Propose
+    let propose_ix = test.propose_owner_transfer_ix(&user_pubkey, &vault_pda, &new_owner);
+    let result = test
+        .process_transaction(&[propose_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+    assert_eq!(test.get_vault_account(&vault_pda).await.pending_owner, Some(new_owner));
+
+    // 3. Accept and verify: the vault moves between the owner indexes
+    let accept_ix = test.accept_owner_transfer_ix(&new_owner, &vault_pda, &user_pubkey);
+    let result = test.process_transaction(&[accept_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.owner, new_owner);
+    assert_eq!(vault_state.pending_owner, None);
+    assert!(test.get_owner_index_account(&user_pubkey).await.subaccounts.is_empty());
+    let new_owner_index = test.get_owner_index_account(&new_owner).await;
+    assert_eq!(new_owner_index.subaccounts.len(), 1);
+    assert_eq!(new_owner_index.subaccounts[0].vault, vault_pda);
+}
+
+#[tokio::test]
+async fn test_propose_owner_transfer_error_self() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+
+    // 2. Propose the current owner
+    let propose_ix = test.propose_owner_transfer_ix(&user_pubkey, &vault_pda, &user_pubkey);
+    let result = test
+        .process_transaction(&[propose_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidNewOwner);
+    assert_eq!(test.get_vault_account(&vault_pda).await.pending_owner, None);
+}
+
+#[tokio::test]
+async fn test_accept_owner_transfer_error_not_pending_owner() {
+    // 1. Setup: the transfer is proposed to someone else
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let proposed_owner = Pubkey::new_unique();
+    let propose_ix = test.propose_owner_transfer_ix(&user_pubkey, &vault_pda, &proposed_owner);
+    test.process_transaction(&[propose_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. The test payer tries to accept
+    let payer = test.context.payer.pubkey();
+    let accept_ix = test.accept_owner_transfer_ix(&payer, &vault_pda, &user_pubkey);
+    let result = test.process_transaction(&[accept_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::UnauthorizedPendingOwner);
+    assert_eq!(test.get_vault_account(&vault_pda).await.owner, user_pubkey);
+}
+
+#[tokio::test]
+async fn test_accept_owner_transfer_revokes_delegations() {
+    // 1. Setup: the owner empowers an operator, a co-signer, a lockdown
+    // recovery key and a guardian, then hands the vault to the test payer
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let operator = Keypair::new();
+    let guardian = Keypair::new();
+    let new_owner = test.context.payer.pubkey();
+    let new_owner_ata = test.create_and_fund_user_ata(&new_owner).await;
+
+    let grant_ix = test.grant_operator_ix(
+        &user_pubkey,
+        &vault_pda,
+        &operator.pubkey(),
+        OPERATOR_SCOPE_WITHDRAW_TO_OWNER,
+        None,
+    );
+    let cosigner_ix = test.set_cosigner_ix(&user_pubkey, &vault_pda, None, Some(Pubkey::new_unique()), 1_000_000);
+    let lockdown_ix = test.configure_lockdown_ix(&user_pubkey, &vault_pda, &Pubkey::new_unique(), 3_600);
+    let recovery_ix = test.configure_recovery_ix(&user_pubkey, &vault_pda, vec![guardian.pubkey()], 1, 3_600);
+    let propose_ix = test.propose_owner_transfer_ix(&user_pubkey, &vault_pda, &new_owner);
+    test.process_transaction(
+        &[grant_ix, cosigner_ix, lockdown_ix, recovery_ix, propose_ix],
+        &[&test.user_keypair.insecure_clone()],
+    )
+    .await
+    .unwrap();
+
+    // 2. Accept
+    let accept_ix = test.accept_owner_transfer_ix(&new_owner, &vault_pda, &user_pubkey);
+    let result = test.process_transaction(&[accept_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: nothing the previous owner set up still has a say
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.delegation_epoch, 1);
+    assert_eq!(vault_state.cosigner, None);
+    assert_eq!(vault_state.cosigner_threshold, 0);
+    assert_eq!(vault_state.lockdown_recovery_key, None);
+
+    let withdraw_ix = test.operator_withdraw_ix(&operator.pubkey(), &vault_pda, &vault_ata, &new_owner_ata, 1_000_000);
+    let result = test.process_transaction(&[withdraw_ix], &[&operator]).await;
+    assert_program_error(result, errors::ErrorCode::DelegationRevoked);
+
+    let initiate_ix = test.initiate_recovery_ix(&guardian.pubkey(), &vault_pda, &guardian.pubkey());
+    let result = test.process_transaction(&[initiate_ix], &[&guardian]).await;
+    assert_program_error(result, errors::ErrorCode::DelegationRevoked);
+    assert_eq!(test.get_vault_account(&vault_pda).await.total_balance, 10_000_000);
+}
+
+#[tokio::test]
+async fn test_accept_owner_transfer_error_previous_session_key() {
+    // 1. Setup: a session key registered by the previous owner
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let session_key = Keypair::new();
+    let expires_at = test.get_clock().await.unix_timestamp + 3_600;
+    let new_owner = test.context.payer.pubkey();
+    let new_owner_ata = test.create_and_fund_user_ata(&new_owner).await;
+
+    let register_ix = test.register_session_key_ix(
+        &user_pubkey,
+        &vault_pda,
+        &session_key.pubkey(),
+        SESSION_SCOPE_WITHDRAW,
+        5_000_000,
+        expires_at,
+    );
+    let propose_ix = test.propose_owner_transfer_ix(&user_pubkey, &vault_pda, &new_owner);
+    let accept_ix = test.accept_owner_transfer_ix(&new_owner, &vault_pda, &user_pubkey);
+    test.process_transaction(
+        &[register_ix, propose_ix, accept_ix],
+        &[&test.user_keypair.insecure_clone()],
+    )
+    .await
+    .unwrap();
+
+    // 2. The session tries to pay the new owner
+    let withdraw_ix = test.session_withdraw_ix(&session_key.pubkey(), &vault_pda, &vault_ata, &new_owner_ata, 1_000_000);
+    let result = test.process_transaction(&[withdraw_ix], &[&session_key]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::DelegationRevoked);
+    assert_eq!(test.get_vault_account(&vault_pda).await.total_balance, 10_000_000);
+}
+
+#[tokio::test]
+async fn test_accept_owner_transfer_new_owner_replaces_spending_policy() {
+    // 1. Setup: the previous owner allowlisted a destination of their own
+    // choosing before handing the vault over
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let new_owner = test.context.payer.pubkey();
+
+    let policy_ix = test.initialize_spending_policy_ix(&user_pubkey, &vault_pda, 5_000_000, 8_000_000, 0);
+    let destination_ix = test.add_withdrawal_destination_ix(&user_pubkey, &vault_pda, &Pubkey::new_unique());
+    let propose_ix = test.propose_owner_transfer_ix(&user_pubkey, &vault_pda, &new_owner);
+    let accept_ix = test.accept_owner_transfer_ix(&new_owner, &vault_pda, &user_pubkey);
+    test.process_transaction(
+        &[policy_ix, destination_ix, propose_ix, accept_ix],
+        &[&test.user_keypair.insecure_clone()],
+    )
+    .await
+    .unwrap();
+
+    // 2. The new owner sets up their own policy
+    let policy_ix = test.initialize_spending_policy_ix(&new_owner, &vault_pda, 2_000_000, 4_000_000, 3_600);
+    let result = test.process_transaction(&[policy_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: the old allowlist is gone
+    let policy = test.get_spending_policy_account(&vault_pda).await;
+    assert_eq!(policy.delegation_epoch, 1);
+    assert_eq!(policy.max_per_transaction, 2_000_000);
+    assert!(policy.destinations.is_empty());
+}
+
+#[tokio::test]
+async fn test_cancel_owner_transfer_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let new_owner = test.context.payer.pubkey();
+    let propose_ix = test.propose_owner_transfer_ix(&user_pubkey, &vault_pda, &new_owner);
+    test.process_transaction(&[propose_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Cancel
+    let cancel_ix = test.cancel_owner_transfer_ix(&user_pubkey, &vault_pda);
+    let result = test
+        .process_transaction(&[cancel_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: the proposed owner can no longer accept
+    assert_eq!(test.get_vault_account(&vault_pda).await.pending_owner, None);
+    let accept_ix = test.accept_owner_transfer_ix(&new_owner, &vault_pda, &user_pubkey);
+    let result = test.process_transaction(&[accept_ix], &[]).await;
+    assert_program_error(result, errors::ErrorCode::NoPendingOwnerTransfer);
+}
+
+#[tokio::test]
+async fn test_cancel_owner_transfer_error_nothing_pending() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+
+    // 2. Cancel without a proposal
+    let cancel_ix = test.cancel_owner_transfer_ix(&user_pubkey, &vault_pda);
+    let result = test
+        .process_transaction(&[cancel_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::NoPendingOwnerTransfer);
+}
+
+#[tokio::test]
+async fn test_configure_recovery_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let guardians = vec![Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+
+    // 2. Two of three guardians, one day delay
+    let configure_ix = test.configure_recovery_ix(&user_pubkey, &vault_pda, guardians.clone(), 2, 86_400);
+    let result = test
+        .process_transaction(&[configure_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    let recovery_config = test.get_recovery_config_account(&vault_pda).await;
+    assert_eq!(recovery_config.vault, vault_pda);
+    assert_eq!(recovery_config.guardians, guardians);
+    assert_eq!(recovery_config.threshold, 2);
+    assert_eq!(recovery_config.recovery_delay, 86_400);
+    assert!(!recovery_config.has_pending_recovery());
+}
+
+#[tokio::test]
+async fn test_configure_recovery_error_threshold_above_guardians() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+
+    // 2. Require three approvals from two guardians
+    let guardians = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+    let configure_ix = test.configure_recovery_ix(&user_pubkey, &vault_pda, guardians, 3, 86_400);
+    let result = test
+        .process_transaction(&[configure_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidRecoveryConfig);
+    let (recovery_config, _) = test.find_recovery_pda(&vault_pda);
+    assert!(test.get_account_data(&recovery_config).await.is_none());
+}
+
+#[tokio::test]
+async fn test_recovery_to_new_owner_success() {
+    // 1. Setup: two of two guardians, one hour delay
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let first_guardian = Keypair::new();
+    let second_guardian = Keypair::new();
+    let guardians = vec![first_guardian.pubkey(), second_guardian.pubkey()];
+    let configure_ix = test.configure_recovery_ix(&user_pubkey, &vault_pda, guardians, 2, 3_600);
+    test.process_transaction(&[configure_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+    let new_owner = test.context.payer.pubkey();
+
+    // 2. Both guardians back the new owner
+    let initiate_ix = test.initiate_recovery_ix(&first_guardian.pubkey(), &vault_pda, &new_owner);
+    let result = test.process_transaction(&[initiate_ix], &[&first_guardian]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    let approve_ix = test.approve_recovery_ix(&second_guardian.pubkey(), &vault_pda);
+    let result = test.process_transaction(&[approve_ix], &[&second_guardian]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    let recovery_config = test.get_recovery_config_account(&vault_pda).await;
+    let clock = test.get_clock().await;
+    assert_eq!(recovery_config.proposed_owner, Some(new_owner));
+    assert_eq!(recovery_config.approvals.len(), 2);
+    assert_eq!(recovery_config.recovery_ready_at, clock.unix_timestamp + 3_600);
+
+    // 3. Execute after the delay and verify
+    test.advance_clock(3_600).await;
+    let execute_ix = test.execute_recovery_ix(&new_owner, &vault_pda, &user_pubkey);
+    let result = test.process_transaction(&[execute_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    assert_eq!(test.get_vault_account(&vault_pda).await.owner, new_owner);
+    assert!(!test.get_recovery_config_account(&vault_pda).await.has_pending_recovery());
+    assert_eq!(test.get_owner_index_account(&new_owner).await.subaccounts[0].vault, vault_pda);
+}
+
+#[tokio::test]
+async fn test_execute_recovery_revokes_delegations() {
+    // 1. Setup: a locked down vault with an operator, a co-signer and one
+    // guardian
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let operator = Keypair::new();
+    let guardian = Keypair::new();
+    let new_owner = test.context.payer.pubkey();
+    let new_owner_ata = test.create_and_fund_user_ata(&new_owner).await;
+
+    let grant_ix = test.grant_operator_ix(
+        &user_pubkey,
+        &vault_pda,
+        &operator.pubkey(),
+        OPERATOR_SCOPE_WITHDRAW_TO_OWNER,
+        None,
+    );
+    let cosigner_ix = test.set_cosigner_ix(&user_pubkey, &vault_pda, None, Some(Pubkey::new_unique()), 1_000_000);
+    let recovery_ix = test.configure_recovery_ix(&user_pubkey, &vault_pda, vec![guardian.pubkey()], 1, 3_600);
+    let configure_lockdown_ix = test.configure_lockdown_ix(&user_pubkey, &vault_pda, &Pubkey::new_unique(), 3_600);
+    let owner_lockdown_ix = test.owner_lockdown_ix(&user_pubkey, &vault_pda);
+    test.process_transaction(
+        &[grant_ix, cosigner_ix, recovery_ix, configure_lockdown_ix, owner_lockdown_ix],
+        &[&test.user_keypair.insecure_clone()],
+    )
+    .await
+    .unwrap();
+
+    let initiate_ix = test.initiate_recovery_ix(&guardian.pubkey(), &vault_pda, &new_owner);
+    test.process_transaction(&[initiate_ix], &[&guardian]).await.unwrap();
+    test.advance_clock(3_600).await;
+
+    // 2. Recover to the test payer
+    let execute_ix = test.execute_recovery_ix(&new_owner, &vault_pda, &user_pubkey);
+    let result = test.process_transaction(&[execute_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: the new owner starts from a clean slate
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.owner, new_owner);
+    assert_eq!(vault_state.delegation_epoch, 1);
+    assert_eq!(vault_state.cosigner, None);
+    assert_eq!(vault_state.lockdown_recovery_key, None);
+    assert!(!vault_state.locked_down);
+
+    let withdraw_ix = test.operator_withdraw_ix(&operator.pubkey(), &vault_pda, &vault_ata, &new_owner_ata, 1_000_000);
+    let result = test.process_transaction(&[withdraw_ix], &[&operator]).await;
+    assert_program_error(result, errors::ErrorCode::DelegationRevoked);
+}
+
+#[tokio::test]
+async fn test_execute_recovery_error_guardians_reused() {
+    // 1. Setup: a single guardian recovers the vault to the test payer
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let guardian = Keypair::new();
+    let new_owner = test.context.payer.pubkey();
+    let configure_ix = test.configure_recovery_ix(&user_pubkey, &vault_pda, vec![guardian.pubkey()], 1, 3_600);
+    test.process_transaction(&[configure_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+    let initiate_ix = test.initiate_recovery_ix(&guardian.pubkey(), &vault_pda, &new_owner);
+    test.process_transaction(&[initiate_ix], &[&guardian]).await.unwrap();
+    test.advance_clock(3_600).await;
+    let execute_ix = test.execute_recovery_ix(&new_owner, &vault_pda, &user_pubkey);
+    test.process_transaction(&[execute_ix], &[]).await.unwrap();
+
+    // 2. The guardian tries to start another recovery
+    let initiate_ix = test.initiate_recovery_ix(&guardian.pubkey(), &vault_pda, &guardian.pubkey());
+    let result = test.process_transaction(&[initiate_ix], &[&guardian]).await;
+
+    // 3. Verify: the new owner has to choose guardians again
+    assert_program_error(result, errors::ErrorCode::DelegationRevoked);
+    assert_eq!(test.get_vault_account(&vault_pda).await.owner, new_owner);
+}
+
+#[tokio::test]
+async fn test_initiate_recovery_error_not_guardian() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let configure_ix = test.configure_recovery_ix(&user_pubkey, &vault_pda, vec![Pubkey::new_unique()], 1, 3_600);
+    test.process_transaction(&[configure_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. A stranger starts a recovery to themself
+    let stranger = Keypair::new();
+    let initiate_ix = test.initiate_recovery_ix(&stranger.pubkey(), &vault_pda, &stranger.pubkey());
+    let result = test.process_transaction(&[initiate_ix], &[&stranger]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::UnauthorizedGuardian);
+    assert!(!test.get_recovery_config_account(&vault_pda).await.has_pending_recovery());
+}
+
+#[tokio::test]
+async fn test_approve_recovery_error_already_approved() {
+    // 1. Setup: the initiating guardian already counts as an approval
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let guardian = Keypair::new();
+    let guardians = vec![guardian.pubkey(), Pubkey::new_unique()];
+    let configure_ix = test.configure_recovery_ix(&user_pubkey, &vault_pda, guardians, 2, 3_600);
+    test.process_transaction(&[configure_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+    let initiate_ix = test.initiate_recovery_ix(&guardian.pubkey(), &vault_pda, &Pubkey::new_unique());
+    test.process_transaction(&[initiate_ix], &[&guardian]).await.unwrap();
+
+    // 2. Approve a second time
+    let approve_ix = test.approve_recovery_ix(&guardian.pubkey(), &vault_pda);
+    let result = test.process_transaction(&[approve_ix], &[&guardian]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::GuardianAlreadyApproved);
+    let recovery_config = test.get_recovery_config_account(&vault_pda).await;
+    assert_eq!(recovery_config.approvals.len(), 1);
+    assert_eq!(recovery_config.recovery_ready_at, 0);
+}
+
+#[tokio::test]
+async fn test_veto_recovery_success() {
+    // 1. Setup: a single guardian started a recovery
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let guardian = Keypair::new();
+    let configure_ix = test.configure_recovery_ix(&user_pubkey, &vault_pda, vec![guardian.pubkey()], 1, 3_600);
+    test.process_transaction(&[configure_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+    let new_owner = test.context.payer.pubkey();
+    let initiate_ix = test.initiate_recovery_ix(&guardian.pubkey(), &vault_pda, &new_owner);
+    test.process_transaction(&[initiate_ix], &[&guardian]).await.unwrap();
+
+    // 2. The owner vetoes
+    let veto_ix = test.veto_recovery_ix(&user_pubkey, &vault_pda);
+    let result = test
+        .process_transaction(&[veto_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: the recovery can no longer run
+    assert!(!test.get_recovery_config_account(&vault_pda).await.has_pending_recovery());
+    test.advance_clock(3_600).await;
+    let execute_ix = test.execute_recovery_ix(&new_owner, &vault_pda, &user_pubkey);
+    let result = test.process_transaction(&[execute_ix], &[]).await;
+    assert_program_error(result, errors::ErrorCode::NoPendingRecovery);
+    assert_eq!(test.get_vault_account(&vault_pda).await.owner, user_pubkey);
+}
+
+#[tokio::test]
+async fn test_veto_recovery_error_nothing_pending() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let configure_ix = test.configure_recovery_ix(&user_pubkey, &vault_pda, vec![Pubkey::new_unique()], 1, 3_600);
+    test.process_transaction(&[configure_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Veto with no recovery started
+    let veto_ix = test.veto_recovery_ix(&user_pubkey, &vault_pda);
+    let result = test
+        .process_transaction(&[veto_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::NoPendingRecovery);
+}
+
+#[tokio::test]
+async fn test_execute_recovery_error_not_ready() {
+    // 1. Setup: the threshold is reached but the delay has not passed
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let guardian = Keypair::new();
+    let configure_ix = test.configure_recovery_ix(&user_pubkey, &vault_pda, vec![guardian.pubkey()], 1, 3_600);
+    test.process_transaction(&[configure_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+    let new_owner = test.context.payer.pubkey();
+    let initiate_ix = test.initiate_recovery_ix(&guardian.pubkey(), &vault_pda, &new_owner);
+    test.process_transaction(&[initiate_ix], &[&guardian]).await.unwrap();
+    test.advance_clock(1_800).await;
+
+    // 2. Execute early
+    let execute_ix = test.execute_recovery_ix(&new_owner, &vault_pda, &user_pubkey);
+    let result = test.process_transaction(&[execute_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::RecoveryNotReady);
+    assert_eq!(test.get_vault_account(&vault_pda).await.owner, user_pubkey);
+}
+
+#[tokio::test]
+async fn test_configure_lockdown_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let recovery_key = Pubkey::new_unique();
+
+    // 2. Configure a recovery key with a one day release delay
+    let configure_ix = test.configure_lockdown_ix(&user_pubkey, &vault_pda, &recovery_key, 86_400);
+    let result = test
+        .process_transaction(&[configure_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.lockdown_recovery_key, Some(recovery_key));
+    assert_eq!(vault_state.lockdown_delay, 86_400);
+    assert!(!vault_state.locked_down);
+}
+
+#[tokio::test]
+async fn test_configure_lockdown_error_owner_as_recovery_key() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+
+    // 2. The owner key cannot also be the recovery key
+    let configure_ix = test.configure_lockdown_ix(&user_pubkey, &vault_pda, &user_pubkey, 86_400);
+    let result = test
+        .process_transaction(&[configure_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidLockdownConfig);
+    assert_eq!(test.get_vault_account(&vault_pda).await.lockdown_recovery_key, None);
+}
+
+#[tokio::test]
+async fn test_owner_lockdown_blocks_withdrawals() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let configure_ix = test.configure_lockdown_ix(&user_pubkey, &vault_pda, &Pubkey::new_unique(), 86_400);
+    test.process_transaction(&[configure_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Lock the vault down
+    let lockdown_ix = test.owner_lockdown_ix(&user_pubkey, &vault_pda);
+    let result = test
+        .process_transaction(&[lockdown_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: even the owner key cannot withdraw
+    assert!(test.get_vault_account(&vault_pda).await.locked_down);
+    let withdraw_ix = test.withdraw_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, 1_000_000);
+    let result = test
+        .process_transaction(&[withdraw_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert_program_error(result, errors::ErrorCode::VaultLockedDown);
+}
+
+#[tokio::test]
+async fn test_owner_lockdown_error_not_configured() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+
+    // 2. Lock down without a recovery key to undo it
+    let lockdown_ix = test.owner_lockdown_ix(&user_pubkey, &vault_pda);
+    let result = test
+        .process_transaction(&[lockdown_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::LockdownNotConfigured);
+    assert!(!test.get_vault_account(&vault_pda).await.locked_down);
+}
+
+#[tokio::test]
+async fn test_request_and_lift_lockdown_success() {
+    // 1. Setup: a locked down vault
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let recovery_key = Keypair::new();
+    let configure_ix = test.configure_lockdown_ix(&user_pubkey, &vault_pda, &recovery_key.pubkey(), 3_600);
+    let lockdown_ix = test.owner_lockdown_ix(&user_pubkey, &vault_pda);
+    test.process_transaction(&[configure_ix, lockdown_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. The recovery key asks for release
+    let request_ix = test.request_lockdown_release_ix(&recovery_key.pubkey(), &vault_pda);
+    let result = test.process_transaction(&[request_ix], &[&recovery_key]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    let clock = test.get_clock().await;
+    assert_eq!(
+        test.get_vault_account(&vault_pda).await.lockdown_release_at,
+        clock.unix_timestamp + 3_600
+    );
+
+    // 3. Lift after the delay and verify
+    test.advance_clock(3_600).await;
+    let lift_ix = test.lift_lockdown_ix(&recovery_key.pubkey(), &vault_pda);
+    let result = test.process_transaction(&[lift_ix], &[&recovery_key]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert!(!vault_state.locked_down);
+    assert_eq!(vault_state.lockdown_release_at, 0);
+}
+
+#[tokio::test]
+async fn test_request_lockdown_release_error_wrong_key() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let configure_ix = test.configure_lockdown_ix(&user_pubkey, &vault_pda, &Pubkey::new_unique(), 3_600);
+    let lockdown_ix = test.owner_lockdown_ix(&user_pubkey, &vault_pda);
+    test.process_transaction(&[configure_ix, lockdown_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Another key asks for release
+    let stranger = Keypair::new();
+    let request_ix = test.request_lockdown_release_ix(&stranger.pubkey(), &vault_pda);
+    let result = test.process_transaction(&[request_ix], &[&stranger]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::UnauthorizedRecoveryKey);
+    assert_eq!(test.get_vault_account(&vault_pda).await.lockdown_release_at, 0);
+}
+
+#[tokio::test]
+async fn test_lift_lockdown_error_release_not_ready() {
+    // 1. Setup: release requested but the delay has not passed
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let recovery_key = Keypair::new();
+    let configure_ix = test.configure_lockdown_ix(&user_pubkey, &vault_pda, &recovery_key.pubkey(), 3_600);
+    let lockdown_ix = test.owner_lockdown_ix(&user_pubkey, &vault_pda);
+    test.process_transaction(&[configure_ix, lockdown_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+    let request_ix = test.request_lockdown_release_ix(&recovery_key.pubkey(), &vault_pda);
+    test.process_transaction(&[request_ix], &[&recovery_key]).await.unwrap();
+    test.advance_clock(1_800).await;
+
+    // 2. Lift early
+    let lift_ix = test.lift_lockdown_ix(&recovery_key.pubkey(), &vault_pda);
+    let result = test.process_transaction(&[lift_ix], &[&recovery_key]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::LockdownReleaseNotReady);
+    assert!(test.get_vault_account(&vault_pda).await.locked_down);
+}
+
+#[tokio::test]
+async fn test_set_cosigner_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let cosigner = Keypair::new();
+
+    // 2. Require the co-signer above 2 tokens
+    let set_ix = test.set_cosigner_ix(&user_pubkey, &vault_pda, None, Some(cosigner.pubkey()), 2_000_000);
+    let result = test
+        .process_transaction(&[set_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: small withdrawals pass, large ones need the co-signer
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.cosigner, Some(cosigner.pubkey()));
+    assert_eq!(vault_state.cosigner_threshold, 2_000_000);
+
+    let small_ix = test.withdraw_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, 1_000_000);
+    let result = test
+        .process_transaction(&[small_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    let large_ix = test.withdraw_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, 5_000_000);
+    let result = test
+        .process_transaction(&[large_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert_program_error(result, errors::ErrorCode::CosignerRequired);
+}
+
+#[tokio::test]
+async fn test_set_cosigner_error_owner_as_cosigner() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+
+    // 2. The owner cannot be their own second factor
+    let set_ix = test.set_cosigner_ix(&user_pubkey, &vault_pda, None, Some(user_pubkey), 0);
+    let result = test
+        .process_transaction(&[set_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidCosigner);
+    assert_eq!(test.get_vault_account(&vault_pda).await.cosigner, None);
+}
+
+#[tokio::test]
+async fn test_set_cosigner_error_remove_without_current_cosigner() {
+    // 1. Setup: a vault with a co-signer
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let cosigner = Keypair::new();
+    let set_ix = test.set_cosigner_ix(&user_pubkey, &vault_pda, None, Some(cosigner.pubkey()), 0);
+    test.process_transaction(&[set_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. The owner alone tries to drop it
+    let remove_ix = test.set_cosigner_ix(&user_pubkey, &vault_pda, None, None, 0);
+    let result = test
+        .process_transaction(&[remove_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert_program_error(result, errors::ErrorCode::CosignerRequired);
+
+    // 3. Verify: with the co-signer's signature the removal goes through
+    let remove_ix = test.set_cosigner_ix(&user_pubkey, &vault_pda, Some(&cosigner.pubkey()), None, 0);
+    let result = test
+        .process_transaction(&[remove_ix], &[&test.user_keypair.insecure_clone(), &cosigner])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+    assert_eq!(test.get_vault_account(&vault_pda).await.cosigner, None);
+}
+
+#[tokio::test]
+async fn test_create_multisig_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let payer_pubkey = test.context.payer.pubkey();
+    let create_key = Pubkey::new_unique();
+    let (multisig, _) = test.find_multisig_pda(&create_key);
+
+    // 2. Create a 2 of 2 multisig
+    let create_ix = test.create_multisig_ix(&user_pubkey, &create_key, vec![user_pubkey, payer_pubkey], 2);
+    let result = test
+        .process_transaction(&[create_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    let multisig_state = test.get_multisig_account(&multisig).await;
+    assert_eq!(multisig_state.create_key, create_key);
+    assert_eq!(multisig_state.members, vec![user_pubkey, payer_pubkey]);
+    assert_eq!(multisig_state.threshold, 2);
+    assert_eq!(multisig_state.proposal_count, 0);
+}
+
+#[tokio::test]
+async fn test_create_multisig_error_threshold_above_members() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+
+    // 2. Ask for more approvals than there are members
+    let create_ix = test.create_multisig_ix(&user_pubkey, &Pubkey::new_unique(), vec![user_pubkey], 2);
+    let result = test
+        .process_transaction(&[create_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidMultisigConfig);
+}
+
+#[tokio::test]
+async fn test_initialize_multisig_vault_success() {
+    // 1. Setup & 2. Open a vault owned by the multisig
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let payer_pubkey = test.context.payer.pubkey();
+    let (multisig, vault_pda, user_ata) = test
+        .setup_multisig_vault(vec![user_pubkey, payer_pubkey], 2, 10_000_000)
+        .await;
+
+    // 3. Verify
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.owner, multisig);
+    assert_eq!(vault_state.total_balance, 10_000_000);
+    assert_eq!(vault_state.available_balance, 10_000_000);
+    assert_eq!(test.get_owner_index_account(&multisig).await.subaccounts.len(), 1);
+    assert_eq!(test.get_token_balance(&user_ata).await, common::USER_STARTING_USDT - 10_000_000);
+}
+
+#[tokio::test]
+async fn test_initialize_multisig_vault_error_not_member() {
+    // 1. Setup: a multisig the test user is not part of
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let user_ata = test.create_and_fund_user_ata(&user_pubkey).await;
+    let create_key = Pubkey::new_unique();
+    let (multisig, _) = test.find_multisig_pda(&create_key);
+    let init_auth_ix = test.initialize_authority_ix();
+    let create_ix = test.create_multisig_ix(&user_pubkey, &create_key, vec![Pubkey::new_unique()], 1);
+    test.process_transaction(&[init_auth_ix, create_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. The outsider tries to open a vault for it
+    let init_vault_ix = test.initialize_multisig_vault_ix(&user_pubkey, &multisig, 0, &user_ata, 10_000_000);
+    let result = test
+        .process_transaction(&[init_vault_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::NotMultisigMember);
+}
+
+#[tokio::test]
+async fn test_multisig_withdraw_proposal_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let payer_pubkey = test.context.payer.pubkey();
+    let (multisig, vault_pda, user_ata) = test
+        .setup_multisig_vault(vec![user_pubkey, payer_pubkey], 2, 10_000_000)
+        .await;
+    let (proposal, _) = test.find_proposal_pda(&multisig, 0);
+
+    // 2. Propose, approve and execute a withdrawal
+    let action = MultisigAction::Withdraw { destination: user_ata, amount: 4_000_000 };
+    let propose_ix = test.create_multisig_proposal_ix(&user_pubkey, &multisig, &vault_pda, 0, action);
+    let result = test
+        .process_transaction(&[propose_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    let approve_ix = test.approve_multisig_proposal_ix(&payer_pubkey, &multisig, &proposal);
+    let result = test.process_transaction(&[approve_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    let proposal_state = test.get_multisig_proposal_account(&proposal).await;
+    assert_eq!(proposal_state.approvals, vec![user_pubkey, payer_pubkey]);
+    assert!(!proposal_state.executed);
+
+    let balance_before = test.get_token_balance(&user_ata).await;
+    let execute_ix = test.execute_multisig_proposal_ix(&user_pubkey, &multisig, &proposal, &vault_pda, Some(&user_ata));
+    let result = test
+        .process_transaction(&[execute_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    assert!(test.get_multisig_proposal_account(&proposal).await.executed);
+    assert_eq!(test.get_multisig_account(&multisig).await.proposal_count, 1);
+    assert_eq!(test.get_vault_account(&vault_pda).await.total_balance, 6_000_000);
+    assert_eq!(test.get_token_balance(&user_ata).await, balance_before + 4_000_000);
+}
+
+#[tokio::test]
+async fn test_create_multisig_proposal_error_zero_amount() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (multisig, vault_pda, user_ata) = test.setup_multisig_vault(vec![user_pubkey], 1, 10_000_000).await;
+
+    // 2. Propose an empty withdrawal
+    let action = MultisigAction::Withdraw { destination: user_ata, amount: 0 };
+    let propose_ix = test.create_multisig_proposal_ix(&user_pubkey, &multisig, &vault_pda, 0, action);
+    let result = test
+        .process_transaction(&[propose_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidAmount);
+    assert_eq!(test.get_multisig_account(&multisig).await.proposal_count, 0);
+}
+
+#[tokio::test]
+async fn test_approve_multisig_proposal_error_already_approved() {
+    // 1. Setup: the proposer's approval is recorded on creation
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let payer_pubkey = test.context.payer.pubkey();
+    let (multisig, vault_pda, user_ata) = test
+        .setup_multisig_vault(vec![user_pubkey, payer_pubkey], 2, 10_000_000)
+        .await;
+    let (proposal, _) = test.find_proposal_pda(&multisig, 0);
+    let action = MultisigAction::Withdraw { destination: user_ata, amount: 1_000_000 };
+    let propose_ix = test.create_multisig_proposal_ix(&user_pubkey, &multisig, &vault_pda, 0, action);
+    test.process_transaction(&[propose_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. The proposer approves again
+    let approve_ix = test.approve_multisig_proposal_ix(&user_pubkey, &multisig, &proposal);
+    let result = test
+        .process_transaction(&[approve_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::MemberAlreadyApproved);
+    assert_eq!(test.get_multisig_proposal_account(&proposal).await.approvals, vec![user_pubkey]);
+}
+
+#[tokio::test]
+async fn test_execute_multisig_proposal_error_threshold_not_met() {
+    // 1. Setup: a 2 of 2 proposal with one approval
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let payer_pubkey = test.context.payer.pubkey();
+    let (multisig, vault_pda, user_ata) = test
+        .setup_multisig_vault(vec![user_pubkey, payer_pubkey], 2, 10_000_000)
+        .await;
+    let (proposal, _) = test.find_proposal_pda(&multisig, 0);
+    let action = MultisigAction::Withdraw { destination: user_ata, amount: 1_000_000 };
+    let propose_ix = test.create_multisig_proposal_ix(&user_pubkey, &multisig, &vault_pda, 0, action);
+    test.process_transaction(&[propose_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Execute early
+    let execute_ix = test.execute_multisig_proposal_ix(&user_pubkey, &multisig, &proposal, &vault_pda, Some(&user_ata));
+    let result = test
+        .process_transaction(&[execute_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::ProposalThresholdNotMet);
+    assert_eq!(test.get_vault_account(&vault_pda).await.total_balance, 10_000_000);
+}
+
+#[tokio::test]
+async fn test_multisig_withdraw_proposal_with_cooldown_success() {
+    // 1. Setup: a 2 of 2 multisig vault under a one hour cooldown
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let payer_pubkey = test.context.payer.pubkey();
+    let (multisig, vault_pda, user_ata) = test
+        .setup_multisig_vault(vec![user_pubkey, payer_pubkey], 2, 10_000_000)
+        .await;
+    let (proposal, _) = test.find_proposal_pda(&multisig, 0);
+    let cooldown_ix = test.set_withdrawal_cooldown_ix(3_600);
+    test.process_transaction(&[cooldown_ix], &[]).await.unwrap();
+
+    let action = MultisigAction::Withdraw { destination: user_ata, amount: 4_000_000 };
+    let propose_ix = test.create_multisig_proposal_ix(&user_pubkey, &multisig, &vault_pda, 0, action);
+    let approve_ix = test.approve_multisig_proposal_ix(&payer_pubkey, &multisig, &proposal);
+    test.process_transaction(&[propose_ix, approve_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. The first execution requests the withdrawal
+    let execute_ix = test.execute_multisig_proposal_ix(&user_pubkey, &multisig, &proposal, &vault_pda, Some(&user_ata));
+    let result = test
+        .process_transaction(&[execute_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    let clock = test.get_clock().await;
+    let proposal_state = test.get_multisig_proposal_account(&proposal).await;
+    assert!(!proposal_state.executed);
+    assert_eq!(proposal_state.withdrawal_ready_at, clock.unix_timestamp + 3_600);
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.pending_withdrawal, 4_000_000);
+    assert_eq!(vault_state.available_balance, 6_000_000);
+
+    // 3. A later execution pays it out once ready
+    test.advance_clock(3_600).await;
+    let balance_before = test.get_token_balance(&user_ata).await;
+    let execute_ix = test.execute_multisig_proposal_ix(&payer_pubkey, &multisig, &proposal, &vault_pda, Some(&user_ata));
+    let result = test.process_transaction(&[execute_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 4. Verify
+    assert!(test.get_multisig_proposal_account(&proposal).await.executed);
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.pending_withdrawal, 0);
+    assert_eq!(vault_state.total_balance, 6_000_000);
+    assert_eq!(test.get_token_balance(&user_ata).await, balance_before + 4_000_000);
+}
+
+#[tokio::test]
+async fn test_multisig_withdraw_proposal_error_cooldown_not_ready() {
+    // 1. Setup: a requested multisig withdrawal still inside its cooldown
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let payer_pubkey = test.context.payer.pubkey();
+    let (multisig, vault_pda, user_ata) = test
+        .setup_multisig_vault(vec![user_pubkey, payer_pubkey], 1, 10_000_000)
+        .await;
+    let (proposal, _) = test.find_proposal_pda(&multisig, 0);
+    let cooldown_ix = test.set_withdrawal_cooldown_ix(3_600);
+    test.process_transaction(&[cooldown_ix], &[]).await.unwrap();
+
+    let action = MultisigAction::Withdraw { destination: user_ata, amount: 4_000_000 };
+    let propose_ix = test.create_multisig_proposal_ix(&user_pubkey, &multisig, &vault_pda, 0, action);
+    let execute_ix = test.execute_multisig_proposal_ix(&user_pubkey, &multisig, &proposal, &vault_pda, Some(&user_ata));
+    test.process_transaction(&[propose_ix, execute_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+    test.advance_clock(1_800).await;
+
+    // 2. Execute again before the request is ready
+    let execute_ix = test.execute_multisig_proposal_ix(&payer_pubkey, &multisig, &proposal, &vault_pda, Some(&user_ata));
+    let result = test.process_transaction(&[execute_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::WithdrawalNotReady);
+    assert!(!test.get_multisig_proposal_account(&proposal).await.executed);
+    assert_eq!(test.get_vault_account(&vault_pda).await.pending_withdrawal, 4_000_000);
+}
+
+#[tokio::test]
+async fn test_multisig_vault_instruction_spending_policy_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let payer_pubkey = test.context.payer.pubkey();
+    let (multisig, vault_pda, _user_ata) = test
+        .setup_multisig_vault(vec![user_pubkey, payer_pubkey], 2, 10_000_000)
+        .await;
+    let (proposal, _) = test.find_proposal_pda(&multisig, 0);
+
+    // 2. Propose a spending policy, with the executing member paying rent
+    let mut policy_ix = test.initialize_spending_policy_ix(&multisig, &vault_pda, 2_000_000, 5_000_000, 0);
+    policy_ix.accounts[1] = AccountMeta::new(payer_pubkey, true);
+    let action = test.vault_instruction_action(&policy_ix);
+    let propose_ix = test.create_multisig_proposal_ix(&user_pubkey, &multisig, &vault_pda, 0, action);
+    let approve_ix = test.approve_multisig_proposal_ix(&payer_pubkey, &multisig, &proposal);
+    test.process_transaction(&[propose_ix, approve_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    let execute_ix =
+        test.execute_multisig_vault_instruction_ix(&payer_pubkey, &multisig, &proposal, &vault_pda, &policy_ix);
+    let result = test.process_transaction(&[execute_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    assert!(test.get_multisig_proposal_account(&proposal).await.executed);
+    let policy = test.get_spending_policy_account(&vault_pda).await;
+    assert_eq!(policy.vault, vault_pda);
+    assert_eq!(policy.max_per_transaction, 2_000_000);
+    assert_eq!(policy.max_per_day, 5_000_000);
+}
+
+#[tokio::test]
+async fn test_multisig_vault_instruction_close_vault_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (multisig, vault_pda, user_ata) = test.setup_multisig_vault(vec![user_pubkey], 1, 10_000_000).await;
+    let vault_ata = test.find_vault_token_account(&vault_pda);
+    let (proposal, _) = test.find_proposal_pda(&multisig, 0);
+
+    // 2. Close the vault through a proposal; rent goes back to the member
+    // who opened it
+    let mut close_ix = test.close_vault_ix(&multisig, &vault_pda, &user_ata, &vault_ata);
+    close_ix.accounts[3] = AccountMeta::new(user_pubkey, false);
+    let action = test.vault_instruction_action(&close_ix);
+    let propose_ix = test.create_multisig_proposal_ix(&user_pubkey, &multisig, &vault_pda, 0, action);
+    let execute_ix =
+        test.execute_multisig_vault_instruction_ix(&user_pubkey, &multisig, &proposal, &vault_pda, &close_ix);
+    let balance_before = test.get_token_balance(&user_ata).await;
+    let result = test
+        .process_transaction(&[propose_ix, execute_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    assert!(test.get_multisig_proposal_account(&proposal).await.executed);
+    assert!(test.get_account_data(&vault_pda).await.is_none());
+    assert_eq!(test.get_token_balance(&user_ata).await, balance_before + 10_000_000);
+}
+
+#[tokio::test]
+async fn test_create_multisig_proposal_error_nested_execute() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (multisig, vault_pda, _user_ata) = test.setup_multisig_vault(vec![user_pubkey], 1, 10_000_000).await;
+    let (proposal, _) = test.find_proposal_pda(&multisig, 0);
+
+    // 2. Propose a vault instruction that executes a proposal
+    let nested_ix = test.execute_multisig_proposal_ix(&multisig, &multisig, &proposal, &vault_pda, None);
+    let action = test.vault_instruction_action(&nested_ix);
+    let propose_ix = test.create_multisig_proposal_ix(&user_pubkey, &multisig, &vault_pda, 0, action);
+    let result = test
+        .process_transaction(&[propose_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidProposalAccounts);
+    assert_eq!(test.get_multisig_account(&multisig).await.proposal_count, 0);
+}
+
+#[tokio::test]
+async fn test_register_session_key_and_withdraw_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let session_key = Keypair::new();
+    let expires_at = test.get_clock().await.unix_timestamp + 3_600;
+
+    // 2. Register a one hour withdraw session capped at 5 tokens
+    let register_ix = test.register_session_key_ix(
+        &user_pubkey,
+        &vault_pda,
+        &session_key.pubkey(),
+        SESSION_SCOPE_WITHDRAW,
+        5_000_000,
+        expires_at,
+    );
+    let result = test
+        .process_transaction(&[register_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    let withdraw_ix = test.session_withdraw_ix(&session_key.pubkey(), &vault_pda, &vault_ata, &user_ata, 3_000_000);
+    let result = test.process_transaction(&[withdraw_ix], &[&session_key]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    let session = test.get_session_key_account(&vault_pda, &session_key.pubkey()).await;
+    assert_eq!(session.vault, vault_pda);
+    assert_eq!(session.scopes, SESSION_SCOPE_WITHDRAW);
+    assert_eq!(session.expires_at, expires_at);
+    assert_eq!(session.used_amount, 3_000_000);
+    assert_eq!(test.get_vault_account(&vault_pda).await.total_balance, 7_000_000);
+}
+
+#[tokio::test]
+async fn test_register_session_key_error_owner_key() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let expires_at = test.get_clock().await.unix_timestamp + 3_600;
+
+    // 2. The owner key cannot be its own session key
+    let register_ix = test.register_session_key_ix(
+        &user_pubkey,
+        &vault_pda,
+        &user_pubkey,
+        SESSION_SCOPE_WITHDRAW,
+        5_000_000,
+        expires_at,
+    );
+    let result = test
+        .process_transaction(&[register_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidSessionKey);
+}
+
+#[tokio::test]
+async fn test_session_withdraw_error_scope_not_allowed() {
+    // 1. Setup: a deposit-only session
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let session_key = Keypair::new();
+    let expires_at = test.get_clock().await.unix_timestamp + 3_600;
+    let register_ix = test.register_session_key_ix(
+        &user_pubkey,
+        &vault_pda,
+        &session_key.pubkey(),
+        SESSION_SCOPE_DEPOSIT,
+        5_000_000,
+        expires_at,
+    );
+    test.process_transaction(&[register_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. The session tries to withdraw
+    let withdraw_ix = test.session_withdraw_ix(&session_key.pubkey(), &vault_pda, &vault_ata, &user_ata, 1_000_000);
+    let result = test.process_transaction(&[withdraw_ix], &[&session_key]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::SessionScopeNotAllowed);
+    assert_eq!(test.get_vault_account(&vault_pda).await.total_balance, 10_000_000);
+}
+
+#[tokio::test]
+async fn test_session_withdraw_error_expired() {
+    // 1. Setup: a session that has run out
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let session_key = Keypair::new();
+    let expires_at = test.get_clock().await.unix_timestamp + 60;
+    let register_ix = test.register_session_key_ix(
+        &user_pubkey,
+        &vault_pda,
+        &session_key.pubkey(),
+        SESSION_SCOPE_WITHDRAW,
+        5_000_000,
+        expires_at,
+    );
+    test.process_transaction(&[register_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+    test.advance_clock(60).await;
+
+    // 2. Withdraw after expiry
+    let withdraw_ix = test.session_withdraw_ix(&session_key.pubkey(), &vault_pda, &vault_ata, &user_ata, 1_000_000);
+    let result = test.process_transaction(&[withdraw_ix], &[&session_key]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::SessionExpired);
+}
+
+#[tokio::test]
+async fn test_revoke_session_key_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let session_key = Pubkey::new_unique();
+    let expires_at = test.get_clock().await.unix_timestamp + 3_600;
+    let register_ix = test.register_session_key_ix(
+        &user_pubkey,
+        &vault_pda,
+        &session_key,
+        SESSION_SCOPE_WITHDRAW,
+        5_000_000,
+        expires_at,
+    );
+    test.process_transaction(&[register_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Revoke it
+    let revoke_ix = test.revoke_session_key_ix(&user_pubkey, &vault_pda, &session_key);
+    let result = test
+        .process_transaction(&[revoke_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify the session account is closed
+    let (session, _) = test.find_session_pda(&vault_pda, &session_key);
+    assert!(test.get_account_data(&session).await.is_none());
+}
+
+#[tokio::test]
+async fn test_revoke_session_key_error_unauthorized_owner() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let session_key = Pubkey::new_unique();
+    let expires_at = test.get_clock().await.unix_timestamp + 3_600;
+    let register_ix = test.register_session_key_ix(
+        &user_pubkey,
+        &vault_pda,
+        &session_key,
+        SESSION_SCOPE_WITHDRAW,
+        5_000_000,
+        expires_at,
+    );
+    test.process_transaction(&[register_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Someone else tries to revoke it
+    let stranger = Keypair::new();
+    let revoke_ix = test.revoke_session_key_ix(&stranger.pubkey(), &vault_pda, &session_key);
+    let result = test.process_transaction(&[revoke_ix], &[&stranger]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::UnauthorizedOwner);
+    assert_eq!(test.get_session_key_account(&vault_pda, &session_key).await.max_amount, 5_000_000);
+}
+
+#[tokio::test]
+async fn test_configure_fees_and_withdraw_with_fee_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(20_000_000).await;
+    let protocol_state_pda = test.protocol_state_pda;
+    let treasury_ata = test.create_token_account(&protocol_state_pda).await;
+
+    // 2. Charge 1% on withdrawals, then withdraw 10 tokens
+    let fees_ix = test.configure_fees_ix(0, 100, 0, 0);
+    let result = test.process_transaction(&[fees_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    let balance_before = test.get_token_balance(&user_ata).await;
+    let withdraw_ix = test.withdraw_with_fee_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, &treasury_ata, 10_000_000);
+    let result = test
+        .process_transaction(&[withdraw_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    let protocol_state = test.get_protocol_state_account().await;
+    assert_eq!(protocol_state.withdraw_fee_bps, 100);
+    assert_eq!(protocol_state.total_fees_collected, 100_000);
+    assert_eq!(test.get_token_balance(&treasury_ata).await, 100_000);
+    assert_eq!(test.get_token_balance(&user_ata).await, balance_before + 9_900_000);
+    assert_eq!(test.get_vault_account(&vault_pda).await.total_balance, 10_000_000);
+}
+
+#[tokio::test]
+async fn test_configure_fees_error_above_maximum() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+
+    // 2. Ask for an 11% deposit fee
+    let fees_ix = test.configure_fees_ix(1_100, 0, 0, 0);
+    let result = test.process_transaction(&[fees_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidFeeConfig);
+    assert_eq!(test.get_protocol_state_account().await.deposit_fee_bps, 0);
+}
+
+#[tokio::test]
+async fn test_withdraw_with_fee_error_missing_treasury() {
+    // 1. Setup: a withdraw fee is set
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let fees_ix = test.configure_fees_ix(0, 100, 0, 0);
+    test.process_transaction(&[fees_ix], &[]).await.unwrap();
+
+    // 2. Withdraw without a treasury to pay the fee into
+    let withdraw_ix = test.withdraw_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, 1_000_000);
+    let result = test
+        .process_transaction(&[withdraw_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::MissingTreasuryAccount);
+}
+
+#[tokio::test]
+async fn test_deposit_for_with_fee_success() {
+    // 1. Setup: a 1% deposit fee
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+    let payer = test.context.payer.pubkey();
+    let payer_ata = test.create_and_fund_user_ata(&payer).await;
+    let protocol_state_pda = test.protocol_state_pda;
+    let treasury_ata = test.create_token_account(&protocol_state_pda).await;
+    let beneficiary = Pubkey::new_unique();
+    let fees_ix = test.configure_fees_ix(100, 0, 0, 0);
+    test.process_transaction(&[fees_ix], &[]).await.unwrap();
+
+    // 2. Open a vault for someone else with 10 tokens
+    let deposit_for_ix = test.deposit_for_with_fee_ix(&payer, &payer_ata, &beneficiary, &treasury_ata, 10_000_000);
+    let logs = test.simulate_transaction_logs(std::slice::from_ref(&deposit_for_ix), &[]).await;
+    let result = test.process_transaction(&[deposit_for_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: the vault is credited net of the fee
+    let event: DepositForEvent = find_event(&logs).expect("no deposit for event emitted");
+    assert_eq!(event.amount, 10_000_000);
+    assert_eq!(event.fee, 100_000);
+    let (vault_pda, _) = test.find_vault_pda(&beneficiary);
+    assert_eq!(test.get_vault_account(&vault_pda).await.total_balance, 9_900_000);
+    assert_eq!(test.get_token_balance(&test.find_vault_token_account(&vault_pda)).await, 9_900_000);
+    assert_eq!(test.get_token_balance(&treasury_ata).await, 100_000);
+    assert_eq!(test.get_protocol_state_account().await.total_fees_collected, 100_000);
+}
+
+#[tokio::test]
+async fn test_deposit_for_with_fee_error_missing_treasury() {
+    // 1. Setup: a deposit fee is set
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+    let payer = test.context.payer.pubkey();
+    let payer_ata = test.create_and_fund_user_ata(&payer).await;
+    let beneficiary = Pubkey::new_unique();
+    let fees_ix = test.configure_fees_ix(100, 0, 0, 0);
+    test.process_transaction(&[fees_ix], &[]).await.unwrap();
+
+    // 2. Deposit without a treasury to pay the fee into
+    let deposit_for_ix = test.deposit_for_ix(&payer, &payer_ata, &beneficiary, 0, 10_000_000);
+    let result = test.process_transaction(&[deposit_for_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::MissingTreasuryAccount);
+    assert_eq!(test.get_token_balance(&payer_ata).await, common::USER_STARTING_USDT);
+}
+
+#[tokio::test]
+async fn test_execute_withdrawal_with_fee_success() {
+    // 1. Setup: a ready request under a 1% withdraw fee
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let protocol_state_pda = test.protocol_state_pda;
+    let treasury_ata = test.create_token_account(&protocol_state_pda).await;
+    let fees_ix = test.configure_fees_ix(0, 100, 0, 0);
+    let cooldown_ix = test.set_withdrawal_cooldown_ix(3_600);
+    test.process_transaction(&[fees_ix, cooldown_ix], &[]).await.unwrap();
+    let request_ix = test.request_withdrawal_ix(&user_pubkey, &vault_pda, &vault_ata, 4_000_000);
+    test.process_transaction(&[request_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+    test.advance_clock(3_600).await;
+
+    // 2. Execute
+    let balance_before = test.get_token_balance(&user_ata).await;
+    let execute_ix =
+        test.execute_withdrawal_with_fee_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, &treasury_ata);
+    let user_keypair = test.user_keypair.insecure_clone();
+    let logs = test.simulate_transaction_logs(std::slice::from_ref(&execute_ix), &[&user_keypair]).await;
+    let result = test.process_transaction(&[execute_ix], &[&user_keypair]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    let event: WithdrawalExecutedEvent = find_event(&logs).expect("no withdrawal executed event emitted");
+    assert_eq!(event.amount, 4_000_000);
+    assert_eq!(event.fee, 40_000);
+    assert_eq!(test.get_vault_account(&vault_pda).await.total_balance, 6_000_000);
+    assert_eq!(test.get_token_balance(&user_ata).await, balance_before + 3_960_000);
+    assert_eq!(test.get_token_balance(&treasury_ata).await, 40_000);
+    assert_eq!(test.get_protocol_state_account().await.total_fees_collected, 40_000);
+}
+
+#[tokio::test]
+async fn test_close_vault_with_fee_success() {
+    // 1. Setup: a 1% withdraw fee
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let protocol_state_pda = test.protocol_state_pda;
+    let treasury_ata = test.create_token_account(&protocol_state_pda).await;
+    let fees_ix = test.configure_fees_ix(0, 100, 0, 0);
+    test.process_transaction(&[fees_ix], &[]).await.unwrap();
+
+    // 2. Close the vault
+    let balance_before = test.get_token_balance(&user_ata).await;
+    let close_ix = test.close_vault_with_fee_ix(&user_pubkey, &vault_pda, &user_ata, &vault_ata, &treasury_ata);
+    let user_keypair = test.user_keypair.insecure_clone();
+    let logs = test.simulate_transaction_logs(std::slice::from_ref(&close_ix), &[&user_keypair]).await;
+    let result = test.process_transaction(&[close_ix], &[&user_keypair]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    let event: VaultClosedEvent = find_event(&logs).expect("no vault closed event emitted");
+    assert_eq!(event.withdrawn, 9_900_000);
+    assert_eq!(event.fee, 100_000);
+    assert!(test.get_account_data(&vault_pda).await.is_none());
+    assert_eq!(test.get_token_balance(&user_ata).await, balance_before + 9_900_000);
+    assert_eq!(test.get_token_balance(&treasury_ata).await, 100_000);
+}
+
+#[tokio::test]
+async fn test_set_fee_manager_and_withdraw_fees_success() {
+    // 1. Setup: collect a fee into the treasury
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(20_000_000).await;
+    let protocol_state_pda = test.protocol_state_pda;
+    let treasury_ata = test.create_token_account(&protocol_state_pda).await;
+    let fees_ix = test.configure_fees_ix(0, 100, 0, 0);
+    test.process_transaction(&[fees_ix], &[]).await.unwrap();
+    let withdraw_ix = test.withdraw_with_fee_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, &treasury_ata, 10_000_000);
+    test.process_transaction(&[withdraw_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Hand fee withdrawals to a manager, who sweeps the treasury
+    let fee_manager = Keypair::new();
+    let manager_ix = test.set_fee_manager_ix(&fee_manager.pubkey());
+    let result = test.process_transaction(&[manager_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    let destination = test.create_token_account(&fee_manager.pubkey()).await;
+    let sweep_ix = test.withdraw_fees_ix(&fee_manager.pubkey(), &treasury_ata, &destination, 100_000);
+    let result = test.process_transaction(&[sweep_ix], &[&fee_manager]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    assert_eq!(test.get_protocol_state_account().await.fee_manager, fee_manager.pubkey());
+    assert_eq!(test.get_token_balance(&treasury_ata).await, 0);
+    assert_eq!(test.get_token_balance(&destination).await, 100_000);
+}
+
+#[tokio::test]
+async fn test_set_fee_manager_error_unauthorized_admin() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+    let fee_manager_before = test.get_protocol_state_account().await.fee_manager;
+
+    // 2. The vault owner tries to make themselves fee manager
+    let user_pubkey = test.user_pubkey();
+    let manager_ix = test.as_signer(test.set_fee_manager_ix(&user_pubkey), &user_pubkey);
+    let result = test
+        .process_transaction(&[manager_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::UnauthorizedAdmin);
+    assert_eq!(test.get_protocol_state_account().await.fee_manager, fee_manager_before);
+}
+
+#[tokio::test]
+async fn test_withdraw_fees_error_unauthorized_fee_manager() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (_vault_pda, _vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let protocol_state_pda = test.protocol_state_pda;
+    let treasury_ata = test.create_token_account(&protocol_state_pda).await;
+    test.mint_tokens(&treasury_ata, 1_000_000).await;
+
+    // 2. The vault owner tries to sweep the treasury
+    let sweep_ix = test.withdraw_fees_ix(&user_pubkey, &treasury_ata, &user_ata, 1_000_000);
+    let result = test
+        .process_transaction(&[sweep_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::UnauthorizedFeeManager);
+    assert_eq!(test.get_token_balance(&treasury_ata).await, 1_000_000);
+}
+
+#[tokio::test]
+async fn test_initialize_share_pool_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+
+    // 2. Create the pool for the test mint
+    let init_pool_ix = test.initialize_share_pool_ix();
+    let result = test.process_transaction(&[init_pool_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: empty, fully reserved and kept by the admin
+    let share_pool = test.get_share_pool_account().await;
+    assert_eq!(share_pool.mint, test.usdt_mint);
+    assert_eq!(share_pool.token_account, test.find_pool_token_account());
+    assert_eq!(share_pool.total_shares, 0);
+    assert_eq!(share_pool.total_assets, 0);
+    assert_eq!(share_pool.reserve_bps, 10_000);
+    assert_eq!(share_pool.keeper, test.context.payer.pubkey());
+}
+
+#[tokio::test]
+async fn test_initialize_share_pool_error_unauthorized_admin() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+
+    // 2. The vault owner tries to create the pool
+    let user_pubkey = test.user_pubkey();
+    let init_pool_ix = test.as_signer(test.initialize_share_pool_ix(), &user_pubkey);
+    let result = test
+        .process_transaction(&[init_pool_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::UnauthorizedAdmin);
+}
+
+#[tokio::test]
+async fn test_set_pooled_accounting_error_vault_not_empty() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+
+    // 2. Switch a funded vault to share accounting
+    let pooled_ix = test.set_pooled_accounting_ix(&user_pubkey, &vault_pda, true);
+    let result = test
+        .process_transaction(&[pooled_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::VaultNotEmpty);
+    assert!(!test.get_vault_account(&vault_pda).await.pooled);
+}
+
+#[tokio::test]
+async fn test_deposit_pooled_success() {
+    // 1. Setup & 2. Deposit 10 USDT into the pool
+    let mut test = CollateralVaultProgramTest::new().await;
+    let deposit = 10_000_000;
+    let (vault_pda, vault_ata, user_ata) = test.setup_pooled_vault(deposit).await;
+
+    // 3. Verify: shares are minted one to one into an empty pool
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert!(vault_state.pooled);
+    assert_eq!(vault_state.shares, deposit);
+    assert_eq!(vault_state.total_balance, deposit);
+    let share_pool = test.get_share_pool_account().await;
+    assert_eq!(share_pool.total_shares, deposit);
+    assert_eq!(share_pool.total_assets, deposit);
+    let pool_ata = test.find_pool_token_account();
+    assert_eq!(test.get_token_balance(&pool_ata).await, deposit);
+    assert_eq!(test.get_token_balance(&vault_ata).await, 0);
+    assert_eq!(test.get_token_balance(&user_ata).await, common::USER_STARTING_USDT - deposit);
+}
+
+#[tokio::test]
+async fn test_deposit_pooled_error_vault_not_pooled() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let init_pool_ix = test.initialize_share_pool_ix();
+    test.process_transaction(&[init_pool_ix], &[]).await.unwrap();
+
+    // 2. Deposit into the pool for a vault that never opted in
+    let deposit_ix = test.deposit_pooled_ix(&user_pubkey, &user_ata, &vault_pda, &vault_ata, 1_000_000);
+    let result = test
+        .process_transaction(&[deposit_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::VaultNotPooled);
+}
+
+#[tokio::test]
+async fn test_add_pool_yield_and_withdraw_pooled_success() {
+    // 1. Setup: 10 USDT of shares, then 1 USDT of yield
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_pooled_vault(10_000_000).await;
+
+    let yield_ix = test.add_pool_yield_ix(&user_pubkey, &user_ata, 1_000_000);
+    let result = test
+        .process_transaction(&[yield_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+    assert_eq!(test.get_share_pool_account().await.total_assets, 11_000_000);
+
+    // 2. Withdraw 5.5 USDT
+    let balance_before = test.get_token_balance(&user_ata).await;
+    let withdraw_ix = test.withdraw_pooled_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, 5_500_000);
+    let result = test
+        .process_transaction(&[withdraw_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: burned shares round up, so the dust stays with the pool
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.shares, 4_999_999);
+    assert_eq!(vault_state.total_balance, 5_499_999);
+    let share_pool = test.get_share_pool_account().await;
+    assert_eq!(share_pool.total_shares, 4_999_999);
+    assert_eq!(share_pool.total_assets, 5_500_000);
+    assert_eq!(test.get_token_balance(&user_ata).await, balance_before + 5_500_000);
+}
+
+#[tokio::test]
+async fn test_add_pool_yield_error_no_shares() {
+    // 1. Setup: an empty pool
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (_vault_pda, _vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let init_pool_ix = test.initialize_share_pool_ix();
+    test.process_transaction(&[init_pool_ix], &[]).await.unwrap();
+
+    // 2. Donate to it
+    let yield_ix = test.add_pool_yield_ix(&user_pubkey, &user_ata, 1_000_000);
+    let result = test
+        .process_transaction(&[yield_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::ZeroShares);
+}
+
+#[tokio::test]
+async fn test_withdraw_pooled_error_insufficient_available_balance() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_pooled_vault(10_000_000).await;
+
+    // 2. Withdraw more than the shares are worth
+    let withdraw_ix = test.withdraw_pooled_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, 10_000_001);
+    let result = test
+        .process_transaction(&[withdraw_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InsufficientAvailableBalance);
+    assert_eq!(test.get_vault_account(&vault_pda).await.shares, 10_000_000);
+}
+
+#[tokio::test]
+async fn test_request_and_execute_pooled_withdrawal_success() {
+    // 1. Setup: a pooled vault under a one hour cooldown
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_pooled_vault(10_000_000).await;
+    let cooldown_ix = test.set_withdrawal_cooldown_ix(3_600);
+    test.process_transaction(&[cooldown_ix], &[]).await.unwrap();
+
+    let withdraw_ix = test.withdraw_pooled_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, 4_000_000);
+    let result = test
+        .process_transaction(&[withdraw_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert_program_error(result, errors::ErrorCode::WithdrawalCooldownRequired);
+
+    // 2. Queue 4 USDT instead
+    let request_ix = test.request_pooled_withdrawal_ix(&user_pubkey, &vault_pda, &vault_ata, 4_000_000);
+    let result = test
+        .process_transaction(&[request_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+    assert_eq!(test.get_vault_account(&vault_pda).await.pending_withdrawal, 4_000_000);
+    assert_eq!(test.get_share_pool_account().await.pending_assets, 4_000_000);
+
+    // 3. Execute after the cooldown and verify
+    test.advance_clock(3_600).await;
+    let balance_before = test.get_token_balance(&user_ata).await;
+    let execute_ix = test.execute_pooled_withdrawal_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata);
+    let result = test
+        .process_transaction(&[execute_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.pending_withdrawal, 0);
+    assert_eq!(vault_state.total_balance, 6_000_000);
+    assert_eq!(test.get_share_pool_account().await.pending_assets, 0);
+    assert_eq!(test.get_token_balance(&user_ata).await, balance_before + 4_000_000);
+}
+
+#[tokio::test]
+async fn test_emergency_withdraw_pooled_vault_success() {
+    // 1. Setup: a pooled vault with locked collateral, then an emergency
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let deposit = 10_000_000;
+    let (vault_pda, vault_ata, user_ata) = test.setup_pooled_vault(deposit).await;
+    test.authorize_test_caller().await;
+    let lock_ix = test.lock_pooled_collateral_ix(&user_pubkey, &vault_pda, &vault_ata, 4_000_000);
+    let declare_ix = test.declare_emergency_ix();
+    test.process_transaction(&[lock_ix, declare_ix], &[]).await.unwrap();
+    assert_eq!(test.get_share_pool_account().await.locked_assets, 4_000_000);
+
+    // 2. Exit through the pool
+    let emergency_ix = test.emergency_withdraw_pooled_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, None);
+    let result = test
+        .process_transaction(&[emergency_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: all shares burned and paid out of the pool
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.shares, 0);
+    assert_eq!(vault_state.total_balance, 0);
+    let share_pool = test.get_share_pool_account().await;
+    assert_eq!(share_pool.total_shares, 0);
+    assert_eq!(share_pool.locked_assets, 0);
+    let pool_ata = test.find_pool_token_account();
+    assert_eq!(test.get_token_balance(&pool_ata).await, 0);
+    assert_eq!(test.get_token_balance(&user_ata).await, common::USER_STARTING_USDT);
+}
+
+#[tokio::test]
+async fn test_emergency_withdraw_pooled_vault_broken_program_success() {
+    // 1. Setup: a pooled vault with 4 USDT locked by a program that breaks
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let deposit = 10_000_000;
+    let (vault_pda, vault_ata, user_ata) = test.setup_pooled_vault(deposit).await;
+    test.authorize_test_caller().await;
+    let broken_program_id = test.program_id;
+    let configure_ix = test.configure_emergency_ix(3_600);
+    let lock_ix = test.lock_pooled_collateral_ix(&user_pubkey, &vault_pda, &vault_ata, 4_000_000);
+    let mark_ix = test.mark_program_broken_ix(&broken_program_id);
+    test.process_transaction(&[configure_ix, lock_ix, mark_ix], &[]).await.unwrap();
+    test.advance_clock(3_600).await;
+    let user_balance_before = test.get_token_balance(&user_ata).await;
+
+    // 2. Take out the broken program's lock through the pool
+    let emergency_ix = test.emergency_withdraw_pooled_ix(
+        &user_pubkey,
+        &vault_pda,
+        &vault_ata,
+        &user_ata,
+        Some(&broken_program_id),
+    );
+    let result = test
+        .process_transaction(&[emergency_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: only the locked 4 USDT left the pool
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.total_balance, 6_000_000);
+    assert_eq!(vault_state.locked_balance, 0);
+    let share_pool = test.get_share_pool_account().await;
+    assert_eq!(share_pool.total_assets, 6_000_000);
+    assert_eq!(share_pool.locked_assets, 0);
+    let pool_ata = test.find_pool_token_account();
+    assert_eq!(test.get_token_balance(&pool_ata).await, 6_000_000);
+    assert_eq!(test.get_token_balance(&user_ata).await, user_balance_before + 4_000_000);
+}
+
+#[tokio::test]
+async fn test_transfer_collateral_from_pooled_vault_success() {
+    // 1. Setup: a pooled vault and a plain subaccount vault
+    let mut test = CollateralVaultProgramTest::new().await;
+    let (from_vault, from_ata, user_ata) = test.setup_pooled_vault(10_000_000).await;
+    let (to_vault, to_ata) = test.setup_subaccount_vault(1, &user_ata, 5_000_000).await;
+    test.authorize_test_caller().await;
+
+    // 2. Move 2 USDT out of the pool
+    let transfer_ix = test.transfer_pooled_collateral_ix(&from_vault, &from_ata, &to_vault, &to_ata, 2_000_000);
+    let result = test.process_transaction(&[transfer_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    assert_eq!(test.get_vault_account(&from_vault).await.total_balance, 8_000_000);
+    assert_eq!(test.get_share_pool_account().await.total_assets, 8_000_000);
+    let pool_ata = test.find_pool_token_account();
+    assert_eq!(test.get_token_balance(&pool_ata).await, 8_000_000);
+    assert_eq!(test.get_vault_account(&to_vault).await.total_balance, 7_000_000);
+    assert_eq!(test.get_token_balance(&to_ata).await, 7_000_000);
+}
+
+#[tokio::test]
+async fn test_configure_pool_strategies_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_pooled_vault(10_000_000).await;
+    let keeper = Pubkey::new_unique();
+
+    // 2. Hand deployments to a keeper and keep 25% liquid
+    let configure_ix = test.configure_pool_strategies_ix(&keeper, 2_500);
+    let result = test.process_transaction(&[configure_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    let share_pool = test.get_share_pool_account().await;
+    assert_eq!(share_pool.keeper, keeper);
+    assert_eq!(share_pool.reserve_bps, 2_500);
+}
+
+#[tokio::test]
+async fn test_configure_pool_strategies_error_reserve_above_full() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_pooled_vault(10_000_000).await;
+    let keeper = test.context.payer.pubkey();
+
+    // 2. Ask to keep more than everything liquid
+    let configure_ix = test.configure_pool_strategies_ix(&keeper, 10_001);
+    let result = test.process_transaction(&[configure_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidStrategyConfig);
+    assert_eq!(test.get_share_pool_account().await.reserve_bps, 10_000);
+}
+
+#[tokio::test]
+async fn test_set_strategy_inactive_blocks_deploy() {
+    // 1. Setup: a strategy registered but switched off
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_pooled_vault(100_000_000).await;
+    let keeper = test.context.payer.pubkey();
+    let init_strategy_ix = test.initialize_mock_strategy_ix();
+    let configure_ix = test.configure_pool_strategies_ix(&keeper, 0);
+    let set_strategy_ix = test.set_strategy_ix(5_000, false);
+    let result = test
+        .process_transaction(&[init_strategy_ix, configure_ix, set_strategy_ix], &[])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    let strategy = test.get_strategy_account().await;
+    assert_eq!(strategy.mint, test.usdt_mint);
+    assert_eq!(strategy.program_id, mock_strategy::id());
+    assert_eq!(strategy.max_allocation_bps, 5_000);
+    assert!(!strategy.active);
+
+    // 2. Deploy to it
+    let deploy_ix = test.deploy_to_strategy_ix(10_000_000);
+    let result = test.process_transaction(&[deploy_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::StrategyInactive);
+    assert_eq!(test.get_share_pool_account().await.deployed_assets, 0);
+}
+
+#[tokio::test]
+async fn test_set_strategy_error_allocation_above_full() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_pooled_vault(10_000_000).await;
+
+    // 2. Cap the strategy above the whole pool
+    let set_strategy_ix = test.set_strategy_ix(10_001, true);
+    let result = test.process_transaction(&[set_strategy_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidStrategyConfig);
+}
+
+#[tokio::test]
+async fn test_deploy_to_strategy_error_unauthorized_keeper() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_pooled_vault(100_000_000).await;
+    test.setup_mock_strategy(0, 5_000).await;
+
+    // 2. The vault owner tries to move pool funds
+    let user_pubkey = test.user_pubkey();
+    let deploy_ix = test.as_signer(test.deploy_to_strategy_ix(10_000_000), &user_pubkey);
+    let result = test
+        .process_transaction(&[deploy_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::UnauthorizedKeeper);
+}
+
+#[tokio::test]
+async fn test_initialize_insurance_fund_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+
+    // 2. Create the fund for the test mint
+    let init_fund_ix = test.initialize_insurance_fund_ix();
+    let result = test.process_transaction(&[init_fund_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    let insurance_fund = test.get_insurance_fund_account().await;
+    assert_eq!(insurance_fund.mint, test.usdt_mint);
+    assert_eq!(insurance_fund.token_account, test.find_insurance_token_account());
+    assert_eq!(insurance_fund.balance, 0);
+    assert_eq!(insurance_fund.bad_debt_count, 0);
+}
+
+#[tokio::test]
+async fn test_initialize_insurance_fund_error_unauthorized_admin() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    test.setup_vault(10_000_000).await;
+
+    // 2. The vault owner tries to create the fund
+    let user_pubkey = test.user_pubkey();
+    let init_fund_ix = test.as_signer(test.initialize_insurance_fund_ix(), &user_pubkey);
+    let result = test
+        .process_transaction(&[init_fund_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::UnauthorizedAdmin);
+}
+
+#[tokio::test]
+async fn test_contribute_insurance_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let (_vault_pda, _vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    test.setup_insurance_fund(&user_ata, 0).await;
+
+    // 2. Contribute 3 USDT
+    let contribute_ix = test.contribute_insurance_ix(&user_ata, 3_000_000);
+    let result = test
+        .process_transaction(&[contribute_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    let insurance_fund = test.get_insurance_fund_account().await;
+    assert_eq!(insurance_fund.balance, 3_000_000);
+    assert_eq!(insurance_fund.total_contributed, 3_000_000);
+    let fund_ata = test.find_insurance_token_account();
+    assert_eq!(test.get_token_balance(&fund_ata).await, 3_000_000);
+}
+
+#[tokio::test]
+async fn test_contribute_insurance_error_zero_amount() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let (_vault_pda, _vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    test.setup_insurance_fund(&user_ata, 0).await;
+
+    // 2. Contribute nothing
+    let contribute_ix = test.contribute_insurance_ix(&user_ata, 0);
+    let result = test
+        .process_transaction(&[contribute_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidAmount);
+}
+
+#[tokio::test]
+async fn test_fund_insurance_from_fees_success() {
+    // 1. Setup: 2 USDT of collected fees in the treasury
+    let mut test = CollateralVaultProgramTest::new().await;
+    let (_vault_pda, _vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    test.setup_insurance_fund(&user_ata, 0).await;
+    let protocol_state_pda = test.protocol_state_pda;
+    let treasury_ata = test.create_token_account(&protocol_state_pda).await;
+    test.mint_tokens(&treasury_ata, 2_000_000).await;
+
+    // 2. The admin moves half of it into the fund
+    let admin = test.context.payer.pubkey();
+    let fund_ix = test.fund_insurance_from_fees_ix(&admin, &treasury_ata, 1_000_000);
+    let result = test.process_transaction(&[fund_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    let insurance_fund = test.get_insurance_fund_account().await;
+    assert_eq!(insurance_fund.balance, 1_000_000);
+    assert_eq!(insurance_fund.total_contributed, 1_000_000);
+    assert_eq!(test.get_token_balance(&treasury_ata).await, 1_000_000);
+}
+
+#[tokio::test]
+async fn test_fund_insurance_from_fees_error_unauthorized_fee_manager() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (_vault_pda, _vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    test.setup_insurance_fund(&user_ata, 0).await;
+    let protocol_state_pda = test.protocol_state_pda;
+    let treasury_ata = test.create_token_account(&protocol_state_pda).await;
+    test.mint_tokens(&treasury_ata, 2_000_000).await;
+
+    // 2. The vault owner tries to move treasury fees
+    let fund_ix = test.fund_insurance_from_fees_ix(&user_pubkey, &treasury_ata, 1_000_000);
+    let result = test
+        .process_transaction(&[fund_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::UnauthorizedFeeManager);
+    assert_eq!(test.get_token_balance(&treasury_ata).await, 2_000_000);
+}
+
+#[tokio::test]
+async fn test_open_reward_position_stakes_locked_collateral() {
+    // 1. Setup: 6 of 10 USDT already locked
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    test.authorize_test_caller().await;
+    let lock_ix = test.lock_collateral_ix(&user_pubkey, &vault_pda, 6_000_000);
+    let create_pool_ix = test.create_reward_pool_ix(1_000);
+    test.process_transaction(&[lock_ix, create_pool_ix], &[]).await.unwrap();
+
+    // 2. Open the position
+    let open_ix = test.open_reward_position_ix(&vault_pda, &vault_ata);
+    let result = test.process_transaction(&[open_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: only the locked part is staked
+    let reward_position = test.get_reward_position_account(&vault_pda).await;
+    assert_eq!(reward_position.vault, vault_pda);
+    assert_eq!(reward_position.staked, 6_000_000);
+    assert_eq!(reward_position.earned, 0);
+    assert_eq!(test.get_reward_pool_account().await.total_staked, 6_000_000);
+}
+
+#[tokio::test]
+async fn test_open_reward_position_error_wrong_token_account() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let (vault_pda, _vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let create_pool_ix = test.create_reward_pool_ix(1_000);
+    test.process_transaction(&[create_pool_ix], &[]).await.unwrap();
+
+    // 2. Open with a token account that does not belong to the vault
+    let open_ix = test.open_reward_position_ix(&vault_pda, &user_ata);
+    let result = test.process_transaction(&[open_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::UnauthorizedOwner);
+    let (reward_position, _) = test.find_reward_position_pda(&vault_pda);
+    assert!(test.get_account_data(&reward_position).await.is_none());
+}
+
+#[tokio::test]
+async fn test_lock_and_unlock_collateral_restake_reward_position() {
+    // 1. Setup: the position is opened with nothing locked
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    test.authorize_test_caller().await;
+    let create_pool_ix = test.create_reward_pool_ix(1_000);
+    let open_ix = test.open_reward_position_ix(&vault_pda, &vault_ata);
+    test.process_transaction(&[create_pool_ix, open_ix], &[]).await.unwrap();
+    assert_eq!(test.get_vault_account(&vault_pda).await.reward_positions, 1);
+
+    // 2. Lock 6 USDT
+    let lock_ix = test.lock_collateral_ix(&user_pubkey, &vault_pda, 6_000_000);
+    let lock_ix = test.with_reward_position(lock_ix, &vault_pda);
+    let result = test.process_transaction(&[lock_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    assert_eq!(test.get_reward_position_account(&vault_pda).await.staked, 6_000_000);
+    assert_eq!(test.get_reward_pool_account().await.total_staked, 6_000_000);
+
+    // 3. Verify: unlocking takes the stake straight back out of the pool
+    let unlock_ix = test.unlock_collateral_ix(&user_pubkey, &vault_pda, 6_000_000);
+    let unlock_ix = test.with_reward_position(unlock_ix, &vault_pda);
+    let result = test.process_transaction(&[unlock_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    assert_eq!(test.get_reward_position_account(&vault_pda).await.staked, 0);
+    assert_eq!(test.get_reward_pool_account().await.total_staked, 0);
+}
+
+#[tokio::test]
+async fn test_lock_collateral_error_missing_reward_position() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    test.authorize_test_caller().await;
+    let create_pool_ix = test.create_reward_pool_ix(1_000);
+    let open_ix = test.open_reward_position_ix(&vault_pda, &vault_ata);
+    test.process_transaction(&[create_pool_ix, open_ix], &[]).await.unwrap();
+
+    // 2. Lock without the vault's reward position
+    let lock_ix = test.lock_collateral_ix(&user_pubkey, &vault_pda, 6_000_000);
+    let result = test.process_transaction(&[lock_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidRewardAccounts);
+    assert_eq!(test.get_vault_account(&vault_pda).await.locked_balance, 0);
+    assert_eq!(test.get_reward_pool_account().await.total_staked, 0);
+}
+
+#[tokio::test]
+async fn test_close_reward_position_success() {
+    // 1. Setup: 10 USDT committed and staked in a funded pool
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+
+    let until = test.get_clock().await.unix_timestamp + 86_400;
+    let reward_duration = 1_000;
+    let create_pool_ix = test.create_reward_pool_ix(reward_duration);
+    let commit_ix = test.commit_lock_ix(&user_pubkey, &vault_pda, &vault_ata, false, 10_000_000, until);
+    let open_ix = test.open_reward_position_ix(&vault_pda, &vault_ata);
+    test.process_transaction(
+        &[create_pool_ix, commit_ix, open_ix],
+        &[&test.user_keypair.insecure_clone()],
+    )
+    .await
+    .unwrap();
+    test.fund_reward_pool(1_000_000).await;
+    test.advance_clock(2 * reward_duration).await;
+
+    // 2. Claim and close the position
+    let claim_ix = test.claim_rewards_ix(&user_pubkey, &vault_pda, &user_ata);
+    let close_ix = test.close_reward_position_ix(&user_pubkey, &vault_pda);
+    let result = test
+        .process_transaction(&[claim_ix, close_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: the stake left the pool and the vault no longer tracks it
+    let (reward_position, _) = test.find_reward_position_pda(&vault_pda);
+    assert!(test.get_account_data(&reward_position).await.is_none());
+    assert_eq!(test.get_reward_pool_account().await.total_staked, 0);
+    assert_eq!(test.get_vault_account(&vault_pda).await.reward_positions, 0);
+}
+
+#[tokio::test]
+async fn test_close_reward_position_error_unclaimed_rewards() {
+    // 1. Setup: the position has earned part of the emission
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+
+    let until = test.get_clock().await.unix_timestamp + 86_400;
+    let create_pool_ix = test.create_reward_pool_ix(1_000);
+    let commit_ix = test.commit_lock_ix(&user_pubkey, &vault_pda, &vault_ata, false, 10_000_000, until);
+    let open_ix = test.open_reward_position_ix(&vault_pda, &vault_ata);
+    test.process_transaction(
+        &[create_pool_ix, commit_ix, open_ix],
+        &[&test.user_keypair.insecure_clone()],
+    )
+    .await
+    .unwrap();
+    test.fund_reward_pool(1_000_000).await;
+    test.advance_clock(500).await;
+
+    // 2. Close without claiming
+    let close_ix = test.close_reward_position_ix(&user_pubkey, &vault_pda);
+    let result = test
+        .process_transaction(&[close_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::UnclaimedRewards);
+    assert_eq!(test.get_reward_pool_account().await.total_staked, 10_000_000);
+    assert_eq!(test.get_vault_account(&vault_pda).await.reward_positions, 1);
+}
+
+#[tokio::test]
+async fn test_balance_at_past_slots_success() {
+    // 1. Setup: 10 USDT at setup, topped up by 5 USDT some slots later
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let setup_slot = test.get_clock().await.slot;
+
+    test.warp_to_slot(setup_slot + 10).await;
+    let deposit_ix = test.deposit_ix(&user_pubkey, &user_ata, &vault_pda, &vault_ata, 5_000_000);
+    test.process_transaction(&[deposit_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+    let deposit_slot = test.get_clock().await.slot;
+    test.warp_to_slot(deposit_slot + 10).await;
+
+    // 2. Query a slot between the two changes
+    let balance_ix = test.balance_at_ix(&vault_pda, setup_slot + 5);
+    let result = test.process_transaction(&[balance_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify the recorded history
+    let checkpoints = test.get_vault_account(&vault_pda).await.balance_checkpoints;
+    assert_eq!(checkpoints.balance_at(setup_slot + 5).unwrap(), 10_000_000);
+    assert_eq!(checkpoints.balance_at(deposit_slot).unwrap(), 15_000_000);
+}
+
+#[tokio::test]
+async fn test_balance_at_error_current_slot() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let (vault_pda, _vault_ata, _user_ata) = test.setup_vault(10_000_000).await;
+    let current_slot = test.get_clock().await.slot;
+
+    // 2. Ask for a slot that has not finished
+    let balance_ix = test.balance_at_ix(&vault_pda, current_slot);
+    let result = test.process_transaction(&[balance_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::CheckpointUnavailable);
+}
+
+#[tokio::test]
+async fn test_commit_lock_success() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, user_ata) = test.setup_vault(50_000_000).await;
+    let until = test.get_clock().await.unix_timestamp + 86_400;
+
+    // 2. Commit 30 USDT for a day
+    let commit_ix = test.commit_lock_ix(&user_pubkey, &vault_pda, &vault_ata, false, 30_000_000, until);
+    let result = test
+        .process_transaction(&[commit_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: the committed amount can no longer be withdrawn
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.committed_balance, 30_000_000);
+    assert_eq!(vault_state.committed_until, until);
+    assert_eq!(vault_state.available_balance, 20_000_000);
+    assert_eq!(vault_state.total_balance, 50_000_000);
+
+    let withdraw_ix = test.withdraw_ix(&user_pubkey, &vault_pda, &vault_ata, &user_ata, 30_000_000);
+    let result = test
+        .process_transaction(&[withdraw_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert_program_error(result, errors::ErrorCode::InsufficientAvailableBalance);
+}
+
+#[tokio::test]
+async fn test_commit_lock_error_until_in_past() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, _user_ata) = test.setup_vault(50_000_000).await;
+    let until = test.get_clock().await.unix_timestamp - 1;
+
+    // 2. Commit with a release time that has already passed
+    let commit_ix = test.commit_lock_ix(&user_pubkey, &vault_pda, &vault_ata, false, 30_000_000, until);
+    let result = test
+        .process_transaction(&[commit_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidCommitment);
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.committed_balance, 0);
+    assert_eq!(vault_state.available_balance, 50_000_000);
+}
+
+#[tokio::test]
+async fn test_release_commitment_success() {
+    // 1. Setup: 30 USDT committed for an hour
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, _user_ata) = test.setup_vault(50_000_000).await;
+    let until = test.get_clock().await.unix_timestamp + 3_600;
+    let commit_ix = test.commit_lock_ix(&user_pubkey, &vault_pda, &vault_ata, false, 30_000_000, until);
+    test.process_transaction(&[commit_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Release once the commitment has expired
+    test.advance_clock(3_600).await;
+    let release_ix = test.release_commitment_ix(&user_pubkey, &vault_pda, &vault_ata, false);
+    let result = test
+        .process_transaction(&[release_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.committed_balance, 0);
+    assert_eq!(vault_state.available_balance, 50_000_000);
+}
+
+#[tokio::test]
+async fn test_release_commitment_error_still_active() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let user_pubkey = test.user_pubkey();
+    let (vault_pda, vault_ata, _user_ata) = test.setup_vault(50_000_000).await;
+    let until = test.get_clock().await.unix_timestamp + 3_600;
+    let commit_ix = test.commit_lock_ix(&user_pubkey, &vault_pda, &vault_ata, false, 30_000_000, until);
+    test.process_transaction(&[commit_ix], &[&test.user_keypair.insecure_clone()])
+        .await
+        .unwrap();
+
+    // 2. Release before the commitment expires
+    test.advance_clock(1_800).await;
+    let release_ix = test.release_commitment_ix(&user_pubkey, &vault_pda, &vault_ata, false);
+    let result = test
+        .process_transaction(&[release_ix], &[&test.user_keypair.insecure_clone()])
+        .await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::CommitmentActive);
+    let vault_state = test.get_vault_account(&vault_pda).await;
+    assert_eq!(vault_state.committed_balance, 30_000_000);
+    assert_eq!(vault_state.available_balance, 20_000_000);
+}
+
+#[tokio::test]
+async fn test_settle_batch_success() {
+    // 1. Setup: three vaults owing each other in a circle
+    let mut test = CollateralVaultProgramTest::new().await;
+    let (first_vault, first_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let (second_vault, second_ata) = test.setup_subaccount_vault(1, &user_ata, 5_000_000).await;
+    let (third_vault, third_ata) = test.setup_subaccount_vault(2, &user_ata, 5_000_000).await;
+    test.authorize_test_caller().await;
+
+    // 2. Settle the legs, which net to -3, +1 and +2 USDT
+    let vaults = [(first_vault, first_ata), (second_vault, second_ata), (third_vault, third_ata)];
+    let legs = vec![
+        SettlementLeg { from: 0, to: 1, amount: 4_000_000 },
+        SettlementLeg { from: 1, to: 2, amount: 3_000_000 },
+        SettlementLeg { from: 2, to: 0, amount: 1_000_000 },
+    ];
+    let settle_ix = test.settle_batch_ix(&vaults, legs, false);
+    let result = test.process_transaction(&[settle_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify
+    assert_eq!(test.get_vault_account(&first_vault).await.total_balance, 7_000_000);
+    assert_eq!(test.get_token_balance(&first_ata).await, 7_000_000);
+    assert_eq!(test.get_vault_account(&second_vault).await.total_balance, 6_000_000);
+    assert_eq!(test.get_token_balance(&second_ata).await, 6_000_000);
+    assert_eq!(test.get_vault_account(&third_vault).await.total_balance, 7_000_000);
+    assert_eq!(test.get_token_balance(&third_ata).await, 7_000_000);
+}
+
+#[tokio::test]
+async fn test_settle_batch_with_pooled_vault_success() {
+    // 1. Setup: a pooled vault between two plain subaccount vaults
+    let mut test = CollateralVaultProgramTest::new().await;
+    let (pooled_vault, pooled_ata, user_ata) = test.setup_pooled_vault(10_000_000).await;
+    let (second_vault, second_ata) = test.setup_subaccount_vault(1, &user_ata, 5_000_000).await;
+    let (third_vault, third_ata) = test.setup_subaccount_vault(2, &user_ata, 5_000_000).await;
+    test.authorize_test_caller().await;
+
+    // 2. The pool pays out 2 USDT and takes in 1 USDT
+    let vaults = [(pooled_vault, pooled_ata), (second_vault, second_ata), (third_vault, third_ata)];
+    let legs = vec![
+        SettlementLeg { from: 0, to: 1, amount: 2_000_000 },
+        SettlementLeg { from: 2, to: 0, amount: 1_000_000 },
+    ];
+    let settle_ix = test.settle_batch_ix(&vaults, legs, true);
+    let result = test.process_transaction(&[settle_ix], &[]).await;
+    assert!(result.is_ok(), "Transaction failed: {:?}", result.err());
+
+    // 3. Verify: only the pool's net debit left the pool token account
+    assert_eq!(test.get_vault_account(&pooled_vault).await.total_balance, 9_000_000);
+    assert_eq!(test.get_share_pool_account().await.total_assets, 9_000_000);
+    let pool_ata = test.find_pool_token_account();
+    assert_eq!(test.get_token_balance(&pool_ata).await, 9_000_000);
+    assert_eq!(test.get_vault_account(&second_vault).await.total_balance, 7_000_000);
+    assert_eq!(test.get_token_balance(&second_ata).await, 7_000_000);
+    assert_eq!(test.get_vault_account(&third_vault).await.total_balance, 4_000_000);
+    assert_eq!(test.get_token_balance(&third_ata).await, 4_000_000);
+}
+
+#[tokio::test]
+async fn test_settle_batch_error_insufficient_balance() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let (first_vault, first_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let (second_vault, second_ata) = test.setup_subaccount_vault(1, &user_ata, 5_000_000).await;
+    test.authorize_test_caller().await;
+
+    // 2. One leg is covered, the other nets the second vault below zero
+    let vaults = [(first_vault, first_ata), (second_vault, second_ata)];
+    let legs = vec![
+        SettlementLeg { from: 0, to: 1, amount: 2_000_000 },
+        SettlementLeg { from: 1, to: 0, amount: 8_000_000 },
+    ];
+    let settle_ix = test.settle_batch_ix(&vaults, legs, false);
+    let result = test.process_transaction(&[settle_ix], &[]).await;
+
+    // 3. Verify: nothing in the batch was applied
+    assert_program_error(result, errors::ErrorCode::InsufficientAvailableBalance);
+    assert_eq!(test.get_vault_account(&first_vault).await.total_balance, 10_000_000);
+    assert_eq!(test.get_token_balance(&first_ata).await, 10_000_000);
+    assert_eq!(test.get_vault_account(&second_vault).await.total_balance, 5_000_000);
+    assert_eq!(test.get_token_balance(&second_ata).await, 5_000_000);
+}
+
+#[tokio::test]
+async fn test_settle_batch_error_leg_out_of_range() {
+    // 1. Setup
+    let mut test = CollateralVaultProgramTest::new().await;
+    let (first_vault, first_ata, user_ata) = test.setup_vault(10_000_000).await;
+    let (second_vault, second_ata) = test.setup_subaccount_vault(1, &user_ata, 5_000_000).await;
+    test.authorize_test_caller().await;
+
+    // 2. Refer to a third vault that was not passed in
+    let vaults = [(first_vault, first_ata), (second_vault, second_ata)];
+    let legs = vec![SettlementLeg { from: 0, to: 2, amount: 1_000_000 }];
+    let settle_ix = test.settle_batch_ix(&vaults, legs, false);
+    let result = test.process_transaction(&[settle_ix], &[]).await;
+
+    // 3. Verify
+    assert_program_error(result, errors::ErrorCode::InvalidSettlementLeg);
+    assert_eq!(test.get_vault_account(&first_vault).await.total_balance, 10_000_000);
 }
\ No newline at end of file
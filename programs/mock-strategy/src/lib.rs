@@ -0,0 +1,147 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Mint, Token, TokenAccount, Transfer},
+};
+
+declare_id!("4bv71qKuyM2GmSuu3aySwLBgt7t2iUdxRYfncwNd5gg4");
+
+pub const STRATEGY_SEED: &[u8] = b"strategy";
+
+/// Minimal yield strategy for the collateral vault tests. It holds whatever
+/// the depositor sends and hands it back on request; tests simulate yield
+/// by transferring extra tokens into its token account.
+#[program]
+pub mod mock_strategy {
+    use super::*;
+
+    pub fn initialize(ctx: Context<Initialize>, depositor: Pubkey) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        state.depositor = depositor;
+        state.mint = ctx.accounts.mint.key();
+        state.token_account = ctx.accounts.strategy_token_account.key();
+        state.bump = ctx.bumps.state;
+        Ok(())
+    }
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.depositor_token_account.to_account_info(),
+                    to: ctx.accounts.strategy_token_account.to_account_info(),
+                    authority: ctx.accounts.depositor.to_account_info(),
+                },
+            ),
+            amount,
+        )
+    }
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        let state = &ctx.accounts.state;
+        let mint = state.mint;
+        let seeds = &[STRATEGY_SEED, mint.as_ref(), &[state.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.strategy_token_account.to_account_info(),
+                    to: ctx.accounts.depositor_token_account.to_account_info(),
+                    authority: state.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )
+    }
+}
+
+#[account]
+pub struct StrategyState {
+    pub depositor: Pubkey,
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub bump: u8,
+}
+
+impl StrategyState {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 1;
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = StrategyState::LEN,
+        seeds = [STRATEGY_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub state: Account<'info, StrategyState>,
+
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = state
+    )]
+    pub strategy_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    pub depositor: Signer<'info>,
+
+    #[account(
+        seeds = [STRATEGY_SEED, state.mint.as_ref()],
+        bump = state.bump,
+        has_one = depositor
+    )]
+    pub state: Account<'info, StrategyState>,
+
+    #[account(mut)]
+    pub depositor_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = state.token_account
+    )]
+    pub strategy_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    pub depositor: Signer<'info>,
+
+    #[account(
+        seeds = [STRATEGY_SEED, state.mint.as_ref()],
+        bump = state.bump,
+        has_one = depositor
+    )]
+    pub state: Account<'info, StrategyState>,
+
+    #[account(mut)]
+    pub depositor_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = state.token_account
+    )]
+    pub strategy_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}